@@ -856,6 +856,49 @@ impl Client {
         Ok(package.ident.into())
     }
 
+    /// Returns every release of a package in a channel, for the given target.
+    ///
+    /// Builder lists the releases a page at a time; every page is fetched.
+    ///
+    /// # Failures
+    ///
+    /// * Package cannot be found
+    /// * Remote Builder is not available
+    pub fn list_channel_packages(&self,
+                                 (package, target): (&PackageIdent, PackageTarget),
+                                 channel: &ChannelIdent,
+                                 token: Option<&str>)
+                                 -> Result<Vec<PackageIdent>> {
+        let path = channel_package_path(channel, package);
+        let mut packages = Vec::new();
+        loop {
+            let range = packages.len();
+            let custom = |url: &mut Url| {
+                url.query_pairs_mut()
+                   .append_pair("range", &range.to_string())
+                   .append_pair("target", &target.to_string());
+            };
+            let mut res = self.maybe_add_authz(self.0.get_with_custom_url(&path, custom), token)
+                              .send()?;
+
+            match res.status {
+                StatusCode::Ok | StatusCode::PartialContent => {
+                    let mut encoded = String::new();
+                    res.read_to_string(&mut encoded)
+                       .map_err(Error::BadResponseBody)?;
+                    debug!("Body: {:?}", encoded);
+                    let results: PackageResults<PackageIdent> = serde_json::from_str(&encoded)?;
+                    let page = results.data.len();
+                    packages.extend(results.data);
+                    if page == 0 || packages.len() as isize >= results.total_count {
+                        return Ok(packages);
+                    }
+                }
+                _ => return Err(err_from_response(res)),
+            }
+        }
+    }
+
     /// Upload a package to a remote Builder.
     ///
     /// # Failures
@@ -16,10 +16,15 @@
 //!
 //! This will connect to a given butterfly members `Pull` thread, and inject a rumor.
 
+use std::net::TcpStream;
+
 use habitat_core::{crypto::SymKey,
                    service::ServiceGroup};
+#[cfg(feature = "zmq-transport")]
 use zmq;
 
+#[cfg(feature = "zmq-transport")]
+use crate::ZMQ_CONTEXT;
 use crate::{error::{Error,
                     Result},
             message,
@@ -27,17 +32,47 @@ use crate::{error::{Error,
                     service_config::ServiceConfig,
                     service_file::ServiceFile,
                     Rumor},
-            ZMQ_CONTEXT};
+            server::transport::{self,
+                                GossipTransport}};
+
+enum Connection {
+    #[cfg(feature = "zmq-transport")]
+    Zmq(zmq::Socket),
+    Tcp(TcpStream),
+}
 
-/// Holds a ZMQ Push socket, and an optional ring encryption key.
+/// Holds a connection to the server's gossip port, and an optional ring encryption key.
 pub struct Client {
-    socket:   zmq::Socket,
-    ring_key: Option<SymKey>,
+    connection: Connection,
+    ring_key:   Option<SymKey>,
 }
 
 impl Client {
-    /// Connect this client to the address, and optionally encrypt the traffic.
+    /// Connect this client to the address over the default gossip transport, and optionally
+    /// encrypt the traffic.
     pub fn new(addr: &str, ring_key: Option<SymKey>) -> Result<Client> {
+        Self::with_transport(addr, ring_key, GossipTransport::default())
+    }
+
+    /// Connect this client to the address over the gossip transport the server listens with,
+    /// and optionally encrypt the traffic.
+    pub fn with_transport(addr: &str,
+                          ring_key: Option<SymKey>,
+                          gossip_transport: GossipTransport)
+                          -> Result<Client> {
+        let connection = match gossip_transport {
+            #[cfg(feature = "zmq-transport")]
+            GossipTransport::Zmq => Connection::Zmq(Self::connect_zmq(addr)?),
+            GossipTransport::Tcp => {
+                Connection::Tcp(transport::connect(addr).map_err(Error::GossipConnectError)?)
+            }
+        };
+        Ok(Client { connection,
+                    ring_key })
+    }
+
+    #[cfg(feature = "zmq-transport")]
+    fn connect_zmq(addr: &str) -> Result<zmq::Socket> {
         let socket = (**ZMQ_CONTEXT).as_mut()
                                     .socket(zmq::PUSH)
                                     .expect("Failure to create the ZMQ push socket");
@@ -53,7 +88,7 @@ impl Client {
               .expect("Failure to set the ZMQ send timeout");
         let to_addr = format!("tcp://{}", addr);
         socket.connect(&to_addr).map_err(Error::ZmqConnectError)?;
-        Ok(Client { socket, ring_key })
+        Ok(socket)
     }
 
     /// Create a departure notification and send it to the server.
@@ -62,32 +97,43 @@ impl Client {
         self.send(&departure)
     }
 
-    /// Create a service configuration and send it to the server.
+    /// Create a service configuration, signed by `signer` if it has a `signature`, and send it to
+    /// the server.
     pub fn send_service_config(&mut self,
                                service_group: ServiceGroup,
                                incarnation: u64,
                                config: &[u8],
-                               encrypted: bool)
+                               encrypted: bool,
+                               signer: Option<String>,
+                               signature: Option<Vec<u8>>)
                                -> Result<()> {
         let mut sc = ServiceConfig::new("butterflyclient", service_group, config.to_vec());
         sc.incarnation = incarnation;
         sc.encrypted = encrypted;
+        sc.signer = signer;
+        sc.signature = signature;
         self.send(&sc)
     }
 
-    /// Create a service file and send it to the server.
+    /// Create a service file, signed by `signer` if it has a `signature`, and send it to the
+    /// server.
+    #[allow(clippy::too_many_arguments)]
     pub fn send_service_file<S>(&mut self,
                                 service_group: ServiceGroup,
                                 filename: S,
                                 incarnation: u64,
                                 body: &[u8],
-                                encrypted: bool)
+                                encrypted: bool,
+                                signer: Option<String>,
+                                signature: Option<Vec<u8>>)
                                 -> Result<()>
         where S: Into<String>
     {
         let mut sf = ServiceFile::new("butterflyclient", service_group, filename, body.to_vec());
         sf.incarnation = incarnation;
         sf.encrypted = encrypted;
+        sf.signer = signer;
+        sf.signature = signature;
         self.send(&sf)
     }
 
@@ -97,6 +143,12 @@ impl Client {
     {
         let bytes = rumor.write_to_bytes()?;
         let wire_msg = message::generate_wire(bytes, self.ring_key.as_ref())?;
-        self.socket.send(&wire_msg, 0).map_err(Error::ZmqSendError)
+        match self.connection {
+            #[cfg(feature = "zmq-transport")]
+            Connection::Zmq(ref socket) => socket.send(&wire_msg, 0).map_err(Error::ZmqSendError),
+            Connection::Tcp(ref mut stream) => {
+                transport::write_frame(stream, &wire_msg).map_err(Error::GossipSendError)
+            }
+        }
     }
 }
@@ -23,8 +23,11 @@ use std::{error,
 use habitat_core;
 use prost;
 use toml;
+#[cfg(feature = "zmq-transport")]
 use zmq;
 
+use crate::server::transport::GOSSIP_TRANSPORTS;
+
 pub type Result<T> = result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -35,21 +38,30 @@ pub enum Error {
     DatFileIO(PathBuf, io::Error),
     DecodeError(prost::DecodeError),
     EncodeError(prost::EncodeError),
+    GossipConnectError(io::Error),
+    GossipSendError(io::Error),
     HabitatCore(habitat_core::error::Error),
     IncarnationIO(PathBuf, io::Error),
     IncarnationParse(PathBuf, num::ParseIntError),
+    InvalidGossipTransport(String),
     InvalidIncarnationSynchronization,
     InvalidRumorShareLimit,
     NonExistentRumor(String, String),
     ProtocolMismatch(&'static str),
+    RetirePrimaryRingKey(String),
+    RingKeyMismatch(String, String),
+    RingNotEncrypted,
     ServiceConfigDecode(String, toml::de::Error),
     ServiceConfigNotUtf8(String, str::Utf8Error),
     SocketCloneError,
     SocketSetReadTimeout(io::Error),
     SocketSetWriteTimeout(io::Error),
+    #[cfg(feature = "zmq-transport")]
     ZmqConnectError(zmq::Error),
+    #[cfg(feature = "zmq-transport")]
     ZmqSendError(zmq::Error),
     UnknownIOError(io::Error),
+    UnknownRingKeyRevision(String),
 }
 
 impl fmt::Display for Error {
@@ -74,6 +86,10 @@ impl fmt::Display for Error {
             Error::UnknownIOError(ref err) => format!("Error reading or writing: {}", err),
             Error::DecodeError(ref err) => format!("Failed to decode protocol message: {}", err),
             Error::EncodeError(ref err) => format!("Failed to encode protocol message: {}", err),
+            Error::GossipConnectError(ref err) => format!("Cannot connect gossip socket: {}", err),
+            Error::GossipSendError(ref err) => {
+                format!("Cannot send message through gossip socket: {}", err)
+            }
             Error::HabitatCore(ref err) => format!("{}", err),
             Error::IncarnationIO(ref path, ref err) => {
                 format!("Error reading or writing incarnation store file {}: {}",
@@ -89,6 +105,11 @@ impl fmt::Display for Error {
                                                          incarnation from non-existent \
                                                          incarnation store"
                                                                            .to_string(),
+            Error::InvalidGossipTransport(ref value) => {
+                format!("Invalid gossip transport '{}', must be one of: {}",
+                        value,
+                        GOSSIP_TRANSPORTS.join(", "))
+            }
             Error::InvalidRumorShareLimit => {
                 "Rumor share limit should be a positive integer".to_string()
             }
@@ -100,6 +121,18 @@ impl fmt::Display for Error {
                 format!("Received an unsupported or bad protocol message. Missing field: {}",
                         field)
             }
+            Error::RetirePrimaryRingKey(ref rev) => {
+                format!("Cannot retire ring key revision {}, it is the primary revision; promote \
+                         another revision first",
+                        rev)
+            }
+            Error::RingKeyMismatch(ref ring, ref key) => {
+                format!("Ring key {} is not a revision of the ring key {}",
+                        key, ring)
+            }
+            Error::RingNotEncrypted => {
+                "Cannot add a ring key revision, the ring is not encrypted".to_string()
+            }
             Error::ServiceConfigDecode(ref sg, ref err) => {
                 format!("Cannot decode service config: group={}, {:?}", sg, err)
             }
@@ -113,10 +146,13 @@ impl fmt::Display for Error {
             Error::SocketSetWriteTimeout(ref err) => {
                 format!("Cannot set UDP socket write timeout: {}", err)
             }
+            #[cfg(feature = "zmq-transport")]
             Error::ZmqConnectError(ref err) => format!("Cannot connect ZMQ socket: {}", err),
+            #[cfg(feature = "zmq-transport")]
             Error::ZmqSendError(ref err) => {
                 format!("Cannot send message through ZMQ socket: {}", err)
             }
+            Error::UnknownRingKeyRevision(ref rev) => format!("Unknown ring key revision {}", rev),
         };
         write!(f, "{}", msg)
     }
@@ -132,9 +168,12 @@ impl error::Error for Error {
             Error::UnknownIOError(_) => "Unknown I/O error",
             Error::DecodeError(ref err) => err.description(),
             Error::EncodeError(ref err) => err.description(),
+            Error::GossipConnectError(_) => "Cannot connect gossip socket",
+            Error::GossipSendError(_) => "Cannot send message through gossip socket",
             Error::HabitatCore(_) => "Habitat core error",
             Error::IncarnationIO(..) => "Error reading or writing incarnation store file",
             Error::IncarnationParse(..) => "Error parsing value from incarnation store file",
+            Error::InvalidGossipTransport(_) => "Invalid gossip transport",
             Error::InvalidIncarnationSynchronization => {
                 "Tried to synchronize own member incarnation from non-existent incarnation store"
             }
@@ -143,13 +182,19 @@ impl error::Error for Error {
             Error::ProtocolMismatch(_) => {
                 "Received an unprocessable wire message from another Supervisor"
             }
+            Error::RetirePrimaryRingKey(_) => "Cannot retire the primary ring key revision",
+            Error::RingKeyMismatch(..) => "Ring key is not a revision of the ring's key",
+            Error::RingNotEncrypted => "Cannot add a ring key revision to an unencrypted ring",
             Error::ServiceConfigDecode(..) => "Cannot decode service config into TOML",
             Error::ServiceConfigNotUtf8(..) => "Cannot read service config bytes to UTF-8",
             Error::SocketCloneError => "Cannot clone the underlying UDP socket",
             Error::SocketSetReadTimeout(_) => "Cannot set UDP socket read timeout",
             Error::SocketSetWriteTimeout(_) => "Cannot set UDP socket write timeout",
+            #[cfg(feature = "zmq-transport")]
             Error::ZmqConnectError(_) => "Cannot connect ZMQ socket",
+            #[cfg(feature = "zmq-transport")]
             Error::ZmqSendError(_) => "Cannot send message through ZMQ socket",
+            Error::UnknownRingKeyRevision(_) => "Unknown ring key revision",
         }
     }
 }
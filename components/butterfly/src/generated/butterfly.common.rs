@@ -7,4 +7,7 @@ pub struct Wire {
     pub nonce: ::std::option::Option<std::vec::Vec<u8>>,
     #[prost(bytes, optional, tag="3")]
     pub payload: ::std::option::Option<std::vec::Vec<u8>>,
+    /// Revision of the ring key the payload is encrypted with.
+    #[prost(string, optional, tag="4")]
+    pub key_revision: ::std::option::Option<std::string::String>,
 }
@@ -13,6 +13,13 @@ pub struct Election {
     pub status: ::std::option::Option<i32>,
     #[prost(string, repeated, tag="6")]
     pub votes: ::std::vec::Vec<std::string::String>,
+    /// Declared in the candidate's service spec; ranks above suitability.
+    #[prost(uint64, optional, tag="7")]
+    pub priority: ::std::option::Option<u64>,
+    /// Set when the candidate led the previous term, and its service asks to keep a sticky leader
+    /// while it is healthy; ranks above priority.
+    #[prost(bool, optional, tag="8")]
+    pub incumbent: ::std::option::Option<bool>,
 }
 pub mod election {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -41,6 +48,15 @@ pub struct Service {
     pub cfg: ::std::option::Option<std::vec::Vec<u8>>,
     #[prost(message, optional, tag="12")]
     pub sys: ::std::option::Option<SysInfo>,
+    /// Release this member rolled back from after it failed its health check following an update.
+    #[prost(string, optional, tag="13")]
+    pub rolled_back: ::std::option::Option<std::string::String>,
+    /// Result of this member's most recent health check: OK, WARNING, CRITICAL or UNKNOWN.
+    #[prost(string, optional, tag="14")]
+    pub health_check: ::std::option::Option<std::string::String>,
+    /// When `health_check` last changed to its current value, in seconds since the Unix epoch.
+    #[prost(int64, optional, tag="15")]
+    pub health_check_since: ::std::option::Option<i64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -53,6 +69,10 @@ pub struct ServiceConfig {
     pub encrypted: ::std::option::Option<bool>,
     #[prost(bytes, optional, tag="4")]
     pub config: ::std::option::Option<std::vec::Vec<u8>>,
+    #[prost(string, optional, tag="5")]
+    pub signer: ::std::option::Option<std::string::String>,
+    #[prost(bytes, optional, tag="6")]
+    pub signature: ::std::option::Option<std::vec::Vec<u8>>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -67,6 +87,10 @@ pub struct ServiceFile {
     pub filename: ::std::option::Option<std::string::String>,
     #[prost(bytes, optional, tag="5")]
     pub body: ::std::option::Option<std::vec::Vec<u8>>,
+    #[prost(string, optional, tag="6")]
+    pub signer: ::std::option::Option<std::string::String>,
+    #[prost(bytes, optional, tag="7")]
+    pub signature: ::std::option::Option<std::vec::Vec<u8>>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
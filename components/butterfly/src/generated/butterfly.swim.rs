@@ -16,6 +16,9 @@ pub struct Member {
     pub persistent: ::std::option::Option<bool>,
     #[prost(bool, optional, tag="7", default="false")]
     pub departed: ::std::option::Option<bool>,
+    /// Operator-assigned key/value pairs, e.g. zone=us-east-1a
+    #[prost(map="string, string", tag="8")]
+    pub labels: ::std::collections::HashMap<std::string::String, std::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -40,6 +43,17 @@ pub struct PingReq {
     pub from: ::std::option::Option<Member>,
     #[prost(message, optional, tag="2")]
     pub target: ::std::option::Option<Member>,
+    /// Whether to send a Nack back if the target doesn't ack either (Lifeguard)
+    #[prost(bool, optional, tag="3", default="false")]
+    pub nack: ::std::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+#[derive(Serialize, Deserialize)]
+pub struct Nack {
+    #[prost(message, optional, tag="1")]
+    pub from: ::std::option::Option<Member>,
+    #[prost(message, optional, tag="2")]
+    pub target: ::std::option::Option<Member>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -68,7 +82,7 @@ pub struct Swim {
     pub r#type: i32,
     #[prost(message, repeated, tag="5")]
     pub membership: ::std::vec::Vec<Membership>,
-    #[prost(oneof="swim::Payload", tags="2, 3, 4")]
+    #[prost(oneof="swim::Payload", tags="2, 3, 4, 6")]
     pub payload: ::std::option::Option<swim::Payload>,
 }
 pub mod swim {
@@ -79,6 +93,7 @@ pub mod swim {
         Ping = 1,
         Ack = 2,
         Pingreq = 3,
+        Nack = 4,
     }
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     #[derive(Serialize, Deserialize)]
@@ -89,5 +104,7 @@ pub mod swim {
         Ack(super::Ack),
         #[prost(message, tag="4")]
         Pingreq(super::PingReq),
+        #[prost(message, tag="6")]
+        Nack(super::Nack),
     }
 }
@@ -0,0 +1,130 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The revisions of a ring key that a member encrypts and decrypts gossip with.
+//!
+//! A ring key is rotated without partitioning the ring by adding its new revision to every
+//! member, promoting it to primary once every member has it, and then retiring the old one.
+
+use crate::error::{Error,
+                   Result};
+use habitat_core::crypto::SymKey;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default)]
+pub struct Keyring {
+    /// Every revision messages are decrypted with, by revision.
+    keys:    BTreeMap<String, SymKey>,
+    /// The revision messages are encrypted with; `None` on an unencrypted ring.
+    primary: Option<String>,
+}
+
+impl Keyring {
+    pub fn new(primary: Option<SymKey>) -> Self {
+        let mut keyring = Keyring::default();
+        if let Some(key) = primary {
+            keyring.primary = Some(key.rev.clone());
+            keyring.keys.insert(key.rev.clone(), key);
+        }
+        keyring
+    }
+
+    /// The revision messages are encrypted with.
+    pub fn primary(&self) -> Option<&SymKey> {
+        self.primary.as_ref().and_then(|rev| self.keys.get(rev))
+    }
+
+    pub fn get(&self, rev: &str) -> Option<&SymKey> { self.keys.get(rev) }
+
+    /// Every known revision, oldest first.
+    pub fn revisions(&self) -> Vec<&str> { self.keys.keys().map(String::as_str).collect() }
+
+    /// Decrypt messages with another revision of the ring key. Adding a revision that is already
+    /// known does nothing.
+    pub fn add(&mut self, key: SymKey) -> Result<()> {
+        let ring = self.primary()
+                       .map(|primary| primary.name.clone())
+                       .ok_or(Error::RingNotEncrypted)?;
+        if key.name != ring {
+            return Err(Error::RingKeyMismatch(ring, key.name_with_rev()));
+        }
+        self.keys.entry(key.rev.clone()).or_insert(key);
+        Ok(())
+    }
+
+    /// Encrypt messages with a known revision from now on.
+    pub fn promote(&mut self, rev: &str) -> Result<()> {
+        if !self.keys.contains_key(rev) {
+            return Err(Error::UnknownRingKeyRevision(rev.to_string()));
+        }
+        self.primary = Some(rev.to_string());
+        Ok(())
+    }
+
+    /// Stop decrypting messages with a revision, returning it. The primary revision can't be
+    /// retired; promote another one first.
+    pub fn retire(&mut self, rev: &str) -> Result<SymKey> {
+        if self.primary.as_ref().map(String::as_str) == Some(rev) {
+            return Err(Error::RetirePrimaryRingKey(rev.to_string()));
+        }
+        self.keys
+            .remove(rev)
+            .ok_or_else(|| Error::UnknownRingKeyRevision(rev.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str, rev: &str) -> SymKey {
+        let mut key = SymKey::generate_pair_for_ring(name).unwrap();
+        key.rev = rev.to_string();
+        key
+    }
+
+    #[test]
+    fn keys_are_added_promoted_and_retired() {
+        let mut keyring = Keyring::new(Some(key("beyonce", "20190101000000")));
+        keyring.add(key("beyonce", "20190201000000")).unwrap();
+        assert_eq!(keyring.revisions(),
+                   vec!["20190101000000", "20190201000000"]);
+        assert_eq!(keyring.primary().unwrap().rev, "20190101000000");
+
+        keyring.promote("20190201000000").unwrap();
+        assert_eq!(keyring.primary().unwrap().rev, "20190201000000");
+
+        keyring.retire("20190101000000").unwrap();
+        assert_eq!(keyring.revisions(), vec!["20190201000000"]);
+        assert!(keyring.get("20190101000000").is_none());
+    }
+
+    #[test]
+    fn the_primary_key_cannot_be_retired() {
+        let mut keyring = Keyring::new(Some(key("beyonce", "20190101000000")));
+        assert!(keyring.retire("20190101000000").is_err());
+        assert!(keyring.primary().is_some());
+    }
+
+    #[test]
+    fn only_revisions_of_the_same_ring_key_are_added() {
+        let mut keyring = Keyring::new(Some(key("beyonce", "20190101000000")));
+        assert!(keyring.add(key("jayz", "20190201000000")).is_err());
+        assert!(keyring.promote("20190201000000").is_err());
+
+        let mut unencrypted = Keyring::new(None);
+        assert!(unencrypted.add(key("beyonce", "20190201000000")).is_err());
+        assert!(unencrypted.primary().is_none());
+    }
+}
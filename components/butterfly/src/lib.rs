@@ -13,7 +13,7 @@
 // limitations under the License.
 
 //! Butterfly is the [SWIM](http://www.cs.cornell.edu/projects/Quicksilver/public_pdfs/SWIM.pdf)
-//! implementation for Habitat, along with a ZeroMQ based gossip protocol.
+//! implementation for Habitat, along with a gossip protocol over ZeroMQ or framed TCP.
 //!
 //! It implements SWIM+Susp+Inf. It uses Newscast-style "heat" tracking to share membership rumors,
 //! while trying to keep UDP packet sizes below 512 bytes. It has the following changes:
@@ -56,6 +56,7 @@ extern crate serde_derive;
 pub mod trace;
 pub mod client;
 pub mod error;
+pub mod keyring;
 pub mod member;
 pub mod message;
 pub mod protocol;
@@ -64,8 +65,10 @@ pub mod server;
 pub mod swim;
 
 pub use crate::server::Server;
+#[cfg(feature = "zmq-transport")]
 use std::cell::UnsafeCell;
 
+#[cfg(feature = "zmq-transport")]
 lazy_static! {
     /// A threadsafe shared ZMQ context for consuming services.
     ///
@@ -79,12 +82,16 @@ lazy_static! {
 
 /// This is a wrapper to provide interior mutability of an underlying `zmq::Context` and allows
 /// for sharing/sending of a `zmq::Context` between threads.
+#[cfg(feature = "zmq-transport")]
 pub struct ServerContext(UnsafeCell<zmq::Context>);
 
+#[cfg(feature = "zmq-transport")]
 impl ServerContext {
     #[allow(clippy::mut_from_ref)]
     pub fn as_mut(&self) -> &mut zmq::Context { unsafe { &mut *self.0.get() } }
 }
 
+#[cfg(feature = "zmq-transport")]
 unsafe impl Send for ServerContext {}
+#[cfg(feature = "zmq-transport")]
 unsafe impl Sync for ServerContext {}
@@ -15,6 +15,7 @@
 //! Tracks membership. Contains both the `Member` struct and the `MemberList`.
 
 use std::{collections::{hash_map,
+                        BTreeMap,
                         HashMap},
           fmt,
           net::SocketAddr,
@@ -56,6 +57,9 @@ use crate::{error::{Error,
 /// How many nodes do we target when we need to run PingReq.
 const PINGREQ_TARGETS: usize = 5;
 
+/// The label that places a member in a zone, e.g. `zone=us-east-1a`.
+pub const ZONE_LABEL: &str = "zone";
+
 lazy_static! {
     static ref PEER_HEALTH_COUNT: IntGaugeVec =
         register_int_gauge_vec!("hab_butterfly_peer_health_total",
@@ -150,6 +154,10 @@ pub struct Member {
     pub gossip_port: u16,
     pub persistent:  bool,
     pub departed:    bool,
+    /// Operator-assigned key/value pairs (e.g. `zone=us-east-1a`) that services can use to
+    /// choose between the members of a group they bind to.
+    #[serde(default)]
+    pub labels:      BTreeMap<String, String>,
 }
 
 impl Member {
@@ -168,6 +176,9 @@ impl Member {
             }
         }
     }
+
+    /// The zone this member is in, from its `zone` label.
+    pub fn zone(&self) -> Option<&str> { self.labels.get(ZONE_LABEL).map(String::as_str) }
 }
 
 impl Default for Member {
@@ -183,7 +194,8 @@ impl Default for Member {
                  swim_port:   0,
                  gossip_port: 0,
                  persistent:  false,
-                 departed:    false, }
+                 departed:    false,
+                 labels:      BTreeMap::new(), }
     }
 }
 
@@ -207,7 +219,8 @@ impl From<Member> for proto::Member {
                         swim_port:   Some(value.swim_port.into()),
                         gossip_port: Some(value.gossip_port.into()),
                         persistent:  Some(value.persistent),
-                        departed:    Some(value.departed), }
+                        departed:    Some(value.departed),
+                        labels:      value.labels.into_iter().collect(), }
     }
 }
 
@@ -301,7 +314,8 @@ impl FromProto<proto::Member> for Member {
                                       .and_then(as_port)
                                       .ok_or(Error::ProtocolMismatch("gossip-port"))?,
                     persistent:  proto.persistent.unwrap_or(false),
-                    departed:    proto.departed.unwrap_or(false), })
+                    departed:    proto.departed.unwrap_or(false),
+                    labels:      proto.labels.into_iter().collect(), })
     }
 }
 
@@ -607,21 +621,57 @@ impl MemberList {
         members
     }
 
+    /// A randomized list of members to check that keeps most traffic within `zone`: every member
+    /// in the zone, followed by at most `cross_zone_fanout` alive members of other zones. Members
+    /// without a zone are checked as though they were in ours, so that members that don't carry
+    /// the label still hear everything.
+    pub fn zoned_check_list(&self,
+                            exclude_id: &str,
+                            zone: &str,
+                            cross_zone_fanout: usize)
+                            -> Vec<Member> {
+        let entries = self.read_entries();
+        let (mut in_zone, cross_zone): (Vec<_>, Vec<_>) =
+            entries.values()
+                   .filter(|member_list::Entry { member, .. }| member.id != exclude_id)
+                   .partition(|member_list::Entry { member, .. }| {
+                       member.zone().map_or(true, |z| z == zone)
+                   });
+        in_zone.shuffle(&mut thread_rng());
+        let cross_zone =
+            cross_zone.into_iter()
+                      .filter(|member_list::Entry { health, .. }| *health == Health::Alive)
+                      .choose_multiple(&mut thread_rng(), cross_zone_fanout);
+        in_zone.into_iter()
+               .chain(cross_zone)
+               .map(|member_list::Entry { member, .. }| member.clone())
+               .collect()
+    }
+
     /// Takes a function whose first argument is a member, and calls it for every pingreq target.
+    /// Members in the target's zone are chosen before members of other zones, so that probing a
+    /// member crosses zones as little as possible.
     pub fn with_pingreq_targets(&self,
                                 sending_member_id: &str,
                                 target_member_id: &str,
                                 mut with_closure: impl FnMut(&Member)) {
-        for member_list::Entry { member, .. } in
-            self.read_entries()
-                .values()
-                .filter(|member_list::Entry { member, health, .. }| {
-                    member.id != sending_member_id
-                    && member.id != target_member_id
-                    && *health == Health::Alive
-                })
-                .choose_multiple(&mut thread_rng(), PINGREQ_TARGETS)
-        {
+        let entries = self.read_entries();
+        let target_zone = entries.get(target_member_id)
+                                 .and_then(|member_list::Entry { member, .. }| member.zone());
+        let mut targets: Vec<_> = entries.values()
+                                         .filter(|member_list::Entry { member, health, .. }| {
+                                             member.id != sending_member_id
+                                             && member.id != target_member_id
+                                             && *health == Health::Alive
+                                         })
+                                         .map(|member_list::Entry { member, .. }| member)
+                                         .collect();
+        targets.shuffle(&mut thread_rng());
+        if let Some(zone) = target_zone {
+            // The sort is stable, so the members of each zone stay shuffled.
+            targets.sort_by_key(|member| member.zone() != Some(zone));
+        }
+        for member in targets.into_iter().take(PINGREQ_TARGETS) {
             with_closure(member);
         }
     }
@@ -670,14 +720,23 @@ impl MemberList {
     /// appropriately, and a list of newly-Confirmed Member IDs is
     /// returned.
     pub fn members_expired_to_confirmed(&self, timeout: Duration) -> Vec<String> {
-        self.members_expired_to(Health::Confirmed, timeout)
+        self.members_expired_to(Health::Confirmed, |_| timeout)
+    }
+
+    /// Like `members_expired_to_confirmed`, but each Suspect member
+    /// has a timeout of its own, looked up by its ID. The member list
+    /// is locked while `timeout_of` is called.
+    pub fn members_expired_to_confirmed_by(&self,
+                                           timeout_of: impl Fn(&str) -> Duration)
+                                           -> Vec<String> {
+        self.members_expired_to(Health::Confirmed, timeout_of)
     }
 
     /// Query the list of aging Confirmed members to find those which
     /// have now expired to Departed. Health is updated appropriately,
     /// and a list of newly-Departed Member IDs is returned.
     pub fn members_expired_to_departed(&self, timeout: Duration) -> Vec<String> {
-        self.members_expired_to(Health::Departed, timeout)
+        self.members_expired_to(Health::Departed, |_| timeout)
     }
 
     /// Return the member IDs of all members that have "timed out" to
@@ -685,14 +744,17 @@ impl MemberList {
     ///
     /// For instance,
     ///
-    ///   members_expired_to(Health::Departed, timeout)
+    ///   members_expired_to(Health::Departed, |_| timeout)
     ///
     /// will return the IDs of those members that have been
     /// `Confirmed` for longer than the given `timeout`.
     ///
     /// The newly-updated health status is recorded properly.
     // TODO (CM): Better return type than Vec<String>
-    fn members_expired_to(&self, expiring_to: Health, timeout: Duration) -> Vec<String> {
+    fn members_expired_to(&self,
+                          expiring_to: Health,
+                          timeout_of: impl Fn(&str) -> Duration)
+                          -> Vec<String> {
         let now = SteadyTime::now();
         let precursor_health = match expiring_to {
             Health::Confirmed => Health::Suspect,
@@ -707,7 +769,7 @@ impl MemberList {
                     let member_list::Entry { health,
                                              health_updated_at,
                                              .. } = v;
-                    if *health == precursor_health && now >= *health_updated_at + timeout {
+                    if *health == precursor_health && now >= *health_updated_at + timeout_of(id) {
                         *health = expiring_to;
                         *health_updated_at = now;
                         Some(id.clone())
@@ -727,6 +789,20 @@ impl MemberList {
     pub fn contains_member(&self, member_id: &str) -> bool {
         self.read_entries().contains_key(member_id)
     }
+
+    /// Returns true if the member is `Departed`, or has been `Confirmed`
+    /// for longer than the given `timeout`. Members we know nothing
+    /// about haven't expired.
+    pub fn is_expired(&self, member_id: &str, timeout: Duration) -> bool {
+        match self.read_entries().get(member_id) {
+            Some(member_list::Entry { health: Health::Departed,
+                                      .. }) => true,
+            Some(member_list::Entry { health: Health::Confirmed,
+                                      health_updated_at,
+                                      .. }) => SteadyTime::now() >= *health_updated_at + timeout,
+            _ => false,
+        }
+    }
 }
 
 /// This proxy wraps a MemberList so that we can customize its serialization logic.
@@ -764,10 +840,11 @@ impl<'a> Serialize for MemberProxy<'a> {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
         where S: Serializer
     {
-        let mut strukt = serializer.serialize_struct("member", 6)?;
+        let mut strukt = serializer.serialize_struct("member", 7)?;
         strukt.serialize_field("address", &self.0.address)?;
         strukt.serialize_field("gossip_port", &self.0.gossip_port)?;
         strukt.serialize_field("incarnation", &self.0.incarnation)?;
+        strukt.serialize_field("labels", &self.0.labels)?;
         strukt.serialize_field("persistent", &self.0.persistent)?;
         strukt.serialize_field("swim_port", &self.0.swim_port)?;
         strukt.serialize_field("health", &self.1)?;
@@ -815,7 +892,9 @@ mod tests {
                     protocol::Message};
         #[test]
         fn encode_decode_roundtrip() {
-            let member = Member::default();
+            let mut member = Member::default();
+            member.labels
+                  .insert("zone".to_string(), "us-east-1a".to_string());
             let membership = Membership { member,
                                           health: Health::Suspect };
 
@@ -927,6 +1006,54 @@ mod tests {
               });
         }
 
+        fn zoned_member(zone: &str) -> Member {
+            let mut member = Member::default();
+            member.labels
+                  .insert(ZONE_LABEL.to_string(), zone.to_string());
+            member
+        }
+
+        #[test]
+        fn pingreq_targets_prefer_the_target_zone() {
+            let ml = MemberList::new();
+            let from = zoned_member("a");
+            let target = zoned_member("b");
+            ml.insert(from.clone(), Health::Alive);
+            ml.insert(target.clone(), Health::Alive);
+            for _ in 0..PINGREQ_TARGETS {
+                ml.insert(zoned_member("a"), Health::Alive);
+                ml.insert(zoned_member("b"), Health::Alive);
+            }
+            ml.with_pingreq_targets(&from.id, &target.id, |m| {
+                  assert_eq!(m.zone(), Some("b"));
+              });
+        }
+
+        #[test]
+        fn zoned_check_list_limits_cross_zone_members() {
+            let ml = MemberList::new();
+            let me = zoned_member("a");
+            ml.insert(me.clone(), Health::Alive);
+            for _ in 0..3 {
+                ml.insert(zoned_member("a"), Health::Alive);
+                ml.insert(zoned_member("b"), Health::Alive);
+                ml.insert(zoned_member("c"), Health::Alive);
+            }
+            ml.insert(Member::default(), Health::Alive);
+            ml.insert(zoned_member("b"), Health::Confirmed);
+
+            let check_list = ml.zoned_check_list(&me.id, "a", 2);
+            assert_eq!(check_list.len(), 6);
+            assert!(check_list.iter().all(|m| m.id != me.id));
+            // Members of our zone, and those without one, come first
+            assert!(check_list[..4].iter()
+                                   .all(|m| m.zone().map_or(true, |z| z == "a")));
+            assert!(check_list[4..].iter().all(|m| {
+                                              m.zone() != Some("a")
+                                              && ml.health_of(m) == Some(Health::Alive)
+                                          }));
+        }
+
         #[test]
         fn insert_no_member() {
             let ml = MemberList::new();
@@ -1396,6 +1523,34 @@ mod tests {
                            "Member should have a health of Departed after timing out");
             }
 
+            #[test]
+            fn members_expire_once_departed_or_confirmed_past_the_timeout() {
+                let ml = MemberList::new();
+                let alive = Member::default();
+                let confirmed = Member::default();
+                let departed = Member::default();
+                let small_seconds = 1;
+                let small_timeout =
+                    Duration::from_std(StdDuration::from_secs(small_seconds)).unwrap();
+
+                assert!(ml.insert(alive.clone(), Health::Alive));
+                assert!(ml.insert(confirmed.clone(), Health::Confirmed));
+                assert!(ml.insert(departed.clone(), Health::Departed));
+
+                assert!(!ml.is_expired(&alive.id, small_timeout));
+                assert!(!ml.is_expired(&confirmed.id, small_timeout),
+                        "A Confirmed member shouldn't expire before the timeout");
+                assert!(ml.is_expired(&departed.id, small_timeout));
+                assert!(!ml.is_expired("unknown-member", small_timeout));
+
+                // Allow the Confirmed to age
+                thread::sleep(StdDuration::from_secs(small_seconds));
+
+                assert!(ml.is_expired(&confirmed.id, small_timeout),
+                        "A Confirmed member should expire after the timeout");
+                assert!(!ml.is_expired(&alive.id, small_timeout));
+            }
+
             #[test]
             fn suspect_timeout_is_appropriately_selective() {
                 let ml = MemberList::new();
@@ -18,6 +18,7 @@ use prost::Message;
 
 use crate::{error::{Error,
                     Result},
+            keyring::Keyring,
             protocol::Wire};
 
 pub fn generate_wire(payload: Vec<u8>, ring_key: Option<&SymKey>) -> Result<Vec<u8>> {
@@ -27,6 +28,7 @@ pub fn generate_wire(payload: Vec<u8>, ring_key: Option<&SymKey>) -> Result<Vec<
         let (nonce, encrypted_payload) = ring_key.encrypt(&payload)?;
         wire.nonce = Some(nonce);
         wire.payload = Some(encrypted_payload);
+        wire.key_revision = Some(ring_key.rev.clone());
     } else {
         wire.payload = Some(payload);
     }
@@ -35,12 +37,21 @@ pub fn generate_wire(payload: Vec<u8>, ring_key: Option<&SymKey>) -> Result<Vec<
     Ok(buf.to_vec())
 }
 
-pub fn unwrap_wire(payload: &[u8], ring_key: Option<&SymKey>) -> Result<Vec<u8>> {
+/// Unwrap a message, decrypting it with the revision of the ring key it names. Messages that
+/// don't name one were sent by older members and are decrypted with the primary revision.
+pub fn unwrap_wire(payload: &[u8], ring_keys: &Keyring) -> Result<Vec<u8>> {
     let wire = Wire::decode(payload)?;
     let payload = wire.payload
                       .ok_or(Error::ProtocolMismatch("missing payload"))?;
-    if let Some(ring_key) = ring_key {
+    if let Some(primary) = ring_keys.primary() {
         let nonce = wire.nonce.ok_or(Error::ProtocolMismatch("missing nonce"))?;
+        let ring_key = match wire.key_revision {
+            Some(rev) => {
+                ring_keys.get(&rev)
+                         .ok_or_else(|| Error::UnknownRingKeyRevision(rev.clone()))?
+            }
+            None => primary,
+        };
         Ok(ring_key.decrypt(&nonce, &payload)?)
     } else {
         Ok(payload)
@@ -61,7 +61,9 @@ impl From<CElection> for Rumor {
                                  term:          Some(value.term),
                                  suitability:   Some(value.suitability),
                                  status:        Some(value.status as i32),
-                                 votes:         value.votes.clone(), };
+                                 votes:         value.votes.clone(),
+                                 priority:      Some(value.priority),
+                                 incumbent:     Some(value.incumbent), };
         Rumor { r#type:  RumorType::Election as i32,
                 tag:     Vec::default(),
                 from_id: Some(value.member_id),
@@ -76,7 +78,9 @@ impl From<CElectionUpdate> for Rumor {
                                  term:          Some(value.term),
                                  suitability:   Some(value.suitability),
                                  status:        Some(value.status as i32),
-                                 votes:         value.votes.clone(), };
+                                 votes:         value.votes.clone(),
+                                 priority:      Some(value.priority),
+                                 incumbent:     Some(value.incumbent), };
         Rumor { r#type:  RumorType::ElectionUpdate as i32,
                 tag:     Vec::default(),
                 from_id: Some(value.member_id.clone()),
@@ -86,13 +90,16 @@ impl From<CElectionUpdate> for Rumor {
 
 impl From<CService> for Rumor {
     fn from(value: CService) -> Self {
-        let payload = Service { member_id:     Some(value.member_id.clone()),
-                                service_group: Some(value.service_group.to_string()),
-                                incarnation:   Some(value.incarnation),
-                                initialized:   Some(value.initialized),
-                                pkg:           Some(value.pkg),
-                                cfg:           Some(value.cfg),
-                                sys:           Some(value.sys.into()), };
+        let payload = Service { member_id:          Some(value.member_id.clone()),
+                                service_group:      Some(value.service_group.to_string()),
+                                incarnation:        Some(value.incarnation),
+                                initialized:        Some(value.initialized),
+                                pkg:                Some(value.pkg),
+                                cfg:                Some(value.cfg),
+                                sys:                Some(value.sys.into()),
+                                rolled_back:        value.rolled_back,
+                                health_check:       value.health_check,
+                                health_check_since: value.health_check_since, };
         Rumor { r#type:  RumorType::Service as i32,
                 tag:     Vec::default(),
                 from_id: Some(value.member_id),
@@ -105,7 +112,9 @@ impl From<CServiceConfig> for Rumor {
         let payload = ServiceConfig { service_group: Some(value.service_group.to_string()),
                                       incarnation:   Some(value.incarnation),
                                       encrypted:     Some(value.encrypted),
-                                      config:        Some(value.config), };
+                                      config:        Some(value.config),
+                                      signer:        value.signer,
+                                      signature:     value.signature, };
         Rumor { r#type:  RumorType::ServiceConfig as i32,
                 tag:     Vec::default(),
                 from_id: Some(value.from_id),
@@ -119,7 +128,9 @@ impl From<CServiceFile> for Rumor {
                                     incarnation:   Some(value.incarnation),
                                     encrypted:     Some(value.encrypted),
                                     filename:      Some(value.filename),
-                                    body:          Some(value.body), };
+                                    body:          Some(value.body),
+                                    signer:        value.signer,
+                                    signature:     value.signature, };
         Rumor { r#type:  RumorType::ServiceFile as i32,
                 tag:     Vec::default(),
                 from_id: Some(value.from_id),
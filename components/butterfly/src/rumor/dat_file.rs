@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs::File,
+use std::{fs::{self,
+               File},
           io::{self,
                BufReader,
                BufWriter,
@@ -27,6 +28,7 @@ use std::{fs::File,
 use byteorder::{ByteOrder,
                 LittleEndian};
 use habitat_core::fs::AtomicWriter;
+use prost::Message as ProstMessage;
 
 use crate::{error::{Error,
                     Result},
@@ -37,8 +39,11 @@ use crate::{error::{Error,
             rumor::{Departure,
                     Election,
                     ElectionUpdate,
+                    ProtoRumor,
                     Rumor,
+                    RumorEnvelope,
                     RumorStore,
+                    RumorType,
                     Service,
                     ServiceConfig,
                     ServiceFile},
@@ -46,6 +51,15 @@ use crate::{error::{Error,
 
 const HEADER_VERSION: u8 = 2;
 
+/// The kinds of rumor in a DAT file, in the order their sections are written.
+const SECTIONS: [RumorType; 7] = [RumorType::Member,
+                                  RumorType::Service,
+                                  RumorType::ServiceConfig,
+                                  RumorType::ServiceFile,
+                                  RumorType::Election,
+                                  RumorType::ElectionUpdate,
+                                  RumorType::Departure];
+
 /// A versioned binary file containing rumors exchanged by the butterfly server which have
 /// been periodically persisted to disk.
 ///
@@ -69,8 +83,111 @@ impl DatFile {
                   header:      Header::default(), }
     }
 
+    /// A DAT file at the given path, rather than in a Supervisor's data path; used to inspect
+    /// and edit one offline.
+    pub fn from_path<T: AsRef<Path>>(path: T) -> Self {
+        DatFile { path:        path.as_ref().to_path_buf(),
+                  header_size: 0,
+                  header:      Header::default(), }
+    }
+
     pub fn path(&self) -> &Path { &self.path }
 
+    /// Read every rumor in the file without loading them into a server.
+    ///
+    /// Unlike `read_into`, this checks the structure of the whole file: the header's version
+    /// and size, that every section holds exactly as many bytes as the header says and only
+    /// rumors of its own kind, and that nothing follows the last section.
+    pub fn read_contents(&mut self) -> Result<DatFileContents> {
+        let bytes = fs::read(&self.path).map_err(|err| Error::DatFileIO(self.path.clone(), err))?;
+        let version = *bytes.first()
+                            .ok_or_else(|| self.invalid("the file is empty".to_string()))?;
+        if version == 0 || version > HEADER_VERSION {
+            return Err(self.invalid(format!("unknown header version {}", version)));
+        }
+        // The version 1 header is six lengths; later ones are prefixed with their own size.
+        let expected_header_size = match version {
+            1 => 48,
+            _ => mem::size_of::<Header>() as u64 + 8,
+        };
+        if (bytes.len() as u64) < 1 + expected_header_size {
+            return Err(self.invalid(format!("the header is truncated; the file is only {} \
+                                             bytes",
+                                            bytes.len())));
+        }
+        let (header_size, header) = Header::from_bytes(&bytes[1..], version);
+        if header_size != expected_header_size {
+            return Err(self.invalid(format!("the header says it is {} bytes, but a version {} \
+                                             header is {} bytes",
+                                            header_size, version, expected_header_size)));
+        }
+        self.header = header;
+        self.header_size = header_size;
+
+        let mut entries = Vec::new();
+        let mut offset = self.member_offset();
+        for section in SECTIONS.iter() {
+            let end = match offset.checked_add(self.header.section_len(*section)) {
+                Some(end) => end,
+                None => {
+                    return Err(self.invalid(format!("the {} section is {} bytes long, past \
+                                                     the largest possible file",
+                                                    section,
+                                                    self.header.section_len(*section))));
+                }
+            };
+            if end > bytes.len() as u64 {
+                return Err(self.invalid(format!("the {} section ends at byte {}, past the end \
+                                                 of the file at byte {}",
+                                                section,
+                                                end,
+                                                bytes.len())));
+            }
+            while offset < end {
+                let entry = self.read_entry(&bytes[..end as usize], offset, *section)?;
+                offset += 8 + entry.len();
+                entries.push(entry);
+            }
+        }
+        if offset != bytes.len() as u64 {
+            return Err(self.invalid(format!("{} unexpected bytes follow the last section",
+                                            bytes.len() as u64 - offset)));
+        }
+
+        Ok(DatFileContents { version,
+                             header_size,
+                             header: self.header.clone(),
+                             entries })
+    }
+
+    /// Replace the file with the given rumors, keeping each exactly as it was read. The
+    /// entries are written in the section for their kind of rumor, whatever order they're in.
+    pub fn write_entries(&self, entries: &[DatFileEntry]) -> Result<()> {
+        let mut header = Header::default();
+        let w =
+            AtomicWriter::new(&self.path).map_err(|err| Error::DatFileIO(self.path.clone(), err))?;
+        w.with_writer(|mut f| {
+             let mut writer = BufWriter::new(&mut f);
+             self.init(&mut writer)?;
+             for section in SECTIONS.iter() {
+                 for entry in entries.iter().filter(|e| e.rumor.r#type == *section) {
+                     *header.section_len_mut(*section) +=
+                         self.write_entry(&mut writer, &entry.bytes)?;
+                 }
+             }
+             writer.seek(SeekFrom::Start(1))?;
+             self.write_header(&mut writer, &header)?;
+             writer.flush()?;
+             Ok(())
+         })
+         .map_err(|err| {
+             match err {
+                 Error::UnknownIOError(e) => Error::DatFileIO(self.path.clone(), e),
+                 e => e,
+             }
+         })
+    }
+
     pub fn read_into(&mut self, server: &Server) -> Result<()> {
         let mut version = [0; 1];
         let mut size_buf = [0; 8];
@@ -266,6 +383,64 @@ impl DatFile {
 
     fn member_offset(&self) -> u64 { 1 + self.header_size }
 
+    /// Read the entry at `offset`, which must fit in `bytes` and be a rumor of the given kind.
+    fn read_entry(&self, bytes: &[u8], offset: u64, section: RumorType) -> Result<DatFileEntry> {
+        let start = offset as usize + 8;
+        if start > bytes.len() {
+            return Err(self.invalid(format!("the length of the entry at byte {} overruns the \
+                                             {} section",
+                                            offset, section)));
+        }
+        let len = LittleEndian::read_u64(&bytes[offset as usize..start]);
+        if len > (bytes.len() - start) as u64 {
+            return Err(self.invalid(format!("the {} byte entry at byte {} overruns the {} \
+                                             section",
+                                            len, offset, section)));
+        }
+        let rumor_bytes = &bytes[start..start + len as usize];
+        // Check the kind of rumor before decoding the rest of it; fake rumors can't be decoded.
+        let proto = match ProtoRumor::decode(rumor_bytes) {
+            Ok(proto) => proto,
+            Err(err) => {
+                let reason = format!("the entry at byte {} is not a rumor: {}", offset, err);
+                return Err(self.invalid(reason));
+            }
+        };
+        if proto.r#type != section as i32 {
+            return Err(self.invalid(format!("the entry at byte {} in the {} section is not a \
+                                             {} rumor",
+                                            offset, section, section)));
+        }
+        match RumorEnvelope::decode(rumor_bytes) {
+            Ok(rumor) => {
+                Ok(DatFileEntry { offset,
+                                  rumor,
+                                  bytes: rumor_bytes.to_vec() })
+            }
+            Err(err) => {
+                let reason = format!("the {} rumor at byte {} is invalid: {}",
+                                     section, offset, err);
+                Err(self.invalid(reason))
+            }
+        }
+    }
+
+    fn write_entry<W>(&self, writer: &mut W, bytes: &[u8]) -> Result<u64>
+        where W: Write
+    {
+        let mut len_buf = [0; 8];
+        LittleEndian::write_u64(&mut len_buf, bytes.len() as u64);
+        writer.write_all(&len_buf)
+              .and_then(|_| writer.write_all(bytes))
+              .map_err(|err| Error::DatFileIO(self.path.clone(), err))?;
+        Ok((len_buf.len() + bytes.len()) as u64)
+    }
+
+    fn invalid(&self, reason: String) -> Error {
+        Error::BadDatFile(self.path.clone(),
+                          io::Error::new(io::ErrorKind::InvalidData, reason))
+    }
+
     #[allow(dead_code)]
     fn service_offset(&self) -> u64 { self.member_offset() + self.header.member_len }
 
@@ -356,11 +531,37 @@ impl DatFile {
     }
 }
 
+/// Everything in a DAT file, as read by `DatFile::read_contents`.
+#[derive(Debug, Serialize)]
+pub struct DatFileContents {
+    pub version:     u8,
+    pub header_size: u64,
+    pub header:      Header,
+    pub entries:     Vec<DatFileEntry>,
+}
+
+/// A rumor in a DAT file, and where in the file it was read from.
+#[derive(Debug, Serialize)]
+pub struct DatFileEntry {
+    pub offset: u64,
+    pub rumor:  RumorEnvelope,
+    /// The rumor as it was encoded in the file, so it can be written back unchanged.
+    #[serde(skip)]
+    bytes:      Vec<u8>,
+}
+
+impl DatFileEntry {
+    /// How many bytes the rumor takes up in the file, not counting its length.
+    pub fn len(&self) -> u64 { self.bytes.len() as u64 }
+
+    pub fn is_empty(&self) -> bool { self.bytes.is_empty() }
+}
+
 /// Describes contents and structure of dat file.
 ///
 /// The information in this header is used to enable IO seeking operations on a binary dat
 /// file containing rumors exchanged by the butterfly server.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
 pub struct Header {
     pub member_len:         u64,
     pub service_len:        u64,
@@ -420,6 +621,35 @@ impl Header {
         }
     }
 
+    /// The length of the section holding the given kind of rumor.
+    pub fn section_len(&self, rumor_type: RumorType) -> u64 {
+        match rumor_type {
+            RumorType::Member => self.member_len,
+            RumorType::Service => self.service_len,
+            RumorType::ServiceConfig => self.service_config_len,
+            RumorType::ServiceFile => self.service_file_len,
+            RumorType::Election => self.election_len,
+            RumorType::ElectionUpdate => self.update_len,
+            RumorType::Departure => self.departure_len,
+            RumorType::Fake | RumorType::Fake2 => 0,
+        }
+    }
+
+    fn section_len_mut(&mut self, rumor_type: RumorType) -> &mut u64 {
+        match rumor_type {
+            RumorType::Member => &mut self.member_len,
+            RumorType::Service => &mut self.service_len,
+            RumorType::ServiceConfig => &mut self.service_config_len,
+            RumorType::ServiceFile => &mut self.service_file_len,
+            RumorType::Election => &mut self.election_len,
+            RumorType::ElectionUpdate => &mut self.update_len,
+            RumorType::Departure => &mut self.departure_len,
+            RumorType::Fake | RumorType::Fake2 => {
+                unreachable!("DAT files have no section for {} rumors", rumor_type)
+            }
+        }
+    }
+
     pub fn write_to_bytes(&self) -> Result<Vec<u8>> {
         // The header is the size of the struct plus 8 bytes for the length of the header itself.
         let header_size = mem::size_of::<Self>() + 8;
@@ -441,7 +671,88 @@ mod tests {
     use std::mem;
 
     use super::*;
+    use crate::member::{Health,
+                        Member};
+    use habitat_core::service::ServiceGroup;
     use rand;
+    use tempfile::TempDir;
+
+    fn entry<T>(rumor: &T) -> DatFileEntry
+        where T: Message<ProtoRumor>
+    {
+        let bytes = rumor.write_to_bytes().unwrap();
+        DatFileEntry { offset: 0,
+                       rumor: RumorEnvelope::decode(&bytes).unwrap(),
+                       bytes }
+    }
+
+    fn write_dat_file(tmpdir: &TempDir) -> PathBuf {
+        let path = tmpdir.path().join("test.rst");
+        let service_group = ServiceGroup::new(None, "witcher", "prod", None).unwrap();
+        let membership = Membership { member: Member::default(),
+                                      health: Health::Alive, };
+        let service_config = ServiceConfig::new("ciri", service_group, b"sword = true".to_vec());
+        let entries = [entry(&Departure::new("geralt")),
+                       entry(&service_config),
+                       entry(&membership)];
+        DatFile::from_path(&path).write_entries(&entries).unwrap();
+        path
+    }
+
+    #[test]
+    fn entries_are_written_to_their_sections_and_read_back() {
+        let tmpdir = TempDir::new().unwrap();
+        let contents = DatFile::from_path(write_dat_file(&tmpdir)).read_contents()
+                                                                  .unwrap();
+        let types: Vec<RumorType> = contents.entries.iter().map(|e| e.rumor.r#type).collect();
+        assert_eq!(types,
+                   vec![RumorType::Member,
+                        RumorType::ServiceConfig,
+                        RumorType::Departure]);
+        assert_eq!(contents.entries[0].offset, 1 + contents.header_size);
+        assert_eq!(contents.header.departure_len, 8 + contents.entries[2].len());
+        assert_eq!(contents.header.service_len, 0);
+    }
+
+    #[test]
+    fn truncated_files_are_invalid() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = write_dat_file(&tmpdir);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.pop();
+        fs::write(&path, &bytes).unwrap();
+        match DatFile::from_path(&path).read_contents() {
+            Err(Error::BadDatFile(..)) => (),
+            other => panic!("Expected a bad DAT file, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn trailing_bytes_are_invalid() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = write_dat_file(&tmpdir);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.extend_from_slice(&[0; 8]);
+        fs::write(&path, &bytes).unwrap();
+        match DatFile::from_path(&path).read_contents() {
+            Err(Error::BadDatFile(..)) => (),
+            other => panic!("Expected a bad DAT file, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn overflowing_section_lengths_are_invalid() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = write_dat_file(&tmpdir);
+        let mut bytes = fs::read(&path).unwrap();
+        // The service section's length follows the version, the header size and the member length.
+        LittleEndian::write_u64(&mut bytes[17..25], u64::max_value() - 8);
+        fs::write(&path, &bytes).unwrap();
+        match DatFile::from_path(&path).read_contents() {
+            Err(Error::BadDatFile(..)) => (),
+            other => panic!("Expected a bad DAT file, got {:?}", other),
+        }
+    }
 
     #[test]
     fn read_write_header() {
@@ -21,6 +21,9 @@
 //! It uses a particular variant I think of as the "highlander" model. A given election will
 //! devolve to a single, universal rumor, which when it is received by the winner will result in
 //! the election finishing. There can, in the end, be only one.
+//!
+//! Candidates are ranked by whether they are a sticky incumbent, then by the leader priority
+//! declared in their service spec, then by their suitability, with the member id breaking ties.
 
 use std::ops::{Deref,
                DerefMut};
@@ -55,6 +58,10 @@ pub struct Election {
     pub suitability:   u64,
     pub status:        ElectionStatus,
     pub votes:         Vec<String>,
+    /// The leader priority declared in the candidate's service spec.
+    pub priority:      u64,
+    /// Whether the candidate led the previous term and asks to keep leading while it's healthy.
+    pub incumbent:     bool,
 }
 
 impl Election {
@@ -78,7 +85,9 @@ impl Election {
                    } else {
                        ElectionStatus::NoQuorum
                    },
-                   votes: vec![from_id] }
+                   votes: vec![from_id],
+                   priority: 0,
+                   incumbent: false }
     }
 
     /// Insert a vote for the election.
@@ -103,6 +112,9 @@ impl Election {
 
     /// Sets the status of the election to "NoQuorum"
     pub fn no_quorum(&mut self) { self.status = ElectionStatus::NoQuorum; }
+
+    /// How this election's candidate ranks against another's; the higher one wins.
+    fn rank(&self) -> (bool, u64, u64) { (self.incumbent, self.priority, self.suitability) }
 }
 
 impl ElectionRumor for Election {
@@ -119,6 +131,8 @@ impl PartialEq for Election {
         self.service_group == other.service_group
         && self.member_id == other.member_id
         && self.suitability == other.suitability
+        && self.priority == other.priority
+        && self.incumbent == other.incumbent
         && self.votes == other.votes
         && self.status == other.status
         && self.term == other.term
@@ -142,7 +156,9 @@ impl FromProto<ProtoRumor> for Election {
                       status:        payload.status
                                             .and_then(ElectionStatus::from_i32)
                                             .unwrap_or(ElectionStatus::Running),
-                      votes:         payload.votes, })
+                      votes:         payload.votes,
+                      priority:      payload.priority.unwrap_or(0),
+                      incumbent:     payload.incumbent.unwrap_or(false), })
     }
 }
 
@@ -153,7 +169,9 @@ impl From<Election> for newscast::Election {
                              term:          Some(value.term),
                              suitability:   Some(value.suitability),
                              status:        Some(value.status as i32),
-                             votes:         value.votes, }
+                             votes:         value.votes,
+                             priority:      Some(value.priority),
+                             incumbent:     Some(value.incumbent), }
     }
 }
 
@@ -175,13 +193,13 @@ impl Rumor for Election {
         } else if self.term > other.term {
             debug!("stored rumor represents a newer term than received; keep sharing it");
             true
-        } else if self.suitability > other.suitability {
-            debug!("stored rumor is more suitable; take received rumor's votes and share");
+        } else if self.rank() > other.rank() {
+            debug!("stored rumor ranks higher; take received rumor's votes and share");
             self.steal_votes(&mut other);
             true
-        } else if other.suitability > self.suitability {
-            debug!("received rumor is more suitable; take stored rumor's votes, replace stored \
-                    and share");
+        } else if other.rank() > self.rank() {
+            debug!("received rumor ranks higher; take stored rumor's votes, replace stored and \
+                    share");
             other.steal_votes(self);
             *self = other;
             true
@@ -361,4 +379,27 @@ mod tests {
         assert_eq!(e1.member_id, "d");
         assert_eq!(e1.votes.len(), 4);
     }
+
+    #[test]
+    fn merge_priority_ranks_above_suitability() {
+        let mut e1 = create_election("a", 10);
+        let mut e2 = create_election("b", 0);
+        e2.priority = 1;
+        let e3 = create_election("c", 20);
+        assert_eq!(e1.merge(e2), true);
+        assert_eq!(e1.merge(e3), true);
+        assert_eq!(e1.member_id, "b");
+        assert_eq!(e1.votes.len(), 3);
+    }
+
+    #[test]
+    fn merge_incumbent_ranks_above_priority() {
+        let mut e1 = create_election("a", 0);
+        e1.incumbent = true;
+        let mut e2 = create_election("b", 10);
+        e2.priority = 10;
+        assert_eq!(e1.merge(e2), true);
+        assert_eq!(e1.member_id, "a");
+        assert_eq!(e1.votes.len(), 2);
+    }
 }
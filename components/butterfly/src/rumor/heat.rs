@@ -192,6 +192,14 @@ impl RumorHeat {
         }
         debug!("Purged {} heat count entries for {:?}", count, id);
     }
+
+    /// Stop tracking rumors that were garbage collected; there's nothing left to share.
+    pub fn forget(&self, rumors: &[RumorKey]) {
+        let mut heat_map = self.0.write().expect("RumorHeat lock poisoned");
+        for rumor in rumors {
+            heat_map.remove(rumor);
+        }
+    }
 }
 
 impl Default for RumorHeat {
@@ -67,6 +67,10 @@ lazy_static! {
         register_int_counter_vec!("hab_butterfly_ignored_rumor_total",
                                   "How many rumors we ignore",
                                   &["rumor"]).unwrap();
+    static ref EXPIRED_RUMOR_COUNT: IntCounterVec =
+        register_int_counter_vec!("hab_butterfly_expired_rumor_total",
+                                  "How many rumors we garbage collect",
+                                  &["rumor"]).unwrap();
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -80,6 +84,33 @@ pub enum RumorKind {
     ServiceFile(ServiceFile),
 }
 
+impl RumorKind {
+    /// The service group this rumor is about; members and departures aren't about one.
+    pub fn service_group(&self) -> Option<&str> {
+        match *self {
+            RumorKind::Departure(_) | RumorKind::Membership(_) => None,
+            RumorKind::Election(ref election) => Some(election.key()),
+            RumorKind::ElectionUpdate(ref election) => Some(election.key()),
+            RumorKind::Service(ref service) => Some(service.key()),
+            RumorKind::ServiceConfig(ref service_config) => Some(service_config.key()),
+            RumorKind::ServiceFile(ref service_file) => Some(service_file.key()),
+        }
+    }
+
+    /// The member this rumor is about: the member itself, its departure, or a service it runs.
+    pub fn member_id(&self) -> Option<&str> {
+        match *self {
+            RumorKind::Departure(ref departure) => Some(departure.id()),
+            RumorKind::Membership(ref membership) => Some(&membership.member.id),
+            RumorKind::Service(ref service) => Some(service.id()),
+            RumorKind::Election(_)
+            | RumorKind::ElectionUpdate(_)
+            | RumorKind::ServiceConfig(_)
+            | RumorKind::ServiceFile(_) => None,
+        }
+    }
+}
+
 impl From<RumorKind> for RumorPayload {
     fn from(value: RumorKind) -> Self {
         match value {
@@ -366,6 +397,32 @@ impl<T> RumorStore<T> where T: Rumor
         list.get_mut(key).and_then(|r| r.remove(id));
     }
 
+    /// Remove every rumor that satisfies the predicate, along with any key left without rumors.
+    /// Returns the keys of the removed rumors.
+    pub fn remove_where<P>(&self, mut predicate: P) -> Vec<RumorKey>
+        where P: FnMut(&T) -> bool
+    {
+        let mut removed = vec![];
+        let mut list = self.list.write().expect("Rumor store lock poisoned");
+        for rumors in list.values_mut() {
+            rumors.retain(|_, rumor| {
+                      if predicate(rumor) {
+                          removed.push(RumorKey::from(&*rumor));
+                          false
+                      } else {
+                          true
+                      }
+                  });
+        }
+        list.retain(|_, rumors| !rumors.is_empty());
+        if let Some(rumor) = removed.first() {
+            EXPIRED_RUMOR_COUNT.with_label_values(&[&rumor.kind.to_string()])
+                               .inc_by(removed.len() as i64);
+            self.increment_update_counter();
+        }
+        removed
+    }
+
     pub fn with_keys<F>(&self, mut with_closure: F)
         where F: FnMut((&String, &HashMap<String, T>))
     {
@@ -600,7 +657,9 @@ mod tests {
     mod rumor_store {
         use super::FakeRumor;
         use crate::rumor::{Rumor,
-                           RumorStore};
+                           RumorKey,
+                           RumorStore,
+                           RumorType};
         use std::usize;
 
         fn create_rumor_store() -> RumorStore<FakeRumor> { RumorStore::default() }
@@ -689,6 +748,27 @@ mod tests {
             rs.assert_rumor_is(&key, &member_id, |o| o.id == member_id);
         }
 
+        #[test]
+        fn remove_where_removes_matching_rumors_and_empty_keys() {
+            let rs = create_rumor_store();
+            let f1 = FakeRumor::default();
+            let f1_id = f1.id.clone();
+            let key = f1.key.clone();
+            let f2 = FakeRumor::default();
+            rs.insert(f1);
+            rs.insert(f2);
+            let counter = rs.get_update_counter();
+
+            let removed = rs.remove_where(|r| r.id == f1_id);
+            assert_eq!(removed, vec![RumorKey::new(RumorType::Fake, &f1_id, &key)]);
+            assert!(!rs.contains_rumor(&key, &f1_id));
+            assert_eq!(rs.len_for_key(&key), 1);
+            assert_eq!(rs.get_update_counter(), counter + 1);
+
+            assert_eq!(rs.remove_where(|_| true).len(), 1);
+            assert!(rs.list.read().unwrap().is_empty());
+        }
+
         #[test]
         fn with_rumor_calls_closure_with_none_if_rumor_missing() {
             let rs = create_rumor_store();
@@ -40,13 +40,19 @@ use crate::{error::{Error,
 
 #[derive(Debug, Clone)]
 pub struct Service {
-    pub member_id:     String,
-    pub service_group: ServiceGroup,
-    pub incarnation:   u64,
-    pub initialized:   bool,
-    pub pkg:           String,
-    pub cfg:           Vec<u8>,
-    pub sys:           SysInfo,
+    pub member_id:          String,
+    pub service_group:      ServiceGroup,
+    pub incarnation:        u64,
+    pub initialized:        bool,
+    pub pkg:                String,
+    pub cfg:                Vec<u8>,
+    pub sys:                SysInfo,
+    /// The release this member rolled back from, if its last update failed its health check.
+    pub rolled_back:        Option<String>,
+    /// The result of this member's most recent health check, if it has run one.
+    pub health_check:       Option<String>,
+    /// When `health_check` last changed to its current value, in seconds since the Unix epoch.
+    pub health_check_since: Option<i64>,
 }
 
 // Ensures that `cfg` is rendered as a map, and not an array of bytes
@@ -54,7 +60,7 @@ impl Serialize for Service {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
         where S: Serializer
     {
-        let mut strukt = serializer.serialize_struct("service", 7)?;
+        let mut strukt = serializer.serialize_struct("service", 10)?;
         let cfg: toml::value::Table = toml::from_slice(&self.cfg).unwrap_or_default();
         strukt.serialize_field("member_id", &self.member_id)?;
         strukt.serialize_field("service_group", &self.service_group)?;
@@ -63,6 +69,9 @@ impl Serialize for Service {
         strukt.serialize_field("cfg", &cfg)?;
         strukt.serialize_field("sys", &self.sys)?;
         strukt.serialize_field("initialized", &self.initialized)?;
+        strukt.serialize_field("rolled_back", &self.rolled_back)?;
+        strukt.serialize_field("health_check", &self.health_check)?;
+        strukt.serialize_field("health_check_since", &self.health_check_since)?;
         strukt.end()
     }
 }
@@ -108,6 +117,9 @@ impl Service {
                   initialized: false,
                   pkg: package.to_string(),
                   sys,
+                  rolled_back: None,
+                  health_check: None,
+                  health_check_since: None,
                   cfg: cfg.map(|v| {
                               // Directly serializing a toml::value::Table can lead to an error
                               // Wrapping it in a toml::value::Value makes this operation safe
@@ -127,31 +139,37 @@ impl FromProto<newscast::Rumor> for Service {
             RumorPayload::Service(payload) => payload,
             _ => panic!("from-bytes service"),
         };
-        Ok(Service { member_id:     payload.member_id
-                                           .ok_or(Error::ProtocolMismatch("member-id"))?,
+        Ok(Service { member_id:          payload.member_id
+                                                .ok_or(Error::ProtocolMismatch("member-id"))?,
                      service_group:
                          payload.service_group
                                 .ok_or(Error::ProtocolMismatch("service-group"))
                                 .and_then(|s| ServiceGroup::from_str(&s).map_err(Error::from))?,
-                     incarnation:   payload.incarnation.unwrap_or(0),
-                     initialized:   payload.initialized.unwrap_or(false),
-                     pkg:           payload.pkg.ok_or(Error::ProtocolMismatch("pkg"))?,
-                     cfg:           payload.cfg.unwrap_or_default(),
-                     sys:           payload.sys
-                                           .ok_or(Error::ProtocolMismatch("sys"))
-                                           .and_then(SysInfo::from_proto)?, })
+                     incarnation:        payload.incarnation.unwrap_or(0),
+                     initialized:        payload.initialized.unwrap_or(false),
+                     pkg:                payload.pkg.ok_or(Error::ProtocolMismatch("pkg"))?,
+                     cfg:                payload.cfg.unwrap_or_default(),
+                     sys:                payload.sys
+                                                .ok_or(Error::ProtocolMismatch("sys"))
+                                                .and_then(SysInfo::from_proto)?,
+                     rolled_back:        payload.rolled_back,
+                     health_check:       payload.health_check,
+                     health_check_since: payload.health_check_since, })
     }
 }
 
 impl From<Service> for newscast::Service {
     fn from(value: Service) -> Self {
-        newscast::Service { member_id:     Some(value.member_id),
-                            service_group: Some(value.service_group.to_string()),
-                            incarnation:   Some(value.incarnation),
-                            initialized:   Some(value.initialized),
-                            pkg:           Some(value.pkg),
-                            cfg:           Some(value.cfg),
-                            sys:           Some(value.sys.into()), }
+        newscast::Service { member_id:          Some(value.member_id),
+                            service_group:      Some(value.service_group.to_string()),
+                            incarnation:        Some(value.incarnation),
+                            initialized:        Some(value.initialized),
+                            pkg:                Some(value.pkg),
+                            cfg:                Some(value.cfg),
+                            sys:                Some(value.sys.into()),
+                            rolled_back:        value.rolled_back,
+                            health_check:       value.health_check,
+                            health_check_since: value.health_check_since, }
     }
 }
 
@@ -42,6 +42,9 @@ pub struct ServiceConfig {
     pub incarnation:   u64,
     pub encrypted:     bool,
     pub config:        Vec<u8>, // TODO: make this a String
+    /// Name with revision of the key that `signature` was made with, if the config is signed.
+    pub signer:        Option<String>,
+    pub signature:     Option<Vec<u8>>,
 }
 
 impl PartialOrd for ServiceConfig {
@@ -60,6 +63,8 @@ impl PartialEq for ServiceConfig {
         && self.incarnation == other.incarnation
         && self.encrypted == other.encrypted
         && self.config == other.config
+        && self.signer == other.signer
+        && self.signature == other.signature
     }
 }
 
@@ -72,7 +77,9 @@ impl ServiceConfig {
                         service_group,
                         incarnation: 0,
                         encrypted: false,
-                        config }
+                        config,
+                        signer: None,
+                        signature: None }
     }
 
     pub fn encrypt(&mut self, user_pair: &BoxKeyPair, service_pair: &BoxKeyPair) -> Result<()> {
@@ -129,7 +136,9 @@ impl FromProto<ProtoRumor> for ServiceConfig {
                                       })?,
                            incarnation:   payload.incarnation.unwrap_or(0),
                            encrypted:     payload.encrypted.unwrap_or(false),
-                           config:        payload.config.unwrap_or_default(), })
+                           config:        payload.config.unwrap_or_default(),
+                           signer:        payload.signer,
+                           signature:     payload.signature, })
     }
 }
 
@@ -138,7 +147,9 @@ impl From<ServiceConfig> for newscast::ServiceConfig {
         newscast::ServiceConfig { service_group: Some(value.service_group.to_string()),
                                   incarnation:   Some(value.incarnation),
                                   encrypted:     Some(value.encrypted),
-                                  config:        Some(value.config), }
+                                  config:        Some(value.config),
+                                  signer:        value.signer,
+                                  signature:     value.signature, }
     }
 }
 
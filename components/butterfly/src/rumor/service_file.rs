@@ -41,6 +41,9 @@ pub struct ServiceFile {
     pub encrypted:     bool,
     pub filename:      String,
     pub body:          Vec<u8>, // TODO: make this a String
+    /// Name with revision of the key that `signature` was made with, if the file is signed.
+    pub signer:        Option<String>,
+    pub signature:     Option<Vec<u8>>,
 }
 
 impl PartialOrd for ServiceFile {
@@ -60,6 +63,8 @@ impl PartialEq for ServiceFile {
         && self.encrypted == other.encrypted
         && self.filename == other.filename
         && self.body == other.body
+        && self.signer == other.signer
+        && self.signature == other.signature
     }
 }
 
@@ -78,7 +83,9 @@ impl ServiceFile {
                       incarnation: 0,
                       encrypted: false,
                       filename: filename.into(),
-                      body }
+                      body,
+                      signer: None,
+                      signature: None }
     }
 
     /// Encrypt the contents of the service file
@@ -122,7 +129,9 @@ impl FromProto<ProtoRumor> for ServiceFile {
                          encrypted:     payload.encrypted.unwrap_or(false),
                          filename:      payload.filename
                                                .ok_or(Error::ProtocolMismatch("filename"))?,
-                         body:          payload.body.unwrap_or_default(), })
+                         body:          payload.body.unwrap_or_default(),
+                         signer:        payload.signer,
+                         signature:     payload.signature, })
     }
 }
 
@@ -132,7 +141,9 @@ impl From<ServiceFile> for newscast::ServiceFile {
                                 incarnation:   Some(value.incarnation),
                                 encrypted:     Some(value.encrypted),
                                 filename:      Some(value.filename),
-                                body:          Some(value.body), }
+                                body:          Some(value.body),
+                                signer:        value.signer,
+                                signature:     value.signature, }
     }
 }
 
@@ -15,10 +15,24 @@
 //! Periodically check membership rumors to automatically "time out"
 //! `Suspect` rumors to `Confirmed`, and `Confirmed` rumors to
 //! `Departed`.
+//!
+//! Service rumors of members that have departed, or been `Confirmed`
+//! for longer than the rumor expiry, are garbage collected along the
+//! way, as are the configuration and file rumors of service groups
+//! that have had no members for that long, after members have left
+//! them.
 
-use std::{thread,
+use std::{collections::HashMap,
+          num::ParseIntError,
+          result,
+          str::FromStr,
+          thread,
           time::Duration};
 
+use habitat_core::env::Config as EnvConfig;
+use time::{Duration as TimeDuration,
+           SteadyTime};
+
 use crate::{rumor::{RumorKey,
                     RumorType},
             server::{timing::Timing,
@@ -26,20 +40,65 @@ use crate::{rumor::{RumorKey,
 
 const LOOP_DELAY_MS: u64 = 500;
 
+/// How many rumor expiries the tombstones of collected rumors are
+/// kept for. Other members collect their copies after one expiry, so
+/// by then none should still be gossiping them.
+const TOMBSTONE_EXPIRIES: i32 = 4;
+
+/// How long, in seconds, the rumors of a service are kept once its
+/// member is confirmed dead, or once its service group has no members
+/// left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RumorExpiry(TimeDuration);
+
+impl Default for RumorExpiry {
+    /// One hour.
+    fn default() -> Self { RumorExpiry(TimeDuration::hours(1)) }
+}
+
+impl FromStr for RumorExpiry {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let raw = s.parse::<u32>()?;
+        Ok(RumorExpiry(TimeDuration::seconds(i64::from(raw))))
+    }
+}
+
+impl EnvConfig for RumorExpiry {
+    const ENVVAR: &'static str = "HAB_RUMOR_EXPIRY_SECS";
+}
+
 pub struct Expire {
     pub server: Server,
     pub timing: Timing,
+    /// When each service group with configuration or file rumors was
+    /// first seen without members.
+    orphaned:   HashMap<String, SteadyTime>,
 }
 
 impl Expire {
-    pub fn new(server: Server, timing: Timing) -> Expire { Expire { server, timing } }
+    pub fn new(server: Server, timing: Timing) -> Expire {
+        Expire { server,
+                 timing,
+                 orphaned: HashMap::new() }
+    }
 
-    pub fn run(&self) {
+    pub fn run(&mut self) {
         loop {
+            let suspicion_timeout = self.timing.suspicion_timeout_duration();
+            let members = self.server.member_list.len();
             let newly_confirmed_members =
                 self.server
                     .member_list
-                    .members_expired_to_confirmed(self.timing.suspicion_timeout_duration());
+                    .members_expired_to_confirmed_by(|id| {
+                        self.server
+                            .lifeguard
+                            .suspicion_timeout(id, suspicion_timeout, members)
+                    });
+            self.server
+                .lifeguard
+                .end_suspicions(|id| self.server.member_list.health_of_by_id(id));
 
             for id in newly_confirmed_members {
                 self.server
@@ -59,7 +118,37 @@ impl Expire {
                     .start_hot_rumor(RumorKey::new(RumorType::Member, &id, ""));
             }
 
+            let expiry = RumorExpiry::configured_value().0;
+            let expired =
+                self.server.expire_service_rumors(expiry) + self.tombstone_orphans(expiry);
+            if expired > 0 {
+                debug!("Garbage collected {} rumors", expired);
+                // Compact the rumor file right away, rather than on the next persist.
+                self.server.persist_data();
+            }
+            self.server.expire_tombstones(expiry * TOMBSTONE_EXPIRIES);
+
             thread::sleep(Duration::from_millis(LOOP_DELAY_MS));
         }
     }
+
+    /// Tombstone the configuration and file rumors of service groups
+    /// that have had no members for longer than `expiry`, returning
+    /// how many rumors were removed.
+    fn tombstone_orphans(&mut self, expiry: TimeDuration) -> usize {
+        let now = SteadyTime::now();
+        let orphaned_groups = self.server.orphaned_service_groups();
+        // Groups that gained a member again start over.
+        self.orphaned
+            .retain(|group, _| orphaned_groups.contains(group));
+        let mut removed = 0;
+        for group in orphaned_groups {
+            let orphaned_at = *self.orphaned.entry(group.clone()).or_insert(now);
+            if now >= orphaned_at + expiry {
+                removed += self.server.tombstone_service_group(&group);
+                self.orphaned.remove(&group);
+            }
+        }
+        removed
+    }
 }
@@ -24,12 +24,16 @@ use std::{net::{SocketAddr,
 use habitat_core::util::ToI64;
 use prometheus::{IntCounterVec,
                  IntGaugeVec};
+use time::SteadyTime;
 
 use super::AckSender;
-use crate::{member::Health,
+use crate::{member::{Health,
+                     Membership},
             server::{outbound,
+                     timing::Timing,
                      Server},
             swim::{Ack,
+                   Nack,
                    Ping,
                    PingReq,
                    Swim,
@@ -47,19 +51,41 @@ lazy_static! {
                                 &["type", "mode"]).unwrap();
 }
 
+/// How often `run_nacks` looks for pingreqs to nack.
+const NACK_LOOP_DELAY_MS: u64 = 100;
+
+/// Send the nacks for the pingreqs whose targets didn't ack in time. This one thread waits on
+/// every pingreq we relay, however many we are asked to.
+pub fn run_nacks(server: &Server, socket: &UdpSocket) {
+    loop {
+        if !server.paused() {
+            for (requester, target) in server.lifeguard.overdue_relays(SteadyTime::now()) {
+                outbound::nack(server, socket, &requester, &target);
+            }
+        }
+        thread::sleep(Duration::from_millis(NACK_LOOP_DELAY_MS));
+    }
+}
+
 /// Takes the Server and a channel to send received Acks to the outbound thread.
 pub struct Inbound {
     pub server:      Server,
     pub socket:      UdpSocket,
     pub tx_outbound: AckSender,
+    pub timing:      Timing,
 }
 
 impl Inbound {
     /// Create a new Inbound.
-    pub fn new(server: Server, socket: UdpSocket, tx_outbound: AckSender) -> Inbound {
+    pub fn new(server: Server,
+               socket: UdpSocket,
+               tx_outbound: AckSender,
+               timing: Timing)
+               -> Inbound {
         Inbound { server,
                   socket,
-                  tx_outbound }
+                  tx_outbound,
+                  timing }
     }
 
     /// Run the thread. Listens for messages up to 1k in size, and then processes them accordingly.
@@ -139,6 +165,14 @@ impl Inbound {
                             }
                             self.process_pingreq(addr, pingreq);
                         }
+                        SwimKind::Nack(nack) => {
+                            if self.server.is_member_blocked(&nack.from.id) {
+                                debug!("Not processing message from {} - it is blocked",
+                                       nack.from.id);
+                                continue;
+                            }
+                            self.process_nack(addr, nack);
+                        }
                     }
                 }
                 Err(e) => {
@@ -167,6 +201,7 @@ impl Inbound {
         trace_it!(SWIM: &self.server, TraceKind::RecvPingReq, &msg.from.id, addr, &msg);
         msg.from.address = addr.ip().to_string();
         let id = msg.target.id.clone(); // TODO: see if we can eliminate this clone
+        let mut pinged = false;
         self.server.member_list.with_member(&id, |target| {
                                    if let Some(target) = target {
                                        // Set the route-back address to the one we received the
@@ -176,10 +211,29 @@ impl Inbound {
                                                       target,
                                                       target.swim_socket_address(),
                                                       Some(&msg.from));
+                                       pinged = true;
                                    } else {
                                        error!("PingReq request {:?} for invalid target", msg);
                                    }
                                });
+        if pinged && msg.nack {
+            self.nack_unless_acked(msg);
+        }
+    }
+
+    /// Send a nack back to the member that asked us to ping a target, if the target hasn't acked
+    /// by the time our own ping would have timed out. The nack is sent by `run_nacks`.
+    fn nack_unless_acked(&self, msg: PingReq) {
+        self.server
+            .lifeguard
+            .relaying(&msg.target, &msg.from, self.timing.ping_timeout());
+    }
+
+    /// Process nack messages; counted by the outbound thread once its probe is over.
+    fn process_nack(&self, addr: SocketAddr, msg: Nack) {
+        trace_it!(SWIM: &self.server, TraceKind::RecvNack, &msg.from.id, addr, &msg);
+        trace!("Nack from {}@{} for {}", msg.from.id, addr, msg.target.id);
+        self.server.lifeguard.nacked(&msg.target.id);
     }
 
     /// Process ack messages; forwards to the outbound thread.
@@ -207,17 +261,16 @@ impl Inbound {
                 (forward_to_addr, addr.ip().to_string())
             };
             msg.from.address = from_addr;
+            if let Some(ref forward_to) = msg.forward_to {
+                self.server.lifeguard.relayed(&msg.from.id, &forward_to.id);
+            }
             outbound::forward_ack(&self.server, &self.socket, forward_to_addr, msg);
             return;
         }
+        let from_id = msg.from.id.clone();
         let memberships = msg.membership.clone();
         match self.tx_outbound.send((addr, msg)) {
-            Ok(()) => {
-                for membership in memberships {
-                    self.server
-                        .insert_member_from_rumor(membership.member, membership.health);
-                }
-            }
+            Ok(()) => self.insert_memberships(&from_id, memberships.into_iter()),
             Err(e) => panic!("Outbound thread has died - this shouldn't happen: #{:?}", e),
         }
     }
@@ -225,18 +278,28 @@ impl Inbound {
     /// Process ping messages.
     fn process_ping(&self, addr: SocketAddr, mut msg: Ping) {
         trace_it!(SWIM: &self.server, TraceKind::RecvPing, &msg.from.id, addr, &msg);
+        // With Lifeguard, take in the rumors first: if the member pinging us suspects us, the
+        // ack then already carries our refutation back to it.
+        if self.server.lifeguard.enabled() {
+            self.insert_memberships(&msg.from.id, msg.membership.drain(..));
+        }
         outbound::ack(&self.server, &self.socket, &msg.from, addr, msg.forward_to);
         // Populate the member for this sender with its remote address
         msg.from.address = addr.ip().to_string();
         trace!("Ping from {}@{}", msg.from.id, addr);
+        let from_id = msg.from.id.clone();
         if msg.from.departed {
             self.server.insert_member(msg.from, Health::Departed);
         } else {
             self.server.insert_member(msg.from, Health::Alive);
         }
-        for membership in msg.membership {
+        self.insert_memberships(&from_id, msg.membership.into_iter());
+    }
+
+    fn insert_memberships(&self, from_id: &str, memberships: impl Iterator<Item = Membership>) {
+        for membership in memberships {
             self.server
-                .insert_member_from_rumor(membership.member, membership.health);
+                .insert_member_from_rumor(from_id, membership.member, membership.health);
         }
     }
 }
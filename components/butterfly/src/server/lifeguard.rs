@@ -0,0 +1,407 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Lifeguard extensions to the SWIM failure detector.
+//!
+//! Lifeguard (https://arxiv.org/abs/1707.00788) keeps a slow or overloaded member from wrongly
+//! suspecting its healthy peers:
+//!
+//! * A local health multiplier counts recent evidence that *we* are the unhealthy one - probes that
+//!   went unanswered, pingreq helpers that didn't nack, and suspicions about ourselves we had to
+//!   refute - and stretches our probe timeouts by it.
+//! * A suspicion starts out with a long timeout, which shrinks towards the usual one as other
+//!   members independently report the same suspicion.
+//! * Probes always carry the target's membership, so a suspect hears of the suspicion from the
+//!   member probing it. With Lifeguard, it takes that in before acking, so the ack already carries
+//!   its refutation.
+//!
+//! Suspicions are tracked whether or not Lifeguard is enabled, so that the rate of false
+//! positives can be compared before turning it on.
+
+use std::{collections::{HashMap,
+                        HashSet},
+          sync::{atomic::{AtomicBool,
+                          AtomicUsize,
+                          Ordering},
+                 Mutex}};
+
+use habitat_core::util::ToI64;
+use prometheus::{IntCounter,
+                 IntCounterVec,
+                 IntGauge};
+use time::{Duration,
+           SteadyTime};
+
+use crate::member::{Health,
+                    Incarnation,
+                    Member};
+
+/// The local health score never goes above this, so probe timeouts are stretched at most
+/// `MAX_LOCAL_HEALTH + 1` times.
+const MAX_LOCAL_HEALTH: usize = 8;
+
+/// How many times longer than the usual suspicion timeout an unconfirmed suspicion lasts.
+const SUSPICION_MAX_TIMEOUT_MULT: i32 = 6;
+
+/// How many independent confirmations bring a suspicion down to the usual timeout.
+const SUSPICION_EXPECTED_CONFIRMATIONS: usize = 3;
+
+lazy_static! {
+    static ref LOCAL_HEALTH_MULTIPLIER: IntGauge =
+        register_int_gauge!("hab_butterfly_swim_local_health_multiplier",
+                            "How many times longer than usual SWIM probes wait for acks").unwrap();
+    static ref SUSPICIONS_ENDED: IntCounterVec =
+        register_int_counter_vec!("hab_butterfly_swim_suspicions_total",
+                                  "Total number of suspicions about other members, by whether \
+                                   the member was confirmed or turned out to be alive",
+                                  &["outcome"]).unwrap();
+    static ref SUSPICIONS_REFUTED: IntCounter =
+        register_int_counter!("hab_butterfly_swim_suspicions_refuted_total",
+                              "Total number of suspicions about this member it refuted").unwrap();
+}
+
+#[derive(Debug)]
+struct Suspicion {
+    incarnation:   Incarnation,
+    /// The other members we heard the suspicion from.
+    confirmations: HashSet<String>,
+}
+
+impl Suspicion {
+    fn new(incarnation: Incarnation) -> Self {
+        Suspicion { incarnation,
+                    confirmations: HashSet::new() }
+    }
+}
+
+/// A pingreq we pinged the target for, and will nack unless the target acks by `deadline`.
+#[derive(Debug)]
+struct Relay {
+    requester: Member,
+    target:    Member,
+    deadline:  SteadyTime,
+}
+
+#[derive(Debug, Default)]
+pub struct Lifeguard {
+    enabled:      AtomicBool,
+    local_health: AtomicUsize,
+    /// The members currently suspect, by ID.
+    suspicions:   Mutex<HashMap<String, Suspicion>>,
+    /// The nacks received for the member we are probing, by its ID.
+    nacks:        Mutex<HashMap<String, usize>>,
+    /// The pingreqs we pinged a target for, and haven't forwarded an ack or sent a nack for yet,
+    /// by their (target, requester) IDs.
+    relayed:      Mutex<HashMap<(String, String), Relay>>,
+}
+
+impl Lifeguard {
+    pub fn enabled(&self) -> bool { self.enabled.load(Ordering::Relaxed) }
+
+    pub fn set_enabled(&self, enabled: bool) { self.enabled.store(enabled, Ordering::Relaxed) }
+
+    /// How many times longer than usual probes should wait for acks; always 1 when Lifeguard is
+    /// disabled.
+    pub fn health_multiplier(&self) -> i64 {
+        if self.enabled() {
+            self.local_health.load(Ordering::Relaxed).to_i64() + 1
+        } else {
+            1
+        }
+    }
+
+    /// A member acked our ping directly.
+    pub fn probe_succeeded(&self) {
+        let health = self.local_health.load(Ordering::Relaxed).saturating_sub(1);
+        self.set_local_health(health);
+    }
+
+    /// Neither a member nor any pingreq helper acked, and `missed_nacks` helpers didn't nack
+    /// either.
+    pub fn probe_failed(&self, missed_nacks: usize) { self.worsen_local_health(1 + missed_nacks) }
+
+    /// Another member suspected us, or confirmed us dead, while we were alive.
+    pub fn refuted(&self) {
+        SUSPICIONS_REFUTED.inc();
+        self.worsen_local_health(1);
+    }
+
+    fn worsen_local_health(&self, by: usize) {
+        let health = self.local_health.load(Ordering::Relaxed) + by;
+        self.set_local_health(health.min(MAX_LOCAL_HEALTH));
+    }
+
+    fn set_local_health(&self, health: usize) {
+        self.local_health.store(health, Ordering::Relaxed);
+        LOCAL_HEALTH_MULTIPLIER.set(health.to_i64() + 1);
+    }
+
+    /// Record that the member `member_id` is suspect at `incarnation`, as reported by the member
+    /// `from`, or by our own probe if `None`.
+    pub fn suspect(&self, member_id: &str, incarnation: Incarnation, from: Option<&str>) {
+        let mut suspicions = self.suspicions.lock().expect("Suspicions lock poisoned");
+        let suspicion = suspicions.entry(member_id.to_string())
+                                  .or_insert_with(|| Suspicion::new(incarnation));
+        if suspicion.incarnation != incarnation {
+            *suspicion = Suspicion::new(incarnation);
+        }
+        if let Some(from) = from {
+            if from != member_id {
+                suspicion.confirmations.insert(from.to_string());
+            }
+        }
+    }
+
+    /// How long `member_id` stays suspect before it is confirmed, in a ring of `members`.
+    ///
+    /// Without Lifeguard, that is always `min`. With it, an unconfirmed suspicion lasts
+    /// `SUSPICION_MAX_TIMEOUT_MULT` times longer, and the timeout shrinks logarithmically to
+    /// `min` as confirmations come in from the other members.
+    pub fn suspicion_timeout(&self, member_id: &str, min: Duration, members: usize) -> Duration {
+        if !self.enabled() {
+            return min;
+        }
+        // Neither the suspect nor ourselves can confirm the suspicion.
+        let expected = SUSPICION_EXPECTED_CONFIRMATIONS.min(members.saturating_sub(2));
+        if expected == 0 {
+            return min;
+        }
+        let confirmations = self.suspicions
+                                .lock()
+                                .expect("Suspicions lock poisoned")
+                                .get(member_id)
+                                .map_or(0, |s| s.confirmations.len().min(expected));
+        let max = min * SUSPICION_MAX_TIMEOUT_MULT;
+        let fraction = ((confirmations + 1) as f64).ln() / ((expected + 1) as f64).ln();
+        let shrink_ms = ((max - min).num_milliseconds() as f64 * fraction) as i64;
+        max - Duration::milliseconds(shrink_ms)
+    }
+
+    /// Stop tracking the suspicions that have ended, counting how they ended.
+    pub fn end_suspicions(&self, health_of: impl Fn(&str) -> Option<Health>) {
+        // The member list is locked while suspicion timeouts are looked up, so don't look up
+        // health while holding on to the suspicions.
+        let suspects: Vec<String> = self.suspicions
+                                        .lock()
+                                        .expect("Suspicions lock poisoned")
+                                        .keys()
+                                        .cloned()
+                                        .collect();
+        let ended: Vec<String> =
+            suspects.into_iter()
+                    .filter(|member_id| {
+                        match health_of(member_id) {
+                            Some(Health::Suspect) => false,
+                            Some(Health::Alive) => {
+                                SUSPICIONS_ENDED.with_label_values(&["false_positive"])
+                                                .inc();
+                                true
+                            }
+                            Some(_) => {
+                                SUSPICIONS_ENDED.with_label_values(&["confirmed"]).inc();
+                                true
+                            }
+                            None => true,
+                        }
+                    })
+                    .collect();
+        let mut suspicions = self.suspicions.lock().expect("Suspicions lock poisoned");
+        for member_id in ended {
+            suspicions.remove(&member_id);
+        }
+    }
+
+    /// We pinged `target` because `requester` asked us to, and will nack if it doesn't ack by
+    /// `deadline`.
+    pub fn relaying(&self, target: &Member, requester: &Member, deadline: SteadyTime) {
+        let relay = Relay { requester: requester.clone(),
+                            target: target.clone(),
+                            deadline };
+        self.relayed
+            .lock()
+            .expect("Relayed pingreqs lock poisoned")
+            .insert((target.id.clone(), requester.id.clone()), relay);
+    }
+
+    /// Stop waiting for an ack from `target` to forward to `requester`, returning whether we
+    /// were still waiting.
+    pub fn relayed(&self, target: &str, requester: &str) -> bool {
+        self.relayed
+            .lock()
+            .expect("Relayed pingreqs lock poisoned")
+            .remove(&(target.to_string(), requester.to_string()))
+            .is_some()
+    }
+
+    /// Stop waiting on the pingreqs whose targets haven't acked by `now`, returning the
+    /// (requester, target) pairs to nack.
+    pub fn overdue_relays(&self, now: SteadyTime) -> Vec<(Member, Member)> {
+        let mut relayed = self.relayed.lock().expect("Relayed pingreqs lock poisoned");
+        let overdue: Vec<(String, String)> = relayed.iter()
+                                                    .filter(|(_, relay)| relay.deadline <= now)
+                                                    .map(|(key, _)| key.clone())
+                                                    .collect();
+        overdue.into_iter()
+               .filter_map(|key| relayed.remove(&key))
+               .map(|relay| (relay.requester, relay.target))
+               .collect()
+    }
+
+    /// A pingreq helper nacked our probe of `target`.
+    pub fn nacked(&self, target: &str) {
+        *self.nacks
+             .lock()
+             .expect("Nacks lock poisoned")
+             .entry(target.to_string())
+             .or_insert(0) += 1;
+    }
+
+    /// How many pingreq helpers nacked our probe of `target`, forgetting them.
+    pub fn take_nacks(&self, target: &str) -> usize {
+        self.nacks
+            .lock()
+            .expect("Nacks lock poisoned")
+            .remove(target)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lifeguard() -> Lifeguard {
+        let lifeguard = Lifeguard::default();
+        lifeguard.set_enabled(true);
+        lifeguard
+    }
+
+    #[test]
+    fn local_health_multiplier_rises_with_failures_and_falls_with_acks() {
+        let lifeguard = lifeguard();
+        assert_eq!(lifeguard.health_multiplier(), 1);
+        lifeguard.probe_failed(2);
+        assert_eq!(lifeguard.health_multiplier(), 4);
+        lifeguard.probe_succeeded();
+        assert_eq!(lifeguard.health_multiplier(), 3);
+        for _ in 0..10 {
+            lifeguard.refuted();
+        }
+        assert_eq!(lifeguard.health_multiplier(), MAX_LOCAL_HEALTH.to_i64() + 1);
+        for _ in 0..10 {
+            lifeguard.probe_succeeded();
+        }
+        assert_eq!(lifeguard.health_multiplier(), 1);
+    }
+
+    #[test]
+    fn local_health_is_ignored_when_disabled() {
+        let lifeguard = Lifeguard::default();
+        lifeguard.probe_failed(3);
+        assert_eq!(lifeguard.health_multiplier(), 1);
+    }
+
+    #[test]
+    fn suspicion_timeout_shrinks_with_confirmations() {
+        let lifeguard = lifeguard();
+        let min = Duration::seconds(10);
+        lifeguard.suspect("suspect", Incarnation::from(1), None);
+        assert_eq!(lifeguard.suspicion_timeout("suspect", min, 10),
+                   Duration::seconds(60));
+
+        lifeguard.suspect("suspect", Incarnation::from(1), Some("a"));
+        let one = lifeguard.suspicion_timeout("suspect", min, 10);
+        assert!(one < Duration::seconds(60) && one > min);
+
+        // The same member confirming again doesn't count, nor does the suspect itself.
+        lifeguard.suspect("suspect", Incarnation::from(1), Some("a"));
+        lifeguard.suspect("suspect", Incarnation::from(1), Some("suspect"));
+        assert_eq!(lifeguard.suspicion_timeout("suspect", min, 10), one);
+
+        lifeguard.suspect("suspect", Incarnation::from(1), Some("b"));
+        lifeguard.suspect("suspect", Incarnation::from(1), Some("c"));
+        assert_eq!(lifeguard.suspicion_timeout("suspect", min, 10), min);
+
+        // A suspicion at a new incarnation starts over.
+        lifeguard.suspect("suspect", Incarnation::from(2), None);
+        assert_eq!(lifeguard.suspicion_timeout("suspect", min, 10),
+                   Duration::seconds(60));
+    }
+
+    #[test]
+    fn suspicion_timeout_is_the_minimum_when_nobody_can_confirm() {
+        let lifeguard = lifeguard();
+        let min = Duration::seconds(10);
+        lifeguard.suspect("suspect", Incarnation::from(1), None);
+        assert_eq!(lifeguard.suspicion_timeout("suspect", min, 2), min);
+        lifeguard.set_enabled(false);
+        assert_eq!(lifeguard.suspicion_timeout("suspect", min, 10), min);
+    }
+
+    #[test]
+    fn ended_suspicions_are_forgotten() {
+        let lifeguard = lifeguard();
+        lifeguard.suspect("alive", Incarnation::from(1), None);
+        lifeguard.suspect("suspect", Incarnation::from(1), Some("a"));
+        lifeguard.end_suspicions(|id| {
+                     if id == "alive" {
+                         Some(Health::Alive)
+                     } else {
+                         Some(Health::Suspect)
+                     }
+                 });
+        let suspicions = lifeguard.suspicions.lock().unwrap();
+        assert!(!suspicions.contains_key("alive"));
+        assert!(suspicions.contains_key("suspect"));
+    }
+
+    #[test]
+    fn nacks_are_counted_per_target() {
+        let lifeguard = lifeguard();
+        lifeguard.nacked("target");
+        lifeguard.nacked("target");
+        assert_eq!(lifeguard.take_nacks("target"), 2);
+        assert_eq!(lifeguard.take_nacks("target"), 0);
+
+        let target = Member { id: "target".to_string(),
+                              ..Default::default() };
+        let requester = Member { id: "requester".to_string(),
+                                 ..Default::default() };
+        lifeguard.relaying(&target, &requester, SteadyTime::now());
+        assert!(lifeguard.relayed("target", "requester"));
+        assert!(!lifeguard.relayed("target", "requester"));
+    }
+
+    #[test]
+    fn overdue_relays_are_nacked_once() {
+        let lifeguard = lifeguard();
+        let now = SteadyTime::now();
+        let target = Member { id: "target".to_string(),
+                              ..Default::default() };
+        let requester = Member { id: "requester".to_string(),
+                                 ..Default::default() };
+        let later = Member { id: "later".to_string(),
+                             ..Default::default() };
+        lifeguard.relaying(&target, &requester, now);
+        lifeguard.relaying(&target, &later, now + Duration::seconds(1));
+
+        let overdue = lifeguard.overdue_relays(now);
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].0.id, "requester");
+        assert_eq!(overdue[0].1.id, "target");
+        assert!(lifeguard.overdue_relays(now).is_empty());
+        assert!(!lifeguard.relayed("target", "requester"));
+        assert!(lifeguard.relayed("target", "later"));
+    }
+}
@@ -16,18 +16,23 @@
 //!
 //! Creates `Server` structs, that hold everything we need to run the SWIM and Gossip protocol.
 //! Winds up with 5 separate threads - inbound (incoming connections), outbound (the Probe
-//! protocol), expire (turning Suspect members into Confirmed members), push (the fan-out rumors),
-//! and pull (the inbound receipt of rumors.).
+//! protocol), expire (turning Suspect members into Confirmed members, and garbage collecting stale
+//! rumors), push (the fan-out rumors), and pull (the inbound receipt of rumors.).
 
 mod expire;
 mod inbound;
 mod incarnation_store;
+mod lifeguard;
 mod outbound;
 mod pull;
 mod push;
 pub mod timing;
+pub mod transport;
 
-use self::incarnation_store::IncarnationStore;
+use self::{incarnation_store::IncarnationStore,
+           lifeguard::Lifeguard,
+           transport::{ConnectionPool,
+                       GossipTransport}};
 use crate::{error::{Error,
                     Result},
             member::{Health,
@@ -84,6 +89,8 @@ use std::{collections::{HashMap,
           thread,
           time::{Duration,
                  Instant}};
+use time::{Duration as TimeDuration,
+           SteadyTime};
 
 /// The maximum number of other members we should notify when we shut
 /// down and leave the ring.
@@ -113,6 +120,23 @@ type AckSender = mpsc::Sender<(SocketAddr, Ack)>;
 
 pub trait Suitability: Debug + Send + Sync {
     fn get(&self, service_group: &str) -> u64;
+
+    /// The leader priority declared for our member of the service group; it ranks above
+    /// suitability.
+    fn leader_priority(&self, _service_group: &str) -> u64 { 0 }
+
+    /// Whether we should keep leading the service group through a re-election if we lead it now,
+    /// as long as we're still around to take part.
+    fn sticky_leader(&self, _service_group: &str) -> bool { false }
+}
+
+/// Decides whether configuration and file rumors are accepted into the rumor stores, e.g. by
+/// who signed them. An untrusted rumor never displaces the last trusted one, however high its
+/// incarnation.
+pub trait RumorTrust: Debug + Send + Sync {
+    fn trusts_service_config(&self, service_config: &ServiceConfig) -> bool;
+
+    fn trusts_service_file(&self, service_file: &ServiceFile) -> bool;
 }
 
 /// Encapsulate a `Member` with the added understanding that this
@@ -247,7 +271,7 @@ pub struct Server {
     // depends on it being so. Refactor so it can be private.
     pub member:               Arc<RwLock<Myself>>,
     pub member_list:          Arc<MemberList>,
-    ring_key:                 Arc<Option<SymKey>>,
+    ring_keys:                Arc<RwLock<Keyring>>,
     rumor_heat:               RumorHeat,
     pub service_store:        RumorStore<Service>,
     pub service_config_store: RumorStore<ServiceConfig>,
@@ -255,9 +279,23 @@ pub struct Server {
     pub election_store:       RumorStore<Election>,
     pub update_store:         RumorStore<ElectionUpdate>,
     pub departure_store:      RumorStore<Departure>,
+    /// The incarnations of garbage collected configuration and file rumors, and when they were
+    /// collected, so that copies still being gossiped by other members don't bring them back.
+    tombstones:               Arc<RwLock<HashMap<RumorKey, (u64, SteadyTime)>>>,
+    /// The service groups that members have left. Only their configuration and file rumors are
+    /// orphaned once no members remain, as config is often applied before a service is loaded.
+    vacated_groups:           Arc<RwLock<HashSet<String>>>,
     swim_addr:                SocketAddr,
     gossip_addr:              SocketAddr,
+    gossip_transport:         GossipTransport,
+    /// Idle connections to other members, when gossiping over TCP.
+    gossip_connections:       ConnectionPool,
+    lifeguard:                Arc<Lifeguard>,
+    /// How many members outside our zone rumors are pushed to each round; every member is
+    /// gossiped with when this is `None`, or when we aren't in a zone.
+    cross_zone_fanout:        Option<usize>,
     suitability_lookup:       Arc<Box<dyn Suitability>>,
+    rumor_trust:              Option<Arc<Box<dyn RumorTrust>>>,
     data_path:                Option<PathBuf>,
     dat_file:                 Option<Arc<Mutex<DatFile>>>,
     socket:                   Option<UdpSocket>,
@@ -277,7 +315,7 @@ impl Clone for Server {
                  member_id:            self.member_id.clone(),
                  member:               self.member.clone(),
                  member_list:          self.member_list.clone(),
-                 ring_key:             self.ring_key.clone(),
+                 ring_keys:            self.ring_keys.clone(),
                  rumor_heat:           self.rumor_heat.clone(),
                  service_store:        self.service_store.clone(),
                  service_config_store: self.service_config_store.clone(),
@@ -285,9 +323,16 @@ impl Clone for Server {
                  election_store:       self.election_store.clone(),
                  update_store:         self.update_store.clone(),
                  departure_store:      self.departure_store.clone(),
+                 tombstones:           self.tombstones.clone(),
+                 vacated_groups:       self.vacated_groups.clone(),
                  swim_addr:            self.swim_addr,
                  gossip_addr:          self.gossip_addr,
+                 gossip_transport:     self.gossip_transport,
+                 gossip_connections:   self.gossip_connections.clone(),
+                 lifeguard:            self.lifeguard.clone(),
+                 cross_zone_fanout:    self.cross_zone_fanout,
                  suitability_lookup:   self.suitability_lookup.clone(),
+                 rumor_trust:          self.rumor_trust.clone(),
                  data_path:            self.data_path.clone(),
                  dat_file:             self.dat_file.clone(),
                  departed:             self.departed.clone(),
@@ -341,7 +386,7 @@ impl Server {
                             member_id:            Arc::new(member_id),
                             member:               Arc::new(RwLock::new(myself)),
                             member_list:          Arc::new(MemberList::new()),
-                            ring_key:             Arc::new(ring_key),
+                            ring_keys:            Arc::new(RwLock::new(Keyring::new(ring_key))),
                             rumor_heat:           RumorHeat::default(),
                             service_store:        RumorStore::default(),
                             service_config_store: RumorStore::default(),
@@ -349,9 +394,16 @@ impl Server {
                             election_store:       RumorStore::default(),
                             update_store:         RumorStore::default(),
                             departure_store:      RumorStore::default(),
+                            tombstones:           Arc::new(RwLock::new(HashMap::new())),
+                            vacated_groups:       Arc::new(RwLock::new(HashSet::new())),
                             swim_addr:            swim_socket_addr,
                             gossip_addr:          gossip_socket_addr,
+                            gossip_transport:     GossipTransport::default(),
+                            gossip_connections:   ConnectionPool::default(),
+                            lifeguard:            Arc::new(Lifeguard::default()),
+                            cross_zone_fanout:    None,
                             suitability_lookup:   Arc::new(suitability_lookup),
+                            rumor_trust:          None,
                             data_path:            data_path.as_ref().map(|p| p.into()),
                             dat_file:             None,
                             departed:             Arc::new(AtomicBool::new(false)),
@@ -453,6 +505,13 @@ impl Server {
                 let mut me = self.member.write().expect("Member lock is poisoned");
                 me.incarnation_store = Some(store);
                 me.sync_incarnation()?;
+                // Members that already know our persisted incarnation
+                // would otherwise keep gossiping our old labels.
+                if let Some(persisted) = self.member_list.get_cloned(&self.member_id) {
+                    if persisted.labels != me.member.labels {
+                        me.increment_incarnation();
+                    }
+                }
             }
         }
 
@@ -466,6 +525,7 @@ impl Server {
               .map_err(Error::SocketSetReadTimeout)?;
 
         let server_a = self.clone();
+        let timing_a = timing.clone();
         let socket_a = match socket.try_clone() {
             Ok(socket_a) => socket_a,
             Err(_) => return Err(Error::SocketCloneError),
@@ -476,12 +536,25 @@ impl Server {
         };
         self.socket = Some(socket_expire);
 
-        let _ =
-            thread::Builder::new().name(format!("inbound-{}", self.name()))
-                                  .spawn(move || {
-                                      inbound::Inbound::new(server_a, socket_a, tx_outbound).run();
-                                      panic!("You should never, ever get here, judy");
-                                  });
+        let _ = thread::Builder::new().name(format!("inbound-{}", self.name()))
+                                      .spawn(move || {
+                                          inbound::Inbound::new(server_a,
+                                                                socket_a,
+                                                                tx_outbound,
+                                                                timing_a).run();
+                                          panic!("You should never, ever get here, judy");
+                                      });
+
+        let server_nack = self.clone();
+        let socket_nack = match socket.try_clone() {
+            Ok(socket_nack) => socket_nack,
+            Err(_) => return Err(Error::SocketCloneError),
+        };
+        let _ = thread::Builder::new().name(format!("nack-{}", self.name()))
+                                      .spawn(move || {
+                                          inbound::run_nacks(&server_nack, &socket_nack);
+                                          panic!("You should never, ever get here, nell");
+                                      });
 
         let server_b = self.clone();
         let socket_b = match socket.try_clone() {
@@ -571,6 +644,56 @@ impl Server {
     /// Return the gossip address we are bound to
     pub fn gossip_addr(&self) -> &SocketAddr { &self.gossip_addr }
 
+    /// Return the transport rumors are gossiped over
+    pub fn gossip_transport(&self) -> GossipTransport { self.gossip_transport }
+
+    /// Gossip rumors over `transport`. This has to be set before the server is started, and every
+    /// member of the ring has to use the same transport.
+    pub fn set_gossip_transport(&mut self, transport: GossipTransport) {
+        self.gossip_transport = transport;
+    }
+
+    /// Whether SWIM uses the Lifeguard extensions to adapt its timeouts.
+    pub fn lifeguard_enabled(&self) -> bool { self.lifeguard.enabled() }
+
+    /// Use the Lifeguard extensions to SWIM: stretch probe timeouts while this member seems to be
+    /// the slow one, and confirm suspicions faster the more members share them.
+    pub fn set_lifeguard_enabled(&self, enabled: bool) { self.lifeguard.set_enabled(enabled) }
+
+    /// How many members outside our zone rumors are pushed to each round, if gossip is kept
+    /// within zones.
+    pub fn cross_zone_fanout(&self) -> Option<usize> { self.cross_zone_fanout }
+
+    /// Keep gossip within our zone: push rumors to every member in it, but only to `fanout`
+    /// randomly chosen members of other zones each round. This has to be set before the server
+    /// is started, and only applies while we carry a `zone` label.
+    pub fn set_cross_zone_fanout(&mut self, fanout: Option<usize>) {
+        self.cross_zone_fanout = fanout;
+    }
+
+    /// Only accept the configuration and file rumors `trust` allows; every one is accepted
+    /// otherwise. This has to be set before the server is started.
+    pub fn set_rumor_trust(&mut self, trust: Box<dyn RumorTrust>) {
+        self.rumor_trust = Some(Arc::new(trust));
+    }
+
+    /// The members to push rumors to this round, in the order they should be gossiped with.
+    pub fn gossip_check_list(&self) -> Vec<Member> {
+        let zone = self.member
+                       .read()
+                       .expect("Member lock is poisoned")
+                       .member
+                       .zone()
+                       .map(ToString::to_string);
+        match (zone, self.cross_zone_fanout) {
+            (Some(zone), Some(fanout)) => {
+                self.member_list
+                    .zoned_check_list(self.member_id(), &zone, fanout)
+            }
+            _ => self.member_list.check_list(self.member_id()),
+        }
+    }
+
     /// Return the port number of the gossip socket we are bound to.
     fn gossip_port(&self) -> u16 { self.gossip_addr.port() }
 
@@ -604,6 +727,9 @@ impl Server {
             if health == Health::Departed {
                 self.rumor_heat.purge(&member_id);
             }
+            if health == Health::Suspect && member_id != self.member_id() {
+                self.lifeguard.suspect(&member_id, trace_incarnation, None);
+            }
             self.rumor_heat.start_hot_rumor(rk);
         }
     }
@@ -655,12 +781,23 @@ impl Server {
     }
 
     /// Given a membership record and some health, insert it into the Member List.
-    fn insert_member_from_rumor(&self, member: Member, mut health: Health) {
+    fn insert_member_from_rumor(&self, from_id: &str, member: Member, mut health: Health) {
         let rk: RumorKey = RumorKey::from(&member);
         if member.id == self.member_id() && health != Health::Alive {
             let mut me = self.member.write().expect("Member lock is poisoned");
             if member.incarnation >= me.incarnation() {
                 me.refute_incarnation(member.incarnation);
+                self.lifeguard.refuted();
+                // Whoever thought we were dead may have garbage collected our service rumors
+                // in the meantime, so share them again.
+                if health == Health::Confirmed {
+                    self.service_store.with_keys(|(_, rumors)| {
+                                          if let Some(service) = rumors.get(self.member_id()) {
+                                              self.rumor_heat
+                                                  .start_hot_rumor(RumorKey::from(service));
+                                          }
+                                      });
+                }
                 health = Health::Alive;
             }
         }
@@ -683,6 +820,18 @@ impl Server {
             }
             self.rumor_heat.start_hot_rumor(rk);
         }
+
+        // A suspicion we already share still counts as a confirmation of it.
+        if health == Health::Suspect && member_id != self.member_id() {
+            let current = self.member_list.membership_for(&member_id);
+            if current.map_or(false, |m| {
+                          m.health == Health::Suspect && m.member.incarnation == trace_incarnation
+                      })
+            {
+                self.lifeguard
+                    .suspect(&member_id, trace_incarnation, Some(from_id));
+            }
+        }
     }
 
     /// Insert a service rumor into the service store.
@@ -697,6 +846,10 @@ impl Server {
     /// See https://github.com/habitat-sh/habitat/issues/1994
     /// See Server::check_quorum
     pub fn insert_service(&self, service: Service) {
+        // A departed member never comes back, so neither do its services.
+        if self.member_list.health_of_by_id(&service.member_id) == Some(Health::Departed) {
+            return;
+        }
         Self::insert_service_impl(service,
                                   &self.service_store,
                                   &self.member_list,
@@ -738,6 +891,16 @@ impl Server {
     /// Insert a service config rumor into the service store.
     pub fn insert_service_config(&self, service_config: ServiceConfig) {
         let rk = RumorKey::from(&service_config);
+        if self.is_tombstoned(&rk, service_config.incarnation) {
+            return;
+        }
+        if let Some(ref trust) = self.rumor_trust {
+            if !trust.trusts_service_config(&service_config) {
+                debug!("Ignoring untrusted config for {} at incarnation {}",
+                       service_config.service_group, service_config.incarnation);
+                return;
+            }
+        }
         if self.service_config_store.insert(service_config) {
             self.rumor_heat.start_hot_rumor(rk);
         }
@@ -746,11 +909,107 @@ impl Server {
     /// Insert a service file rumor into the service file store.
     pub fn insert_service_file(&self, service_file: ServiceFile) {
         let rk = RumorKey::from(&service_file);
+        if self.is_tombstoned(&rk, service_file.incarnation) {
+            return;
+        }
+        if let Some(ref trust) = self.rumor_trust {
+            if !trust.trusts_service_file(&service_file) {
+                debug!("Ignoring untrusted file {} for {} at incarnation {}",
+                       service_file.filename, service_file.service_group, service_file.incarnation);
+                return;
+            }
+        }
         if self.service_file_store.insert(service_file) {
             self.rumor_heat.start_hot_rumor(rk);
         }
     }
 
+    /// Returns true if the rumor was garbage collected at this incarnation or a later one. A newer
+    /// incarnation lifts the tombstone.
+    fn is_tombstoned(&self, rk: &RumorKey, incarnation: u64) -> bool {
+        let mut tombstones = self.tombstones.write().expect("Tombstones lock poisoned");
+        match tombstones.get(rk) {
+            Some(&(tombstoned, _)) if incarnation <= tombstoned => true,
+            Some(_) => {
+                tombstones.remove(rk);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Garbage collect the rumors of services whose member has departed, or has been confirmed
+    /// dead for longer than `timeout`. Returns how many rumors were removed.
+    pub fn expire_service_rumors(&self, timeout: TimeDuration) -> usize {
+        let removed = self.service_store
+                          .remove_where(|s| self.member_list.is_expired(&s.member_id, timeout));
+        self.vacated_groups
+            .write()
+            .expect("Vacated groups lock poisoned")
+            .extend(removed.iter().map(|rk| rk.key.clone()));
+        self.rumor_heat.forget(&removed);
+        removed.len()
+    }
+
+    /// The service groups with configuration or file rumors, but no members left. Groups that
+    /// never had a member aren't orphaned, so config applied before a service is loaded stays.
+    pub fn orphaned_service_groups(&self) -> HashSet<String> {
+        let mut groups = HashSet::new();
+        self.service_config_store.with_keys(|(group, _)| {
+                                     groups.insert(group.clone());
+                                 });
+        self.service_file_store.with_keys(|(group, _)| {
+                                   groups.insert(group.clone());
+                               });
+        let mut vacated = self.vacated_groups
+                              .write()
+                              .expect("Vacated groups lock poisoned");
+        // Forget vacated groups with nothing left to collect, so the set stays bounded.
+        vacated.retain(|group| groups.contains(group));
+        groups.retain(|group| {
+                  vacated.contains(group) && self.service_store.len_for_key(group) == 0
+              });
+        groups
+    }
+
+    /// Garbage collect the configuration and file rumors of a service group, tombstoning them so
+    /// that copies still being gossiped don't bring them back. Returns how many rumors were
+    /// removed.
+    pub fn tombstone_service_group(&self, service_group: &str) -> usize {
+        {
+            let now = SteadyTime::now();
+            let mut tombstones = self.tombstones.write().expect("Tombstones lock poisoned");
+            self.service_config_store
+                .with_rumors(service_group, |config| {
+                    tombstones.insert(RumorKey::from(config), (config.incarnation, now));
+                });
+            self.service_file_store
+                .with_rumors(service_group, |file| {
+                    tombstones.insert(RumorKey::from(file), (file.incarnation, now));
+                });
+        }
+        self.vacated_groups
+            .write()
+            .expect("Vacated groups lock poisoned")
+            .remove(service_group);
+        let mut removed = self.service_config_store
+                              .remove_where(|config| config.key() == service_group);
+        removed.extend(self.service_file_store
+                           .remove_where(|file| file.key() == service_group));
+        self.rumor_heat.forget(&removed);
+        removed.len()
+    }
+
+    /// Forget the tombstones of rumors collected longer than `expiry` ago, by which time no member
+    /// should still be gossiping them. Returns how many tombstones were removed.
+    pub fn expire_tombstones(&self, expiry: TimeDuration) -> usize {
+        let now = SteadyTime::now();
+        let mut tombstones = self.tombstones.write().expect("Tombstones lock poisoned");
+        let before = tombstones.len();
+        tombstones.retain(|_, &mut (_, tombstoned_at)| now < tombstoned_at + expiry);
+        before - tombstones.len()
+    }
+
     /// Insert a departure rumor into the departure store.
     pub fn insert_departure(&self, departure: Departure) {
         let rk = RumorKey::from(&departure);
@@ -826,13 +1085,36 @@ impl Server {
     /// Start an election for the given service group, declaring this members suitability and the
     /// term for the election.
     pub fn start_election(&self, service_group: &str, term: u64) {
+        self.start_election_impl(service_group, term, false)
+    }
+
+    /// Replace the election stored for the service group with a new one for the given term. If we
+    /// led the one it replaces and are a sticky leader, we stand as the incumbent, which keeps
+    /// leadership from moving to another member as long as we take part.
+    fn restart_election(&self, service_group: &str, term: u64) {
+        let mut led = false;
+        self.election_store
+            .with_rumor(service_group, "election", |election| {
+                led = election.is_finished() && election.member_id == self.member_id()
+            });
+        let incumbent = led && self.suitability_lookup.sticky_leader(service_group);
+        if incumbent {
+            debug!("Standing as the incumbent leader of {}", service_group);
+        }
+        self.election_store.remove(service_group, "election");
+        self.start_election_impl(service_group, term, incumbent);
+    }
+
+    fn start_election_impl(&self, service_group: &str, term: u64, incumbent: bool) {
         let suitability = self.suitability_lookup.get(&service_group);
         let has_quorum = self.check_quorum(service_group);
-        let e = Election::new(self.member_id(),
-                              service_group,
-                              term,
-                              suitability,
-                              has_quorum);
+        let mut e = Election::new(self.member_id(),
+                                  service_group,
+                                  term,
+                                  suitability,
+                                  has_quorum);
+        e.priority = self.suitability_lookup.leader_priority(service_group);
+        e.incumbent = incumbent;
         if !has_quorum {
             warn!("start_election check_quorum failed: {:?}", e);
         }
@@ -958,8 +1240,7 @@ impl Server {
         for (service_group, old_term) in elections_to_restart {
             let term = old_term + 1;
             warn!("Starting a new election for {} {}", service_group, term);
-            self.election_store.remove(&service_group, "election");
-            self.start_election(&service_group, term);
+            self.restart_election(&service_group, term);
         }
 
         for (service_group, old_term) in update_elections_to_restart {
@@ -996,8 +1277,7 @@ impl Server {
                     });
                 if new_term {
                     debug!("removing old rumor and starting new election");
-                    self.election_store.remove(election.key(), election.id());
-                    self.start_election(&election.service_group, election.term);
+                    self.restart_election(&election.service_group, election.term);
                 }
                 // If we are the member that this election is voting for, then check to see if the
                 // election is over! If it is, mark this election as final before you process it.
@@ -1132,12 +1412,18 @@ impl Server {
         }
     }
 
+    /// The revisions of the ring key this server encrypts and decrypts with. They can be changed
+    /// while the server runs, to rotate the ring key.
+    pub fn ring_keys(&self) -> Arc<RwLock<Keyring>> { self.ring_keys.clone() }
+
     fn generate_wire(&self, payload: Vec<u8>) -> Result<Vec<u8>> {
-        message::generate_wire(payload, (*self.ring_key).as_ref())
+        let ring_keys = self.ring_keys.read().expect("Ring keys lock poisoned");
+        message::generate_wire(payload, ring_keys.primary())
     }
 
     fn unwrap_wire(&self, payload: &[u8]) -> Result<Vec<u8>> {
-        message::unwrap_wire(payload, (*self.ring_key).as_ref())
+        let ring_keys = self.ring_keys.read().expect("Ring keys lock poisoned");
+        message::unwrap_wire(payload, &ring_keys)
     }
 
     pub fn persist_data(&self) {
@@ -1315,13 +1601,16 @@ mod tests {
     fn check_quorum_returns(val: bool) -> impl Fn(&str) -> bool { move |_: &str| val }
 
     fn mock_service(member: &Member) -> Service {
-        Service { member_id:     member.id.clone(),
-                  service_group: ServiceGroup::from_str("group.default").unwrap(),
-                  incarnation:   Default::default(),
-                  initialized:   Default::default(),
-                  pkg:           Default::default(),
-                  cfg:           Default::default(),
-                  sys:           Default::default(), }
+        Service { member_id:          member.id.clone(),
+                  service_group:      ServiceGroup::from_str("group.default").unwrap(),
+                  incarnation:        Default::default(),
+                  initialized:        Default::default(),
+                  pkg:                Default::default(),
+                  cfg:                Default::default(),
+                  sys:                Default::default(),
+                  rolled_back:        Default::default(),
+                  health_check:       Default::default(),
+                  health_check_since: Default::default(), }
     }
 
     #[test]
@@ -1574,18 +1863,26 @@ mod tests {
     }
 
     mod server {
-        use crate::{member::Member,
+        use super::mock_service;
+        use crate::{member::{Health,
+                             Member},
+                    rumor::{service_config::ServiceConfig,
+                            service_file::ServiceFile},
                     server::{timing::Timing,
+                             RumorTrust,
                              Server,
                              Suitability},
                     trace::Trace};
+        use habitat_core::service::ServiceGroup;
         use std::{fs::File,
                   io::prelude::*,
                   net::{IpAddr,
                         Ipv4Addr,
                         SocketAddr},
+                  str::FromStr,
                   sync::Mutex};
         use tempfile::TempDir;
+        use time::Duration as TimeDuration;
 
         lazy_static! {
             static ref SWIM_PORT: Mutex<u16> = Mutex::new(6666);
@@ -1598,6 +1895,18 @@ mod tests {
             fn get(&self, _service_group: &str) -> u64 { 0 }
         }
 
+        #[derive(Debug)]
+        struct SignedOnly;
+        impl RumorTrust for SignedOnly {
+            fn trusts_service_config(&self, service_config: &ServiceConfig) -> bool {
+                service_config.signer.is_some()
+            }
+
+            fn trusts_service_file(&self, service_file: &ServiceFile) -> bool {
+                service_file.signer.is_some()
+            }
+        }
+
         fn start_server() -> Server {
             let swim_port;
             {
@@ -1677,5 +1986,117 @@ mod tests {
             server.start(Timing::default())
                   .expect("Server failed to start");
         }
+
+        #[test]
+        fn service_rumors_of_departed_members_are_expired() {
+            let server = start_server();
+            let alive = Member::default();
+            let departed = Member::default();
+            server.insert_member(alive.clone(), Health::Alive);
+            server.insert_member(departed.clone(), Health::Alive);
+            server.insert_service(mock_service(&alive));
+            server.insert_service(mock_service(&departed));
+            server.member_list.set_departed(&departed.id);
+
+            assert_eq!(server.expire_service_rumors(TimeDuration::hours(1)), 1);
+            assert!(server.service_store
+                          .contains_rumor("group.default", &alive.id));
+            assert!(!server.service_store
+                           .contains_rumor("group.default", &departed.id));
+
+            // Gossip about the departed member's service doesn't bring it back
+            server.insert_service(mock_service(&departed));
+            assert!(!server.service_store
+                           .contains_rumor("group.default", &departed.id));
+        }
+
+        #[test]
+        fn orphaned_config_and_file_rumors_are_tombstoned() {
+            let server = start_server();
+            let member = Member::default();
+            let service_group = ServiceGroup::from_str("group.default").unwrap();
+            let mut config = ServiceConfig::new("sup", service_group.clone(), b"port = 1".to_vec());
+            config.incarnation = 1;
+            let file = ServiceFile::new("sup", service_group, "ca.crt", b"cert".to_vec());
+            server.insert_member(member.clone(), Health::Alive);
+            server.insert_service(mock_service(&member));
+            server.insert_service_config(config.clone());
+            server.insert_service_file(file);
+            assert!(server.orphaned_service_groups().is_empty());
+
+            server.member_list.set_departed(&member.id);
+            server.expire_service_rumors(TimeDuration::hours(1));
+            assert!(server.orphaned_service_groups().contains("group.default"));
+            assert_eq!(server.tombstone_service_group("group.default"), 2);
+            assert!(server.orphaned_service_groups().is_empty());
+
+            // Copies still being gossiped stay gone, but a newer version is welcome
+            server.insert_service_config(config.clone());
+            assert_eq!(server.service_config_store.len_for_key("group.default"), 0);
+            config.incarnation = 2;
+            server.insert_service_config(config);
+            assert_eq!(server.service_config_store.len_for_key("group.default"), 1);
+        }
+
+        #[test]
+        fn config_applied_before_a_service_is_loaded_is_not_orphaned() {
+            let server = start_server();
+            let service_group = ServiceGroup::from_str("group.default").unwrap();
+            server.insert_service_config(ServiceConfig::new("sup",
+                                                            service_group,
+                                                            b"port = 1".to_vec()));
+            server.expire_service_rumors(TimeDuration::hours(1));
+            assert!(server.orphaned_service_groups().is_empty());
+        }
+
+        #[test]
+        fn tombstones_expire() {
+            let server = start_server();
+            let member = Member::default();
+            let service_group = ServiceGroup::from_str("group.default").unwrap();
+            let config = ServiceConfig::new("sup", service_group, b"port = 1".to_vec());
+            server.insert_member(member.clone(), Health::Alive);
+            server.insert_service(mock_service(&member));
+            server.insert_service_config(config.clone());
+            server.member_list.set_departed(&member.id);
+            server.expire_service_rumors(TimeDuration::hours(1));
+            server.tombstone_service_group("group.default");
+
+            assert_eq!(server.expire_tombstones(TimeDuration::hours(1)), 0);
+            server.insert_service_config(config.clone());
+            assert_eq!(server.service_config_store.len_for_key("group.default"), 0);
+
+            assert_eq!(server.expire_tombstones(TimeDuration::zero()), 1);
+            server.insert_service_config(config);
+            assert_eq!(server.service_config_store.len_for_key("group.default"), 1);
+        }
+
+        #[test]
+        fn untrusted_config_and_file_rumors_are_ignored() {
+            let mut server = start_server();
+            server.set_rumor_trust(Box::new(SignedOnly));
+            let service_group = ServiceGroup::from_str("group.default").unwrap();
+            let mut trusted =
+                ServiceConfig::new("sup", service_group.clone(), b"port = 1".to_vec());
+            trusted.incarnation = 1;
+            trusted.signer = Some("ops-20190101000000".to_string());
+            server.insert_service_config(trusted.clone());
+
+            // However high its incarnation, an untrusted rumor doesn't mask later trusted ones
+            let mut untrusted =
+                ServiceConfig::new("sup", service_group.clone(), b"port = 666".to_vec());
+            untrusted.incarnation = u64::max_value();
+            server.insert_service_config(untrusted);
+            server.service_config_store
+                  .assert_rumor_is("group.default", "service_config", |c| c.incarnation == 1);
+            trusted.incarnation = 2;
+            server.insert_service_config(trusted);
+            server.service_config_store
+                  .assert_rumor_is("group.default", "service_config", |c| c.incarnation == 2);
+
+            let file = ServiceFile::new("sup", service_group, "ca.crt", b"cert".to_vec());
+            server.insert_service_file(file);
+            assert_eq!(server.service_file_store.len_for_key("group.default"), 0);
+        }
     }
 }
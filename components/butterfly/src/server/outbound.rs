@@ -38,6 +38,7 @@ use crate::{member::{Health,
             server::{timing::Timing,
                      Server},
             swim::{Ack,
+                   Nack,
                    Ping,
                    PingReq,
                    Swim},
@@ -142,12 +143,16 @@ impl Outbound {
 
             for member in check_list {
                 if self.server.member_list.pingable(&member) {
+                    // With Lifeguard, we wait longer for acks while we seem to be the slow one.
+                    let timing =
+                        self.timing
+                            .with_health_multiplier(self.server.lifeguard.health_multiplier());
                     // This is the timeout for the next protocol period - if we
                     // complete faster than this, we want to wait in the end
                     // until this timer expires.
-                    let next_protocol_period = self.timing.next_protocol_period();
+                    let next_protocol_period = timing.next_protocol_period();
 
-                    self.probe(member);
+                    self.probe(member, &timing);
 
                     if SteadyTime::now() <= next_protocol_period {
                         let wait_time =
@@ -184,7 +189,10 @@ impl Outbound {
     /// PING_RECV_QUEUE_EMPTY_SLEEP_MS, and try again.
     ///
     /// If we don't receive anything at all in the Ping/PingReq loop, we mark the member as Suspect.
-    fn probe(&mut self, member: Member) {
+    ///
+    /// With Lifeguard, every probe updates our local health: a direct ack makes it better, and
+    /// getting no ack makes it worse, more so for every pingreq helper that didn't nack.
+    fn probe(&mut self, member: Member, timing: &Timing) {
         let pa_timer = SWIM_PROBE_DURATION.with_label_values(&["ping/ack"])
                                           .start_timer();
         let mut pr_timer: Option<HistogramTimer> = None;
@@ -196,14 +204,18 @@ impl Outbound {
         SWIM_PROBES_SENT.with_label_values(&["ping"]).inc();
         ping(&self.server, &self.socket, &member, addr, None);
 
-        if self.recv_ack(&member, addr, AckFrom::Ping) {
+        if self.recv_ack(&member, addr, AckFrom::Ping, timing) {
             trace_it!(PROBE: &self.server, TraceKind::ProbeAckReceived, &member.id, addr);
             trace_it!(PROBE: &self.server, TraceKind::ProbeComplete, &member.id, addr);
             SWIM_PROBES_SENT.with_label_values(&["ack"]).inc();
             pa_timer.observe_duration();
+            self.server.lifeguard.probe_succeeded();
             return;
         }
 
+        // Forget any nacks that arrived too late for an earlier probe.
+        self.server.lifeguard.take_nacks(&member.id);
+        let mut pingreq_targets = 0;
         self.server.member_list.with_pingreq_targets(
             self.server.member_id(),
             &member.id,
@@ -219,10 +231,13 @@ impl Outbound {
                         .start_timer(),
                 );
                 pingreq(&self.server, &self.socket, pingreq_target, &member);
+                pingreq_targets += 1;
             },
         );
 
-        if self.recv_ack(&member, addr, AckFrom::PingReq) {
+        let acked = self.recv_ack(&member, addr, AckFrom::PingReq, timing);
+        let nacks = self.server.lifeguard.take_nacks(&member.id);
+        if acked {
             SWIM_PROBES_SENT.with_label_values(&["ack"]).inc();
             trace_it!(PROBE: &self.server, TraceKind::ProbeComplete, &member.id, addr);
         } else {
@@ -235,6 +250,12 @@ impl Outbound {
             self.server.insert_member(member, Health::Suspect);
             SWIM_PROBES_SENT.with_label_values(&["pingreq/failure"])
                             .inc();
+            let missed_nacks = if self.server.lifeguard.enabled() {
+                pingreq_targets - nacks.min(pingreq_targets)
+            } else {
+                0
+            };
+            self.server.lifeguard.probe_failed(missed_nacks);
         }
 
         if pr_timer.is_some() {
@@ -243,10 +264,15 @@ impl Outbound {
     }
 
     /// Listen for an ack from the `Inbound` thread.
-    fn recv_ack(&mut self, member: &Member, addr: SocketAddr, ack_from: AckFrom) -> bool {
+    fn recv_ack(&mut self,
+                member: &Member,
+                addr: SocketAddr,
+                ack_from: AckFrom,
+                timing: &Timing)
+                -> bool {
         let timeout = match ack_from {
-            AckFrom::Ping => self.timing.ping_timeout(),
-            AckFrom::PingReq => self.timing.pingreq_timeout(),
+            AckFrom::Ping => timing.ping_timeout(),
+            AckFrom::PingReq => timing.pingreq_timeout(),
         };
         loop {
             match self.rx_inbound.try_recv() {
@@ -327,7 +353,8 @@ pub fn populate_membership_rumors(server: &Server, target: &Member, swim: &mut S
 pub fn pingreq(server: &Server, socket: &UdpSocket, pingreq_target: &Member, target: &Member) {
     let pingreq = PingReq { membership: vec![],
                             from:       server.member.read().unwrap().as_member(),
-                            target:     target.clone(), };
+                            target:     target.clone(),
+                            nack:       server.lifeguard.enabled(), };
     let mut swim: Swim = pingreq.into();
     let addr = pingreq_target.swim_socket_address();
     populate_membership_rumors(server, target, &mut swim);
@@ -484,3 +511,37 @@ pub fn ack(server: &Server,
     }
     trace_it!(SWIM: server, TraceKind::SendAck, &target.id, addr, &swim);
 }
+
+/// Send a Nack to the member that asked us to ping `target`, which didn't ack.
+pub fn nack(server: &Server, socket: &UdpSocket, requester: &Member, target: &Member) {
+    let nack = Nack { membership: vec![],
+                      from:       server.member.read().unwrap().as_member(),
+                      target:     target.clone(), };
+    let addr = requester.swim_socket_address();
+    let swim: Swim = nack.into();
+    let bytes = match swim.clone().encode() {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Generating protocol message failed: {}", e);
+            return;
+        }
+    };
+    let payload = match server.generate_wire(bytes) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Generating protocol message failed: {}", e);
+            return;
+        }
+    };
+    match socket.send_to(&payload, addr) {
+        Ok(_s) => {
+            let label_values = &["nack"];
+            SWIM_MESSAGES_SENT.with_label_values(label_values).inc();
+            SWIM_BYTES_SENT.with_label_values(label_values)
+                           .set(payload.len().to_i64());
+            trace!("Sent nack to {}@{} for {}", requester.id, addr, target.id);
+        }
+        Err(e) => error!("Failed nack to {}@{}: {}", requester.id, addr, e),
+    }
+    trace_it!(SWIM: server, TraceKind::SendNack, &requester.id, addr, &swim);
+}
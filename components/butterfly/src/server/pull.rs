@@ -14,21 +14,32 @@
 
 //! The pull thread.
 //!
-//! This module handles pulling all the pushed rumors from every member off a ZMQ socket.
+//! This module handles pulling all the pushed rumors from every member, off the TCP connections
+//! members open to our gossip port or, with the `zmq-transport` feature, off a ZMQ socket.
 
-use std::{thread,
+use std::{io,
+          net::{TcpListener,
+                TcpStream},
+          sync::{atomic::{AtomicUsize,
+                          Ordering},
+                 Arc},
+          thread,
           time::Duration};
 
 use habitat_core::util::ToI64;
 use prometheus::{IntCounterVec,
                  IntGaugeVec};
+#[cfg(feature = "zmq-transport")]
 use zmq;
 
+#[cfg(feature = "zmq-transport")]
+use crate::ZMQ_CONTEXT;
 use crate::{rumor::{RumorEnvelope,
                     RumorKind},
-            server::Server,
-            trace::TraceKind,
-            ZMQ_CONTEXT};
+            server::{transport::{self,
+                                 GossipTransport},
+                     Server},
+            trace::TraceKind};
 
 lazy_static! {
     static ref GOSSIP_MESSAGES_RECEIVED: IntCounterVec =
@@ -41,6 +52,24 @@ lazy_static! {
                                 &["type", "mode", "blocked"]).unwrap();
 }
 
+/// One of the `transport::MAX_INBOUND_CONNECTIONS` inbound connections we read from at once,
+/// given back when it is dropped.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn take(active: &Arc<AtomicUsize>) -> Option<ConnectionSlot> {
+        if active.fetch_add(1, Ordering::SeqCst) >= transport::MAX_INBOUND_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(ConnectionSlot(Arc::clone(active)))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) { self.0.fetch_sub(1, Ordering::SeqCst); }
+}
+
 /// Takes a reference to the server itself
 pub struct Pull {
     pub server: Server,
@@ -50,9 +79,19 @@ impl Pull {
     /// Create a new Pull
     pub fn new(server: Server) -> Pull { Pull { server } }
 
-    /// Run this thread. Creates a socket, binds to the `gossip_addr`, then processes messages as
-    /// they are received. Uses a ZMQ pull socket, so inbound messages are fair-queued.
+    /// Run this thread, receiving rumors over the server's gossip transport.
     pub fn run(&mut self) {
+        match self.server.gossip_transport() {
+            #[cfg(feature = "zmq-transport")]
+            GossipTransport::Zmq => self.run_zmq(),
+            GossipTransport::Tcp => self.run_tcp(),
+        }
+    }
+
+    /// Creates a socket, binds to the `gossip_addr`, then processes messages as they are
+    /// received. Uses a ZMQ pull socket, so inbound messages are fair-queued.
+    #[cfg(feature = "zmq-transport")]
+    fn run_zmq(&mut self) {
         let socket = (**ZMQ_CONTEXT).as_mut()
                                     .socket(zmq::PULL)
                                     .expect("Failure to create the ZMQ pull socket");
@@ -76,72 +115,149 @@ impl Pull {
                 }
             };
 
-            let payload = match self.server.unwrap_wire(&msg) {
-                Ok(payload) => payload,
+            self.receive(&msg);
+        }
+    }
+
+    /// Binds a TCP listener to the `gossip_addr`, then reads the frames sent over each inbound
+    /// connection on a thread of its own, up to `transport::MAX_INBOUND_CONNECTIONS` at once.
+    fn run_tcp(&mut self) {
+        let addr = self.server.gossip_addr();
+        let listener = TcpListener::bind(addr).expect("Failure to bind the gossip TCP listener");
+        let active = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
                 Err(e) => {
-                    // NOTE: In the future, we might want to block people who send us
-                    // garbage all the time.
-                    error!("Error parsing protocol message: {:?}", e);
-                    let label_values = &["unwrap_wire", "failure", "unknown"];
-                    GOSSIP_BYTES_RECEIVED.with_label_values(label_values)
-                                         .set(msg.len().to_i64());
-                    GOSSIP_MESSAGES_RECEIVED.with_label_values(label_values)
-                                            .inc();
+                    error!("Error accepting gossip connection: {:?}", e);
                     continue;
                 }
             };
-
-            let proto = match RumorEnvelope::decode(&payload) {
-                Ok(proto) => proto,
-                Err(e) => {
-                    error!("Error parsing protocol message: {:?}", e);
-                    let label_values = &["undecodable", "failure", "unknown"];
-                    GOSSIP_BYTES_RECEIVED.with_label_values(label_values)
-                                         .set(payload.len().to_i64());
-                    GOSSIP_MESSAGES_RECEIVED.with_label_values(label_values)
-                                            .inc();
-                    continue 'recv;
+            let slot = match ConnectionSlot::take(&active) {
+                Some(slot) => slot,
+                None => {
+                    warn!("Closing gossip connection from {:?}; already reading from {} \
+                           connections",
+                          stream.peer_addr(),
+                          transport::MAX_INBOUND_CONNECTIONS);
+                    continue;
                 }
             };
+            let mut pull = Pull::new(self.server.clone());
+            if let Err(e) = thread::Builder::new().name(String::from("pull-connection"))
+                                                  .spawn(move || {
+                                                      pull.read_frames(stream);
+                                                      drop(slot);
+                                                  })
+            {
+                error!("Could not spawn thread: {}", e);
+            }
+        }
+    }
 
-            let blocked = self.server.is_member_blocked(&proto.from_id);
-            let blocked_label = if blocked { "true" } else { "false" };
-            let label_values = &[&proto.r#type.to_string(), "success", blocked_label];
+    /// Processes the frames sent over a connection until the other member closes it, or it has
+    /// been idle for `transport::IDLE_TIMEOUT_SECS`. While the server is paused nothing is read,
+    /// so senders block once the socket's buffers fill up.
+    fn read_frames(&mut self, mut stream: TcpStream) {
+        loop {
+            while self.server.paused() {
+                thread::sleep(Duration::from_millis(100));
+            }
+            match transport::read_stream_frame(&mut stream) {
+                Ok(msg) => self.receive(&msg),
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return,
+                Err(e) => {
+                    debug!("Closing gossip connection: {:?}", e);
+                    return;
+                }
+            }
+        }
+    }
 
-            GOSSIP_MESSAGES_RECEIVED.with_label_values(label_values)
-                                    .inc();
-            GOSSIP_BYTES_RECEIVED.with_label_values(label_values)
-                                 .set(payload.len().to_i64());
+    /// Unwraps, decodes and applies a single wire message.
+    fn receive(&self, msg: &[u8]) {
+        let payload = match self.server.unwrap_wire(msg) {
+            Ok(payload) => payload,
+            Err(e) => {
+                // NOTE: In the future, we might want to block people who send us
+                // garbage all the time.
+                error!("Error parsing protocol message: {:?}", e);
+                let label_values = &["unwrap_wire", "failure", "unknown"];
+                GOSSIP_BYTES_RECEIVED.with_label_values(label_values)
+                                     .set(msg.len().to_i64());
+                GOSSIP_MESSAGES_RECEIVED.with_label_values(label_values)
+                                        .inc();
+                return;
+            }
+        };
 
-            if blocked {
-                warn!("Not processing message from {} - it is blocked",
-                      proto.from_id);
-                continue 'recv;
+        let proto = match RumorEnvelope::decode(&payload) {
+            Ok(proto) => proto,
+            Err(e) => {
+                error!("Error parsing protocol message: {:?}", e);
+                let label_values = &["undecodable", "failure", "unknown"];
+                GOSSIP_BYTES_RECEIVED.with_label_values(label_values)
+                                     .set(payload.len().to_i64());
+                GOSSIP_MESSAGES_RECEIVED.with_label_values(label_values)
+                                        .inc();
+                return;
             }
+        };
 
-            trace_it!(GOSSIP: &self.server, TraceKind::RecvRumor, &proto.from_id, &proto);
-            match proto.kind {
-                RumorKind::Membership(membership) => {
-                    self.server
-                        .insert_member_from_rumor(membership.member, membership.health);
-                }
-                RumorKind::Service(service) => self.server.insert_service(*service),
-                RumorKind::ServiceConfig(service_config) => {
-                    self.server.insert_service_config(service_config);
-                }
-                RumorKind::ServiceFile(service_file) => {
-                    self.server.insert_service_file(service_file);
-                }
-                RumorKind::Election(election) => {
-                    self.server.insert_election(election);
-                }
-                RumorKind::ElectionUpdate(election) => {
-                    self.server.insert_update_election(election);
-                }
-                RumorKind::Departure(departure) => {
-                    self.server.insert_departure(departure);
-                }
+        let blocked = self.server.is_member_blocked(&proto.from_id);
+        let blocked_label = if blocked { "true" } else { "false" };
+        let label_values = &[&proto.r#type.to_string(), "success", blocked_label];
+
+        GOSSIP_MESSAGES_RECEIVED.with_label_values(label_values)
+                                .inc();
+        GOSSIP_BYTES_RECEIVED.with_label_values(label_values)
+                             .set(payload.len().to_i64());
+
+        if blocked {
+            warn!("Not processing message from {} - it is blocked",
+                  proto.from_id);
+            return;
+        }
+
+        trace_it!(GOSSIP: &self.server, TraceKind::RecvRumor, &proto.from_id, &proto);
+        match proto.kind {
+            RumorKind::Membership(membership) => {
+                self.server
+                    .insert_member_from_rumor(&proto.from_id, membership.member, membership.health);
+            }
+            RumorKind::Service(service) => self.server.insert_service(*service),
+            RumorKind::ServiceConfig(service_config) => {
+                self.server.insert_service_config(service_config);
+            }
+            RumorKind::ServiceFile(service_file) => {
+                self.server.insert_service_file(service_file);
+            }
+            RumorKind::Election(election) => {
+                self.server.insert_election(election);
+            }
+            RumorKind::ElectionUpdate(election) => {
+                self.server.insert_update_election(election);
+            }
+            RumorKind::Departure(departure) => {
+                self.server.insert_departure(departure);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inbound_connections_are_capped() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let slots: Vec<ConnectionSlot> =
+            (0..transport::MAX_INBOUND_CONNECTIONS).map(|_| ConnectionSlot::take(&active).unwrap())
+                                                   .collect();
+        assert!(ConnectionSlot::take(&active).is_none());
+        drop(slots);
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+        assert!(ConnectionSlot::take(&active).is_some());
+    }
+}
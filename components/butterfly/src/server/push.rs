@@ -15,7 +15,9 @@
 //! The push thread.
 //!
 //! This is the thread for distributing rumors to members. It distributes to `FANOUT` members, no
-//! more often than `Timing::GOSSIP_PERIOD_DEFAULT_MS`.
+//! more often than `Timing::GOSSIP_PERIOD_DEFAULT_MS`. When gossip is kept within zones, members
+//! of our own zone are gossiped with first, and only a few members of other zones are gossiped
+//! with each round; see `Server::set_cross_zone_fanout`.
 
 use std::{thread,
           time::Duration};
@@ -24,8 +26,11 @@ use habitat_core::util::ToI64;
 use prometheus::{IntCounterVec,
                  IntGaugeVec};
 use time::SteadyTime;
+#[cfg(feature = "zmq-transport")]
 use zmq;
 
+#[cfg(feature = "zmq-transport")]
+use crate::ZMQ_CONTEXT;
 use crate::{member::{Member,
                      Membership},
             rumor::{RumorEnvelope,
@@ -33,9 +38,10 @@ use crate::{member::{Member,
                     RumorKind,
                     RumorType},
             server::{timing::Timing,
+                     transport::{self,
+                                 GossipTransport},
                      Server},
-            trace::TraceKind,
-            ZMQ_CONTEXT};
+            trace::TraceKind};
 
 const FANOUT: usize = 5;
 
@@ -74,7 +80,7 @@ impl Push {
 
             self.server.update_gossip_round();
 
-            let mut check_list = self.server.member_list.check_list(self.server.member_id());
+            let mut check_list = self.server.gossip_check_list();
             let long_wait = self.timing.gossip_timeout();
 
             'fanout: loop {
@@ -150,16 +156,32 @@ impl PushWorker {
     /// Create a new PushWorker.
     pub fn new(server: Server) -> PushWorker { PushWorker { server } }
 
-    /// Send the list of rumors to a given member. This method creates an outbound socket and then
+    /// Send the list of rumors to a given member over the server's gossip transport. Rumors are
+    /// only cooled if we could reach the member; otherwise they are retried in a later round.
+    fn send_rumors(&self, member: &Member, rumors: &[RumorKey]) {
+        let to_addr = format!("{}:{}", member.address, member.gossip_port);
+        let mut payloads = Vec::with_capacity(rumors.len());
+        for rumor_key in rumors {
+            if let Some(payload) = self.wire_payload(member, rumor_key) {
+                payloads.push((rumor_key, payload));
+            }
+        }
+        let sent = match self.server.gossip_transport() {
+            #[cfg(feature = "zmq-transport")]
+            GossipTransport::Zmq => self.send_zmq(member, &to_addr, &payloads),
+            GossipTransport::Tcp => self.send_tcp(member, &to_addr, &payloads),
+        };
+        if sent {
+            self.server.rumor_heat.cool_rumors(&member.id, &rumors);
+        }
+    }
+
+    /// Send wire messages to a member over ZMQ. This method creates an outbound socket and then
     /// closes the connection as soon as we are done sending rumors. ZeroMQ may choose to keep the
     /// connection and socket open for 1 second longer - so it is possible, but unlikely, that this
     /// method can lose messages.
-    // If we ever need to modify this function, it would be an excellent opportunity to
-    // simplify the redundant aspects and remove this allow(clippy::cyclomatic_complexity),
-    // but changing it in the absence of other necessity seems like too much risk for the
-    // expected reward.
-    #[allow(clippy::cyclomatic_complexity)]
-    fn send_rumors(&self, member: &Member, rumors: &[RumorKey]) {
+    #[cfg(feature = "zmq-transport")]
+    fn send_zmq(&self, member: &Member, to_addr: &str, payloads: &[(&RumorKey, Vec<u8>)]) -> bool {
         let socket = (**ZMQ_CONTEXT).as_mut()
                                     .socket(zmq::PUSH)
                                     .expect("Failure to create the ZMQ push socket");
@@ -173,7 +195,6 @@ impl PushWorker {
               .expect("Failure to set the ZMQ push socket hwm");
         socket.set_sndtimeo(500)
               .expect("Failure to set the ZMQ send timeout");
-        let to_addr = format!("{}:{}", member.address, member.gossip_port);
         match socket.connect(&format!("tcp://{}", to_addr)) {
             Ok(()) => debug!("Connected push socket to {:?}", member),
             Err(e) => {
@@ -181,184 +202,219 @@ impl PushWorker {
                 let label_values = &["socket_connect", "failure"];
                 GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
                 GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                return;
+                return false;
             }
         }
-        'rumorlist: for rumor_key in rumors.iter() {
-            let rumor_as_bytes = match rumor_key.kind {
-                RumorType::Member => {
-                    let send_rumor = match self.create_member_rumor(&rumor_key) {
-                        Some(rumor) => rumor,
-                        None => continue 'rumorlist,
-                    };
-                    trace_it!(GOSSIP: &self.server,
-                              TraceKind::SendRumor,
-                              &member.id,
-                              &send_rumor);
-                    match send_rumor.encode() {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["member_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
-                    }
+        for (rumor_key, payload) in payloads {
+            match socket.send(payload, 0) {
+                Ok(()) => {
+                    record_sent(rumor_key, payload);
+                    debug!("Sent rumor {:?} to {:?}", rumor_key, member);
                 }
-                RumorType::Service => {
-                    // trace_it!(GOSSIP: &self.server,
-                    //           TraceKind::SendRumor,
-                    //           &member.id,
-                    //           &send_rumor);
-                    match self.server
-                              .service_store
-                              .encode(&rumor_key.key, &rumor_key.id)
-                    {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["service_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
-                    }
+                Err(e) => {
+                    warn!("Could not send rumor to {:?} @ {:?}; ZMQ said: {:?}",
+                          member.id, to_addr, e)
                 }
-                RumorType::ServiceConfig => {
-                    // trace_it!(GOSSIP: &self.server,
-                    //           TraceKind::SendRumor,
-                    //           &member.id,
-                    //           &send_rumor);
-                    match self.server
-                              .service_config_store
-                              .encode(&rumor_key.key, &rumor_key.id)
-                    {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["service_config_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
+            }
+        }
+        true
+    }
+
+    /// Send wire messages to a member as frames over a pooled TCP connection. A write blocks
+    /// while the member isn't keeping up with us; if it fails, the connection is dropped and we
+    /// give up on the member until the next round.
+    fn send_tcp(&self, member: &Member, to_addr: &str, payloads: &[(&RumorKey, Vec<u8>)]) -> bool {
+        let mut stream = match self.server.gossip_connections.take(to_addr) {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("Cannot connect to {:?} to gossip: {:?}", member, e);
+                let label_values = &["socket_connect", "failure"];
+                GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                return false;
+            }
+        };
+        for (rumor_key, payload) in payloads {
+            if let Err(e) = transport::write_frame(&mut stream, payload) {
+                warn!("Could not send rumor to {:?} @ {:?}: {:?}",
+                      member.id, to_addr, e);
+                let label_values = &["socket_send", "failure"];
+                GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                return false;
+            }
+            record_sent(rumor_key, payload);
+            debug!("Sent rumor {:?} to {:?}", rumor_key, member);
+        }
+        self.server.gossip_connections.put_back(to_addr, stream);
+        true
+    }
+
+    /// Encode a rumor into the wire message we gossip, or `None` if it can't be sent.
+    // If we ever need to modify this function, it would be an excellent opportunity to
+    // simplify the redundant aspects and remove this allow(clippy::cyclomatic_complexity),
+    // but changing it in the absence of other necessity seems like too much risk for the
+    // expected reward.
+    #[allow(clippy::cyclomatic_complexity)]
+    fn wire_payload(&self, member: &Member, rumor_key: &RumorKey) -> Option<Vec<u8>> {
+        let rumor_as_bytes = match rumor_key.kind {
+            RumorType::Member => {
+                let send_rumor = match self.create_member_rumor(&rumor_key) {
+                    Some(rumor) => rumor,
+                    None => return None,
+                };
+                trace_it!(GOSSIP: &self.server,
+                          TraceKind::SendRumor,
+                          &member.id,
+                          &send_rumor);
+                match send_rumor.encode() {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["member_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
                     }
                 }
-                RumorType::ServiceFile => {
-                    // trace_it!(GOSSIP: &self.server,
-                    //           TraceKind::SendRumor,
-                    //           &member.id,
-                    //           &send_rumor);
-                    match self.server
-                              .service_file_store
-                              .encode(&rumor_key.key, &rumor_key.id)
-                    {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["service_file_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
+            }
+            RumorType::Service => {
+                // trace_it!(GOSSIP: &self.server,
+                //           TraceKind::SendRumor,
+                //           &member.id,
+                //           &send_rumor);
+                match self.server
+                          .service_store
+                          .encode(&rumor_key.key, &rumor_key.id)
+                {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["service_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
                     }
                 }
-                RumorType::Departure => {
-                    match self.server
-                              .departure_store
-                              .encode(&rumor_key.key, &rumor_key.id)
-                    {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["departure_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
+            }
+            RumorType::ServiceConfig => {
+                // trace_it!(GOSSIP: &self.server,
+                //           TraceKind::SendRumor,
+                //           &member.id,
+                //           &send_rumor);
+                match self.server
+                          .service_config_store
+                          .encode(&rumor_key.key, &rumor_key.id)
+                {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["service_config_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
                     }
                 }
-                RumorType::Election => {
-                    // trace_it!(GOSSIP: &self.server,
-                    //           TraceKind::SendRumor,
-                    //           &member.id,
-                    //           &send_rumor);
-                    match self.server
-                              .election_store
-                              .encode(&rumor_key.key, &rumor_key.id)
-                    {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["election_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
+            }
+            RumorType::ServiceFile => {
+                // trace_it!(GOSSIP: &self.server,
+                //           TraceKind::SendRumor,
+                //           &member.id,
+                //           &send_rumor);
+                match self.server
+                          .service_file_store
+                          .encode(&rumor_key.key, &rumor_key.id)
+                {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["service_file_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
                     }
                 }
-                RumorType::ElectionUpdate => {
-                    match self.server
-                              .update_store
-                              .encode(&rumor_key.key, &rumor_key.id)
-                    {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            error!("Could not write our own rumor to bytes; abandoning sending \
-                                    rumor: {:?}",
-                                   e);
-                            let label_values = &["election_update_rumor_encode", "failure"];
-                            GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                            GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
-                            continue 'rumorlist;
-                        }
+            }
+            RumorType::Departure => {
+                match self.server
+                          .departure_store
+                          .encode(&rumor_key.key, &rumor_key.id)
+                {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["departure_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
                     }
                 }
-                RumorType::Fake | RumorType::Fake2 => {
-                    debug!("You have fake rumors; how odd!");
-                    continue 'rumorlist;
-                }
-            };
-            let rumor_len = rumor_as_bytes.len().to_i64();
-            let payload = match self.server.generate_wire(rumor_as_bytes) {
-                Ok(payload) => payload,
-                Err(e) => {
-                    error!("Generating protobuf failed: {}", e);
-                    let label_values = &["generate_wire", "failure"];
-                    GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
-                    GOSSIP_BYTES_SENT.with_label_values(label_values)
-                                     .set(rumor_len);
-                    continue 'rumorlist;
-                }
-            };
-            match socket.send(&payload, 0) {
-                Ok(()) => {
-                    GOSSIP_MESSAGES_SENT.with_label_values(&[&rumor_key.kind.to_string(),
-                                                             "success"])
-                                        .inc();
-                    GOSSIP_BYTES_SENT.with_label_values(&[&rumor_key.kind.to_string(), "success"])
-                                     .set(payload.len().to_i64());
-                    debug!("Sent rumor {:?} to {:?}", rumor_key, member);
+            }
+            RumorType::Election => {
+                // trace_it!(GOSSIP: &self.server,
+                //           TraceKind::SendRumor,
+                //           &member.id,
+                //           &send_rumor);
+                match self.server
+                          .election_store
+                          .encode(&rumor_key.key, &rumor_key.id)
+                {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["election_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
+                    }
                 }
-                Err(e) => {
-                    warn!("Could not send rumor to {:?} @ {:?}; ZMQ said: {:?}",
-                          member.id, to_addr, e)
+            }
+            RumorType::ElectionUpdate => {
+                match self.server
+                          .update_store
+                          .encode(&rumor_key.key, &rumor_key.id)
+                {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!("Could not write our own rumor to bytes; abandoning sending \
+                                rumor: {:?}",
+                               e);
+                        let label_values = &["election_update_rumor_encode", "failure"];
+                        GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                        GOSSIP_BYTES_SENT.with_label_values(label_values).set(0);
+                        return None;
+                    }
                 }
             }
+            RumorType::Fake | RumorType::Fake2 => {
+                debug!("You have fake rumors; how odd!");
+                return None;
+            }
+        };
+        let rumor_len = rumor_as_bytes.len().to_i64();
+        match self.server.generate_wire(rumor_as_bytes) {
+            Ok(payload) => Some(payload),
+            Err(e) => {
+                error!("Generating protobuf failed: {}", e);
+                let label_values = &["generate_wire", "failure"];
+                GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+                GOSSIP_BYTES_SENT.with_label_values(label_values)
+                                 .set(rumor_len);
+                None
+            }
         }
-        self.server.rumor_heat.cool_rumors(&member.id, &rumors);
     }
 
     /// Given a rumorkey, creates a protobuf rumor for sharing.
@@ -375,3 +431,10 @@ impl PushWorker {
         Some(rumor)
     }
 }
+
+fn record_sent(rumor_key: &RumorKey, payload: &[u8]) {
+    let label_values = &[&rumor_key.kind.to_string(), "success"];
+    GOSSIP_MESSAGES_SENT.with_label_values(label_values).inc();
+    GOSSIP_BYTES_SENT.with_label_values(label_values)
+                     .set(payload.len().to_i64());
+}
@@ -62,6 +62,14 @@ impl Timing {
                  departure_timeout_ms }
     }
 
+    /// This timing, with the time probes wait for acks stretched by Lifeguard's local health
+    /// multiplier.
+    pub fn with_health_multiplier(&self, multiplier: i64) -> Timing {
+        Timing { ping_ms: self.ping_ms * multiplier,
+                 pingreq_ms: self.pingreq_ms * multiplier,
+                 ..self.clone() }
+    }
+
     /// When should this gossip period expire
     pub fn gossip_timeout(&self) -> SteadyTime {
         SteadyTime::now() + TimeDuration::milliseconds(self.gossip_period_ms)
@@ -0,0 +1,264 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The transports rumors are gossiped over.
+//!
+//! Rumors are pushed to the gossip port of each member either through ZeroMQ PUSH/PULL sockets,
+//! or as length-prefixed frames over plain TCP connections that are kept open between gossip
+//! rounds. Both carry the same wire messages, so ring encryption works the same either way. A
+//! member only listens with one transport, so every member of a ring has to use the same one.
+//!
+//! ZeroMQ is the default, and is only built with the default `zmq-transport` feature. Builds
+//! without it only have, and default to, the TCP transport.
+
+use std::{collections::HashMap,
+          fmt,
+          io::{self,
+               Read,
+               Write},
+          net::{TcpStream,
+                ToSocketAddrs},
+          str::FromStr,
+          sync::{Arc,
+                 Mutex},
+          time::Duration};
+
+use byteorder::{BigEndian,
+                ByteOrder};
+use time::{Duration as TimeDuration,
+           SteadyTime};
+
+use crate::error::{Error,
+                   Result};
+
+/// The largest frame we'll read; anything bigger is treated as a corrupt stream. Rumors are
+/// kilobytes, and even a service file rumor is far smaller than this.
+const MAX_FRAME_BYTES: usize = 1024 * 1024;
+
+const CONNECT_TIMEOUT_MS: u64 = 500;
+
+/// How long a write may block on a member that isn't reading before we give up on it. The
+/// rumors that weren't sent stay hot, and are retried in a later gossip round.
+const WRITE_TIMEOUT_MS: u64 = 5_000;
+
+/// Outbound connections idle for longer than this are reconnected rather than reused. It is
+/// shorter than `IDLE_TIMEOUT_SECS`, so we never write to a connection the other side has
+/// already closed.
+const POOL_IDLE_SECS: i64 = 60;
+
+/// How long an inbound connection may go without a frame before it is closed.
+pub const IDLE_TIMEOUT_SECS: u64 = 120;
+
+/// How long the rest of a frame may take to arrive once its first byte has.
+const FRAME_TIMEOUT_SECS: u64 = 10;
+
+/// The most inbound connections a member reads from at once. Connections beyond this are
+/// closed as soon as they are accepted, and their rumors are sent again in a later round.
+pub const MAX_INBOUND_CONNECTIONS: usize = 256;
+
+/// The names of the gossip transports this build supports.
+#[cfg(feature = "zmq-transport")]
+pub const GOSSIP_TRANSPORTS: &[&str] = &["zmq", "tcp"];
+#[cfg(not(feature = "zmq-transport"))]
+pub const GOSSIP_TRANSPORTS: &[&str] = &["tcp"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GossipTransport {
+    #[cfg(feature = "zmq-transport")]
+    Zmq,
+    Tcp,
+}
+
+impl Default for GossipTransport {
+    #[cfg(feature = "zmq-transport")]
+    fn default() -> Self { GossipTransport::Zmq }
+
+    #[cfg(not(feature = "zmq-transport"))]
+    fn default() -> Self { GossipTransport::Tcp }
+}
+
+impl FromStr for GossipTransport {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_ref() {
+            #[cfg(feature = "zmq-transport")]
+            "zmq" => Ok(GossipTransport::Zmq),
+            "tcp" => Ok(GossipTransport::Tcp),
+            _ => Err(Error::InvalidGossipTransport(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for GossipTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            #[cfg(feature = "zmq-transport")]
+            GossipTransport::Zmq => "zmq",
+            GossipTransport::Tcp => "tcp",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// Write `payload` as a single frame: its length as a big-endian `u32`, then the payload.
+pub fn write_frame<W>(writer: &mut W, payload: &[u8]) -> io::Result<()>
+    where W: Write
+{
+    if payload.len() > MAX_FRAME_BYTES {
+        return Err(frame_too_large(io::ErrorKind::InvalidInput, payload.len()));
+    }
+    let mut frame = vec![0; 4];
+    BigEndian::write_u32(&mut frame, payload.len() as u32);
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame)
+}
+
+/// Read the next frame written by `write_frame`.
+pub fn read_frame<R>(reader: &mut R) -> io::Result<Vec<u8>>
+    where R: Read
+{
+    let mut len_buf = [0; 4];
+    reader.read_exact(&mut len_buf)?;
+    let len = BigEndian::read_u32(&len_buf) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(frame_too_large(io::ErrorKind::InvalidData, len));
+    }
+    // The buffer only grows as the payload arrives, rather than trusting the length up front.
+    let mut payload = Vec::new();
+    reader.take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() < len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Gossip frame was truncated"));
+    }
+    Ok(payload)
+}
+
+/// Read the next frame from an inbound connection. The connection may go `IDLE_TIMEOUT_SECS`
+/// between frames, but once a frame has started the rest of it has to arrive within
+/// `FRAME_TIMEOUT_SECS`, so a peer can't hold a connection open by trickling bytes.
+pub fn read_stream_frame(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut first = [0; 1];
+    stream.set_read_timeout(Some(Duration::from_secs(IDLE_TIMEOUT_SECS)))?;
+    stream.read_exact(&mut first)?;
+    stream.set_read_timeout(Some(Duration::from_secs(FRAME_TIMEOUT_SECS)))?;
+    read_frame(&mut (&first[..]).chain(stream))
+}
+
+fn frame_too_large(kind: io::ErrorKind, len: usize) -> io::Error {
+    io::Error::new(kind, format!("Gossip frame of {} bytes is too large", len))
+}
+
+/// Open a connection to a member's gossip port.
+pub fn connect(addr: &str) -> io::Result<TcpStream> {
+    let socket_addr =
+        addr.to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::AddrNotAvailable, "No address discovered.")
+            })?;
+    let stream =
+        TcpStream::connect_timeout(&socket_addr, Duration::from_millis(CONNECT_TIMEOUT_MS))?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(Duration::from_millis(WRITE_TIMEOUT_MS)))?;
+    Ok(stream)
+}
+
+/// The TCP connections to other members' gossip ports that aren't in use right now, by
+/// address. A connection is taken out of the pool while rumors are written to it, and only put
+/// back if every write succeeded.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionPool(Arc<Mutex<HashMap<String, (TcpStream, SteadyTime)>>>);
+
+impl ConnectionPool {
+    /// An idle connection to `addr`, or a new one if there isn't one.
+    pub fn take(&self, addr: &str) -> io::Result<TcpStream> {
+        let idle = self.0
+                       .lock()
+                       .expect("Connection pool lock poisoned")
+                       .remove(addr);
+        match idle {
+            Some((stream, since))
+                if SteadyTime::now() - since < TimeDuration::seconds(POOL_IDLE_SECS) =>
+            {
+                Ok(stream)
+            }
+            _ => connect(addr),
+        }
+    }
+
+    pub fn put_back(&self, addr: &str, stream: TcpStream) {
+        self.0
+            .lock()
+            .expect("Connection pool lock poisoned")
+            .insert(addr.to_string(), (stream, SteadyTime::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn frames_roundtrip() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, b"first").unwrap();
+        write_frame(&mut buf, b"").unwrap();
+        write_frame(&mut buf, b"third").unwrap();
+
+        let mut reader = Cursor::new(buf);
+        assert_eq!(read_frame(&mut reader).unwrap(), b"first");
+        assert_eq!(read_frame(&mut reader).unwrap(), b"");
+        assert_eq!(read_frame(&mut reader).unwrap(), b"third");
+        assert_eq!(read_frame(&mut reader).unwrap_err().kind(),
+                   io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let mut len_buf = vec![0; 4];
+        BigEndian::write_u32(&mut len_buf, MAX_FRAME_BYTES as u32 + 1);
+        assert_eq!(read_frame(&mut Cursor::new(len_buf)).unwrap_err().kind(),
+                   io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn truncated_frames_are_rejected() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, b"truncated").unwrap();
+        buf.truncate(buf.len() - 1);
+        assert_eq!(read_frame(&mut Cursor::new(buf)).unwrap_err().kind(),
+                   io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn gossip_transport_from_str() {
+        assert_eq!("tcp".parse::<GossipTransport>().unwrap(),
+                   GossipTransport::Tcp);
+        assert!("udp".parse::<GossipTransport>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "zmq-transport")]
+    fn zmq_gossip_transport_from_str() {
+        assert_eq!("ZMQ".parse::<GossipTransport>().unwrap(),
+                   GossipTransport::Zmq);
+    }
+
+    #[test]
+    #[cfg(not(feature = "zmq-transport"))]
+    fn zmq_gossip_transport_needs_the_feature() {
+        assert!("zmq".parse::<GossipTransport>().is_err());
+    }
+}
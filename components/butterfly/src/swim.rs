@@ -178,6 +178,8 @@ pub struct PingReq {
     pub membership: Vec<Membership>,
     pub from:       Member,
     pub target:     Member,
+    /// Whether the member asking wants a `Nack` if the target doesn't ack.
+    pub nack:       bool,
 }
 
 impl FromProto<proto::Swim> for PingReq {
@@ -196,7 +198,8 @@ impl FromProto<proto::Swim> for PingReq {
                                         .and_then(Member::from_proto)?,
                      target:     payload.target
                                         .ok_or(Error::ProtocolMismatch("from"))
-                                        .and_then(Member::from_proto)?, })
+                                        .and_then(Member::from_proto)?,
+                     nack:       payload.nack.unwrap_or(false), })
     }
 }
 
@@ -205,7 +208,8 @@ impl protocol::Message<proto::Swim> for PingReq {}
 impl From<PingReq> for proto::PingReq {
     fn from(value: PingReq) -> Self {
         proto::PingReq { from:   Some(value.from.into()),
-                         target: Some(value.target.into()), }
+                         target: Some(value.target.into()),
+                         nack:   Some(value.nack), }
     }
 }
 
@@ -229,11 +233,70 @@ impl From<PingReq> for Swim {
     }
 }
 
+/// Sent back to a member that asked us to ping a target on its behalf, when the target didn't ack
+/// us either. It tells the member asking that its own messages are getting through.
+#[derive(Debug, Clone, Serialize)]
+pub struct Nack {
+    pub membership: Vec<Membership>,
+    pub from:       Member,
+    pub target:     Member,
+}
+
+impl FromProto<proto::Swim> for Nack {
+    fn from_proto(value: proto::Swim) -> Result<Self> {
+        let payload = match value.payload.ok_or(Error::ProtocolMismatch("payload"))? {
+            SwimPayload::Nack(nack) => nack,
+            _ => panic!("try-from nack"),
+        };
+        let mut memberships = Vec::with_capacity(value.membership.len());
+        for membership in value.membership {
+            memberships.push(Membership::from_proto(membership)?);
+        }
+        Ok(Nack { membership: memberships,
+                  from:       payload.from
+                                     .ok_or(Error::ProtocolMismatch("from"))
+                                     .and_then(Member::from_proto)?,
+                  target:     payload.target
+                                     .ok_or(Error::ProtocolMismatch("target"))
+                                     .and_then(Member::from_proto)?, })
+    }
+}
+
+impl protocol::Message<proto::Swim> for Nack {}
+
+impl From<Nack> for proto::Nack {
+    fn from(value: Nack) -> Self {
+        proto::Nack { from:   Some(value.from.into()),
+                      target: Some(value.target.into()), }
+    }
+}
+
+impl From<Nack> for proto::Swim {
+    fn from(value: Nack) -> Self {
+        proto::Swim { r#type:     SwimType::Nack as i32,
+                      membership: value.membership
+                                       .clone()
+                                       .into_iter()
+                                       .map(Into::into)
+                                       .collect(),
+                      payload:    Some(SwimPayload::Nack(value.into())), }
+    }
+}
+
+impl From<Nack> for Swim {
+    fn from(value: Nack) -> Self {
+        Swim { r#type:     SwimType::Nack,
+               membership: value.membership.clone(),
+               kind:       SwimKind::Nack(value), }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub enum SwimKind {
     Ping(Ping),
     Ack(Ack),
     PingReq(PingReq),
+    Nack(Nack),
 }
 
 impl From<SwimKind> for SwimPayload {
@@ -242,6 +305,7 @@ impl From<SwimKind> for SwimPayload {
             SwimKind::Ping(ping) => SwimPayload::Ping(ping.into()),
             SwimKind::Ack(ack) => SwimPayload::Ack(ack.into()),
             SwimKind::PingReq(pingreq) => SwimPayload::Pingreq(pingreq.into()),
+            SwimKind::Nack(nack) => SwimPayload::Nack(nack.into()),
         }
     }
 }
@@ -259,6 +323,7 @@ impl SwimKind {
             SwimKind::Ping(_) => "ping",
             SwimKind::Ack(_) => "ack",
             SwimKind::PingReq(_) => "pingreq",
+            SwimKind::Nack(_) => "nack",
         }
     }
 }
@@ -282,6 +347,7 @@ impl Swim {
             SwimType::Ack => SwimKind::Ack(Ack::from_proto(proto)?),
             SwimType::Ping => SwimKind::Ping(Ping::from_proto(proto)?),
             SwimType::Pingreq => SwimKind::PingReq(PingReq::from_proto(proto)?),
+            SwimType::Nack => SwimKind::Nack(Nack::from_proto(proto)?),
         };
         Ok(Swim { r#type,
                   membership: memberships,
@@ -36,11 +36,13 @@ pub enum TraceKind {
     ProbeDeparted,
     ProbePingReq,
     RecvAck,
+    RecvNack,
     RecvPing,
     RecvPingReq,
     RecvRumor,
     SendAck,
     SendForwardAck,
+    SendNack,
     SendPing,
     SendPingReq,
     SendRumor,
@@ -59,11 +61,13 @@ impl fmt::Display for TraceKind {
             TraceKind::ProbeDeparted => write!(f, "ProbeDeparted"),
             TraceKind::ProbePingReq => write!(f, "ProbePingReq"),
             TraceKind::RecvAck => write!(f, "RecvAck"),
+            TraceKind::RecvNack => write!(f, "RecvNack"),
             TraceKind::RecvPing => write!(f, "RecvPing"),
             TraceKind::RecvPingReq => write!(f, "RecvPingReq"),
             TraceKind::RecvRumor => write!(f, "RecvRumor"),
             TraceKind::SendAck => write!(f, "SendAck"),
             TraceKind::SendForwardAck => write!(f, "SendForwardAck"),
+            TraceKind::SendNack => write!(f, "SendNack"),
             TraceKind::SendPing => write!(f, "SendPing"),
             TraceKind::SendPingReq => write!(f, "SendPingReq"),
             TraceKind::SendRumor => write!(f, "SendRumor"),
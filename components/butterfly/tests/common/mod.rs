@@ -13,7 +13,8 @@
 // limitations under the License.
 
 use habitat_butterfly::{member::{Health,
-                                 Member},
+                                 Member,
+                                 ZONE_LABEL},
                         rumor::{departure::Departure,
                                 election::ElectionStatus,
                                 service::{Service,
@@ -21,6 +22,7 @@ use habitat_butterfly::{member::{Health,
                                 service_config::ServiceConfig,
                                 service_file::ServiceFile},
                         server::{timing::Timing,
+                                 transport::GossipTransport,
                                  Server,
                                  Suitability},
                         trace::Trace,
@@ -51,7 +53,38 @@ impl Suitability for NSuitability {
     fn get(&self, _service_group: &str) -> u64 { self.0 }
 }
 
+#[derive(Debug)]
+struct LeaderPriority(u64);
+impl Suitability for LeaderPriority {
+    fn get(&self, _service_group: &str) -> u64 { 0 }
+
+    fn leader_priority(&self, _service_group: &str) -> u64 { self.0 }
+}
+
 pub fn start_server(name: &str, ring_key: Option<SymKey>, suitability: u64) -> Server {
+    start_server_with_transport(name, ring_key, suitability, GossipTransport::default())
+}
+
+pub fn start_server_with_transport(name: &str,
+                                   ring_key: Option<SymKey>,
+                                   suitability: u64,
+                                   gossip_transport: GossipTransport)
+                                   -> Server {
+    start_server_impl(name,
+                      ring_key,
+                      Box::new(NSuitability(suitability)),
+                      gossip_transport,
+                      None)
+}
+
+/// Starts a server; `zone` is the zone the member is in, and how many members of other zones it
+/// gossips with each round.
+fn start_server_impl(name: &str,
+                     ring_key: Option<SymKey>,
+                     suitability_lookup: Box<dyn Suitability>,
+                     gossip_transport: GossipTransport,
+                     zone: Option<(&str, usize)>)
+                     -> Server {
     let swim_port;
     let gossip_port;
     {
@@ -66,6 +99,10 @@ pub fn start_server(name: &str, ring_key: Option<SymKey>, suitability: u64) -> S
     let mut member = Member::default();
     member.swim_port = swim_port;
     member.gossip_port = gossip_port;
+    if let Some((zone, _)) = zone {
+        member.labels
+              .insert(ZONE_LABEL.to_string(), zone.to_string());
+    }
     let mut server = Server::new(listen_swim,
                                  listen_gossip,
                                  member,
@@ -73,7 +110,9 @@ pub fn start_server(name: &str, ring_key: Option<SymKey>, suitability: u64) -> S
                                  ring_key,
                                  Some(String::from(name)),
                                  None,
-                                 Box::new(NSuitability(suitability))).unwrap();
+                                 suitability_lookup).unwrap();
+    server.set_gossip_transport(gossip_transport);
+    server.set_cross_zone_fanout(zone.map(|(_, fanout)| fanout));
     server.start(Timing::default())
           .expect("Cannot start server");
     server
@@ -120,6 +159,19 @@ impl SwimNet {
                                         .collect(), }
     }
 
+    pub fn new_with_leader_priority(priorities: Vec<u64>) -> SwimNet {
+        SwimNet { members: priorities.into_iter()
+                                     .enumerate()
+                                     .map(|(x, priority)| {
+                                         start_server_impl(&format!("{}", x),
+                                                           None,
+                                                           Box::new(LeaderPriority(priority)),
+                                                           GossipTransport::default(),
+                                                           None)
+                                     })
+                                     .collect(), }
+    }
+
     pub fn new(count: usize) -> SwimNet {
         let suitabilities = vec![0; count];
         SwimNet::new_with_suitability(suitabilities)
@@ -134,6 +186,40 @@ impl SwimNet {
         SwimNet { members }
     }
 
+    pub fn new_tcp(count: usize) -> SwimNet {
+        let mut members = Vec::with_capacity(count);
+        for x in 0..count {
+            members.push(start_server_with_transport(&format!("{}", x),
+                                                     None,
+                                                     0,
+                                                     GossipTransport::Tcp));
+        }
+        SwimNet { members }
+    }
+
+    /// A net whose members are in the given zones, each gossiping with `cross_zone_fanout`
+    /// members of other zones per round.
+    pub fn new_zoned(zones: &[&str], cross_zone_fanout: usize) -> SwimNet {
+        SwimNet { members: zones.iter()
+                                .enumerate()
+                                .map(|(x, zone)| {
+                                    start_server_impl(&format!("{}", x),
+                                                      None,
+                                                      Box::new(NSuitability(0)),
+                                                      GossipTransport::default(),
+                                                      Some((*zone, cross_zone_fanout)))
+                                })
+                                .collect(), }
+    }
+
+    pub fn new_lifeguard(count: usize) -> SwimNet {
+        let net = SwimNet::new(count);
+        for member in net.members.iter() {
+            member.set_lifeguard_enabled(true);
+        }
+        net
+    }
+
     pub fn connect(&mut self, from_entry: usize, to_entry: usize) {
         let to = member_from_server(&self.members[to_entry]);
         trace_it!(TEST: &self.members[from_entry], format!("Connected {} {}", self.members[to_entry].name(), self.members[to_entry].member_id()));
@@ -29,3 +29,33 @@ fn symmetric_encryption_of_wire_payloads() {
     assert!(net[1].service_store
                   .contains_rumor("beast.prod", net[0].member_id()));
 }
+
+#[test]
+fn members_read_any_known_revision_of_the_ring_key() {
+    let old_key = SymKey::generate_pair_for_ring("wolverine").expect("Failed to generate an in \
+                                                                      memory symkey");
+    let mut new_key = SymKey::generate_pair_for_ring("wolverine").expect("Failed to generate an \
+                                                                          in memory symkey");
+    new_key.rev = (old_key.rev.parse::<u64>().unwrap() + 1).to_string();
+    let mut net = btest::SwimNet::new_ring_encryption(2, &old_key);
+    net.connect(0, 1);
+    assert_wait_for_health_of!(net, [0..2, 0..2], Health::Alive);
+    for server in net.iter() {
+        server.ring_keys()
+              .write()
+              .expect("Ring keys lock poisoned")
+              .add(new_key.clone())
+              .expect("Cannot add ring key revision");
+    }
+    // Only the sender encrypts with the new revision; the receiver still encrypts with the old.
+    net[0].ring_keys()
+          .write()
+          .expect("Ring keys lock poisoned")
+          .promote(&new_key.rev)
+          .expect("Cannot promote ring key revision");
+    net.add_service(0, "core/beast/1.2.3/20161208121212");
+    net.wait_for_gossip_rounds(2);
+    assert!(net[1].service_store
+                  .contains_rumor("beast.prod", net[0].member_id()));
+    assert_wait_for_health_of!(net, [0..2, 0..2], Health::Alive);
+}
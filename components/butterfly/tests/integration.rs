@@ -16,6 +16,7 @@
 mod common;
 mod encryption;
 mod rumor;
+mod transport;
 
 use common as btest;
 use habitat_butterfly::{self,
@@ -44,6 +45,24 @@ fn six_members_meshed_confirm_one_member() {
     assert_wait_for_health_of!(net, 0, Health::Confirmed);
 }
 
+#[test]
+fn six_members_meshed_with_lifeguard_confirm_one_member() {
+    let mut net = btest::SwimNet::new_lifeguard(6);
+    net.mesh();
+    trace_it!(TEST: &net[0], "Paused");
+    net[0].pause();
+    assert_wait_for_health_of!(net, 0, Health::Confirmed);
+}
+
+#[test]
+fn six_members_meshed_with_lifeguard_partition_one_node_from_another_node_remains_alive() {
+    let mut net = btest::SwimNet::new_lifeguard(6);
+    net.mesh();
+    net.block(0, 1);
+    net.wait_for_rounds(2);
+    assert_wait_for_health_of!(net, 1, Health::Alive);
+}
+
 #[test]
 fn six_members_meshed_partition_one_node_from_another_node_remains_alive() {
     let mut net = btest::SwimNet::new(6);
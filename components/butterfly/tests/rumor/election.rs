@@ -46,6 +46,25 @@ fn three_members_run_election_from_one_starting_rumor() {
     assert_wait_for_equal_election!(net, [0..3, 0..3], "witcher.prod");
 }
 
+#[test]
+fn three_members_elect_the_member_with_the_highest_leader_priority() {
+    let mut net = btest::SwimNet::new_with_leader_priority(vec![0, 5, 1]);
+    net.mesh();
+    net.add_service(0, "core/witcher/1.2.3/20161208121212");
+    net.add_service(1, "core/witcher/1.2.3/20161208121212");
+    net.add_service(2, "core/witcher/1.2.3/20161208121212");
+    net.add_election(0, "witcher");
+    assert_wait_for_election_status!(net, [0..3], "witcher.prod", ElectionStatus::Finished);
+    assert_wait_for_equal_election!(net, [0..3, 0..3], "witcher.prod");
+
+    let mut leader_id = String::new();
+    net[0].election_store
+          .with_rumor("witcher.prod", "election", |e| {
+              leader_id = e.member_id.to_string();
+          });
+    assert_eq!(leader_id, net[1].member_id());
+}
+
 #[test]
 #[ignore]
 fn five_members_elect_a_new_leader_when_the_old_one_dies() {
@@ -23,3 +23,15 @@ fn two_members_share_services() {
     assert!(net[1].service_store
                   .contains_rumor("witcher.prod", net[0].member_id()));
 }
+
+#[test]
+fn members_in_different_zones_share_services() {
+    let mut net = btest::SwimNet::new_zoned(&["a", "a", "a", "b", "b", "b"], 1);
+    net.mesh();
+    net.add_service(0, "core/witcher/1.2.3/20161208121212");
+    net.wait_for_rounds(4);
+    for member in net.iter() {
+        assert!(member.service_store
+                      .contains_rumor("witcher.prod", net[0].member_id()));
+    }
+}
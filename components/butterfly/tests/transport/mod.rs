@@ -0,0 +1,69 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use habitat_butterfly::{client::Client,
+                        server::transport::GossipTransport};
+use habitat_core::service::ServiceGroup;
+
+use crate::btest;
+
+#[test]
+fn members_gossip_over_tcp() {
+    let mut net = btest::SwimNet::new_tcp(3);
+    net.mesh();
+    net.add_service(0, "core/beast/1.2.3/20161208121212");
+    net.add_service_config(0, "beast", "tcp-backlog = 128");
+    net.wait_for_gossip_rounds(2);
+    for server in net[1..].iter() {
+        assert!(server.service_store
+                      .contains_rumor("beast.prod", net[0].member_id()));
+        assert!(server.service_config_store
+                      .contains_rumor("beast.prod", "service_config"));
+    }
+}
+
+#[test]
+fn rumors_keep_flowing_over_pooled_connections() {
+    let mut net = btest::SwimNet::new_tcp(2);
+    net.mesh();
+    net.add_service(0, "core/beast/1.2.3/20161208121212");
+    net.wait_for_gossip_rounds(1);
+    net.add_service(0, "core/witcher/1.2.3/20161208121212");
+    net.wait_for_gossip_rounds(2);
+    assert!(net[1].service_store
+                  .contains_rumor("beast.prod", net[0].member_id()));
+    assert!(net[1].service_store
+                  .contains_rumor("witcher.prod", net[0].member_id()));
+}
+
+#[test]
+fn service_config_via_tcp_client() {
+    let mut net = btest::SwimNet::new_tcp(2);
+    net.mesh();
+    net.wait_for_gossip_rounds(1);
+    let mut client = Client::with_transport(&net[0].gossip_addr().to_string(),
+                                            None,
+                                            GossipTransport::Tcp).expect("Cannot create \
+                                                                          Butterfly Client");
+    client.send_service_config(ServiceGroup::new(None, "witcher", "prod", None).unwrap(),
+                               0,
+                               b"tcp-backlog = 128",
+                               false,
+                               None,
+                               None)
+          .expect("Cannot send the service configuration");
+    net.wait_for_gossip_rounds(1);
+    assert!(net[1].service_config_store
+                  .contains_rumor("witcher.prod", "service_config"));
+}
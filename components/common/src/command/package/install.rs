@@ -28,23 +28,40 @@
 //!
 //! This would install the `3.0.1` version of redis.
 //!
+//! ```bash
+//! $ hab pkg install core/openssl@~1.0
+//! ```
+//!
+//! This would install the latest `1.0.x` version of openssl.
+//!
 //! # Internals
 //!
 //! * Download the artifact
-//! * Verify it is un-altered
-//! * Unpack it
+//! * Download any of its dependencies that aren't installed, several at once
+//! * Verify each is un-altered
+//! * Unpack them in dependency order
 
 use std::{borrow::Cow,
+          collections::{HashSet,
+                        VecDeque},
           fmt,
           fs::{self,
                File},
           io::{self,
                BufRead,
                BufReader},
+          num::ParseIntError,
           path::{Path,
                  PathBuf},
           result::Result as StdResult,
-          str::FromStr};
+          str::FromStr,
+          sync::{atomic::{AtomicBool,
+                          Ordering},
+                 mpsc::{self,
+                        Sender},
+                 Arc,
+                 Mutex},
+          thread};
 
 use crate::{api_client::{self,
                          Client,
@@ -53,6 +70,7 @@ use crate::{api_client::{self,
                     crypto::{artifact,
                              keys::parse_name_with_rev,
                              SigKeyPair},
+                    env::Config as EnvConfig,
                     fs::{cache_key_path,
                          pkg_install_path,
                          svc_hooks_path,
@@ -61,6 +79,7 @@ use crate::{api_client::{self,
                               Identifiable,
                               PackageArchive,
                               PackageIdent,
+                              PackageIdentConstraint,
                               PackageInstall,
                               PackageTarget},
                     ChannelIdent}};
@@ -70,16 +89,40 @@ use retry::retry;
 
 use crate::{error::{Error,
                     Result},
+            lockfile::Lockfile,
             templating::{self,
                          hooks::{Hook,
                                  InstallHook},
                          package::Pkg},
-            ui::{Status,
+            ui::{ConsoleProgressBar,
+                 Status,
                  UIWriter}};
 
 pub const RETRIES: u64 = 5;
 pub const RETRY_WAIT: u64 = 3000;
 
+/// How many of a package's dependencies are downloaded at once, along with the origin keys they
+/// are signed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DownloadWorkers(usize);
+
+impl Default for DownloadWorkers {
+    fn default() -> Self { DownloadWorkers(4) }
+}
+
+impl FromStr for DownloadWorkers {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        // There is always at least one worker, which downloads one artifact at a time.
+        Ok(DownloadWorkers(s.parse::<usize>()?.max(1)))
+    }
+}
+
+impl EnvConfig for DownloadWorkers {
+    const ENVVAR: &'static str = "HAB_DOWNLOAD_WORKERS";
+}
+
 /// Represents a locally-available `.hart` file for package
 /// installation purposes only.
 ///
@@ -115,11 +158,17 @@ pub struct LocalArchive {
     path:   PathBuf,
 }
 
+impl LocalArchive {
+    pub fn path(&self) -> &Path { &self.path }
+}
+
 /// Encapsulate all possible sources we can install packages from.
 #[derive(Clone, Debug)]
 pub enum InstallSource {
     /// We can install from a package identifier
     Ident(PackageIdent, PackageTarget),
+    /// We can install the latest release within a version constraint
+    Constraint(PackageIdentConstraint, PackageTarget),
     /// We can install from a locally-available `.hart` file
     Archive(LocalArchive),
 }
@@ -128,7 +177,9 @@ impl FromStr for InstallSource {
     type Err = hcore::Error;
 
     /// Create an `InstallSource` from either a package identifier
-    /// string (e.g. "core/hab"), or from the path to a local package.
+    /// string (e.g. "core/hab"), a package identifier with a version
+    /// constraint (e.g. "core/openssl@~1.0"), or from the path to a
+    /// local package.
     ///
     /// Returns an error if the string is neither a valid package
     /// identifier, or is not the path to an actual Habitat package.
@@ -155,6 +206,11 @@ impl FromStr for InstallSource {
                 }
             }
 
+            if s.contains('@') {
+                let constraint = s.parse::<PackageIdentConstraint>()?;
+                return Ok(InstallSource::Constraint(constraint, PackageTarget::active_target()));
+            }
+
             match s.parse::<PackageIdent>() {
                 // TODO fn: I would have preferred to explicitly choose a `PackageTarget` here, but
                 // we're limited to the input string in this trait implementation. For the moment
@@ -182,10 +238,18 @@ impl From<(PackageIdent, PackageTarget)> for InstallSource {
     }
 }
 
+impl From<(PackageIdentConstraint, PackageTarget)> for InstallSource {
+    fn from((constraint, target): (PackageIdentConstraint, PackageTarget)) -> Self {
+        InstallSource::Constraint(constraint, target)
+    }
+}
+
 impl Into<PackageIdent> for InstallSource {
+    /// For a version constraint, this is the constrained package's origin and name.
     fn into(self) -> PackageIdent {
         match self {
             InstallSource::Ident(ident, _) => ident,
+            InstallSource::Constraint(constraint, _) => constraint.ident,
             InstallSource::Archive(local_archive) => local_archive.ident,
         }
     }
@@ -195,6 +259,7 @@ impl AsRef<PackageIdent> for InstallSource {
     fn as_ref(&self) -> &PackageIdent {
         match *self {
             InstallSource::Ident(ref ident, _) => ident,
+            InstallSource::Constraint(ref constraint, _) => &constraint.ident,
             InstallSource::Archive(ref local_archive) => &local_archive.ident,
         }
     }
@@ -311,6 +376,10 @@ impl<'a> fmt::Display for FullyQualifiedPackageIdent<'a> {
 /// identifier will result in the installation of latest appropriate
 /// release from the given `channel`.
 ///
+/// If an `InstallSource::Constraint` is given, the latest release
+/// within its version constraint, whether installed or in the given
+/// `channel`, will be installed.
+///
 /// If an `InstallSource::Archive` is given, then this exact artifact will be
 /// installed, instead of retrieving it from Builder.
 ///
@@ -344,7 +413,7 @@ pub fn start<U>(ui: &mut U,
     let key_cache_path = &cache_key_path(Some(fs_root_path));
     debug!("install key_cache_path: {}", key_cache_path.display());
 
-    let api_client = Client::new(url, product, version, Some(fs_root_path))?;
+    let api_client = Arc::new(Client::new(url, product, version, Some(fs_root_path))?);
     let task = InstallTask { install_mode,
                              local_package_usage,
                              api_client,
@@ -352,16 +421,129 @@ pub fn start<U>(ui: &mut U,
                              fs_root_path,
                              artifact_cache_path,
                              key_cache_path,
-                             install_hook_mode };
+                             install_hook_mode,
+                             lockfile: None };
 
     match *install_source {
         InstallSource::Ident(ref ident, target) => {
             task.with_ident(ui, (ident.clone(), target), token)
         }
+        InstallSource::Constraint(ref constraint, target) => {
+            task.with_constraint(ui, (constraint, target), token)
+        }
         InstallSource::Archive(ref local_archive) => task.with_archive(ui, local_archive, token),
     }
 }
 
+/// Download a Habitat package and all its dependencies into the artifact cache, without
+/// installing any of them.
+///
+/// The release to download is the latest one in the channel for `install_source`, whatever is
+/// installed or cached already, and its fully-qualified identifier is returned. Installing it
+/// later finds every artifact it needs in the cache.
+#[allow(clippy::too_many_arguments)]
+pub fn fetch<U>(ui: &mut U,
+                url: &str,
+                channel: &ChannelIdent,
+                install_source: &InstallSource,
+                product: &str,
+                version: &str,
+                fs_root_path: &Path,
+                artifact_cache_path: &Path,
+                token: Option<&str>)
+                -> Result<PackageIdent>
+    where U: UIWriter
+{
+    let key_cache_path = &cache_key_path(Some(fs_root_path));
+    debug!("fetch key_cache_path: {}", key_cache_path.display());
+
+    let api_client = Arc::new(Client::new(url, product, version, Some(fs_root_path))?);
+    let install_mode = InstallMode::Online;
+    let local_package_usage = LocalPackageUsage::default();
+    let task = InstallTask { install_mode: &install_mode,
+                             local_package_usage: &local_package_usage,
+                             api_client,
+                             channel,
+                             fs_root_path,
+                             artifact_cache_path,
+                             key_cache_path,
+                             install_hook_mode: InstallHookMode::Ignore,
+                             lockfile: None };
+
+    let (ident, target) = match *install_source {
+        InstallSource::Ident(ref ident, target) if ident.fully_qualified() => {
+            (FullyQualifiedPackageIdent::from(ident.clone())?, target)
+        }
+        InstallSource::Ident(ref ident, target) => {
+            ui.status(Status::Determining,
+                      format!("latest version of {} in the '{}' channel", ident, channel))?;
+            (task.fetch_latest_pkg_ident_for((ident, target), token)?, target)
+        }
+        InstallSource::Constraint(ref constraint, target) => {
+            ui.status(Status::Determining,
+                      format!("latest version of {} in the '{}' channel",
+                              constraint, channel))?;
+            let ident = task.fetch_latest_pkg_ident_within((constraint, target), token)?
+                            .ok_or_else(|| {
+                                let msg = format!("No release of {} is in the '{}' channel",
+                                                  constraint, channel);
+                                Error::PackageNotFound(msg)
+                            })?;
+            (FullyQualifiedPackageIdent::from(ident)?, target)
+        }
+        InstallSource::Archive(ref local_archive) => {
+            let ident = FullyQualifiedPackageIdent::from(&local_archive.ident)?;
+            task.store_artifact_in_cache(&ident, &local_archive.path)?;
+            (ident, local_archive.target)
+        }
+    };
+    task.fetch_package(ui, (&ident, target), token)?;
+    Ok(ident.as_ref().clone())
+}
+
+/// Install the exact package set recorded in a lockfile.
+///
+/// The package the lockfile was made for is installed along with its dependencies, as with
+/// `start`, but every artifact that is installed must be one of the locked ones, with the
+/// locked checksum. Packages that are already installed are only used if their cached artifact
+/// has the locked checksum; it's an error if there is none to check.
+///
+/// In `InstallMode::Offline`, every locked artifact that isn't installed must already be
+/// cached, along with the public key it is signed with.
+#[allow(clippy::too_many_arguments)]
+pub fn start_locked<U>(ui: &mut U,
+                       url: &str,
+                       lockfile: &Lockfile,
+                       product: &str,
+                       version: &str,
+                       fs_root_path: &Path,
+                       artifact_cache_path: &Path,
+                       token: Option<&str>,
+                       install_mode: &InstallMode,
+                       install_hook_mode: InstallHookMode)
+                       -> Result<PackageInstall>
+    where U: UIWriter
+{
+    let key_cache_path = &cache_key_path(Some(fs_root_path));
+    debug!("install key_cache_path: {}", key_cache_path.display());
+
+    let api_client = Arc::new(Client::new(url, product, version, Some(fs_root_path))?);
+    // Every locked ident is fully qualified, so neither the channel nor any locally installed
+    // packages are ever consulted to resolve one.
+    let channel = ChannelIdent::default();
+    let local_package_usage = LocalPackageUsage::default();
+    let task = InstallTask { install_mode,
+                             local_package_usage: &local_package_usage,
+                             api_client,
+                             channel: &channel,
+                             fs_root_path,
+                             artifact_cache_path,
+                             key_cache_path,
+                             install_hook_mode,
+                             lockfile: Some(lockfile) };
+    task.with_ident(ui, (lockfile.ident.clone(), lockfile.target), token)
+}
+
 pub fn check_install_hooks<T, P>(ui: &mut T,
                                  package: &PackageInstall,
                                  fs_root_path: P)
@@ -418,7 +600,9 @@ fn run_install_hook<T>(ui: &mut T, package: &PackageInstall) -> Result<()>
         templating::compile_for_package_install(package)?;
         if !hook.run(&package.ident().name,
                      &Pkg::from_install(package)?,
-                     None::<&str>)
+                     None::<&str>,
+                     None)
+                .into_value()
         {
             return Err(Error::InstallHookFailed(package.ident().clone()));
         }
@@ -429,13 +613,15 @@ fn run_install_hook<T>(ui: &mut T, package: &PackageInstall) -> Result<()>
 struct InstallTask<'a> {
     install_mode: &'a InstallMode,
     local_package_usage: &'a LocalPackageUsage,
-    api_client: Client,
-    channel: &'a ChannelIdent,
-    fs_root_path: &'a Path,
+    api_client:          Arc<Client>,
+    channel:             &'a ChannelIdent,
+    fs_root_path:        &'a Path,
     /// The path to the local artifact cache (e.g., /hab/cache/artifacts)
     artifact_cache_path: &'a Path,
     key_cache_path: &'a Path,
     install_hook_mode: InstallHookMode,
+    /// When set, every artifact installed must be one of the lockfile's.
+    lockfile: Option<&'a Lockfile>,
 }
 
 impl<'a> InstallTask<'a> {
@@ -466,6 +652,12 @@ impl<'a> InstallTask<'a> {
             Some(package_install) => {
                 // The installed package was found on disk
                 ui.status(Status::Using, &target_ident)?;
+                if self.lockfile.is_some() {
+                    for dependency in package_install.tdeps()?.iter() {
+                        self.check_installed(&FullyQualifiedPackageIdent::from(dependency)?)?;
+                    }
+                    self.check_installed(&target_ident)?;
+                }
                 if self.install_hook_mode != InstallHookMode::Ignore {
                     check_install_hooks(ui, &package_install, self.fs_root_path)?;
                 }
@@ -480,6 +672,20 @@ impl<'a> InstallTask<'a> {
         }
     }
 
+    /// Install the latest release of a package within a version
+    /// constraint, which is first resolved to a fully-qualified
+    /// identifier.
+    fn with_constraint<T>(&self,
+                          ui: &mut T,
+                          (constraint, target): (&PackageIdentConstraint, PackageTarget),
+                          token: Option<&str>)
+                          -> Result<PackageInstall>
+        where T: UIWriter
+    {
+        let ident = self.determine_latest_from_constraint(ui, constraint, target, token)?;
+        self.with_ident(ui, (ident.as_ref().clone(), target), token)
+    }
+
     /// Given an archive on disk, ensure that it is properly installed
     /// and return the package's identifier.
     fn with_archive<T>(&self,
@@ -602,6 +808,63 @@ impl<'a> InstallTask<'a> {
         }
     }
 
+    /// Find the latest release within a version constraint, among
+    /// the installed packages and the releases in the channel. When
+    /// offline, cached artifacts are searched instead of the channel.
+    fn determine_latest_from_constraint<T>(&self,
+                                           ui: &mut T,
+                                           constraint: &PackageIdentConstraint,
+                                           target: PackageTarget,
+                                           token: Option<&str>)
+                                           -> Result<FullyQualifiedPackageIdent<'_>>
+        where T: UIWriter
+    {
+        let installed = PackageInstall::load_matching(constraint, Some(self.fs_root_path));
+        let latest_local = installed.ok().map(|package| package.ident);
+
+        if self.is_offline() {
+            ui.status(Status::Determining,
+                      format!("latest version of {} locally installed or cached (offline)",
+                              constraint))?;
+            let latest_cached = self.latest_cached_ident_where(&constraint.ident, |ident| {
+                                        constraint.matches(ident)
+                                    })
+                                    .ok()
+                                    .map(|ident| ident.as_ref().clone());
+            let latest =
+                latest_local.into_iter()
+                            .chain(latest_cached)
+                            .max()
+                            .ok_or_else(|| {
+                                Error::OfflinePackageNotFound(constraint.ident.clone())
+                            })?;
+            return FullyQualifiedPackageIdent::from(latest);
+        }
+
+        ui.status(Status::Determining,
+                  format!("latest version of {} in the '{}' channel",
+                          constraint, self.channel))?;
+        let latest_remote = self.fetch_latest_pkg_ident_within((constraint, target), token)?;
+
+        match (latest_local, latest_remote) {
+            (Some(local), Some(remote)) => FullyQualifiedPackageIdent::from(local.max(remote)),
+            (Some(local), None) if !self.ignore_locally_installed_packages() => {
+                ui.status(Status::Missing,
+                          format!("remote version of '{}' in the '{}' channel, but an \
+                                   installed version was found locally ({})",
+                                  constraint, self.channel, local))?;
+                FullyQualifiedPackageIdent::from(local)
+            }
+            (_, Some(remote)) => FullyQualifiedPackageIdent::from(remote),
+            (..) => {
+                self.recommend_channels(ui, (&constraint.ident, target), token)?;
+                let msg = format!("No release of {} is in the '{}' channel",
+                                  constraint, self.channel);
+                Err(Error::PackageNotFound(msg))
+            }
+        }
+    }
+
     /// Given the identifier of an artifact, ensure that the artifact,
     /// as well as all its dependencies, have been cached and
     /// installed.
@@ -622,30 +885,29 @@ impl<'a> InstallTask<'a> {
         // Ensure that all transitive dependencies, as well as the
         // original package itself, are cached locally.
         let dependencies = artifact.tdeps()?;
-        let mut artifacts_to_install = Vec::with_capacity(dependencies.len() + 1);
-        // TODO fn: I'd prefer this list to be a `Vec<FullyQualifiedPackageIdent>` but that
-        // requires a conversion that could fail (i.e. returns a `Result<...>`). Should be
-        // possible though.
+        let mut dependencies_to_install = Vec::with_capacity(dependencies.len());
         for dependency in dependencies.iter() {
-            if self.installed_package(&FullyQualifiedPackageIdent::from(dependency)?)
-                   .is_some()
-            {
-                ui.status(Status::Using, dependency)?;
+            let dependency = FullyQualifiedPackageIdent::from(dependency)?;
+            if self.installed_package(&dependency).is_some() {
+                ui.status(Status::Using, &dependency)?;
+                self.check_installed(&dependency)?;
                 if self.install_hook_mode != InstallHookMode::Ignore {
                     run_install_hook_unless_already_successful(
                         ui,
-                        &PackageInstall::load(dependency, Some(self.fs_root_path))?,
+                        &PackageInstall::load(dependency.as_ref(), Some(self.fs_root_path))?,
                     )?;
                 }
             } else {
-                artifacts_to_install.push(self.get_cached_artifact(
-                    ui,
-                    (&FullyQualifiedPackageIdent::from(dependency)?,
-                                        target),
-                    token,
-                )?);
+                dependencies_to_install.push(dependency);
             }
         }
+        // The dependencies are downloaded several at a time, but are still verified, unpacked
+        // and have their install hooks run one at a time, in dependency order.
+        self.fetch_artifacts(ui, &dependencies_to_install, target, token)?;
+        let mut artifacts_to_install = Vec::with_capacity(dependencies_to_install.len() + 1);
+        for dependency in dependencies_to_install.iter() {
+            artifacts_to_install.push(self.get_cached_artifact(ui, (dependency, target), token)?);
+        }
         // The package we're actually trying to install goes last; we
         // want to ensure that its dependencies get installed before
         // it does.
@@ -669,6 +931,28 @@ impl<'a> InstallTask<'a> {
         PackageInstall::load(ident.as_ref(), Some(self.fs_root_path)).map_err(Error::from)
     }
 
+    /// Ensure that the artifact of the identified package and those of
+    /// all its dependencies are cached and verified, without
+    /// installing any of them.
+    fn fetch_package<T>(&self,
+                        ui: &mut T,
+                        (ident, target): (&FullyQualifiedPackageIdent<'_>, PackageTarget),
+                        token: Option<&str>)
+                        -> Result<()>
+        where T: UIWriter
+    {
+        let mut artifact = self.get_cached_artifact(ui, (ident, target), token)?;
+        let tdeps = artifact.tdeps()?;
+        let dependencies = tdeps.iter()
+                                .map(FullyQualifiedPackageIdent::from)
+                                .collect::<Result<Vec<_>>>()?;
+        self.fetch_artifacts(ui, &dependencies, target, token)?;
+        for dependency in dependencies.iter() {
+            self.get_cached_artifact(ui, (dependency, target), token)?;
+        }
+        Ok(())
+    }
+
     /// This ensures the identified package is in the local cache,
     /// verifies it, and returns a handle to the package's metadata.
     fn get_cached_artifact<T>(&self,
@@ -697,9 +981,100 @@ impl<'a> InstallTask<'a> {
         let mut artifact = PackageArchive::new(self.cached_artifact_path(ident));
         ui.status(Status::Verifying, artifact.ident()?)?;
         self.verify_artifact(ui, ident, &mut artifact)?;
+        if let Some(lockfile) = self.lockfile {
+            lockfile.check(&mut artifact)?;
+        }
         Ok(artifact)
     }
 
+    /// Ensure every one of `idents` is cached, along with the origin key it is signed with, by
+    /// downloading up to `DownloadWorkers` of them at once. If a download fails, no more are
+    /// started; those already under way are allowed to finish, and the first error is returned.
+    fn fetch_artifacts<T>(&self,
+                          ui: &mut T,
+                          idents: &[FullyQualifiedPackageIdent<'_>],
+                          target: PackageTarget,
+                          token: Option<&str>)
+                          -> Result<()>
+        where T: UIWriter
+    {
+        let missing: VecDeque<PackageIdent> = idents.iter()
+                                                    .filter(|ident| !self.is_artifact_cached(ident))
+                                                    .map(|ident| ident.as_ref().clone())
+                                                    .collect();
+        if missing.is_empty() {
+            return Ok(());
+        } else if self.is_offline() {
+            return Err(Error::OfflineArtifactNotFound(missing[0].clone()));
+        }
+
+        let total = missing.len();
+        let workers = DownloadWorkers::configured_value().0.min(total);
+        let downloader = Downloader { api_client: Arc::clone(&self.api_client),
+                                      artifact_cache_path: self.artifact_cache_path
+                                                               .to_path_buf(),
+                                      key_cache_path: self.key_cache_path.to_path_buf(),
+                                      token: token.map(str::to_string),
+                                      target,
+                                      queue: Arc::new(Mutex::new(missing)),
+                                      fetched_keys: Arc::new(Mutex::new(HashSet::new())),
+                                      canceled: Arc::new(AtomicBool::new(false)) };
+        let (sender, receiver) = mpsc::channel();
+        let mut handles = Vec::with_capacity(workers);
+        let mut failure = None;
+        for _ in 0..workers {
+            let worker = downloader.clone();
+            let sender = sender.clone();
+            match thread::Builder::new().name(String::from("pkg-download"))
+                                        .spawn(move || worker.run(&sender))
+            {
+                Ok(handle) => handles.push(handle),
+                Err(e) => {
+                    downloader.cancel();
+                    failure = Some(Error::IO(e));
+                    break;
+                }
+            }
+        }
+        // The workers hold the only senders now, so the loop below ends once they have all
+        // finished.
+        drop(sender);
+
+        let mut downloaded = 0;
+        for event in receiver {
+            let displayed = match event {
+                DownloadEvent::Downloading(ident) => ui.status(Status::Downloading, ident),
+                DownloadEvent::Downloaded(ident) => {
+                    downloaded += 1;
+                    ui.status_of(Status::Cached, downloaded, total, ident)
+                }
+                DownloadEvent::OriginKey(name_with_rev) => {
+                    ui.status(Status::Cached,
+                              format!("{} public origin key", name_with_rev))
+                }
+                DownloadEvent::Failed(e) => {
+                    failure.get_or_insert(e);
+                    Ok(())
+                }
+            };
+            if let Err(e) = displayed {
+                downloader.cancel();
+                failure.get_or_insert(Error::IO(e));
+            }
+        }
+        for handle in handles {
+            if handle.join().is_err() {
+                failure.get_or_insert(Error::DownloadFailed(String::from("A download worker \
+                                                                          panicked")));
+            }
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
     /// Adapter function wrapping `PackageArchive::unpack`
     fn unpack_artifact<T>(&self, ui: &mut T, artifact: &mut PackageArchive) -> Result<()>
         where T: UIWriter
@@ -742,6 +1117,20 @@ impl<'a> InstallTask<'a> {
         PackageInstall::load(ident.as_ref(), Some(self.fs_root_path)).ok()
     }
 
+    /// When installing from a lockfile, ensure an installed package is a locked one by the
+    /// checksum of its cached artifact, since there's no telling what it was installed from
+    /// otherwise.
+    fn check_installed(&self, ident: &FullyQualifiedPackageIdent<'_>) -> Result<()> {
+        let lockfile = match self.lockfile {
+            Some(lockfile) => lockfile,
+            None => return Ok(()),
+        };
+        if !self.is_artifact_cached(ident) {
+            return Err(Error::LockfileUncheckedInstall(ident.as_ref().clone()));
+        }
+        lockfile.check(&mut PackageArchive::new(self.cached_artifact_path(ident)))
+    }
+
     /// Checks for the latest installed package or cached artifact that matches a given package
     /// identifier and returns a fully qualified package identifier if a match exists.
     fn latest_installed_or_cached(&self,
@@ -778,6 +1167,17 @@ impl<'a> InstallTask<'a> {
     }
 
     fn latest_cached_ident(&self, ident: &PackageIdent) -> Result<FullyQualifiedPackageIdent<'_>> {
+        self.latest_cached_ident_where(ident, |_| true)
+    }
+
+    /// Like `latest_cached_ident`, but only considering the cached
+    /// artifacts whose identifiers also pass the given test.
+    fn latest_cached_ident_where<F>(&self,
+                                    ident: &PackageIdent,
+                                    matches: F)
+                                    -> Result<FullyQualifiedPackageIdent<'_>>
+        where F: Fn(&PackageIdent) -> bool
+    {
         let filename_glob = {
             let mut ident = ident.clone();
             if ident.version.is_none() {
@@ -805,7 +1205,10 @@ impl<'a> InstallTask<'a> {
                 continue;
             }
             let artifact_ident = artifact_ident.unwrap();
-            if artifact_ident.origin == ident.origin && artifact_ident.name == ident.name {
+            if artifact_ident.origin == ident.origin
+               && artifact_ident.name == ident.name
+               && matches(&artifact_ident)
+            {
                 if latest.is_empty() {
                     latest.push((artifact_ident, artifact));
                 } else if artifact_ident > latest[0].0 {
@@ -843,6 +1246,24 @@ impl<'a> InstallTask<'a> {
         self.fetch_latest_pkg_ident_in_channel_for((ident, target), &self.channel, token)
     }
 
+    /// The latest release in the channel within a version constraint, if there is any.
+    fn fetch_latest_pkg_ident_within(&self,
+                                     (constraint, target): (&PackageIdentConstraint,
+                                      PackageTarget),
+                                     token: Option<&str>)
+                                     -> Result<Option<PackageIdent>> {
+        let listed = self.api_client
+                         .list_channel_packages((&constraint.ident, target), self.channel, token);
+        match listed {
+            Ok(idents) => Ok(idents.into_iter().filter(|i| constraint.matches(i)).max()),
+            Err(APIError(StatusCode::NotFound, _)) => Ok(None),
+            Err(e) => {
+                debug!("error listing idents: {:?}", e);
+                Err(Error::APIClient(e))
+            }
+        }
+    }
+
     fn fetch_latest_pkg_ident_in_channel_for(&self,
                                              (ident, target): (&PackageIdent, PackageTarget),
                                              channel: &ChannelIdent,
@@ -1041,3 +1462,116 @@ impl<'a> InstallTask<'a> {
         Ok(res)
     }
 }
+
+/// What a download worker reports back to the thread that is displaying progress.
+enum DownloadEvent {
+    Downloading(PackageIdent),
+    Downloaded(PackageIdent),
+    OriginKey(String),
+    Failed(Error),
+}
+
+/// Downloads artifacts on worker threads, each of which takes the next artifact from a queue
+/// shared with the others until it is empty or a download has failed.
+#[derive(Clone)]
+struct Downloader {
+    api_client:          Arc<Client>,
+    artifact_cache_path: PathBuf,
+    key_cache_path:      PathBuf,
+    token:               Option<String>,
+    target:              PackageTarget,
+    /// The artifacts no worker has started downloading yet, in dependency order.
+    queue:               Arc<Mutex<VecDeque<PackageIdent>>>,
+    /// The origin keys a worker has already downloaded, or is downloading.
+    fetched_keys:        Arc<Mutex<HashSet<String>>>,
+    canceled:            Arc<AtomicBool>,
+}
+
+impl Downloader {
+    /// Stop the workers from starting any more downloads.
+    fn cancel(&self) { self.canceled.store(true, Ordering::Relaxed); }
+
+    fn run(&self, events: &Sender<DownloadEvent>) {
+        while !self.canceled.load(Ordering::Relaxed) {
+            let next = self.queue
+                           .lock()
+                           .expect("Download queue lock poisoned")
+                           .pop_front();
+            let ident = match next {
+                Some(ident) => ident,
+                None => break,
+            };
+            if let Err(e) = self.download(&ident, events) {
+                self.cancel();
+                // The receiver only goes away once every worker is done
+                let _ = events.send(DownloadEvent::Failed(e));
+            }
+        }
+    }
+
+    /// Download an artifact, and the origin key it is signed with if we don't have it yet.
+    fn download(&self, ident: &PackageIdent, events: &Sender<DownloadEvent>) -> Result<()> {
+        let _ = events.send(DownloadEvent::Downloading(ident.clone()));
+        if retry(RETRIES,
+                 RETRY_WAIT,
+                 || self.fetch_artifact(ident),
+                 Result::is_ok).is_err()
+        {
+            return Err(Error::DownloadFailed(format!("We tried {} times but \
+                                                      could not download {}. \
+                                                      Giving up.",
+                                                     RETRIES, ident)));
+        }
+        let _ = events.send(DownloadEvent::Downloaded(ident.clone()));
+
+        let path = self.artifact_cache_path.join(ident.archive_name()?);
+        if !path.is_file() {
+            // The depot doesn't have it for our target; verifying it will report that.
+            return Ok(());
+        }
+        let name_with_rev = artifact::artifact_signer(&path)?;
+        if SigKeyPair::get_public_key_path(&name_with_rev, &self.key_cache_path).is_ok()
+           || !self.fetched_keys
+                   .lock()
+                   .expect("Fetched keys lock poisoned")
+                   .insert(name_with_rev.clone())
+        {
+            return Ok(());
+        }
+        let (name, rev) = parse_name_with_rev(&name_with_rev)?;
+        self.api_client.fetch_origin_key(&name,
+                                          &rev,
+                                          &self.key_cache_path,
+                                          None::<ConsoleProgressBar>)?;
+        let _ = events.send(DownloadEvent::OriginKey(name_with_rev));
+        Ok(())
+    }
+
+    fn fetch_artifact(&self, ident: &PackageIdent) -> Result<()> {
+        match self.api_client.fetch_package((ident, self.target),
+                                            self.token.as_ref().map(String::as_str),
+                                            &self.artifact_cache_path,
+                                            None::<ConsoleProgressBar>)
+        {
+            Ok(_) => Ok(()),
+            Err(api_client::Error::APIError(StatusCode::NotImplemented, _)) => {
+                println!("Host platform or architecture not supported by the targeted depot; \
+                          skipping.");
+                Ok(())
+            }
+            Err(e) => Err(Error::from(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn download_workers_from_str() {
+        assert_eq!("8".parse::<DownloadWorkers>().unwrap(), DownloadWorkers(8));
+        assert_eq!("0".parse::<DownloadWorkers>().unwrap(), DownloadWorkers(1));
+        assert!("many".parse::<DownloadWorkers>().is_err());
+    }
+}
@@ -49,10 +49,17 @@ pub enum Error {
     HabitatCore(hcore::Error),
     InstallHookFailed(PackageIdent),
     InvalidInstallHookMode(String),
+    InvalidLockfile(String),
     /// Occurs when making lower level IO calls.
     IO(io::Error),
     /// Errors when joining paths :)
     JoinPathsError(env::JoinPathsError),
+    /// An artifact's checksum differs from the one recorded for it in a lockfile: the package,
+    /// the locked checksum and the artifact's checksum.
+    LockfileChecksumMismatch(PackageIdent, String, String),
+    LockfilePackageNotFound(PackageIdent),
+    /// A package is already installed, but has no cached artifact to check against a lockfile.
+    LockfileUncheckedInstall(PackageIdent),
     NetParseError(net::AddrParseError),
     OfflineArtifactNotFound(PackageIdent),
     OfflineOriginKeyNotFound(String),
@@ -118,8 +125,22 @@ impl fmt::Display for Error {
             Error::InvalidInstallHookMode(ref e) => {
                 format!("Invalid InstallHookMode conversion from {}", e)
             }
+            Error::InvalidLockfile(ref e) => format!("Invalid lockfile: {}", e),
             Error::IO(ref err) => format!("{}", err),
             Error::JoinPathsError(ref err) => format!("{}", err),
+            Error::LockfileChecksumMismatch(ref ident, ref locked, ref actual) => {
+                format!("The artifact for {} has checksum {}, but the lockfile requires {}",
+                        ident, actual, locked)
+            }
+            Error::LockfilePackageNotFound(ref ident) => {
+                format!("{} is not one of the packages in the lockfile", ident)
+            }
+            Error::LockfileUncheckedInstall(ref ident) => {
+                format!("{} is already installed, but its artifact isn't cached to check it \
+                         against the lockfile. Reinstall it by uninstalling it with 'hab pkg \
+                         uninstall {}' and installing from the lockfile again",
+                        ident, ident)
+            }
             Error::NetParseError(ref err) => format!("{}", err),
             Error::OfflineArtifactNotFound(ref ident) => {
                 format!("Cached artifact not found in offline mode: {}", ident)
@@ -179,8 +200,16 @@ impl error::Error for Error {
             Error::HabitatCore(ref err) => err.description(),
             Error::InstallHookFailed(_) => "Install hook exited unsuccessfully",
             Error::InvalidInstallHookMode(_) => "Invalid InstallHookMode",
+            Error::InvalidLockfile(_) => "Invalid lockfile",
             Error::IO(ref err) => err.description(),
             Error::JoinPathsError(ref err) => err.description(),
+            Error::LockfileChecksumMismatch(..) => {
+                "An artifact's checksum does not match the one in the lockfile"
+            }
+            Error::LockfilePackageNotFound(_) => "Package not found in the lockfile",
+            Error::LockfileUncheckedInstall(_) => {
+                "An installed package can't be checked against the lockfile"
+            }
             Error::NetParseError(_) => "Can't parse IP:port",
             Error::OfflineArtifactNotFound(_) => "Cached artifact not found in offline mode",
             Error::OfflineOriginKeyNotFound(_) => "Cached origin key not found in offline mode",
@@ -42,8 +42,10 @@ pub mod cli;
 pub mod command;
 pub mod error;
 pub mod locked_env_var;
+pub mod lockfile;
 pub mod output;
 pub mod package_graph;
+pub mod rotating_log;
 pub mod templating;
 pub mod types;
 pub mod ui;
@@ -0,0 +1,231 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lockfiles pin a package and its transitive dependencies to exact artifacts.
+//!
+//! A lockfile is written by `hab pkg lock` and read by `hab pkg install --lockfile`. It records
+//! the fully qualified ident, target and checksum of every artifact in the package set, so
+//! installing from it on any machine either produces exactly the same packages or fails.
+//!
+//! ```toml
+//! ident = "core/redis/4.0.10/20180801003001"
+//! target = "x86_64-linux"
+//!
+//! [[package]]
+//! ident = "core/glibc/2.27/20180608041157"
+//! target = "x86_64-linux"
+//! checksum = "f6f3d7ab7ef2b5a3c9ee0a7b5e8b9e7ff5b1cb95d6e8d4d1e1a4c1c2e6a5e2d7"
+//!
+//! [[package]]
+//! ident = "core/redis/4.0.10/20180801003001"
+//! target = "x86_64-linux"
+//! checksum = "0f3e5b2c7b5a6b9e8d4e1c3a2f5d6e7b8c9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c"
+//! ```
+
+use crate::{error::{Error,
+                    Result},
+            hcore::{fs::AtomicWriter,
+                    package::{PackageArchive,
+                              PackageIdent,
+                              PackageTarget},
+                    util::{deserialize_using_from_str,
+                           serialize_using_to_string}}};
+use std::{fs,
+          io::Write,
+          path::Path,
+          str::FromStr};
+use toml;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Lockfile {
+    /// The package the lockfile was made for.
+    #[serde(deserialize_with = "deserialize_using_from_str",
+            serialize_with = "serialize_using_to_string")]
+    pub ident:    PackageIdent,
+    pub target:   PackageTarget,
+    /// Every package in the set, in dependency order; the package the lockfile was made for is
+    /// last.
+    #[serde(rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LockedPackage {
+    #[serde(deserialize_with = "deserialize_using_from_str",
+            serialize_with = "serialize_using_to_string")]
+    pub ident:    PackageIdent,
+    pub target:   PackageTarget,
+    /// The checksum of the package's artifact, as given by `PackageArchive::checksum`.
+    pub checksum: String,
+}
+
+impl Lockfile {
+    /// Lock a package to the artifacts of its transitive dependencies, in dependency order,
+    /// followed by the package's own artifact.
+    pub fn from_archives(archives: &mut [PackageArchive]) -> Result<Self> {
+        let mut packages = Vec::with_capacity(archives.len());
+        for archive in archives.iter_mut() {
+            packages.push(LockedPackage { ident:    archive.ident()?,
+                                          target:   archive.target()?,
+                                          checksum: archive.checksum()?, });
+        }
+        let (ident, target) = match packages.last() {
+            Some(root) => (root.ident.clone(), root.target),
+            None => return Err(Error::InvalidLockfile("it locks no packages".to_string())),
+        };
+        let lockfile = Lockfile { ident,
+                                  target,
+                                  packages };
+        lockfile.validate()?;
+        Ok(lockfile)
+    }
+
+    pub fn from_file<P>(path: P) -> Result<Self>
+        where P: AsRef<Path>
+    {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn write<P>(&self, path: P) -> Result<()>
+        where P: AsRef<Path>
+    {
+        let contents = toml::to_string(self)?;
+        let w = AtomicWriter::new(path.as_ref())?;
+        w.with_writer(|f| f.write_all(contents.as_bytes()))?;
+        Ok(())
+    }
+
+    pub fn get(&self, ident: &PackageIdent) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| &package.ident == ident)
+    }
+
+    /// Ensure an artifact is one of the locked packages, and that it is the very artifact that
+    /// was locked.
+    pub fn check(&self, archive: &mut PackageArchive) -> Result<()> {
+        let ident = archive.ident()?;
+        let checksum = archive.checksum()?;
+        self.check_checksum(&ident, &checksum)
+    }
+
+    fn check_checksum(&self, ident: &PackageIdent, checksum: &str) -> Result<()> {
+        let package = self.get(ident)
+                          .ok_or_else(|| Error::LockfilePackageNotFound(ident.clone()))?;
+        if package.checksum != checksum {
+            return Err(Error::LockfileChecksumMismatch(ident.clone(),
+                                                       package.checksum.clone(),
+                                                       checksum.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Ensure every locked package is fully qualified, and that the lockfile locks the package
+    /// it was made for.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(package) = self.packages
+                                   .iter()
+                                   .find(|package| !package.ident.fully_qualified())
+        {
+            return Err(Error::InvalidLockfile(format!("{} is not fully qualified",
+                                                      package.ident)));
+        }
+        match self.get(&self.ident) {
+            Some(package) if package.target == self.target => Ok(()),
+            _ => {
+                let msg = format!("it does not lock {} for {}", self.ident, self.target);
+                Err(Error::InvalidLockfile(msg))
+            }
+        }
+    }
+}
+
+impl FromStr for Lockfile {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lockfile: Lockfile = toml::from_str(s).map_err(Error::TomlParser)?;
+        lockfile.validate()?;
+        Ok(lockfile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"
+ident = "core/redis/4.0.10/20180801003001"
+target = "x86_64-linux"
+
+[[package]]
+ident = "core/glibc/2.27/20180608041157"
+target = "x86_64-linux"
+checksum = "aaaa"
+
+[[package]]
+ident = "core/redis/4.0.10/20180801003001"
+target = "x86_64-linux"
+checksum = "bbbb"
+"#;
+
+    #[test]
+    fn lockfile_from_str() {
+        let lockfile: Lockfile = LOCKFILE.parse().unwrap();
+        assert_eq!(lockfile.ident,
+                   PackageIdent::from_str("core/redis/4.0.10/20180801003001").unwrap());
+        assert_eq!(lockfile.target,
+                   PackageTarget::from_str("x86_64-linux").unwrap());
+        assert_eq!(lockfile.packages.len(), 2);
+        assert_eq!(lockfile.packages[0].checksum, "aaaa");
+
+        let roundtripped: Lockfile = toml::to_string(&lockfile).unwrap().parse().unwrap();
+        assert_eq!(roundtripped, lockfile);
+    }
+
+    #[test]
+    fn lockfile_packages_must_be_fully_qualified() {
+        let lockfile = LOCKFILE.replace("core/glibc/2.27/20180608041157", "core/glibc");
+        match lockfile.parse::<Lockfile>() {
+            Err(Error::InvalidLockfile(_)) => (),
+            other => panic!("Expected an invalid lockfile, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn lockfile_must_lock_its_own_package() {
+        let lockfile = LOCKFILE.replacen("core/redis/4.0.10/20180801003001",
+                                         "core/redis/4.0.10/20180901003001",
+                                         1);
+        match lockfile.parse::<Lockfile>() {
+            Err(Error::InvalidLockfile(_)) => (),
+            other => panic!("Expected an invalid lockfile, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn checksums_must_match_the_lockfile() {
+        let lockfile: Lockfile = LOCKFILE.parse().unwrap();
+        let glibc = PackageIdent::from_str("core/glibc/2.27/20180608041157").unwrap();
+        let zlib = PackageIdent::from_str("core/zlib/1.2.11/20180608050617").unwrap();
+
+        assert!(lockfile.check_checksum(&glibc, "aaaa").is_ok());
+        match lockfile.check_checksum(&glibc, "cccc") {
+            Err(Error::LockfileChecksumMismatch(..)) => (),
+            other => panic!("Expected a checksum mismatch, got {:?}", other),
+        }
+        match lockfile.check_checksum(&zlib, "aaaa") {
+            Err(Error::LockfilePackageNotFound(_)) => (),
+            other => panic!("Expected a missing package, got {:?}", other),
+        }
+    }
+}
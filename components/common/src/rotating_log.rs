@@ -0,0 +1,205 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A line-oriented log file that is rotated once it grows too large or too old.
+//!
+//! Rotated copies sit next to the live log with a numeric suffix, the most recent being `.1`;
+//! see `rotated_path`.
+
+use std::{fs::{self,
+               File,
+               OpenOptions},
+          io::{self,
+               Write},
+          path::{Path,
+                 PathBuf},
+          time::{Duration,
+                 Instant}};
+
+pub struct RotatingLog {
+    path:      PathBuf,
+    /// Rotate once the live log reaches this many bytes; `0` disables size-based rotation.
+    max_bytes: u64,
+    /// Rotate once the live log has been written to for this long; zero disables age-based
+    /// rotation.
+    max_age:   Duration,
+    /// How many rotated copies to keep; with none, the live log is truncated instead.
+    keep:      u32,
+    file:      Option<File>,
+    written:   u64,
+    opened:    Instant,
+}
+
+impl RotatingLog {
+    pub fn new<P>(path: P, max_bytes: u64, max_age: Duration, keep: u32) -> Self
+        where P: Into<PathBuf>
+    {
+        RotatingLog { path: path.into(),
+                      max_bytes,
+                      max_age,
+                      keep,
+                      file: None,
+                      written: 0,
+                      opened: Instant::now() }
+    }
+
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Append `line` and a newline to the log, rotating it first if it is due.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.file.is_some() && self.rotation_due() {
+            self.rotate()?;
+        }
+        if self.file.is_none() {
+            self.open()?;
+        }
+        let file = self.file.as_mut().expect("log file opened above");
+        writeln!(file, "{}", line)?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotation_due(&self) -> bool {
+        (self.max_bytes > 0 && self.written >= self.max_bytes)
+        || (self.max_age > Duration::from_secs(0) && self.opened.elapsed() >= self.max_age)
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true)
+                                     .append(true)
+                                     .open(&self.path)?;
+        self.written = file.metadata()?.len();
+        self.opened = Instant::now();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        if self.keep == 0 {
+            return File::create(&self.path).map(|_| ());
+        }
+        let oldest = rotated_path(&self.path, self.keep);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for n in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+}
+
+/// The path of the `n`th most recently rotated copy of the log at `path`. `0` is the live log
+/// itself.
+pub fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    if n == 0 {
+        return path.to_path_buf();
+    }
+    let mut name = path.file_name().map(ToOwned::to_owned).unwrap_or_default();
+    name.push(format!(".{}", n));
+    path.with_file_name(name)
+}
+
+/// The live log at `path` and its rotated copies that exist, oldest first.
+pub fn existing_logs(path: &Path) -> Vec<PathBuf> {
+    let mut logs: Vec<PathBuf> = (1..).map(|n| rotated_path(path, n))
+                                      .take_while(|p| p.exists())
+                                      .collect();
+    logs.reverse();
+    if path.exists() {
+        logs.push(path.to_path_buf());
+    }
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn contents(path: &Path) -> String { fs::read_to_string(path).unwrap() }
+
+    #[test]
+    fn rotated_path_appends_a_numeric_suffix() {
+        let path = Path::new("/hab/svc/redis/logs/run.stdout.log");
+        assert_eq!(rotated_path(path, 0), path);
+        assert_eq!(rotated_path(path, 2),
+                   Path::new("/hab/svc/redis/logs/run.stdout.log.2"));
+    }
+
+    #[test]
+    fn rotates_once_the_log_is_too_large() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run.stdout.log");
+        let mut log = RotatingLog::new(&path, 10, Duration::from_secs(0), 2);
+
+        for line in &["first line", "second line", "third line", "fourth line"] {
+            log.write_line(line).unwrap();
+        }
+
+        assert_eq!(contents(&path), "fourth line\n");
+        assert_eq!(contents(&rotated_path(&path, 1)), "third line\n");
+        assert_eq!(contents(&rotated_path(&path, 2)), "second line\n");
+        assert!(!rotated_path(&path, 3).exists());
+        assert_eq!(existing_logs(&path),
+                   vec![rotated_path(&path, 2), rotated_path(&path, 1), path.clone()]);
+    }
+
+    #[test]
+    fn rotates_once_the_log_is_too_old() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run.stdout.log");
+        let mut log = RotatingLog::new(&path, 0, Duration::from_millis(10), 1);
+
+        log.write_line("old").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        log.write_line("new").unwrap();
+
+        assert_eq!(contents(&path), "new\n");
+        assert_eq!(contents(&rotated_path(&path, 1)), "old\n");
+    }
+
+    #[test]
+    fn truncates_when_no_copies_are_kept() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run.stdout.log");
+        let mut log = RotatingLog::new(&path, 4, Duration::from_secs(0), 0);
+
+        log.write_line("first").unwrap();
+        log.write_line("second").unwrap();
+
+        assert_eq!(contents(&path), "second\n");
+        assert_eq!(existing_logs(&path), vec![path.clone()]);
+    }
+
+    #[test]
+    fn appends_to_an_existing_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run.stdout.log");
+        fs::write(&path, "before restart\n").unwrap();
+        let mut log = RotatingLog::new(&path, 1024, Duration::from_secs(0), 1);
+
+        log.write_line("after restart").unwrap();
+
+        assert_eq!(contents(&path), "before restart\nafter restart\n");
+    }
+}
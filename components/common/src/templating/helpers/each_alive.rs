@@ -20,7 +20,8 @@ use handlebars::{Handlebars,
                  RenderContext,
                  RenderError,
                  Renderable};
-use serde_json::Value as Json;
+use serde_json::{Map,
+                 Value as Json};
 
 use super::{super::RenderResult,
             to_json,
@@ -31,106 +32,116 @@ pub struct EachAliveHelper;
 
 impl HelperDef for EachAliveHelper {
     fn call(&self, h: &Helper<'_>, r: &Handlebars, rc: &mut RenderContext<'_>) -> RenderResult<()> {
-        let value = h.param(0)
-                     .ok_or_else(|| RenderError::new("Param not found for helper \"eachAlive\""))?;
-        if let Some(template) = h.template() {
-            rc.promote_local_vars();
-            let local_path_root = value.path_root()
-                                       .map(|p| format!("{}/{}", rc.get_path(), p));
-            let rendered = match (value.value().is_truthy(), value.value()) {
-                (true, &Json::Array(ref list)) => {
-                    let alive_members: Vec<Json> = list.iter()
-                                                       .filter_map(|m| {
-                                                           m.as_object().and_then(|m| {
-                                if m.contains_key("alive") && m["alive"].as_bool().unwrap() {
-                                    Some(to_json(&m))
-                                } else {
-                                    None
-                                }
-                            })
-                                                       })
-                                                       .collect();
-                    let len = alive_members.len();
-                    for (i, alive_member) in alive_members.iter().enumerate() {
-                        let mut local_rc = rc.derive();
-                        local_rc.set_local_var("@first".to_string(), to_json(&(i == 0usize)));
-                        local_rc.set_local_var("@last".to_string(), to_json(&(i == len - 1)));
-                        local_rc.set_local_var("@index".to_string(), to_json(&i));
-
-                        if let Some(block_param) = h.block_param() {
-                            let mut map = BTreeMap::new();
-                            map.insert(block_param.to_string(), to_json(alive_member));
-                            local_rc.push_block_context(&map)?;
-                        }
-
-                        template.render(r, &mut local_rc)?;
-
-                        if h.block_param().is_some() {
-                            local_rc.pop_block_context();
-                        }
+        each_member("eachAlive", is_alive, h, r, rc)
+    }
+}
+
+/// Whether a serialized service group member is alive.
+pub fn is_alive(member: &Map<String, Json>) -> bool {
+    member.get("alive").and_then(Json::as_bool).unwrap_or(false)
+}
+
+/// Renders the block of the helper `name` like `each`, but only for the service group members
+/// that `include` accepts.
+pub fn each_member(name: &str,
+                   include: fn(&Map<String, Json>) -> bool,
+                   h: &Helper<'_>,
+                   r: &Handlebars,
+                   rc: &mut RenderContext<'_>)
+                   -> RenderResult<()> {
+    let value =
+        h.param(0)
+         .ok_or_else(|| RenderError::new(format!("Param not found for helper \"{}\"", name)))?;
+    if let Some(template) = h.template() {
+        rc.promote_local_vars();
+        let local_path_root = value.path_root()
+                                   .map(|p| format!("{}/{}", rc.get_path(), p));
+        let rendered = match (value.value().is_truthy(), value.value()) {
+            (true, &Json::Array(ref list)) => {
+                let members: Vec<Json> = list.iter()
+                                             .filter_map(Json::as_object)
+                                             .filter(|m| include(m))
+                                             .map(to_json)
+                                             .collect();
+                let len = members.len();
+                for (i, member) in members.iter().enumerate() {
+                    let mut local_rc = rc.derive();
+                    local_rc.set_local_var("@first".to_string(), to_json(&(i == 0usize)));
+                    local_rc.set_local_var("@last".to_string(), to_json(&(i == len - 1)));
+                    local_rc.set_local_var("@index".to_string(), to_json(&i));
+
+                    if let Some(block_param) = h.block_param() {
+                        let mut map = BTreeMap::new();
+                        map.insert(block_param.to_string(), to_json(member));
+                        local_rc.push_block_context(&map)?;
+                    }
+
+                    template.render(r, &mut local_rc)?;
+
+                    if h.block_param().is_some() {
+                        local_rc.pop_block_context();
                     }
-                    Ok(())
                 }
-                (true, &Json::Object(ref obj)) => {
-                    let mut first: bool = true;
-                    if !obj.contains_key("alive") || !obj["alive"].as_bool().unwrap() {
-                        return Ok(());
+                Ok(())
+            }
+            (true, &Json::Object(ref obj)) => {
+                let mut first: bool = true;
+                if !include(obj) {
+                    return Ok(());
+                }
+                for k in obj.keys() {
+                    let mut local_rc = rc.derive();
+                    if let Some(ref p) = local_path_root {
+                        local_rc.push_local_path_root(p.clone());
+                    }
+                    local_rc.set_local_var("@first".to_string(), to_json(&first));
+                    local_rc.set_local_var("@key".to_string(), to_json(k));
+
+                    if first {
+                        first = false;
                     }
-                    for k in obj.keys() {
-                        let mut local_rc = rc.derive();
-                        if let Some(ref p) = local_path_root {
-                            local_rc.push_local_path_root(p.clone());
-                        }
-                        local_rc.set_local_var("@first".to_string(), to_json(&first));
-                        local_rc.set_local_var("@key".to_string(), to_json(k));
-
-                        if first {
-                            first = false;
-                        }
-
-                        if let Some(inner_path) = value.path() {
-                            let new_path =
-                                format!("{}/{}.[{}]", local_rc.get_path(), inner_path, k);
-                            local_rc.set_path(new_path);
-                        }
-
-                        if let Some((bp_key, bp_val)) = h.block_param_pair() {
-                            let mut map = BTreeMap::new();
-                            map.insert(bp_key.to_string(), to_json(k));
-                            map.insert(bp_val.to_string(), to_json(obj.get(k).unwrap()));
-                            local_rc.push_block_context(&map)?;
-                        }
-
-                        template.render(r, &mut local_rc)?;
-
-                        if h.block_param().is_some() {
-                            local_rc.pop_block_context();
-                        }
-
-                        if local_path_root.is_some() {
-                            local_rc.pop_local_path_root();
-                        }
+
+                    if let Some(inner_path) = value.path() {
+                        let new_path = format!("{}/{}.[{}]", local_rc.get_path(), inner_path, k);
+                        local_rc.set_path(new_path);
                     }
-                    Ok(())
-                }
-                (false, _) => {
-                    if let Some(else_template) = h.inverse() {
-                        else_template.render(r, rc)?;
+
+                    if let Some((bp_key, bp_val)) = h.block_param_pair() {
+                        let mut map = BTreeMap::new();
+                        map.insert(bp_key.to_string(), to_json(k));
+                        map.insert(bp_val.to_string(), to_json(obj.get(k).unwrap()));
+                        local_rc.push_block_context(&map)?;
+                    }
+
+                    template.render(r, &mut local_rc)?;
+
+                    if h.block_param().is_some() {
+                        local_rc.pop_block_context();
+                    }
+
+                    if local_path_root.is_some() {
+                        local_rc.pop_local_path_root();
                     }
-                    Ok(())
                 }
-                _ => {
-                    Err(RenderError::new(format!("Param type is not iterable: \
-                                                  {:?}",
-                                                 template)))
+                Ok(())
+            }
+            (false, _) => {
+                if let Some(else_template) = h.inverse() {
+                    else_template.render(r, rc)?;
                 }
-            };
-
-            rc.demote_local_vars();
-            return rendered;
-        }
-        Ok(())
+                Ok(())
+            }
+            _ => {
+                Err(RenderError::new(format!("Param type is not iterable: \
+                                              {:?}",
+                                             template)))
+            }
+        };
+
+        rc.demote_local_vars();
+        return rendered;
     }
+    Ok(())
 }
 
 pub static EACH_ALIVE: EachAliveHelper = EachAliveHelper;
@@ -0,0 +1,68 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use handlebars::{Handlebars,
+                 Helper,
+                 HelperDef,
+                 RenderContext};
+use serde_json::{Map,
+                 Value as Json};
+
+use super::{super::RenderResult,
+            each_alive::{each_member,
+                         is_alive}};
+
+/// Like `eachAlive`, but also leaves out members whose last health check wasn't OK.
+#[derive(Clone, Copy)]
+pub struct EachHealthyHelper;
+
+impl HelperDef for EachHealthyHelper {
+    fn call(&self, h: &Helper<'_>, r: &Handlebars, rc: &mut RenderContext<'_>) -> RenderResult<()> {
+        each_member("eachHealthy", is_healthy, h, r, rc)
+    }
+}
+
+/// Whether a serialized service group member is alive and passing its health check. Members
+/// that don't report a health check, such as those on older Supervisors, count as healthy.
+fn is_healthy(member: &Map<String, Json>) -> bool {
+    is_alive(member)
+    && match member.get("health_check") {
+        None | Some(Json::Null) => true,
+        Some(health_check) => *health_check == "Ok",
+    }
+}
+
+pub static EACH_HEALTHY: EachHealthyHelper = EachHealthyHelper;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_each_healthy_helper() {
+        let json = json!({
+            "members": [
+                { "member_id": "healthy", "alive": true, "health_check": "Ok" },
+                { "member_id": "critical", "alive": true, "health_check": "Critical" },
+                { "member_id": "dead", "alive": false, "health_check": "Ok" },
+                { "member_id": "unreported", "alive": true }
+            ]
+        });
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("eachHealthy", Box::new(EACH_HEALTHY));
+        let template = "{{#eachHealthy members as |m|}}{{m.member_id}} {{/eachHealthy}}";
+        assert_eq!("healthy unreported ",
+                   handlebars.template_render(template, &json).unwrap());
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 mod each_alive;
+mod each_healthy;
 mod pkg_path_for;
 mod str_concat;
 mod str_join;
@@ -28,6 +29,7 @@ use serde_json::{self,
                  Value as Json};
 
 pub use self::{each_alive::EACH_ALIVE,
+               each_healthy::EACH_HEALTHY,
                pkg_path_for::PKG_PATH_FOR,
                str_concat::STR_CONCAT,
                str_join::STR_JOIN,
@@ -21,7 +21,8 @@ use crate::{error::{Error,
 use habitat_core::os::process::windows_child::{Child,
                                                ExitStatus};
 use habitat_core::{crypto,
-                   fs};
+                   fs,
+                   os::process};
 use serde::{Serialize,
             Serializer};
 #[cfg(unix)]
@@ -39,7 +40,10 @@ use std::{ffi::OsStr,
                BufReader},
           path::{Path,
                  PathBuf},
-          result};
+          result,
+          sync::mpsc,
+          thread,
+          time::Duration};
 
 #[cfg(not(windows))]
 pub const HOOK_PERMISSIONS: u32 = 0o755;
@@ -64,6 +68,30 @@ impl Default for ExitCode {
     fn default() -> ExitCode { ExitCode(-1) }
 }
 
+/// What came of running a hook.
+#[derive(Debug)]
+pub enum HookOutcome<V> {
+    /// The hook ran to completion, or couldn't be run at all.
+    Finished(V),
+    /// The hook was killed for running longer than its timeout.
+    TimedOut(V),
+}
+
+impl<V> HookOutcome<V> {
+    pub fn timed_out(&self) -> bool {
+        match *self {
+            HookOutcome::Finished(_) => false,
+            HookOutcome::TimedOut(_) => true,
+        }
+    }
+
+    pub fn into_value(self) -> V {
+        match self {
+            HookOutcome::Finished(value) | HookOutcome::TimedOut(value) => value,
+        }
+    }
+}
+
 // Hook and ExitValue must (currently) be Send so we can use them in
 // futures. ExitValue must currently be Debug because we want to use
 // it in Results
@@ -75,6 +103,9 @@ pub trait Hook: fmt::Debug + Sized + Send {
 
     fn file_name() -> &'static str;
 
+    /// The exit value of a hook that was killed for running longer than its timeout.
+    fn timed_out_value() -> Self::ExitValue { Self::ExitValue::default() }
+
     /// Tries to load a hook if a (deprecated) hook file exists.
     ///
     /// Returns the hook if template file (deprecated or not) is found
@@ -192,12 +223,13 @@ pub trait Hook: fmt::Debug + Sized + Send {
                 Habitat core developers");
     }
 
-    /// Run a compiled hook.
+    /// Run a compiled hook, killing it if it is still running once `timeout` has passed.
     fn run<T>(&self,
               service_group: &str,
               pkg: &Pkg,
-              svc_encrypted_password: Option<T>)
-              -> Self::ExitValue
+              svc_encrypted_password: Option<T>,
+              timeout: Option<Duration>)
+              -> HookOutcome<Self::ExitValue>
         where T: ToString
     {
         let mut child = match Self::exec(self.path(), &pkg, svc_encrypted_password) {
@@ -205,17 +237,25 @@ pub trait Hook: fmt::Debug + Sized + Send {
             Err(err) => {
                 outputln!(preamble service_group,
                     "Hook failed to run, {}, {}", Self::file_name(), err);
-                return Self::ExitValue::default();
+                return HookOutcome::Finished(Self::ExitValue::default());
             }
         };
+        let watchdog = match timeout {
+            Some(timeout) => Watchdog::start(service_group, Self::file_name(), child.id(), timeout),
+            None => None,
+        };
         let mut hook_output = HookOutput::new(self.stdout_log_path(), self.stderr_log_path());
         hook_output.stream_output::<Self>(service_group, &mut child);
-        match child.wait() {
-            Ok(status) => self.handle_exit(pkg, &hook_output, status),
+        let status = child.wait();
+        if watchdog.map_or(false, Watchdog::stop) {
+            return HookOutcome::TimedOut(Self::timed_out_value());
+        }
+        match status {
+            Ok(status) => HookOutcome::Finished(self.handle_exit(pkg, &hook_output, status)),
             Err(err) => {
                 outputln!(preamble service_group,
                     "Hook failed to run, {}, {}", Self::file_name(), err);
-                Self::ExitValue::default()
+                HookOutcome::Finished(Self::ExitValue::default())
             }
         }
     }
@@ -377,6 +417,70 @@ impl Hook for InstallHook {
     fn stderr_log_path(&self) -> &Path { &self.stderr_log_path }
 }
 
+/// Kills a running hook once its timeout has passed, unless it is stopped first.
+struct Watchdog {
+    stop:   mpsc::Sender<()>,
+    handle: thread::JoinHandle<bool>,
+}
+
+impl Watchdog {
+    fn start(service_group: &str,
+             hook_name: &'static str,
+             pid: u32,
+             timeout: Duration)
+             -> Option<Self> {
+        let (stop, stopped) = mpsc::channel();
+        let group = service_group.to_string();
+        let spawned =
+            thread::Builder::new().name(format!("{}-timeout", hook_name))
+                                  .spawn(move || watch(&group, hook_name, pid, timeout, &stopped));
+        match spawned {
+            Ok(handle) => Some(Watchdog { stop, handle }),
+            Err(err) => {
+                outputln!(preamble service_group,
+                          "Unable to enforce the timeout of {}, {}", hook_name, err);
+                None
+            }
+        }
+    }
+
+    /// Stop watching the hook, returning whether it had already been killed.
+    fn stop(self) -> bool {
+        // If the hook was killed, no one is listening any more.
+        self.stop.send(()).ok();
+        self.handle.join().unwrap_or(false)
+    }
+}
+
+/// Wait for the hook to be stopped or for `timeout` to pass, killing the hook in the latter case.
+/// Returns whether it was killed.
+fn watch(service_group: &str,
+         hook_name: &str,
+         pid: u32,
+         timeout: Duration,
+         stopped: &mpsc::Receiver<()>)
+         -> bool {
+    if stopped.recv_timeout(timeout) != Err(mpsc::RecvTimeoutError::Timeout) {
+        return false;
+    }
+    outputln!(preamble service_group,
+              "{} did not finish within {}s, killing it", hook_name, timeout.as_secs());
+    if let Err(err) = kill_hook(pid) {
+        outputln!(preamble service_group, "Failed to kill {}, {}", hook_name, err);
+    }
+    true
+}
+
+/// Kill a hook along with anything it started; hooks run in their own process group.
+#[cfg(unix)]
+fn kill_hook(pid: u32) -> habitat_core::error::Result<()> {
+    process::signal(-(pid as process::Pid), process::Signal::KILL)
+}
+
+/// Kill a hook. Unlike on Unix, anything the hook started is left running.
+#[cfg(windows)]
+fn kill_hook(pid: u32) -> habitat_core::error::Result<()> { process::terminate(pid) }
+
 /// Cryptographically hash the contents of the compiled hook
 /// file.
 ///
@@ -791,4 +895,40 @@ echo "The message is Hello"
 
         stdfs::remove_dir_all(tmp_dir).expect("remove temp dir");
     }
+
+    #[cfg(unix)]
+    fn spawn_in_own_process_group(program: &str, args: &[&str]) -> Child {
+        let mut cmd = Command::new(program);
+        cmd.args(args).before_exec(|| {
+                          unsafe { libc::setpgid(0, 0) };
+                          Ok(())
+                      });
+        cmd.spawn().expect("couldn't spawn process")
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn watchdog_kills_a_hook_that_outlives_its_timeout() {
+        let mut child = spawn_in_own_process_group("sleep", &["30"]);
+        let timeout = Duration::from_millis(100);
+        let watchdog = Watchdog::start("dummy.service", "init", child.id(), timeout).unwrap();
+
+        let status = child.wait().expect("couldn't wait for process");
+
+        assert!(watchdog.stop());
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn watchdog_leaves_a_hook_that_finishes_in_time_alone() {
+        let mut child = spawn_in_own_process_group("true", &[]);
+        let timeout = Duration::from_secs(30);
+        let watchdog = Watchdog::start("dummy.service", "init", child.id(), timeout).unwrap();
+
+        let status = child.wait().expect("couldn't wait for process");
+
+        assert!(!watchdog.stop());
+        assert!(status.success());
+    }
 }
@@ -343,6 +343,19 @@ pub trait UIWriter {
         self.out().flush()
     }
 
+    /// Write a message formatted with `status`, counting how many of `total` operations running
+    /// at once have finished, e.g. downloads on several threads.
+    fn status_of<T>(&mut self,
+                    status: Status,
+                    finished: usize,
+                    total: usize,
+                    message: T)
+                    -> io::Result<()>
+        where T: fmt::Display
+    {
+        self.status(status, format!("{} ({}/{})", message, finished, total))
+    }
+
     /// Write a message formatted with `info`.
     fn info<T>(&mut self, text: T) -> io::Result<()>
         where T: fmt::Display
@@ -262,6 +262,28 @@ impl SigKeyPair {
         Ok((Self::get_pair_for(&name_with_rev, cache_key_path)?, pair_type))
     }
 
+    /// Sign the hash of `data` with the secret key. The signature embeds the hash, and is
+    /// checked with `verify`.
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let hash = hash::hash_bytes(data);
+        Ok(sign::sign(hash.as_bytes(), self.secret()?))
+    }
+
+    /// Check that `signature` was made over `data` with the secret half of this pair.
+    pub fn verify(&self, signature: &[u8], data: &[u8]) -> Result<()> {
+        let signed_hash = sign::verify(signature, self.public()?).map_err(|_| {
+                              Error::CryptoError(format!("Signature verification failed for {}",
+                                                         self.name_with_rev()))
+                          })?;
+        if signed_hash == hash::hash_bytes(data).as_bytes() {
+            Ok(())
+        } else {
+            Err(Error::CryptoError(format!("Signature by {} is for \
+                                            different content",
+                                           self.name_with_rev())))
+        }
+    }
+
     pub fn to_public_string(&self) -> Result<String> {
         match self.public {
             Some(pk) => {
@@ -382,6 +404,19 @@ mod test {
                      .exists());
     }
 
+    #[test]
+    fn sign_and_verify() {
+        let pair = SigKeyPair::generate_pair_for_origin("unicorn").unwrap();
+        let signature = pair.sign(b"port = 8080").unwrap();
+
+        assert!(pair.verify(&signature, b"port = 8080").is_ok());
+        assert!(pair.verify(&signature, b"port = 8081").is_err(),
+                "Signature should not verify different content");
+        let other = SigKeyPair::generate_pair_for_origin("dragon").unwrap();
+        assert!(other.verify(&signature, b"port = 8080").is_err(),
+                "Signature should not verify with another key");
+    }
+
     #[test]
     fn get_pairs_for() {
         let cache = Builder::new().prefix("key_cache").tempdir().unwrap();
@@ -99,6 +99,8 @@ pub enum Error {
     InvalidApplicationEnvironment(String),
     /// Occurs when a service binding cannot be successfully parsed.
     InvalidBinding(String),
+    /// Occurs when a member label cannot be successfully parsed.
+    InvalidLabel(String),
     /// Occurs when a package identifier string cannot be successfully parsed.
     InvalidPackageIdent(String),
     /// Occurs when a package target string cannot be successfully parsed.
@@ -107,6 +109,12 @@ pub enum Error {
     InvalidPackageType(String),
     /// Occurs when a service group string cannot be successfully parsed.
     InvalidServiceGroup(String),
+    /// Occurs when an update batch size string cannot be successfully parsed.
+    InvalidUpdateBatchSize(String),
+    /// Occurs when an update window string cannot be successfully parsed.
+    InvalidUpdateWindow(String),
+    /// Occurs when a version constraint string cannot be successfully parsed.
+    InvalidVersionConstraint(String),
     /// Occurs when an origin is in an invalid format
     InvalidOrigin(String),
     /// Occurs when an OsString path cannot be converted to a String
@@ -277,6 +285,11 @@ impl fmt::Display for Error {
                          <NAME> is a service name, and <SERVICE_GROUP> is a valid service group",
                         binding)
             }
+            Error::InvalidLabel(ref label) => {
+                format!("Invalid label '{}', must be of the form <KEY>=<VALUE> where neither \
+                         contains whitespace, '=', ',', '[' or ']'",
+                        label)
+            }
             Error::InvalidPackageIdent(ref e) => {
                 format!("Invalid package identifier: {:?}. A valid identifier is in the form \
                          origin/name (example: acme/redis)",
@@ -293,6 +306,23 @@ impl fmt::Display for Error {
                          service.group (example: redis.production)",
                         e)
             }
+            Error::InvalidUpdateBatchSize(ref e) => {
+                format!("Invalid update batch size: {}. A valid batch size is a number of members \
+                         (example: 3) or a percentage of the service group (example: 25%)",
+                        e)
+            }
+            Error::InvalidUpdateWindow(ref e) => {
+                format!("Invalid update window: {}. A valid update window is a cron-like schedule \
+                         of minute, hour, day of month, month and day of week (example: \"* 2-4 * \
+                         * sat,sun\")",
+                        e)
+            }
+            Error::InvalidVersionConstraint(ref e) => {
+                format!("Invalid version constraint: {}. A valid constraint is a comma-separated \
+                         list of versions, each prefixed with =, >, >=, <, <= or ~ (example: \
+                         >=1.0.2,<1.1)",
+                        e)
+            }
             Error::InvalidOrigin(ref origin) => {
                 format!("Invalid origin: {}. Origins must begin with a lowercase letter or \
                          number. Allowed characters include lowercase letters, numbers, -, and _. \
@@ -443,6 +473,9 @@ impl error::Error for Error {
                 "Service Bind strings must be in name:service_group format (example \
                  cache:redis.cache@organization)."
             }
+            Error::InvalidLabel(_) => {
+                "Labels must be in key=value format (example: zone=us-east-1a)"
+            }
             Error::InvalidPackageIdent(_) => {
                 "Package identifiers must be in origin/name format (example: acme/redis)"
             }
@@ -454,6 +487,16 @@ impl error::Error for Error {
                 "Service group strings must be in service.group[@organization] format (example: \
                  redis.production or foo.default@bazcorp)"
             }
+            Error::InvalidUpdateBatchSize(_) => {
+                "Update batch sizes must be a number of members or a percentage (example: 3 or 25%)"
+            }
+            Error::InvalidUpdateWindow(_) => {
+                "Update windows must be cron-like schedules (example: \"* 2-4 * * sat,sun\")"
+            }
+            Error::InvalidVersionConstraint(_) => {
+                "Version constraints must be comma-separated versions prefixed with =, >, >=, <, \
+                 <= or ~ (example: >=1.0.2,<1.1)"
+            }
             Error::InvalidOrigin(_) => {
                 "Origins must begin with a lowercase letter or number.  Allowed characters include \
                  a - z, 0 - 9, _, and -. No more than 255 characters."
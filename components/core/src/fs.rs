@@ -274,6 +274,16 @@ pub fn svc_logs_path<T: AsRef<Path>>(service_name: T) -> PathBuf {
     svc_path(service_name).join("logs")
 }
 
+/// Returns the path to the log holding a given service's standard output.
+pub fn svc_stdout_log_file<T: AsRef<Path>>(service_name: T) -> PathBuf {
+    svc_logs_path(service_name).join("run.stdout.log")
+}
+
+/// Returns the path to the log holding a given service's standard error.
+pub fn svc_stderr_log_file<T: AsRef<Path>>(service_name: T) -> PathBuf {
+    svc_logs_path(service_name).join("run.stderr.log")
+}
+
 /// Returns the path to a given service's pid file.
 pub fn svc_pid_file<T: AsRef<Path>>(service_name: T) -> PathBuf {
     svc_path(service_name).join("PID")
@@ -35,6 +35,7 @@ pub use self::windows::{become_command,
                         current_pid,
                         handle_from_pid,
                         is_alive,
+                        terminate,
                         Pid};
 use crate::error::Error;
 use std::{fmt,
@@ -70,6 +70,27 @@ pub fn is_alive(pid: Pid) -> bool {
     }
 }
 
+/// Forcibly terminates the process with the given identifier. A process that has already gone
+/// away is not an error.
+pub fn terminate(pid: Pid) -> Result<()> {
+    let handle = match handle_from_pid(pid) {
+        Some(handle) => handle,
+        None => return Ok(()),
+    };
+    unsafe {
+        let ret = processthreadsapi::TerminateProcess(handle, 1);
+        let _ = handleapi::CloseHandle(handle);
+        if ret == 0 {
+            return Err(Error::TerminateProcessFailed(format!(
+                "Failed to call TerminateProcess on pid {}: {}",
+                pid,
+                io::Error::last_os_error()
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Executes a command as a child process and exits with the child's exit code.
 ///
 /// Note that if successful, this function will not return.
@@ -351,6 +351,227 @@ impl<'a> Iterator for Iter<'a> {
     }
 }
 
+/// A range of versions, such as `>=1.0.2,<1.1` or `~1.0`.
+///
+/// A constraint is a comma-separated list of versions, each prefixed with one of `=`, `>`, `>=`,
+/// `<`, `<=` or `~`, and a version is within it if it satisfies every one of them. A version
+/// without a prefix must be matched exactly. Versions are compared by [`version_sort`], so `1.0`
+/// and `1.0.0` are equal.
+///
+/// `~` allows any later version that has the same major and minor version, or the same major
+/// version when no minor version is given: `~1.0.2` is `>=1.0.2,<1.1`, and `~1` is `>=1,<2`.
+///
+/// [`version_sort`]: fn.version_sort.html
+///
+/// # Examples
+///
+/// ```
+/// use habitat_core::package::VersionConstraint;
+/// use std::str::FromStr;
+///
+/// let constraint = VersionConstraint::from_str("~1.0").unwrap();
+///
+/// assert!(constraint.matches("1.0.2"));
+/// assert!(!constraint.matches("1.1.0"));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VersionConstraint(Vec<(Comparison, String)>);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Comparison {
+    Exact,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Tilde,
+}
+
+impl VersionConstraint {
+    /// Is the version within the constraint?
+    ///
+    /// A version that can't be compared, because it isn't in the form `version_sort` expects,
+    /// is never within it.
+    pub fn matches(&self, version: &str) -> bool {
+        self.0
+            .iter()
+            .all(|(comparison, bound)| comparison.matches(version, bound))
+    }
+}
+
+impl Comparison {
+    /// The comparisons, in the order their prefixes must be tried, so that `>=` isn't taken
+    /// for `>`.
+    const PREFIXED: &'static [Comparison] = &[Comparison::GreaterOrEqual,
+                                              Comparison::LessOrEqual,
+                                              Comparison::Greater,
+                                              Comparison::Less,
+                                              Comparison::Exact,
+                                              Comparison::Tilde];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Comparison::Exact => "=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Tilde => "~",
+        }
+    }
+
+    fn matches(self, version: &str, bound: &str) -> bool {
+        let ordering = match version_sort(version, bound) {
+            Ok(ordering) => ordering,
+            Err(_) => return false,
+        };
+        match self {
+            Comparison::Exact => ordering == Ordering::Equal,
+            Comparison::Greater => ordering == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Tilde => {
+                ordering != Ordering::Less
+                && tilde_upper_bound(bound).map_or(false, |upper| {
+                                               Comparison::Less.matches(version, &upper)
+                                           })
+            }
+        }
+    }
+}
+
+/// The first version a `~` constraint excludes: the next minor version, or the next major
+/// version when the bound has no minor version.
+fn tilde_upper_bound(bound: &str) -> Option<String> {
+    let (parts, _) = split_version(bound).ok()?;
+    let major = parts.get(0)?.parse::<u64>().ok()?;
+    match parts.get(1) {
+        Some(minor) => Some(format!("{}.{}", major, minor.parse::<u64>().ok()? + 1)),
+        None => Some((major + 1).to_string()),
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparisons: Vec<String> =
+            self.0
+                .iter()
+                .map(|(comparison, bound)| format!("{}{}", comparison.prefix(), bound))
+                .collect();
+        write!(f, "{}", comparisons.join(","))
+    }
+}
+
+impl FromStr for VersionConstraint {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let mut comparisons = Vec::new();
+        for part in value.split(',').map(str::trim) {
+            let (comparison, bound) =
+                Comparison::PREFIXED.iter()
+                                    .find(|comparison| part.starts_with(comparison.prefix()))
+                                    .map_or((Comparison::Exact, part), |&comparison| {
+                                        (comparison, &part[comparison.prefix().len()..])
+                                    });
+            // A bound must be a version `version_sort` can compare, starting with a digit.
+            if !bound.starts_with(|c: char| c.is_ascii_digit())
+               || bound.contains(|c: char| c == '/' || c.is_whitespace())
+               || version_sort(bound, bound).is_err()
+            {
+                return Err(Error::InvalidVersionConstraint(value.to_string()));
+            }
+            comparisons.push((comparison, bound.to_string()));
+        }
+        Ok(VersionConstraint(comparisons))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VersionConstraint {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        struct VersionConstraintVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for VersionConstraintVisitor {
+            type Value = VersionConstraint;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a version constraint (example \">=1.0.2,<1.1\")")
+            }
+
+            fn visit_str<E>(self, s: &str) -> std::result::Result<Self::Value, E>
+                where E: serde::de::Error
+            {
+                VersionConstraint::from_str(s).map_err(|_| {
+                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(s), &self)
+                })
+            }
+        }
+
+        deserializer.deserialize_str(VersionConstraintVisitor)
+    }
+}
+
+impl serde::Serialize for VersionConstraint {
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// A package identifier with a range of versions, such as `core/openssl@>=1.0.2,<1.1`.
+///
+/// Where a `PackageIdent` names one version, or the latest of all of them, this names the latest
+/// of the versions within a [`VersionConstraint`].
+///
+/// [`VersionConstraint`]: struct.VersionConstraint.html
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PackageIdentConstraint {
+    /// The origin and name of the package, without a version or release.
+    pub ident:   PackageIdent,
+    pub version: VersionConstraint,
+}
+
+impl PackageIdentConstraint {
+    pub fn new(ident: PackageIdent, version: VersionConstraint) -> Self {
+        PackageIdentConstraint { ident, version }
+    }
+
+    /// Is the identifier of the same package, at a version within the constraint?
+    pub fn matches<I: Identifiable>(&self, ident: &I) -> bool {
+        ident.origin() == self.ident.origin
+        && ident.name() == self.ident.name
+        && ident.version()
+                .map_or(false, |version| self.version.matches(version))
+    }
+}
+
+impl fmt::Display for PackageIdentConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.ident, self.version)
+    }
+}
+
+impl FromStr for PackageIdentConstraint {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let mut parts = value.splitn(2, '@');
+        let ident = PackageIdent::from_str(parts.next().unwrap_or_default())?;
+        if ident.version.is_some() {
+            return Err(Error::InvalidPackageIdent(value.to_string()));
+        }
+        let version = match parts.next() {
+            Some(version) => version.parse()?,
+            None => return Err(Error::InvalidVersionConstraint(value.to_string())),
+        };
+        Ok(PackageIdentConstraint::new(ident, version))
+    }
+}
+
 /// Sorts two packages according to their version.
 ///
 /// We are a bit more strict than your average package management solution on versioning.
@@ -759,4 +980,74 @@ mod tests {
         assert_eq!(Some("rise-up"), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[test]
+    fn version_constraint_ranges() {
+        let constraint = VersionConstraint::from_str(">=1.0.2,<1.1").unwrap();
+        assert!(constraint.matches("1.0.2"));
+        assert!(constraint.matches("1.0.10"));
+        assert!(!constraint.matches("1.0.1"));
+        assert!(!constraint.matches("1.1"));
+        assert!(!constraint.matches("1.1.0"));
+        assert!(!constraint.matches("not-a-version"));
+
+        let constraint = VersionConstraint::from_str("1.0").unwrap();
+        assert!(constraint.matches("1.0.0"));
+        assert!(!constraint.matches("1.0.1"));
+        assert!(!constraint.matches("1.0.0-rc1"));
+    }
+
+    #[test]
+    fn version_constraint_tilde() {
+        let constraint = VersionConstraint::from_str("~1.0.2").unwrap();
+        assert!(constraint.matches("1.0.2"));
+        assert!(constraint.matches("1.0.9"));
+        assert!(!constraint.matches("1.0.1"));
+        assert!(!constraint.matches("1.1.0"));
+
+        let constraint = VersionConstraint::from_str("~1").unwrap();
+        assert!(constraint.matches("1.0.0"));
+        assert!(constraint.matches("1.9"));
+        assert!(!constraint.matches("2.0.0"));
+    }
+
+    #[test]
+    fn version_constraint_from_str() {
+        let constraint = VersionConstraint::from_str(" >=1.0.2 , <1.1 ").unwrap();
+        assert_eq!(constraint.to_string(), ">=1.0.2,<1.1");
+        assert_eq!(VersionConstraint::from_str("1.0").unwrap().to_string(),
+                   "=1.0");
+
+        for invalid in &["", ">=", "~latest", ">=1.0,", "=>1.0", "1.0/20180608041157"] {
+            match VersionConstraint::from_str(invalid) {
+                Err(Error::InvalidVersionConstraint(_)) => (),
+                other => panic!("Expected {:?} to be invalid, got {:?}", invalid, other),
+            }
+        }
+    }
+
+    #[test]
+    fn package_ident_constraint_from_str() {
+        let constraint = PackageIdentConstraint::from_str("core/openssl@>=1.0.2,<1.1").unwrap();
+        assert_eq!(constraint.ident,
+                   PackageIdent::from_str("core/openssl").unwrap());
+        assert_eq!(constraint.to_string(), "core/openssl@>=1.0.2,<1.1");
+
+        assert!(PackageIdentConstraint::from_str("core/openssl").is_err());
+        assert!(PackageIdentConstraint::from_str("core/openssl/1.0.2@~1.0").is_err());
+        assert!(PackageIdentConstraint::from_str("core@~1.0").is_err());
+    }
+
+    #[test]
+    fn package_ident_constraint_matches() {
+        let constraint = PackageIdentConstraint::from_str("core/openssl@~1.0").unwrap();
+        let matches = |ident| constraint.matches(&PackageIdent::from_str(ident).unwrap());
+
+        assert!(matches("core/openssl/1.0.2/20180608041157"));
+        assert!(matches("core/openssl/1.0.2"));
+        assert!(!matches("core/openssl/1.1.0/20180608041157"));
+        assert!(!matches("core/openssl"));
+        assert!(!matches("acme/openssl/1.0.2/20180608041157"));
+        assert!(!matches("core/libressl/1.0.2/20180608041157"));
+    }
 }
@@ -20,7 +20,8 @@ use super::{list::package_list_for_ident,
                        MetaFile,
                        PackageType},
             Identifiable,
-            PackageIdent};
+            PackageIdent,
+            PackageIdentConstraint};
 use crate::{error::{Error,
                     Result},
             fs};
@@ -89,6 +90,34 @@ impl PackageInstall {
         Ok(package_install)
     }
 
+    /// Verifies an installation of a package within a version constraint and returns a struct
+    /// representing the latest such installation, if one exists.
+    ///
+    /// An optional `fs_root` path may be provided to search for a package that is mounted on a
+    /// filesystem not currently rooted at `/`.
+    pub fn load_matching(constraint: &PackageIdentConstraint,
+                         fs_root_path: Option<&Path>)
+                         -> Result<PackageInstall> {
+        let fs_root_path = fs_root_path.map_or(PathBuf::from("/"), Into::into);
+        let package_root_path = fs::pkg_root_path(Some(&fs_root_path));
+        if !package_root_path.exists() {
+            return Err(Error::PackageNotFound(constraint.ident.clone()));
+        }
+
+        let pl = package_list_for_ident(&package_root_path, &constraint.ident)?;
+        let latest = pl.into_iter().filter(|p| constraint.matches(p)).max();
+        match latest {
+            Some(id) => {
+                Ok(PackageInstall { installed_path: fs::pkg_install_path(&id,
+                                                                         Some(&fs_root_path)),
+                                    fs_root_path,
+                                    package_root_path,
+                                    ident: id })
+            }
+            None => Err(Error::PackageNotFound(constraint.ident.clone())),
+        }
+    }
+
     fn resolve_package_install<T>(ident: &PackageIdent,
                                   fs_root_path: Option<T>)
                                   -> Result<PackageInstall>
@@ -1050,6 +1079,31 @@ core/bar=pub:core/publish sub:core/subscribe
         assert_eq!(active_target, loaded.target().unwrap());
     }
 
+    #[test]
+    fn load_matching_loads_the_latest_package_within_the_constraint() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let active_target = PackageTarget::active_target();
+        for ident_s in &["dream-theater/systematic-chaos/1.0.1/20180704142702",
+                         "dream-theater/systematic-chaos/1.0.9/20180704142702",
+                         "dream-theater/systematic-chaos/1.1.0/20180704142702"]
+        {
+            let pkg_install = testing_package_install(ident_s, fs_root.path());
+            write_metafile(&pkg_install, MetaFile::Target, &active_target);
+        }
+
+        let constraint = "dream-theater/systematic-chaos@~1.0".parse().unwrap();
+        let loaded = PackageInstall::load_matching(&constraint, Some(fs_root.path())).unwrap();
+        assert_eq!(loaded.ident,
+                   PackageIdent::from_str("dream-theater/systematic-chaos/1.0.9/20180704142702")
+                   .unwrap());
+
+        let constraint = "dream-theater/systematic-chaos@>=2".parse().unwrap();
+        match PackageInstall::load_matching(&constraint, Some(fs_root.path())) {
+            Err(Error::PackageNotFound(_)) => (),
+            other => panic!("Expected a missing package, got {:?}", other),
+        }
+    }
+
     #[test]
     fn load_at_least_with_missing_target_returns_package_not_found_err() {
         let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
@@ -23,7 +23,9 @@ pub mod target;
 pub use self::{archive::{FromArchive,
                          PackageArchive},
                ident::{Identifiable,
-                       PackageIdent},
+                       PackageIdent,
+                       PackageIdentConstraint,
+                       VersionConstraint},
                install::PackageInstall,
                list::all_packages,
                plan::Plan,
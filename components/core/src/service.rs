@@ -17,13 +17,17 @@ use crate::error::{Error,
 use regex::Regex;
 use serde_derive::{Deserialize,
                    Serialize};
-use std::{fmt,
+use std::{collections::BTreeMap,
+          fmt,
           num::ParseIntError,
           ops::{Deref,
                 DerefMut},
           result,
           str::FromStr,
           time::Duration};
+use time::{self,
+           Timespec,
+           Tm};
 
 lazy_static::lazy_static! {
     static ref SG_FROM_STR_RE: Regex =
@@ -31,6 +35,9 @@ lazy_static::lazy_static! {
 
     static ref AE_FROM_STR_RE: Regex =
         Regex::new(r"\A(?P<application>[^#.@]+)\.(?P<environment>[^#.@]+)\z").unwrap();
+
+    static ref LABEL_FROM_STR_RE: Regex =
+        Regex::new(r"\A(?P<key>[^=,\[\]\s]+)=(?P<value>[^=,\[\]\s]+)\z").unwrap();
 }
 
 /// Determines how the presence of bound service groups affects the
@@ -81,31 +88,71 @@ impl FromStr for BindingMode {
     }
 }
 
+/// Parses a Supervisor label in `key=value` format (example: `zone=us-east-1a`).
+pub fn parse_label(label: &str) -> Result<(String, String)> {
+    let caps = LABEL_FROM_STR_RE.captures(label)
+                                .ok_or_else(|| Error::InvalidLabel(label.to_string()))?;
+    Ok((caps["key"].to_string(), caps["value"].to_string()))
+}
+
 /// A binding from a service name to a service group that provides that service
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct ServiceBind {
     name:          String,
     service_group: ServiceGroup,
+    /// Only members of the service group whose Supervisors carry all of these labels are bound
+    /// to; written as `name:service.group[key=value,...]`.
+    labels:        BTreeMap<String, String>,
 }
 
 impl ServiceBind {
     pub fn new(name: &str, service_group: ServiceGroup) -> Self {
         Self { name: name.to_string(),
-               service_group }
+               service_group,
+               labels: BTreeMap::new() }
+    }
+
+    pub fn with_labels(mut self, labels: BTreeMap<String, String>) -> Self {
+        self.labels = labels;
+        self
     }
 
     pub fn name(&self) -> &str { &self.name }
 
     pub fn service_group(&self) -> &ServiceGroup { &self.service_group }
+
+    pub fn labels(&self) -> &BTreeMap<String, String> { &self.labels }
+
+    /// Whether a member whose Supervisor carries `labels` may be bound to.
+    pub fn selects(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.labels
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+    }
 }
 
 impl FromStr for ServiceBind {
     type Err = Error;
 
     fn from_str(bind_str: &str) -> result::Result<Self, Self::Err> {
-        let parts: Vec<_> = bind_str.split(':').collect();
+        let (bind, labels) = match bind_str.find('[') {
+            Some(start) if bind_str.ends_with(']') => {
+                let labels = &bind_str[start + 1..bind_str.len() - 1];
+                let labels = labels.split(',')
+                                   .map(parse_label)
+                                   .collect::<Result<BTreeMap<_, _>>>()?;
+                (&bind_str[..start], labels)
+            }
+            Some(_) => return Err(Error::InvalidBinding(bind_str.to_string())),
+            None => (bind_str, BTreeMap::new()),
+        };
+        let parts: Vec<_> = bind.split(':').collect();
         match parts.as_slice() {
-            [name, sg_str] => ServiceGroup::from_str(sg_str).map(|sg| ServiceBind::new(name, sg)),
+            [name, sg_str] => {
+                ServiceGroup::from_str(sg_str).map(|sg| {
+                                                  ServiceBind::new(name, sg).with_labels(labels)
+                                              })
+            }
             _ => Err(Error::InvalidBinding(bind_str.to_string())),
         }
     }
@@ -113,7 +160,15 @@ impl FromStr for ServiceBind {
 
 impl fmt::Display for ServiceBind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}", self.name, self.service_group)
+        write!(f, "{}:{}", self.name, self.service_group)?;
+        if !self.labels.is_empty() {
+            let labels: Vec<_> = self.labels
+                                     .iter()
+                                     .map(|(key, value)| format!("{}={}", key, value))
+                                     .collect();
+            write!(f, "[{}]", labels.join(","))?;
+        }
+        Ok(())
     }
 }
 
@@ -422,6 +477,242 @@ impl From<HealthCheckInterval> for Duration {
     fn from(h: HealthCheckInterval) -> Self { h.0 }
 }
 
+/// How many members of a service group update together when a new
+/// release is rolled out in batches: either a fixed number of members,
+/// or a percentage of the group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpdateBatchSize {
+    Members(u32),
+    Percent(u32),
+}
+
+impl UpdateBatchSize {
+    /// The number of members in each batch of a group with
+    /// `group_size` members. Percentages are rounded up, and a batch
+    /// always has at least one member.
+    pub fn members(self, group_size: usize) -> usize {
+        let members = match self {
+            UpdateBatchSize::Members(n) => n as usize,
+            UpdateBatchSize::Percent(p) => (group_size * p as usize + 99) / 100,
+        };
+        members.max(1)
+    }
+}
+
+impl Default for UpdateBatchSize {
+    fn default() -> Self { UpdateBatchSize::Members(1) }
+}
+
+impl fmt::Display for UpdateBatchSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            UpdateBatchSize::Members(n) => write!(f, "{}", n),
+            UpdateBatchSize::Percent(p) => write!(f, "{}%", p),
+        }
+    }
+}
+
+impl FromStr for UpdateBatchSize {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let err = || Error::InvalidUpdateBatchSize(value.to_string());
+        if value.ends_with('%') {
+            match value[..value.len() - 1].parse::<u32>() {
+                Ok(p) if p > 0 && p <= 100 => Ok(UpdateBatchSize::Percent(p)),
+                _ => Err(err()),
+            }
+        } else {
+            match value.parse::<u32>() {
+                Ok(n) if n > 0 => Ok(UpdateBatchSize::Members(n)),
+                _ => Err(err()),
+            }
+        }
+    }
+}
+
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: &[&str] =
+    &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// A cron-like schedule of when automatic updates may be applied.
+///
+/// Like a crontab entry, the schedule has five fields: minute, hour, day of month, month and day
+/// of week. Each field is `*`, a value, a range (`1-5`), a step (`*/15` or `0-30/10`) or a
+/// comma-separated list of those; months and days of the week may also be given by name. The
+/// window is open during every minute (in UTC) the schedule matches, so `* 2-4 * * sat,sun`
+/// allows updates from 02:00 until 04:59 on weekends.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct UpdateWindow {
+    expression: String,
+    minutes:    u64,
+    hours:      u64,
+    days:       u64,
+    months:     u64,
+    weekdays:   u64,
+    /// As with cron, when both the day of month and day of week are restricted, a day matching
+    /// either one is in the window.
+    either_day: bool,
+}
+
+impl UpdateWindow {
+    /// Whether the window is open right now.
+    pub fn is_open(&self) -> bool { self.is_open_at(&time::now_utc()) }
+
+    /// Whether the window is open at the given (UTC) time.
+    pub fn is_open_at(&self, tm: &Tm) -> bool {
+        has_bit(self.minutes, tm.tm_min)
+        && has_bit(self.hours, tm.tm_hour)
+        && has_bit(self.months, tm.tm_mon + 1)
+        && self.day_matches(tm)
+    }
+
+    /// The start of the first minute, at or after the one containing `from`, in which the window
+    /// is open. Returns `None` for a window that never opens, such as `* * 31 2 *`.
+    pub fn next_open(&self, from: Timespec) -> Option<Timespec> {
+        let mut sec = from.sec - from.sec % 60;
+        // Long enough to find a window that only opens on February 29th
+        let limit = sec + 8 * 366 * 24 * 60 * 60;
+        while sec < limit {
+            let tm = time::at_utc(Timespec::new(sec, 0));
+            let into_day = i64::from(tm.tm_hour * 60 * 60 + tm.tm_min * 60);
+            if !has_bit(self.months, tm.tm_mon + 1) || !self.day_matches(&tm) {
+                sec += 24 * 60 * 60 - into_day;
+            } else if !has_bit(self.hours, tm.tm_hour) {
+                sec += 60 * 60 - i64::from(tm.tm_min * 60);
+            } else if !has_bit(self.minutes, tm.tm_min) {
+                sec += 60;
+            } else {
+                return Some(Timespec::new(sec, 0));
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, tm: &Tm) -> bool {
+        let day = has_bit(self.days, tm.tm_mday);
+        let weekday = has_bit(self.weekdays, tm.tm_wday);
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// Parse one field of the schedule into a bitset of the values it matches.
+    fn parse_field(field: &str,
+                   min: u32,
+                   max: u32,
+                   names: &[&str],
+                   first_name: u32)
+                   -> Option<u64> {
+        let value = |s: &str| {
+            s.parse::<u32>().ok().or_else(|| {
+                                     names.iter()
+                                          .position(|n| n.eq_ignore_ascii_case(s))
+                                          .map(|i| i as u32 + first_name)
+                                 })
+        };
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let mut pieces = part.splitn(2, '/');
+            let range = pieces.next()?;
+            let step = match pieces.next() {
+                Some(step) => step.parse::<usize>().ok().filter(|s| *s > 0)?,
+                None => 1,
+            };
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else {
+                let mut bounds = range.splitn(2, '-');
+                let start = value(bounds.next()?)?;
+                match bounds.next() {
+                    Some(end) => (start, value(end)?),
+                    // `5/10` means every 10th value starting at 5
+                    None if step > 1 => (start, max),
+                    None => (start, start),
+                }
+            };
+            if start < min || end > max || start > end {
+                return None;
+            }
+            for v in (start..=end).step_by(step) {
+                bits |= 1 << v;
+            }
+        }
+        Some(bits)
+    }
+}
+
+fn has_bit(bits: u64, value: i32) -> bool { value >= 0 && bits & (1 << value) != 0 }
+
+impl fmt::Display for UpdateWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.expression) }
+}
+
+impl FromStr for UpdateWindow {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let err = || Error::InvalidUpdateWindow(value.to_string());
+        let fields: Vec<_> = value.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(err());
+        }
+        let minutes = Self::parse_field(fields[0], 0, 59, &[], 0).ok_or_else(err)?;
+        let hours = Self::parse_field(fields[1], 0, 23, &[], 0).ok_or_else(err)?;
+        let days = Self::parse_field(fields[2], 1, 31, &[], 0).ok_or_else(err)?;
+        let months = Self::parse_field(fields[3], 1, 12, MONTH_NAMES, 1).ok_or_else(err)?;
+        let mut weekdays = Self::parse_field(fields[4], 0, 7, WEEKDAY_NAMES, 0).ok_or_else(err)?;
+        // Both 0 and 7 are Sunday
+        if has_bit(weekdays, 7) {
+            weekdays |= 1;
+        }
+        Ok(UpdateWindow { expression: fields.join(" "),
+                          minutes,
+                          hours,
+                          days,
+                          months,
+                          weekdays,
+                          either_day: fields[2] != "*" && fields[4] != "*" })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for UpdateWindow {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        struct UpdateWindowVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for UpdateWindowVisitor {
+            type Value = UpdateWindow;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter,
+                       "a cron-like update window (example \"* 2-4 * * sat,sun\")")
+            }
+
+            fn visit_str<E>(self, s: &str) -> std::result::Result<Self::Value, E>
+                where E: serde::de::Error
+            {
+                UpdateWindow::from_str(s).map_err(|_| {
+                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(s), &self)
+                })
+            }
+        }
+
+        deserializer.deserialize_str(UpdateWindowVisitor)
+    }
+}
+
+impl serde::Serialize for UpdateWindow {
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -637,6 +928,50 @@ mod test {
         assert_eq!("name:service.group", bind.to_string());
     }
 
+    #[test]
+    fn service_bind_with_labels_from_str() {
+        let bind = ServiceBind::from_str("name:service.group[zone=us-east-1a,rack=12]").unwrap();
+
+        assert_eq!(bind.service_group,
+                   ServiceGroup::from_str("service.group").unwrap());
+        assert_eq!(bind.labels.get("zone"), Some(&"us-east-1a".to_string()));
+        assert_eq!(bind.labels.get("rack"), Some(&"12".to_string()));
+        assert_eq!("name:service.group[rack=12,zone=us-east-1a]",
+                   bind.to_string());
+    }
+
+    #[test]
+    fn service_bind_with_invalid_labels_from_str() {
+        assert!(ServiceBind::from_str("name:service.group[zone]").is_err());
+        assert!(ServiceBind::from_str("name:service.group[zone=a").is_err());
+        assert!(ServiceBind::from_str("name:service.group[]").is_err());
+    }
+
+    #[test]
+    fn service_bind_selects_members_with_all_its_labels() {
+        let bind = ServiceBind::from_str("name:service.group[zone=us-east-1a]").unwrap();
+        let mut labels = BTreeMap::new();
+        assert!(!bind.selects(&labels));
+
+        labels.insert("zone".to_string(), "us-east-1a".to_string());
+        labels.insert("rack".to_string(), "12".to_string());
+        assert!(bind.selects(&labels));
+
+        labels.insert("zone".to_string(), "us-east-1b".to_string());
+        assert!(!bind.selects(&labels));
+        assert!(ServiceBind::from_str("name:service.group").unwrap()
+                                                           .selects(&labels));
+    }
+
+    #[test]
+    fn label_from_str() {
+        assert_eq!(parse_label("zone=us-east-1a").unwrap(),
+                   ("zone".to_string(), "us-east-1a".to_string()));
+        assert!(parse_label("zone").is_err());
+        assert!(parse_label("zone=").is_err());
+        assert!(parse_label("zone=a=b").is_err());
+    }
+
     #[test]
     fn service_bind_toml_deserialize() {
         #[derive(Deserialize)]
@@ -754,4 +1089,102 @@ mod test {
         assert_eq!("(5s)".to_owned(),
                    format!("{}", HealthCheckInterval::from_str("5").unwrap()));
     }
+
+    #[test]
+    fn update_batch_size_from_str() {
+        assert_eq!(UpdateBatchSize::from_str("3").unwrap(),
+                   UpdateBatchSize::Members(3));
+        assert_eq!(UpdateBatchSize::from_str("25%").unwrap(),
+                   UpdateBatchSize::Percent(25));
+    }
+
+    #[test]
+    fn update_batch_size_from_str_invalid() {
+        assert!(UpdateBatchSize::from_str("0").is_err());
+        assert!(UpdateBatchSize::from_str("0%").is_err());
+        assert!(UpdateBatchSize::from_str("101%").is_err());
+        assert!(UpdateBatchSize::from_str("-2").is_err());
+        assert!(UpdateBatchSize::from_str("many").is_err());
+    }
+
+    #[test]
+    fn update_batch_size_display_round_trips() {
+        for s in &["3", "25%"] {
+            assert_eq!(UpdateBatchSize::from_str(s).unwrap().to_string(), *s);
+        }
+    }
+
+    #[test]
+    fn update_batch_size_members() {
+        assert_eq!(UpdateBatchSize::Members(3).members(10), 3);
+        assert_eq!(UpdateBatchSize::Percent(25).members(10), 3);
+        assert_eq!(UpdateBatchSize::Percent(50).members(10), 5);
+        assert_eq!(UpdateBatchSize::Percent(10).members(3), 1);
+        assert_eq!(UpdateBatchSize::Percent(100).members(0), 1);
+    }
+
+    // Saturday, January 5th 2019, 00:00:00 UTC
+    const SATURDAY: i64 = 1_546_646_400;
+
+    fn utc(sec: i64) -> Tm { time::at_utc(Timespec::new(sec, 0)) }
+
+    #[test]
+    fn update_window_from_str() {
+        assert!(UpdateWindow::from_str("* * * * *").is_ok());
+        assert!(UpdateWindow::from_str("*/15 2-4 1,15 jan-mar sat,sun").is_ok());
+        assert!(UpdateWindow::from_str("0-30/10 2 * * 7").is_ok());
+    }
+
+    #[test]
+    fn update_window_from_str_invalid() {
+        assert!(UpdateWindow::from_str("").is_err());
+        assert!(UpdateWindow::from_str("* * * *").is_err());
+        assert!(UpdateWindow::from_str("60 * * * *").is_err());
+        assert!(UpdateWindow::from_str("* 4-2 * * *").is_err());
+        assert!(UpdateWindow::from_str("* * 0 * *").is_err());
+        assert!(UpdateWindow::from_str("*/0 * * * *").is_err());
+        assert!(UpdateWindow::from_str("* * * * someday").is_err());
+    }
+
+    #[test]
+    fn update_window_display_normalizes_whitespace() {
+        assert_eq!(UpdateWindow::from_str(" *  2-4 * *   sat,sun ").unwrap()
+                                                                   .to_string(),
+                   "* 2-4 * * sat,sun");
+    }
+
+    #[test]
+    fn update_window_is_open_at() {
+        let window = UpdateWindow::from_str("* 2-4 * * sat,sun").unwrap();
+        assert!(!window.is_open_at(&utc(SATURDAY + 60 * 60)));
+        assert!(window.is_open_at(&utc(SATURDAY + 2 * 60 * 60 + 30 * 60)));
+        assert!(window.is_open_at(&utc(SATURDAY + 24 * 60 * 60 + 4 * 60 * 60 + 59 * 60)));
+        assert!(!window.is_open_at(&utc(SATURDAY + 2 * 24 * 60 * 60 + 3 * 60 * 60)));
+    }
+
+    #[test]
+    fn update_window_matches_either_restricted_day() {
+        // The 7th of January 2019 was a Monday
+        let window = UpdateWindow::from_str("* * 7 * sat").unwrap();
+        assert!(window.is_open_at(&utc(SATURDAY)));
+        assert!(window.is_open_at(&utc(SATURDAY + 2 * 24 * 60 * 60)));
+        assert!(!window.is_open_at(&utc(SATURDAY + 24 * 60 * 60)));
+    }
+
+    #[test]
+    fn update_window_next_open() {
+        let window = UpdateWindow::from_str("0 2 * * sat").unwrap();
+        assert_eq!(window.next_open(Timespec::new(SATURDAY + 90, 0)),
+                   Some(Timespec::new(SATURDAY + 2 * 60 * 60, 0)));
+        assert_eq!(window.next_open(Timespec::new(SATURDAY + 2 * 60 * 60 + 30, 0)),
+                   Some(Timespec::new(SATURDAY + 2 * 60 * 60, 0)));
+        assert_eq!(window.next_open(Timespec::new(SATURDAY + 3 * 60 * 60, 0)),
+                   Some(Timespec::new(SATURDAY + 7 * 24 * 60 * 60 + 2 * 60 * 60, 0)));
+    }
+
+    #[test]
+    fn update_window_that_never_opens() {
+        let window = UpdateWindow::from_str("* * 31 feb *").unwrap();
+        assert_eq!(window.next_open(Timespec::new(SATURDAY, 0)), None);
+    }
 }
@@ -37,9 +37,13 @@ use habitat_core::{crypto::{keys::PairType,
                    package::{ident,
                              Identifiable,
                              PackageIdent,
+                             PackageIdentConstraint,
                              PackageTarget},
-                   service::{HealthCheckInterval,
-                             ServiceGroup},
+                   service::{parse_label,
+                             HealthCheckInterval,
+                             ServiceGroup,
+                             UpdateBatchSize,
+                             UpdateWindow},
                    ChannelIdent};
 use habitat_sup_protocol;
 use std::{net::SocketAddr,
@@ -48,6 +52,13 @@ use std::{net::SocketAddr,
           str::FromStr};
 use url::Url;
 
+/// The transports `hab sup run` can gossip over, the default first. ZeroMQ is only offered when
+/// the Supervisor is built with it.
+#[cfg(feature = "zmq-transport")]
+const GOSSIP_TRANSPORTS: &[&str] = &["zmq", "tcp"];
+#[cfg(not(feature = "zmq-transport"))]
+const GOSSIP_TRANSPORTS: &[&str] = &["tcp"];
+
 pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
     let alias_apply = sub_config_apply().about("Alias for 'config apply'")
                                         .aliases(&["ap", "app", "appl"])
@@ -112,6 +123,8 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                     "A version number (positive integer) for this configuration (ex: 42)")
                 (@arg FILE: +required {file_exists} "Path to local file on disk")
                 (@arg USER: -u --user +takes_value "Name of the user key")
+                (@arg SIGNING_KEY: --("signing-key") +takes_value
+                    "Name of a user or origin signing key to sign the file with")
                 (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
                     "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
                 (arg: arg_cache_key_path("Path to search for encryption keys. \
@@ -415,6 +428,37 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                     "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
             )
             (subcommand: sub_pkg_build())
+            (@subcommand bundle =>
+                (about: "Commands relating to bundles of packages, for installing them without \
+                    access to Builder")
+                (@setting ArgRequiredElseHelp)
+                (@subcommand create =>
+                    (about: "Writes a bundle of packages, their dependencies and the public \
+                        origin keys they are signed with")
+                    (@arg PKG_IDENT_OR_ARTIFACT: +required +multiple
+                        "One or more Habitat package identifiers (ex: acme/redis) and/or \
+                        filepaths to a Habitat Artifact \
+                        (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)")
+                    (@arg OUTPUT: -o --output +takes_value default_value[bundle.tar]
+                        "The path to write the bundle to")
+                    (@arg BLDR_URL: --url -u +takes_value {valid_url}
+                        "Specify an alternate Builder endpoint. If not specified, the value will \
+                        be taken from the HAB_BLDR_URL environment variable if defined. \
+                        (default: https://bldr.habitat.sh)")
+                    (@arg CHANNEL: --channel -c +takes_value default_value[stable]
+                        env(ChannelIdent::ENVVAR)
+                        "Bundle packages from the specified release channel")
+                    (@arg AUTH_TOKEN: -z --auth +takes_value "Authentication token for Builder")
+                )
+                (@subcommand install =>
+                    (about: "Verifies and installs every package in a bundle, without contacting \
+                        Builder")
+                    (@arg BUNDLE: +required +takes_value {file_exists}
+                        "The path to a bundle written by 'hab pkg bundle create'")
+                    (@arg IGNORE_INSTALL_HOOK: --("ignore-install-hook")
+                        "Do not run any install hooks")
+                )
+            )
             (@subcommand env =>
                 (about: "Prints the runtime environment of a specific installed package")
                 (@arg PKG_IDENT: +required +takes_value {valid_ident}
@@ -451,8 +495,40 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                 (aliases: &["ha", "has"])
                 (@arg SOURCE: +takes_value {file_exists} "A filepath of the target")
             )
+            (@subcommand gc =>
+                (about: "Removes installed packages and cached artifacts that no service, \
+                    binlink or kept package uses")
+                (@arg DRYRUN: -d --dryrun
+                    "Just show what would be removed and how much space it would free, don't \
+                    actually do it")
+                (@arg KEEP: --keep +takes_value +multiple {valid_ident}
+                    "Identifier of one or more packages to keep every installed release of, \
+                    along with their dependencies (ex: core/redis, core/redis/4.0.14)")
+                (@arg KEEP_RELEASES: --("keep-releases") +takes_value {valid_numeric::<usize>}
+                    default_value("0") "How many of the newest installed releases of every \
+                    package to keep")
+                (@arg BINLINK_DIR: --("binlink-dir") +takes_value {non_empty}
+                    env(BINLINK_DIR_ENVVAR) default_value(DEFAULT_BINLINK_DIR)
+                    "The directory to look for binlinks in; every binlinked package is kept")
+            )
             (subcommand: sub_pkg_install(feature_flags).aliases(
                 &["i", "in", "ins", "inst", "insta", "instal"]))
+            (@subcommand lock =>
+                (about: "Writes a lockfile pinning a package and its dependencies to exact \
+                    artifacts, for use with 'hab pkg install --lockfile'")
+                (@arg PKG_IDENT_OR_ARTIFACT: +required +takes_value
+                    "A Habitat package identifier (ex: acme/redis) or filepath to a Habitat \
+                    Artifact (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)")
+                (@arg LOCKFILE: +required +takes_value
+                    "The path to write the lockfile to (ex: /src/redis.lock)")
+                (@arg BLDR_URL: --url -u +takes_value {valid_url}
+                    "Specify an alternate Builder endpoint. If not specified, the value will \
+                    be taken from the HAB_BLDR_URL environment variable if defined. (default: \
+                    https://bldr.habitat.sh)")
+                (@arg CHANNEL: --channel -c +takes_value default_value[stable]
+                    env(ChannelIdent::ENVVAR) "Lock packages from the specified release channel")
+                (@arg AUTH_TOKEN: -z --auth +takes_value "Authentication token for Builder")
+            )
             (@subcommand path =>
                 (about: "Prints the path to a specific installed release of a package")
                 (aliases: &["p", "pa", "pat"])
@@ -684,6 +760,18 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                         Default value is hab/cache/keys if root and .hab/cache/keys under the home \
                         directory otherwise."))
                 )
+                (@subcommand rotate =>
+                    (about: "Adds, promotes or retires a revision of a running Supervisor's \
+                    ring key")
+                    (aliases: &["r", "ro", "rot", "rota", "rotat"])
+                    (@arg ACTION: +required +takes_value possible_value[add promote retire]
+                        "Add a revision to decrypt gossip with, promote it to encrypt gossip \
+                        with, or retire a revision that isn't used anymore")
+                    (@arg KEY: +required +takes_value
+                        "The ring key name with revision (ex: myring-20160101000000)")
+                    (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+                        "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+                )
             )
         )
         (subcommand: sup_commands())
@@ -707,6 +795,7 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                 )
             )
             (subcommand: sub_svc_load().aliases(&["l", "lo", "loa"]))
+            (subcommand: sub_svc_logs().aliases(&["log"]))
             (subcommand: sub_svc_start().aliases(&["star"]))
             (subcommand: sub_svc_status().aliases(&["stat", "statu"]))
             (subcommand: sub_svc_stop(feature_flags).aliases(&["sto"]))
@@ -799,6 +888,7 @@ pub fn sup_commands() -> App<'static, 'static> {
     (@setting VersionlessSubcommands)
     (@setting SubcommandRequiredElseHelp)
     (subcommand: sub_sup_bash().aliases(&["b", "ba", "bas"]))
+    (subcommand: sub_sup_dat().aliases(&[]))
     (subcommand: sub_sup_depart().aliases(&["d", "de", "dep", "depa", "depart"]))
     (subcommand: sub_sup_run().aliases(&["r", "ru"]))
     (subcommand: sub_sup_secret().aliases(&["sec", "secr"]))
@@ -897,9 +987,13 @@ fn sub_pkg_install(feature_flags: FeatureFlag) -> App<'static, 'static> {
                          https://bldr.habitat.sh)")
         (@arg CHANNEL: --channel -c +takes_value default_value[stable] env(ChannelIdent::ENVVAR)
             "Install from the specified release channel")
-        (@arg PKG_IDENT_OR_ARTIFACT: +required +multiple
-            "One or more Habitat package identifiers (ex: acme/redis) and/or filepaths \
-            to a Habitat Artifact (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)")
+        (@arg PKG_IDENT_OR_ARTIFACT: +multiple required_unless[LOCKFILE]
+            "One or more Habitat package identifiers (ex: acme/redis), package identifiers with a \
+            version constraint (ex: core/openssl@~1.0) and/or filepaths to a Habitat Artifact \
+            (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)")
+        (@arg LOCKFILE: --lockfile +takes_value {file_exists} conflicts_with[PKG_IDENT_OR_ARTIFACT]
+            "Install exactly the packages in a lockfile made by 'hab pkg lock', failing if any \
+            artifact's checksum differs from the locked one")
         (@arg BINLINK: -b --binlink +takes_value {non_empty} env(BINLINK_DIR_ENVVAR)
             default_value(DEFAULT_BINLINK_DIR) "Binlink all binaries from installed package(s)")
         (@arg FORCE: -f --force "Overwrite existing binlinks")
@@ -908,14 +1002,16 @@ fn sub_pkg_install(feature_flags: FeatureFlag) -> App<'static, 'static> {
     );
     if feature_flags.contains(FeatureFlag::OFFLINE_INSTALL) {
         sub = sub.arg(Arg::with_name("OFFLINE").help("Install packages in offline mode")
-                                               .long("offline"));
+                                               .long("offline")
+                                               .conflicts_with("LOCKFILE"));
     };
     if feature_flags.contains(FeatureFlag::IGNORE_LOCAL) {
         sub = sub.arg(Arg::with_name("IGNORE_LOCAL").help("Do not use locally-installed \
                                                            packages when a corresponding \
                                                            package cannot be installed from \
                                                            Builder")
-                                                    .long("ignore-local"));
+                                                    .long("ignore-local")
+                                                    .conflicts_with("LOCKFILE"));
     };
     sub
 }
@@ -930,6 +1026,8 @@ fn sub_config_apply() -> App<'static, 'static> {
     (@arg FILE: {file_exists_or_stdin}
         "Path to local file on disk (ex: /tmp/config.toml, default: <stdin>)")
     (@arg USER: -u --user +takes_value "Name of a user key to use for encryption")
+    (@arg SIGNING_KEY: --("signing-key") +takes_value
+        "Name of a user or origin signing key to sign the configuration with")
     (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
         "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
     (arg: arg_cache_key_path("Path to search for encryption keys. \
@@ -961,6 +1059,43 @@ pub fn sub_sup_secret() -> App<'static, 'static> {
     )
 }
 
+pub fn sub_sup_dat() -> App<'static, 'static> {
+    clap_app!(@subcommand dat =>
+        (about: "Inspect and edit the DAT file a Supervisor persists its rumors to, without \
+            starting a Supervisor")
+        (@setting ArgRequiredElseHelp)
+        (@subcommand show =>
+            (about: "Check a DAT file is valid and print the rumors in it as JSON")
+            (@arg DAT_FILE: +required {file_exists}
+                "Path to the DAT file (ex: /hab/sup/default/data/<MEMBER_ID>.rst)")
+            (@arg RUMOR_TYPE: --("rumor-type") -t +takes_value +multiple
+                possible_value[member service service-config service-file election election-update departure]
+                "Only show rumors of these types")
+            (@arg SERVICE_GROUP: --("service-group") -g +takes_value +multiple {valid_service_group}
+                "Only show rumors about these service groups (ex: redis.default)")
+        )
+        (@subcommand prune =>
+            (about: "Write a copy of a DAT file without the selected rumors or members. Rumors \
+                are removed if they match all of --rumor-type and --service-group, or are about \
+                one of the --member members.")
+            (@arg DAT_FILE: +required {file_exists}
+                "Path to the DAT file (ex: /hab/sup/default/data/<MEMBER_ID>.rst)")
+            (@arg OUTPUT: +required +takes_value "Path to write the new DAT file to")
+            (@group selection =>
+                (@attributes +required +multiple)
+                (@arg MEMBER_ID: --member -m +takes_value +multiple
+                    "Remove these members, their departures and the services they run")
+                (@arg RUMOR_TYPE: --("rumor-type") -t +takes_value +multiple
+                    possible_value[member service service-config service-file election election-update departure]
+                    "Remove rumors of these types")
+                (@arg SERVICE_GROUP: --("service-group") -g +takes_value +multiple
+                    {valid_service_group}
+                    "Remove rumors about these service groups (ex: redis.default)")
+            )
+        )
+    )
+}
+
 pub fn sub_sup_bash() -> App<'static, 'static> {
     clap_app!(@subcommand bash =>
         (about: "Start an interactive Bash-like shell")
@@ -982,6 +1117,16 @@ pub fn sub_sup_run() -> App<'static, 'static> {
         "The listen address for the Gossip System Gateway.")
     (@arg LOCAL_GOSSIP_MODE: --("local-gossip-mode") conflicts_with("LISTEN_GOSSIP") conflicts_with("PEER") conflicts_with("PEER_WATCH_FILE")
         "Start the supervisor in local mode.")
+    (@arg GOSSIP_TRANSPORT: --("gossip-transport") +takes_value possible_values(GOSSIP_TRANSPORTS) default_value(GOSSIP_TRANSPORTS[0])
+        "The transport rumors are gossiped over. Every member of a ring must use the same one. \
+         The tcp transport keeps connections open between gossip rounds.")
+    (@arg LIFEGUARD: --lifeguard
+        "Use the Lifeguard extensions to SWIM failure detection, which wait longer for acks while \
+         this Supervisor seems to be the slow one, and confirm suspicions faster the more \
+         Supervisors share them")
+    (@arg CROSS_ZONE_FANOUT: --("cross-zone-fanout") +takes_value {valid_numeric::<usize>}
+        "Keep gossip within this Supervisor's zone (its `zone` label), pushing rumors to only \
+         this many Supervisors in other zones each round [default: gossip with every zone]")
     (@arg LISTEN_HTTP: --("listen-http") env(LISTEN_HTTP_ADDRESS_ENVVAR) default_value(&LISTEN_HTTP_DEFAULT_ADDR) {valid_socket_addr}
         "The listen address for the HTTP Gateway.")
     (@arg HTTP_DISABLE: --("http-disable") -D
@@ -997,6 +1142,9 @@ pub fn sub_sup_run() -> App<'static, 'static> {
     (@arg PEER_WATCH_FILE: --("peer-watch-file") +takes_value conflicts_with("PEER")
         "Watch this file for connecting to the ring"
     )
+    (@arg LABEL: --label +takes_value +multiple {valid_label}
+        "A KEY=VALUE label to gossip along with this Supervisor's membership, which templates \
+         can read and binds can select members by (ex: --label zone=us-east-1a)")
     (arg: arg_cache_key_path("Path to search for encryption keys. \
         Default value is hab/cache/keys if root and .hab/cache/keys under the home \
         directory otherwise."))
@@ -1021,6 +1169,14 @@ pub fn sub_sup_run() -> App<'static, 'static> {
         "Use package config from this path, rather than the package itself")
     (@arg AUTO_UPDATE: --("auto-update") -A "Enable automatic updates for the Supervisor \
         itself")
+    (@arg AUTO_UPDATE_WINDOW: --("auto-update-window") +takes_value {valid_update_window}
+        requires[AUTO_UPDATE]
+        "Only apply Supervisor updates when this cron-style expression (minute hour \
+         day-of-month month day-of-week, in UTC) matches, e.g. '* 2-4 * * sat,sun'")
+    (@arg ORDERED_STARTUP: --("ordered-startup")
+        "Start services loaded on this Supervisor only once the services they bind to are up \
+         and healthy, stop them before those services, and refuse to load services whose binds \
+         form a cycle")
     (@arg KEY_FILE: --key +takes_value {file_exists} requires[CERT_FILE]
         "Used for enabling TLS for the HTTP gateway. Read private key from KEY_FILE. \
          This should be a RSA private key or PKCS8-encoded private key, in PEM format.")
@@ -1031,10 +1187,18 @@ pub fn sub_sup_run() -> App<'static, 'static> {
     (@arg CA_CERT_FILE: --("ca-certs") +takes_value {file_exists} requires[CERT_FILE] requires[KEY_FILE]
         "Used for enabling client-authentication with TLS for the HTTP gateway. Read CA certificate from CA_CERT_FILE. \
          This should contain PEM-format certificate that can be used to validate client requests.")
+    (@arg EVENT_STREAM_SINK: --("event-stream-sink") +takes_value {valid_event_stream_sink}
+        "Where to send Supervisor events when the event stream is enabled with \
+         HAB_FEAT_EVENT_STREAM. One of 'nats', 'nats:<ADDRESS>', 'file:<PATH>', 'unix:<PATH>' \
+         or 'stdout'. [default: nats]")
+    (@arg EVENT_STREAM_FORMAT: --("event-stream-format") +takes_value possible_value[json protobuf]
+        "The encoding of events sent to a NATS or Unix socket event stream sink. File and \
+         stdout sinks always write JSON lines. [default: json]")
     // === Optional arguments to additionally load an initial service for the Supervisor
     (@arg PKG_IDENT_OR_ARTIFACT: +takes_value "Load the given Habitat package as part of \
         the Supervisor startup specified by a package identifier \
-        (ex: core/redis) or filepath to a Habitat Artifact \
+        (ex: core/redis), a package identifier with a version constraint \
+        (ex: core/openssl@~1.0) or filepath to a Habitat Artifact \
         (ex: /home/core-redis-3.0.7-21120102031201-x86_64-linux.hart).")
     (@arg APPLICATION: --application -a +takes_value requires[ENVIRONMENT]
         "Application name; [default: not set].")
@@ -1044,10 +1208,26 @@ pub fn sub_sup_run() -> App<'static, 'static> {
         "The service group; shared config and topology [default: default].")
     (@arg TOPOLOGY: --topology -t +takes_value possible_value[standalone leader]
         "Service topology; [default: none]")
+    (@arg LEADER_PRIORITY: --("leader-priority") +takes_value {valid_numeric::<u64>}
+        "Prefer this member when electing a leader over members with a lower priority, \
+         whatever their suitability [default: 0]")
+    (@arg STICKY_LEADER: --("sticky-leader")
+        "Keep this member as leader through re-elections while its service is healthy")
     (@arg STRATEGY: --strategy -s +takes_value {valid_update_strategy}
-        "The update strategy; [default: none] [values: none, at-once, rolling]")
+        "The update strategy; [default: none] [values: none, at-once, rolling, batched, canary]")
+    (@arg UPDATE_BATCH_SIZE: --("update-batch-size") +takes_value {valid_update_batch_size}
+        "How many members update at once with the `batched` and `canary` strategies, as a \
+         count or a percentage of the service group (e.g. 3 or 25%) [default: 1]")
+    (@arg UPDATE_CANARY_SOAK: --("update-canary-soak") +takes_value {valid_numeric::<u64>}
+        "How long (seconds) the canary must run a new release before the rest of the service \
+         group updates with the `canary` strategy [default: 300]")
+    (@arg UPDATE_WINDOW: --("update-window") +takes_value {valid_update_window}
+        "Only apply updates found by the update strategy when this cron-style expression \
+         (minute hour day-of-month month day-of-week, in UTC) matches, e.g. \
+         '* 2-4 * * sat,sun' [default: any time]")
     (@arg BIND: --bind +takes_value +multiple
-        "One or more service groups to bind to a configuration")
+        "One or more service groups to bind to a configuration, optionally only to members \
+         whose Supervisors carry the given labels (ex: --bind db:postgres.default[zone=a])")
     (@arg BINDING_MODE: --("binding-mode") +takes_value {valid_binding_mode}
         "Governs how the presence or absence of binds affects service startup. `strict` blocks \
          startup until all binds are present. [default: strict] [values: relaxed, strict]")
@@ -1057,6 +1237,20 @@ pub fn sub_sup_run() -> App<'static, 'static> {
         Implies NO_COLOR")
     (@arg HEALTH_CHECK_INTERVAL: --("health-check-interval") -i +takes_value {valid_health_check_interval}
         "The interval (seconds) on which to run health checks [default: 30]")
+    (@arg RESTART_POLICY: --("restart-policy") +takes_value {valid_restart_policy}
+        "Governs whether the service is restarted when its process exits unexpectedly \
+         [default: always] [values: always, on-failure, never]")
+    (@arg RESTART_MAX_ATTEMPTS: --("restart-max-attempts") +takes_value {valid_numeric::<u32>}
+        "The number of consecutive restarts attempted before giving up; only applies to the \
+         `on-failure` restart policy [default: 10]")
+    (@arg RESTART_BACKOFF: --("restart-backoff") +takes_value {valid_numeric::<u64>}
+        "The delay (seconds) before the first restart; doubled for each consecutive restart \
+         [default: 1]")
+    (@arg RESTART_BACKOFF_MAX: --("restart-backoff-max") +takes_value {valid_numeric::<u64>}
+        "The maximum delay (seconds) between consecutive restarts [default: 300]")
+    (@arg RESTART_RESET_WINDOW: --("restart-reset-window") +takes_value {valid_numeric::<u64>}
+        "How long (seconds) the service must stay up before its consecutive restart count is \
+         reset [default: 300]")
     )
 }
 
@@ -1080,6 +1274,17 @@ pub fn sub_sup_term() -> App<'static, 'static> {
     )
 }
 
+fn sub_svc_logs() -> App<'static, 'static> {
+    clap_app!(@subcommand logs =>
+        (about: "Show the output of a loaded Habitat service.")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident}
+            "A Habitat package identifier (ex: core/redis)")
+        (@arg FOLLOW: -f --follow "Keep streaming the service's output as it is written")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+    )
+}
+
 fn sub_svc_start() -> App<'static, 'static> {
     clap_app!(@subcommand start =>
         (about: "Start a loaded, but stopped, Habitat service.")
@@ -1117,8 +1322,9 @@ fn sub_svc_load() -> App<'static, 'static> {
         (about: "Load a service to be started and supervised by Habitat from a package \
             identifier. If an installed package doesn't satisfy the given package \
             identifier, a suitable package will be installed from Builder.")
-        (@arg PKG_IDENT: +required +takes_value {valid_ident}
-            "A Habitat package identifier (ex: core/redis)")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident_or_constraint}
+            "A Habitat package identifier (ex: core/redis), or one with a version constraint \
+            the service is kept within as it updates (ex: core/openssl@>=1.0.2,<1.1)")
         (@arg APPLICATION: --application -a +takes_value requires[ENVIRONMENT]
             "Application name; [default: not set].")
         (@arg ENVIRONMENT: --environment -e +takes_value requires[APPLICATION]
@@ -1133,10 +1339,27 @@ fn sub_svc_load() -> App<'static, 'static> {
              https://bldr.habitat.sh)")
         (@arg TOPOLOGY: --topology -t +takes_value possible_value[standalone leader]
             "Service topology; [default: none]")
+        (@arg LEADER_PRIORITY: --("leader-priority") +takes_value {valid_numeric::<u64>}
+            "Prefer this member when electing a leader over members with a lower priority, \
+             whatever their suitability [default: 0]")
+        (@arg STICKY_LEADER: --("sticky-leader")
+            "Keep this member as leader through re-elections while its service is healthy")
         (@arg STRATEGY: --strategy -s +takes_value {valid_update_strategy}
-            "The update strategy; [default: none] [values: none, at-once, rolling]")
+            "The update strategy; [default: none] [values: none, at-once, rolling, batched, \
+             canary]")
+        (@arg UPDATE_BATCH_SIZE: --("update-batch-size") +takes_value {valid_update_batch_size}
+            "How many members update at once with the `batched` and `canary` strategies, as a \
+             count or a percentage of the service group (e.g. 3 or 25%) [default: 1]")
+        (@arg UPDATE_CANARY_SOAK: --("update-canary-soak") +takes_value {valid_numeric::<u64>}
+            "How long (seconds) the canary must run a new release before the rest of the \
+             service group updates with the `canary` strategy [default: 300]")
+        (@arg UPDATE_WINDOW: --("update-window") +takes_value {valid_update_window}
+            "Only apply updates found by the update strategy when this cron-style expression \
+             (minute hour day-of-month month day-of-week, in UTC) matches, e.g. \
+             '* 2-4 * * sat,sun' [default: any time]")
         (@arg BIND: --bind +takes_value +multiple
-            "One or more service groups to bind to a configuration")
+            "One or more service groups to bind to a configuration, optionally only to members \
+             whose Supervisors carry the given labels (ex: --bind db:postgres.default[zone=a])")
         (@arg BINDING_MODE: --("binding-mode") +takes_value {valid_binding_mode}
              "Governs how the presence or absence of binds affects service startup. `strict` blocks \
               startup until all binds are present. [default: strict] [values: relaxed, strict]")
@@ -1146,6 +1369,20 @@ fn sub_svc_load() -> App<'static, 'static> {
             "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
         (@arg HEALTH_CHECK_INTERVAL: --("health-check-interval") -i +takes_value {valid_health_check_interval}
             "The interval (seconds) on which to run health checks [default: 30]")
+        (@arg RESTART_POLICY: --("restart-policy") +takes_value {valid_restart_policy}
+            "Governs whether the service is restarted when its process exits unexpectedly \
+             [default: always] [values: always, on-failure, never]")
+        (@arg RESTART_MAX_ATTEMPTS: --("restart-max-attempts") +takes_value {valid_numeric::<u32>}
+            "The number of consecutive restarts attempted before giving up; only applies to the \
+             `on-failure` restart policy [default: 10]")
+        (@arg RESTART_BACKOFF: --("restart-backoff") +takes_value {valid_numeric::<u64>}
+            "The delay (seconds) before the first restart; doubled for each consecutive restart \
+             [default: 1]")
+        (@arg RESTART_BACKOFF_MAX: --("restart-backoff-max") +takes_value {valid_numeric::<u64>}
+            "The maximum delay (seconds) between consecutive restarts [default: 300]")
+        (@arg RESTART_RESET_WINDOW: --("restart-reset-window") +takes_value {valid_numeric::<u64>}
+            "How long (seconds) the service must stay up before its consecutive restart count is \
+             reset [default: 300]")
     );
 
     if cfg!(windows) {
@@ -1269,6 +1506,51 @@ fn valid_update_strategy(val: String) -> result::Result<(), String> {
     }
 }
 
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_update_batch_size(val: String) -> result::Result<(), String> {
+    match UpdateBatchSize::from_str(&val) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_update_window(val: String) -> result::Result<(), String> {
+    match UpdateWindow::from_str(&val) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_label(val: String) -> result::Result<(), String> {
+    match parse_label(&val) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_restart_policy(val: String) -> result::Result<(), String> {
+    match habitat_sup_protocol::types::RestartPolicy::from_str(&val) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("Restart policy: '{}' is not valid", &val)),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_event_stream_sink(val: String) -> result::Result<(), String> {
+    let valid = match val.find(':') {
+        Some(idx) => ["nats", "file", "unix"].contains(&&val[..idx]) && idx + 1 < val.len(),
+        None => val == "nats" || val == "stdout",
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Event stream sink: '{}' is not valid", &val))
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
 fn valid_ident(val: String) -> result::Result<(), String> {
     match PackageIdent::from_str(&val) {
@@ -1281,6 +1563,17 @@ fn valid_ident(val: String) -> result::Result<(), String> {
     }
 }
 
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_ident_or_constraint(val: String) -> result::Result<(), String> {
+    if !val.contains('@') {
+        return valid_ident(val);
+    }
+    match PackageIdentConstraint::from_str(&val) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("'{}' is not valid. {}", &val, e)),
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
 fn valid_target(val: String) -> result::Result<(), String> {
     match PackageTarget::from_str(&val) {
@@ -149,6 +149,44 @@ pub fn binlink_all_in_pkg(ui: &mut UI,
     Ok(())
 }
 
+/// Returns the packages that the binlinks in a destination directory link into.
+pub fn binlinked_packages(dest_path: &Path, fs_root_path: &Path) -> Result<Vec<PackageIdent>> {
+    let dst_path = fs_root_path.join(dest_path.strip_prefix("/")?);
+    let mut idents = Vec::new();
+    if !dst_path.is_dir() {
+        return Ok(idents);
+    }
+    // Binlinks point into the package root as it will be seen at runtime, which on Windows
+    // includes the filesystem root.
+    let pkg_root_paths = [hfs::pkg_root_path(Some(fs_root_path)),
+                          hfs::pkg_root_path(None::<&Path>)];
+    for entry in fs::read_dir(&dst_path)? {
+        // Anything that isn't a binlink is skipped.
+        if let Ok(link) = Binlink::from_file(entry?.path()) {
+            if let Some(ident) = pkg_root_paths.iter()
+                                               .filter_map(|root| link.src.strip_prefix(root).ok())
+                                               .filter_map(ident_from_pkg_path)
+                                               .next()
+            {
+                idents.push(ident);
+            }
+        }
+    }
+    Ok(idents)
+}
+
+/// Parses the ident out of a path within a package root, such as
+/// `core/redis/4.0.10/20180801003001/bin/redis-server`.
+fn ident_from_pkg_path(path: &Path) -> Option<PackageIdent> {
+    let mut parts = path.iter().map(|part| part.to_str());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Some(origin)), Some(Some(name)), Some(Some(version)), Some(Some(release))) => {
+            Some(PackageIdent::new(origin, name, Some(version), Some(release)))
+        }
+        _ => None,
+    }
+}
+
 fn is_dest_on_path<T: AsRef<Path>>(dest_dir: T) -> bool {
     if let Some(val) = env::var_os("PATH") {
         env::split_paths(&val).any(|p| p == dest_dir.as_ref())
@@ -254,6 +292,7 @@ mod test {
     use tempfile::TempDir;
 
     use super::{binlink_all_in_pkg,
+                binlinked_packages,
                 start,
                 Binlink};
 
@@ -349,6 +388,30 @@ mod test {
                                                                            .src);
     }
 
+    #[test]
+    fn binlinked_packages_finds_linked_packages() {
+        let rootfs = TempDir::new().unwrap();
+        let mut tools = HashMap::new();
+        tools.insert("bin", vec!["magicate.exe"]);
+        let ident = fake_bin_pkg_install("acme/cooltools", tools, rootfs.path());
+        let dst_path = Path::new("/opt/bin");
+        fs::create_dir_all(rootfs.path().join("opt/bin")).unwrap();
+        File::create(rootfs.path().join("opt/bin/not-a-binlink")).unwrap();
+
+        assert!(binlinked_packages(&dst_path, rootfs.path()).unwrap()
+                                                            .is_empty());
+
+        let (mut ui, _stdout, _stderr) = ui();
+        start(&mut ui,
+              &ident,
+              "magicate.exe",
+              &dst_path,
+              rootfs.path(),
+              true).unwrap();
+        assert_eq!(binlinked_packages(&dst_path, rootfs.path()).unwrap(),
+                   vec![ident]);
+    }
+
     #[test]
     #[cfg(target_os = "windows")]
     fn binlink_all_in_pkg_symlinks_only_executables() {
@@ -0,0 +1,442 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bundles packages up for installing on machines that can't reach Builder.
+//!
+//! # Examples
+//!
+//! ```bash
+//! $ hab pkg bundle create core/nginx core/redis --output services.tar
+//! $ hab pkg bundle install services.tar
+//! ```
+//!
+//! A bundle is a tar archive holding the artifacts of the packages it was created for and of all
+//! their dependencies, the public origin keys those artifacts are signed with, and an index that
+//! locks each package to its artifacts, as `hab pkg lock` does. Installing a bundle copies the
+//! artifacts and keys into the local caches, and then installs each package from them offline,
+//! failing if any artifact isn't the one in the index.
+
+use std::{collections::{BTreeMap,
+                        BTreeSet},
+          ffi::OsStr,
+          fs::{self,
+               File},
+          io::Read,
+          path::{Path,
+                 PathBuf}};
+
+use tar;
+use tempfile;
+use toml;
+
+use crate::{common::{self,
+                     command::package::install::{self,
+                                                 InstallHookMode,
+                                                 InstallMode,
+                                                 InstallSource},
+                     lockfile::{LockedPackage,
+                                Lockfile},
+                     ui::{Status,
+                          UIWriter,
+                          UI}},
+            hcore::{crypto::{artifact,
+                             keys::{parse_key_str,
+                                    PairType},
+                             SigKeyPair},
+                    fs::{cache_artifact_path,
+                         cache_key_path},
+                    package::PackageArchive,
+                    url::default_bldr_url,
+                    ChannelIdent}};
+
+use crate::{command::pkg::lock,
+            error::{Error,
+                    Result},
+            PRODUCT,
+            VERSION};
+
+const INDEX_FILE: &str = "index.toml";
+const ARTIFACTS_DIR: &str = "artifacts";
+const KEYS_DIR: &str = "keys";
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+struct Index {
+    /// A lockfile for each of the packages the bundle was created for.
+    #[serde(rename = "lock")]
+    locks: Vec<Lockfile>,
+}
+
+/// Write a bundle of packages, their transitive dependencies and the public origin keys they are
+/// signed with.
+///
+/// Each package is locked as `hab pkg lock` would, which downloads it and its dependencies into
+/// the artifact cache here first, without installing them.
+pub fn create(ui: &mut UI,
+              bldr_url: &str,
+              channel: &ChannelIdent,
+              install_sources: &[InstallSource],
+              token: Option<&str>,
+              fs_root_path: &Path,
+              bundle_path: &Path)
+              -> Result<()> {
+    let key_cache_path = cache_key_path(Some(fs_root_path));
+    let mut index = Index::default();
+    // Packages shared by several of the bundled packages are only added once.
+    let mut artifacts = BTreeMap::new();
+    let mut keys = BTreeMap::new();
+    for install_source in install_sources.iter() {
+        let (lockfile, archives) =
+            lock::lock(ui, bldr_url, channel, install_source, token, fs_root_path)?;
+        for (package, archive) in lockfile.packages.iter().zip(archives.into_iter()) {
+            let name_with_rev = artifact::artifact_signer(&archive.path)?;
+            let key_path = SigKeyPair::get_public_key_path(&name_with_rev, &key_cache_path)?;
+            keys.insert(name_with_rev, key_path);
+            artifacts.insert(package.ident.archive_name_with_target(package.target)?,
+                             archive.path);
+        }
+        index.locks.push(lockfile);
+    }
+
+    ui.begin(format!("Bundling {} packages into {}",
+                     artifacts.len(),
+                     bundle_path.display()))?;
+    write(ui, bundle_path, &index, &keys, &artifacts)?;
+    ui.end(format!("Bundle of {} packages written to {}",
+                   artifacts.len(),
+                   bundle_path.display()))?;
+    Ok(())
+}
+
+/// Install every package in a bundle, without contacting Builder.
+///
+/// The bundle's artifacts and keys are copied into the local caches, and each package the bundle
+/// was created for is then installed offline from its lockfile, so every artifact's checksum and
+/// signature are verified before it is installed.
+pub fn install(ui: &mut UI,
+               bundle_path: &Path,
+               fs_root_path: &Path,
+               install_hook_mode: InstallHookMode)
+               -> Result<()> {
+    let artifact_cache_path = cache_artifact_path(Some(fs_root_path));
+    let key_cache_path = cache_key_path(Some(fs_root_path));
+    fs::create_dir_all(&artifact_cache_path)?;
+    fs::create_dir_all(&key_cache_path)?;
+
+    ui.begin(format!("Unpacking {}", bundle_path.display()))?;
+    let index = unpack(ui, bundle_path, &artifact_cache_path, &key_cache_path)?;
+    ui.end(format!("Unpacked {}", bundle_path.display()))?;
+
+    for lockfile in index.locks.iter() {
+        install::start_locked(ui,
+                              &default_bldr_url(),
+                              lockfile,
+                              PRODUCT,
+                              VERSION,
+                              fs_root_path,
+                              &artifact_cache_path,
+                              None,
+                              &InstallMode::Offline,
+                              install_hook_mode)?;
+    }
+    Ok(())
+}
+
+/// Write a bundle's index, then the public keys and the artifacts in it, by their file names.
+fn write(ui: &mut UI,
+         bundle_path: &Path,
+         index: &Index,
+         keys: &BTreeMap<String, PathBuf>,
+         artifacts: &BTreeMap<String, PathBuf>)
+         -> Result<()> {
+    let mut tar = tar::Builder::new(File::create(bundle_path)?);
+    let index = toml::to_string(index)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(index.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, INDEX_FILE, index.as_bytes())?;
+    for (name_with_rev, path) in keys.iter() {
+        ui.status(Status::Adding,
+                  format!("{} public origin key", name_with_rev))?;
+        tar.append_path_with_name(path, format!("{}/{}.pub", KEYS_DIR, name_with_rev))?;
+    }
+    for (archive_name, path) in artifacts.iter() {
+        ui.status(Status::Adding, archive_name)?;
+        tar.append_path_with_name(path, format!("{}/{}", ARTIFACTS_DIR, archive_name))?;
+    }
+    tar.finish()?;
+    Ok(())
+}
+
+/// Copy a bundle's public keys and the artifacts its index locks into the local caches,
+/// returning the index.
+///
+/// The index is read and validated before anything else. Only regular files are taken from the
+/// bundle, and artifacts are staged next to the artifact cache until every one of them has its
+/// locked checksum; none is moved into the cache before then.
+fn unpack(ui: &mut UI,
+          bundle_path: &Path,
+          artifact_cache_path: &Path,
+          key_cache_path: &Path)
+          -> Result<Index> {
+    let index = read_index(bundle_path)?;
+    let mut locked: BTreeMap<String, &LockedPackage> = BTreeMap::new();
+    for lockfile in index.locks.iter() {
+        for package in lockfile.packages.iter() {
+            locked.insert(package.ident.archive_name_with_target(package.target)?,
+                          package);
+        }
+    }
+
+    let staging = tempfile::Builder::new().prefix("bundle-")
+                                          .tempdir_in(artifact_cache_path)?;
+    let mut staged = BTreeSet::new();
+    let mut tar = tar::Archive::new(File::open(bundle_path)?);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        // Files are only ever read by their file name, so a bundle can't write anywhere but the
+        // caches, and links and other special files are refused outright.
+        if !entry.header().entry_type().is_file() {
+            let msg = format!("{} is not a regular file", path.display());
+            return Err(Error::InvalidBundle(msg));
+        }
+        let dir = path.parent().and_then(Path::to_str).unwrap_or_default();
+        let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+        match (dir, file_name) {
+            ("", INDEX_FILE) => (),
+            (KEYS_DIR, _) => {
+                let mut content = String::new();
+                entry.read_to_string(&mut content)?;
+                let (pair_type, name_with_rev, _) = parse_key_str(&content)?;
+                if pair_type != PairType::Public {
+                    return Err(Error::InvalidBundle(format!("it holds the secret key {}",
+                                                            name_with_rev)));
+                }
+                if file_name != format!("{}.pub", name_with_rev) {
+                    let msg = format!("{} holds the key {}", path.display(), name_with_rev);
+                    return Err(Error::InvalidBundle(msg));
+                }
+                SigKeyPair::write_file_from_str(&content, key_cache_path)?;
+                ui.status(Status::Cached,
+                          format!("{} public origin key", name_with_rev))?;
+            }
+            (ARTIFACTS_DIR, archive_name)
+                if locked.contains_key(archive_name) && !staged.contains(archive_name) =>
+            {
+                entry.unpack(staging.path().join(archive_name))?;
+                staged.insert(archive_name.to_string());
+            }
+            _ => {
+                let msg = format!("it holds an unexpected file, {}", path.display());
+                return Err(Error::InvalidBundle(msg));
+            }
+        }
+    }
+
+    for archive_name in staged.iter() {
+        let package = locked[archive_name];
+        let checksum = PackageArchive::new(staging.path().join(archive_name)).checksum()?;
+        if checksum != package.checksum {
+            return Err(common::Error::LockfileChecksumMismatch(package.ident.clone(),
+                                                               package.checksum.clone(),
+                                                               checksum).into());
+        }
+    }
+    for archive_name in staged.iter() {
+        let cached_path = artifact_cache_path.join(archive_name);
+        if cached_path.is_file() {
+            if PackageArchive::new(&cached_path).checksum()? == locked[archive_name].checksum {
+                continue;
+            }
+            ui.warn(format!("Replacing the cached {}, which is not the locked artifact",
+                            archive_name))?;
+        }
+        fs::rename(staging.path().join(archive_name), &cached_path)?;
+        ui.status(Status::Cached, archive_name)?;
+    }
+    Ok(index)
+}
+
+/// Read and validate the index of a bundle.
+fn read_index(bundle_path: &Path) -> Result<Index> {
+    let mut tar = tar::Archive::new(File::open(bundle_path)?);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let is_index =
+            entry.header().entry_type().is_file() && &*entry.path()? == Path::new(INDEX_FILE);
+        if is_index {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            let index: Index = toml::from_str(&content)?;
+            for lockfile in index.locks.iter() {
+                lockfile.validate()?;
+            }
+            return Ok(index);
+        }
+    }
+    Err(Error::InvalidBundle(format!("it has no {}", INDEX_FILE)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hcore::crypto::hash;
+    use std::io;
+    use tempfile::TempDir;
+
+    const ARTIFACT: &str = "core-redis-4.0.14-20190319155852-x86_64-linux.hart";
+
+    const LOCKFILE: &str = r#"
+ident = "core/redis/4.0.14/20190319155852"
+target = "x86_64-linux"
+
+[[package]]
+ident = "core/redis/4.0.14/20190319155852"
+target = "x86_64-linux"
+checksum = "CHECKSUM"
+"#;
+
+    fn index(checksum: &str) -> Index {
+        Index { locks: vec![LOCKFILE.replace("CHECKSUM", checksum).parse().unwrap()], }
+    }
+
+    /// A bundle holding `index` and a single, empty entry of the given type.
+    fn bundle(dir: &Path, index: &Index, path: &str, entry_type: tar::EntryType) -> PathBuf {
+        let bundle_path = dir.join("bundle.tar");
+        let mut tar = tar::Builder::new(File::create(&bundle_path).unwrap());
+        let index = toml::to_string(index).unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(index.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, INDEX_FILE, index.as_bytes())
+           .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_size(0);
+        header.set_mode(0o644);
+        if entry_type == tar::EntryType::Symlink {
+            header.set_link_name("/etc/passwd").unwrap();
+        }
+        header.set_cksum();
+        tar.append_data(&mut header, path, io::empty()).unwrap();
+        tar.finish().unwrap();
+        bundle_path
+    }
+
+    fn caches(dir: &Path) -> (PathBuf, PathBuf) {
+        let (artifact_cache_path, key_cache_path) = (dir.join("artifacts"), dir.join("keys"));
+        fs::create_dir_all(&artifact_cache_path).unwrap();
+        fs::create_dir_all(&key_cache_path).unwrap();
+        (artifact_cache_path, key_cache_path)
+    }
+
+    fn cached(artifact_cache_path: &Path) -> Vec<String> {
+        let mut cached =
+            fs::read_dir(artifact_cache_path).unwrap()
+                                             .map(|entry| {
+                                                 entry.unwrap().file_name().into_string().unwrap()
+                                             })
+                                             .collect::<Vec<_>>();
+        cached.sort();
+        cached
+    }
+
+    #[test]
+    fn bundled_artifacts_are_cached() {
+        let tmpdir = TempDir::new().unwrap();
+        let artifact_path = tmpdir.path().join(ARTIFACT);
+        fs::write(&artifact_path, "not really a hart").unwrap();
+        let index = index(&hash::hash_file(&artifact_path).unwrap());
+        let bundle_path = tmpdir.path().join("bundle.tar");
+        let mut artifacts = BTreeMap::new();
+        artifacts.insert(ARTIFACT.to_string(), artifact_path.clone());
+        write(&mut UI::with_sinks(),
+              &bundle_path,
+              &index,
+              &BTreeMap::new(),
+              &artifacts).unwrap();
+
+        let (artifact_cache_path, key_cache_path) = caches(&tmpdir.path().join("cache"));
+        let unpacked = unpack(&mut UI::with_sinks(),
+                              &bundle_path,
+                              &artifact_cache_path,
+                              &key_cache_path).unwrap();
+        assert_eq!(unpacked, index);
+        assert_eq!(cached(&artifact_cache_path), vec![ARTIFACT.to_string()]);
+        assert_eq!(fs::read(artifact_cache_path.join(ARTIFACT)).unwrap(),
+                   fs::read(&artifact_path).unwrap());
+    }
+
+    #[test]
+    fn files_the_index_does_not_lock_are_refused() {
+        let tmpdir = TempDir::new().unwrap();
+        let bundle_path = bundle(tmpdir.path(),
+                                 &index("aaaa"),
+                                 "artifacts/acme-evil-1.0.0-20190101000000-x86_64-linux.hart",
+                                 tar::EntryType::Regular);
+
+        let (artifact_cache_path, key_cache_path) = caches(&tmpdir.path().join("cache"));
+        match unpack(&mut UI::with_sinks(),
+                     &bundle_path,
+                     &artifact_cache_path,
+                     &key_cache_path)
+        {
+            Err(Error::InvalidBundle(_)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(cached(&artifact_cache_path).is_empty());
+    }
+
+    #[test]
+    fn links_are_refused() {
+        let tmpdir = TempDir::new().unwrap();
+        let bundle_path = bundle(tmpdir.path(),
+                                 &index("aaaa"),
+                                 &format!("artifacts/{}", ARTIFACT),
+                                 tar::EntryType::Symlink);
+
+        let (artifact_cache_path, key_cache_path) = caches(&tmpdir.path().join("cache"));
+        match unpack(&mut UI::with_sinks(),
+                     &bundle_path,
+                     &artifact_cache_path,
+                     &key_cache_path)
+        {
+            Err(Error::InvalidBundle(_)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(cached(&artifact_cache_path).is_empty());
+    }
+
+    #[test]
+    fn artifacts_without_their_locked_checksum_are_not_cached() {
+        let tmpdir = TempDir::new().unwrap();
+        let bundle_path = bundle(tmpdir.path(),
+                                 &index("aaaa"),
+                                 &format!("artifacts/{}", ARTIFACT),
+                                 tar::EntryType::Regular);
+
+        let (artifact_cache_path, key_cache_path) = caches(&tmpdir.path().join("cache"));
+        match unpack(&mut UI::with_sinks(),
+                     &bundle_path,
+                     &artifact_cache_path,
+                     &key_cache_path)
+        {
+            Err(Error::HabitatCommon(common::Error::LockfileChecksumMismatch(..))) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(cached(&artifact_cache_path).is_empty());
+    }
+}
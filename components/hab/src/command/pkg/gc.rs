@@ -0,0 +1,455 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Removes the installed packages and cached artifacts that nothing uses anymore.
+//!
+//! # Examples
+//!
+//! ```bash
+//! $ hab pkg gc --dryrun
+//! $ hab pkg gc --keep core/redis --keep-releases 2
+//! ```
+//!
+//! An installed package is kept if it is, or is a dependency of, any of:
+//!
+//! * a service that is running, or the latest installed release of a service that is loaded,
+//!   within the version constraint of its spec if it has one
+//! * a package with a binlink
+//! * a package on the keep list
+//! * one of the newest installed releases of its package, with `--keep-releases`
+//! * the newest installed release of `core/hab`, `core/hab-sup` and `core/hab-launcher`
+//! * a package a process is running an executable from, such as the running Supervisor and
+//!   Launcher
+//!
+//! Every other package is uninstalled, and every cached artifact of a package that isn't kept
+//! is removed from the artifact cache.
+
+use super::{uninstall,
+            ExecutionStrategy};
+use crate::error::Result;
+use habitat_common::{package_graph::PackageGraph,
+                     ui::{Status,
+                          UIWriter,
+                          UI}};
+use habitat_core::{fs as hfs,
+                   package::{self,
+                             Identifiable,
+                             PackageArchive,
+                             PackageIdent,
+                             PackageIdentConstraint,
+                             PackageInstall}};
+use std::{collections::{BTreeMap,
+                        HashSet},
+          ffi::OsStr,
+          fs,
+          path::{Component,
+                 Path},
+          str::FromStr};
+use toml;
+use walkdir::WalkDir;
+
+/// Habitat's own packages. The newest installed release of each is always kept, as that is the
+/// one a restarted Supervisor and Launcher would run. The releases they are running right now
+/// are found by `running_packages`, since no spec or binlink points at them.
+const HABITAT_PACKAGES: &[&str] = &["core/hab", "core/hab-sup", "core/hab-launcher"];
+
+/// The package a service runs: the latest release satisfying an ident, or the latest release
+/// within a version constraint when its spec has one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServicePackage {
+    Ident(PackageIdent),
+    Constraint(PackageIdentConstraint),
+}
+
+impl ServicePackage {
+    fn matches(&self, ident: &PackageIdent) -> bool {
+        match *self {
+            ServicePackage::Ident(ref service) => ident.satisfies(service),
+            ServicePackage::Constraint(ref constraint) => constraint.matches(ident),
+        }
+    }
+}
+
+impl From<PackageIdent> for ServicePackage {
+    fn from(ident: PackageIdent) -> Self { ServicePackage::Ident(ident) }
+}
+
+/// Remove every installed package that isn't reachable from the given roots, and every cached
+/// artifact of a package that isn't kept.
+///
+/// `services` are the packages of loaded and running services; the latest installed release
+/// of each is kept, as that is the one the Supervisor runs. Every installed release
+/// satisfying an ident in `keep` is kept. `keep_releases` keeps that many of the newest installed
+/// releases of every package.
+pub fn start(ui: &mut UI,
+             fs_root_path: &Path,
+             services: &[ServicePackage],
+             keep: &[PackageIdent],
+             keep_releases: usize,
+             execution_strategy: ExecutionStrategy)
+             -> Result<()> {
+    ui.begin("Collecting unused packages")?;
+    let pkg_root_path = hfs::pkg_root_path(Some(fs_root_path));
+    let installed = package::all_packages(&pkg_root_path)?;
+    let graph = PackageGraph::from_root_path(fs_root_path)?;
+
+    let mut roots = HashSet::new();
+    for service in services.iter() {
+        roots.extend(latest_installed(&installed, service));
+    }
+    for habitat_package in HABITAT_PACKAGES.iter() {
+        let habitat_package = PackageIdent::from_str(habitat_package)?.into();
+        roots.extend(latest_installed(&installed, &habitat_package));
+    }
+    roots.extend(installed.iter()
+                          .filter(|ident| keep.iter().any(|k| ident.satisfies(k)))
+                          .cloned());
+    roots.extend(newest_releases(&installed, keep_releases));
+
+    let mut kept = HashSet::new();
+    for root in roots.iter() {
+        kept.extend(graph.owned_ordered_deps(root));
+        kept.insert(root.clone());
+    }
+
+    let mut freed = 0;
+    let mut removed = 0;
+    for ident in installed.iter().filter(|ident| !kept.contains(*ident)) {
+        let install = PackageInstall::load(ident, Some(fs_root_path))?;
+        freed += disk_usage(install.installed_path());
+        match execution_strategy {
+            ExecutionStrategy::DryRun => ui.status(Status::DryRunDeleting, ident)?,
+            ExecutionStrategy::Run => {
+                ui.status(Status::Deleting, ident)?;
+                uninstall::do_clean_delete(&pkg_root_path, install.installed_path())?;
+            }
+        }
+        removed += 1;
+    }
+
+    let artifact_cache_path = hfs::cache_artifact_path(Some(fs_root_path));
+    let mut pruned = 0;
+    if artifact_cache_path.is_dir() {
+        for entry in fs::read_dir(&artifact_cache_path)? {
+            let path = entry?.path();
+            if path.extension().and_then(OsStr::to_str) != Some("hart") {
+                continue;
+            }
+            match PackageArchive::new(&path).ident() {
+                Ok(ref ident) if kept.contains(ident) => continue,
+                Ok(_) => (),
+                Err(e) => {
+                    ui.warn(format!("Skipping {}, which can't be read: {}", path.display(), e))?;
+                    continue;
+                }
+            }
+            freed += fs::metadata(&path)?.len();
+            let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+            match execution_strategy {
+                ExecutionStrategy::DryRun => ui.status(Status::DryRunDeleting, file_name)?,
+                ExecutionStrategy::Run => {
+                    ui.status(Status::Deleting, file_name)?;
+                    fs::remove_file(&path)?;
+                }
+            }
+            pruned += 1;
+        }
+    }
+
+    let summary = format!("{} packages and {} cached artifacts, freeing {}",
+                          removed,
+                          pruned,
+                          megabytes(freed));
+    match execution_strategy {
+        ExecutionStrategy::DryRun => ui.end(format!("Would remove {} (Dry run)", summary))?,
+        ExecutionStrategy::Run => ui.end(format!("Removed {}", summary))?,
+    }
+    Ok(())
+}
+
+/// The packages of the services loaded into a Supervisor, read from the spec files in its root.
+pub fn loaded_services(sup_root: &Path) -> Result<Vec<ServicePackage>> {
+    let specs_path = sup_root.join("specs");
+    let mut services = Vec::new();
+    if !specs_path.is_dir() {
+        return Ok(services);
+    }
+    for entry in fs::read_dir(&specs_path)? {
+        let path = entry?.path();
+        if path.extension().and_then(OsStr::to_str) != Some("spec") {
+            continue;
+        }
+        let spec: toml::Value = toml::from_str(&fs::read_to_string(&path)?)?;
+        let ident = match spec.get("ident").and_then(toml::Value::as_str) {
+            Some(ident) => PackageIdent::from_str(ident)?,
+            None => continue,
+        };
+        match spec.get("version_constraint").and_then(toml::Value::as_str) {
+            Some(version) => {
+                let constraint = PackageIdentConstraint::new(ident, version.parse()?);
+                services.push(ServicePackage::Constraint(constraint));
+            }
+            None => services.push(ServicePackage::Ident(ident)),
+        }
+    }
+    Ok(services)
+}
+
+/// The installed packages that processes are running an executable from, read from the `exe`
+/// link of every process under `proc_path`. This finds the running Supervisor and Launcher
+/// whichever release they were started from. Processes whose link can't be read are skipped,
+/// and where there is no `proc_path` there are no running packages.
+pub fn running_packages(proc_path: &Path, fs_root_path: &Path) -> Vec<PackageIdent> {
+    let pkg_root_path = hfs::pkg_root_path(Some(fs_root_path));
+    let entries = match fs::read_dir(proc_path) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut running = HashSet::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if let Ok(exe) = fs::read_link(entry.path().join("exe")) {
+            running.extend(package_of(&pkg_root_path, &exe));
+        }
+    }
+    running.into_iter().collect()
+}
+
+/// The package an installed file belongs to, if it is under the package root.
+fn package_of(pkg_root_path: &Path, path: &Path) -> Option<PackageIdent> {
+    let mut parts = path.strip_prefix(pkg_root_path)
+                        .ok()?
+                        .components()
+                        .map(|component| {
+                            match component {
+                                Component::Normal(part) => part.to_str(),
+                                _ => None,
+                            }
+                        });
+    let (origin, name) = (parts.next()??, parts.next()??);
+    let (version, release) = (parts.next()??, parts.next()??);
+    Some(PackageIdent::new(origin, name, Some(version), Some(release)))
+}
+
+/// The latest installed release of a service's package, if any is installed.
+fn latest_installed(installed: &[PackageIdent], service: &ServicePackage) -> Option<PackageIdent> {
+    installed.iter()
+             .filter(|installed| service.matches(installed))
+             .max()
+             .cloned()
+}
+
+/// The newest `count` installed releases of every package.
+fn newest_releases(installed: &[PackageIdent], count: usize) -> Vec<PackageIdent> {
+    let mut releases = BTreeMap::new();
+    for ident in installed.iter() {
+        releases.entry((&ident.origin, &ident.name))
+                .or_insert_with(Vec::new)
+                .push(ident);
+    }
+    releases.into_iter()
+            .flat_map(|(_, mut idents)| {
+                idents.sort_unstable_by(|a, b| b.cmp(a));
+                idents.into_iter().take(count).cloned()
+            })
+            .collect()
+}
+
+/// The size of every file under a directory, in bytes.
+fn disk_usage(path: &Path) -> u64 {
+    WalkDir::new(path).into_iter()
+                      .filter_map(|entry| entry.ok())
+                      .filter_map(|entry| entry.metadata().ok())
+                      .filter(|metadata| metadata.is_file())
+                      .map(|metadata| metadata.len())
+                      .sum()
+}
+
+fn megabytes(bytes: u64) -> String { format!("{:.1} MB", bytes as f64 / 1_000_000.0) }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use habitat_core::package::PackageTarget;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn idents(idents: &[&str]) -> Vec<PackageIdent> {
+        idents.iter()
+              .map(|ident| PackageIdent::from_str(ident).unwrap())
+              .collect()
+    }
+
+    #[test]
+    fn newest_releases_are_kept_for_every_package() {
+        let installed = idents(&["core/redis/4.0.10/20180801003001",
+                                 "core/redis/4.0.14/20190319155852",
+                                 "core/redis/4.0.14/20190411164023",
+                                 "core/nginx/1.15.6/20181212185120",
+                                 "acme/redis/1.0.0/20190101000000"]);
+
+        let mut newest = newest_releases(&installed, 2);
+        newest.sort_unstable_by_key(ToString::to_string);
+        assert_eq!(newest,
+                   idents(&["acme/redis/1.0.0/20190101000000",
+                            "core/nginx/1.15.6/20181212185120",
+                            "core/redis/4.0.14/20190319155852",
+                            "core/redis/4.0.14/20190411164023"]));
+        assert!(newest_releases(&installed, 0).is_empty());
+    }
+
+    fn service(ident: &str) -> ServicePackage { PackageIdent::from_str(ident).unwrap().into() }
+
+    #[test]
+    fn latest_installed_satisfies_the_ident() {
+        let installed = idents(&["core/redis/4.0.10/20180801003001",
+                                 "core/redis/4.0.14/20190319155852",
+                                 "acme/redis/5.0.0/20190101000000"]);
+
+        assert_eq!(latest_installed(&installed, &service("core/redis")),
+                   Some("core/redis/4.0.14/20190319155852".parse().unwrap()));
+        assert_eq!(latest_installed(&installed, &service("core/redis/4.0.10")),
+                   Some("core/redis/4.0.10/20180801003001".parse().unwrap()));
+        assert_eq!(latest_installed(&installed, &service("core/nginx")), None);
+    }
+
+    #[test]
+    fn loaded_services_keep_their_version_constraint() {
+        let sup_root = TempDir::new().unwrap();
+        fs::create_dir(sup_root.path().join("specs")).unwrap();
+        fs::write(sup_root.path().join("specs").join("openssl.spec"),
+                  "ident = \"core/openssl\"\nversion_constraint = \">=1.0.2,<1.1\"\n").unwrap();
+        fs::write(sup_root.path().join("specs").join("redis.spec"),
+                  "ident = \"core/redis\"\n").unwrap();
+        let installed = idents(&["core/openssl/1.0.2r/20190305210149",
+                                 "core/openssl/1.1.1b/20190305210149",
+                                 "core/redis/4.0.14/20190319155852"]);
+
+        let mut services = loaded_services(sup_root.path()).unwrap();
+        services.sort_unstable_by_key(|service| format!("{:?}", service));
+        assert_eq!(services,
+                   vec![ServicePackage::Constraint("core/openssl@>=1.0.2,<1.1".parse().unwrap()),
+                        service("core/redis")]);
+        assert_eq!(latest_installed(&installed, &services[0]),
+                   Some("core/openssl/1.0.2r/20190305210149".parse().unwrap()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn running_packages_are_read_from_process_executables() {
+        let proc_dir = TempDir::new().unwrap();
+        let fs_root = Path::new("/hab-root");
+        let sup = fs_root.join("hab/pkgs/core/hab-sup/0.79.1/20190410220617/bin/hab-sup");
+        let pkg_dir = fs_root.join("hab/pkgs/core/hab-sup");
+        for (pid, exe) in &[("1", Path::new("/sbin/init")),
+                            ("123", sup.as_path()),
+                            ("124", sup.as_path()),
+                            ("125", pkg_dir.as_path())]
+        {
+            fs::create_dir(proc_dir.path().join(pid)).unwrap();
+            std::os::unix::fs::symlink(exe, proc_dir.path().join(pid).join("exe")).unwrap();
+        }
+        // A kernel thread, which has no executable.
+        fs::create_dir(proc_dir.path().join("2")).unwrap();
+
+        assert_eq!(running_packages(proc_dir.path(), fs_root),
+                   idents(&["core/hab-sup/0.79.1/20190410220617"]));
+        assert!(running_packages(&proc_dir.path().join("missing"), fs_root).is_empty());
+    }
+
+    const POSSUMS: &str = "happyhumans/possums/8.1.4/20160427165340";
+
+    /// Install a package under a fake filesystem root, depending on `deps`.
+    fn install(fs_root: &Path, ident: &str, deps: &[&str]) -> PathBuf {
+        let ident = PackageIdent::from_str(ident).unwrap();
+        let path = hfs::pkg_install_path(&ident, Some(fs_root));
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("IDENT"), ident.to_string()).unwrap();
+        fs::write(path.join("TARGET"),
+                  PackageTarget::active_target().to_string()).unwrap();
+        fs::write(path.join("DEPS"), deps.join("\n")).unwrap();
+        path
+    }
+
+    /// Copy the `happyhumans/possums` fixture from core's tests into the artifact cache.
+    fn cache_possums(fs_root: &Path) -> PathBuf {
+        let hart = "happyhumans-possums-8.1.4-20160427165340-x86_64-linux.hart";
+        let cache = hfs::cache_artifact_path(Some(fs_root));
+        fs::create_dir_all(&cache).unwrap();
+        fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join("../core/tests/fixtures")
+                                                      .join(hart),
+                 cache.join(hart)).unwrap();
+        cache.join(hart)
+    }
+
+    #[test]
+    fn start_removes_the_packages_and_artifacts_nothing_keeps() {
+        let fs_root = TempDir::new().unwrap();
+        let root = fs_root.path();
+        let old_redis = install(root, "core/redis/4.0.10/20180801003001", &[]);
+        let redis = install(root,
+                            "core/redis/4.0.14/20190319155852",
+                            &["core/glibc/2.27/20190115002733"]);
+        let glibc = install(root, "core/glibc/2.27/20190115002733", &[]);
+        let old_sup = install(root, "core/hab-sup/0.78.0/20190313123218", &[]);
+        let running_sup = install(root, "core/hab-sup/0.79.1/20190410220617", &[]);
+        let sup = install(root, "core/hab-sup/0.80.0/20190507222301", &[]);
+        let tool = install(root, "acme/tool/1.0.0/20190101000000", &[]);
+        let possums = install(root, POSSUMS, &[]);
+
+        let possums_hart = cache_possums(root);
+        let unreadable_hart = possums_hart.with_file_name("acme-broken.hart");
+        fs::write(&unreadable_hart, "not a hart").unwrap();
+
+        let services = [service("core/redis")];
+        let keep = idents(&["acme/tool", "core/hab-sup/0.79.1/20190410220617"]);
+        start(&mut UI::with_sinks(),
+              root,
+              &services,
+              &keep,
+              0,
+              ExecutionStrategy::DryRun).unwrap();
+        assert!(old_redis.is_dir() && possums_hart.is_file());
+
+        start(&mut UI::with_sinks(),
+              root,
+              &services,
+              &keep,
+              0,
+              ExecutionStrategy::Run).unwrap();
+        for kept in &[&redis, &glibc, &running_sup, &sup, &tool] {
+            assert!(kept.is_dir(), "{} was removed", kept.display());
+        }
+        for removed in &[&old_redis, &old_sup, &possums] {
+            assert!(!removed.exists(), "{} was kept", removed.display());
+        }
+        assert!(!possums_hart.exists());
+        assert!(unreadable_hart.is_file());
+    }
+
+    #[test]
+    fn start_keeps_the_artifacts_of_kept_packages() {
+        let fs_root = TempDir::new().unwrap();
+        let root = fs_root.path();
+        let possums = install(root, POSSUMS, &[]);
+        let possums_hart = cache_possums(root);
+
+        start(&mut UI::with_sinks(),
+              root,
+              &[],
+              &idents(&["happyhumans/possums"]),
+              0,
+              ExecutionStrategy::Run).unwrap();
+        assert!(possums.is_dir() && possums_hart.is_file());
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Writes a lockfile for a package and its transitive dependencies.
+//!
+//! # Examples
+//!
+//! ```bash
+//! $ hab pkg lock core/redis redis.lock
+//! $ hab pkg install --lockfile redis.lock
+//! ```
+//!
+//! The first command resolves the latest `core/redis` in the stable channel and records the
+//! exact artifacts it and its dependencies are made of, without installing any of them. The
+//! second installs those same artifacts on any machine, failing if any of them has changed.
+
+use std::path::Path;
+
+use crate::{common::{command::package::install::{self,
+                                                 InstallSource},
+                     lockfile::Lockfile,
+                     ui::{Status,
+                          UIWriter,
+                          UI}},
+            hcore::{fs::cache_artifact_path,
+                    package::{PackageArchive,
+                              PackageIdent},
+                    ChannelIdent}};
+
+use crate::{error::Result,
+            PRODUCT,
+            VERSION};
+
+/// Write a lockfile for a package and its transitive dependencies.
+pub fn start(ui: &mut UI,
+             bldr_url: &str,
+             channel: &ChannelIdent,
+             install_source: &InstallSource,
+             token: Option<&str>,
+             fs_root_path: &Path,
+             lockfile_path: &Path)
+             -> Result<()> {
+    let (lockfile, _) = lock(ui, bldr_url, channel, install_source, token, fs_root_path)?;
+    lockfile.write(lockfile_path)?;
+    ui.status(Status::Created, lockfile_path.display())?;
+    Ok(())
+}
+
+/// Lock a package and its transitive dependencies, returning the lockfile along with the
+/// artifacts it was made from, in the same order.
+///
+/// The package is resolved to the latest release in the channel, and its artifacts are
+/// downloaded into the artifact cache and verified there; nothing is installed.
+pub fn lock(ui: &mut UI,
+            bldr_url: &str,
+            channel: &ChannelIdent,
+            install_source: &InstallSource,
+            token: Option<&str>,
+            fs_root_path: &Path)
+            -> Result<(Lockfile, Vec<PackageArchive>)> {
+    let artifact_cache_path = cache_artifact_path(Some(fs_root_path));
+    ui.begin(format!("Locking {}", install_source.as_ref()))?;
+    let ident = install::fetch(ui,
+                               bldr_url,
+                               channel,
+                               install_source,
+                               PRODUCT,
+                               VERSION,
+                               fs_root_path,
+                               &artifact_cache_path,
+                               token)?;
+
+    let cached = |ident: &PackageIdent| -> Result<PackageArchive> {
+        Ok(PackageArchive::new(artifact_cache_path.join(ident.archive_name()?)))
+    };
+    let mut package = cached(&ident)?;
+    let mut archives = package.tdeps()?
+                              .iter()
+                              .map(cached)
+                              .collect::<Result<Vec<_>>>()?;
+    archives.push(package);
+
+    let lockfile = Lockfile::from_archives(&mut archives)?;
+    ui.end(format!("Locked {} packages for {}",
+                   lockfile.packages.len(),
+                   lockfile.ident))?;
+    Ok((lockfile, archives))
+}
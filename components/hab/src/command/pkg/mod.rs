@@ -14,6 +14,7 @@
 
 pub mod binlink;
 pub mod build;
+pub mod bundle;
 pub mod channels;
 pub mod delete;
 pub mod demote;
@@ -21,10 +22,12 @@ pub mod dependencies;
 pub mod env;
 pub mod exec;
 pub mod export;
+pub mod gc;
 pub mod hash;
 pub mod header;
 pub mod info;
 pub mod list;
+pub mod lock;
 pub mod path;
 pub mod promote;
 pub mod provides;
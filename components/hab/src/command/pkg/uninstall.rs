@@ -210,7 +210,7 @@ fn maybe_delete(ui: &mut UI,
 
 /// Delete empty parent directories from a given path. don't traverse above
 /// the `pkg_root_path`
-fn do_clean_delete(pkg_root_path: &Path, real_install_path: &Path) -> Result<bool> {
+pub fn do_clean_delete(pkg_root_path: &Path, real_install_path: &Path) -> Result<bool> {
     // This match will always return Ok(Path) as the install path is always 4 levels
     // below the pkg_root_path
     match real_install_path.parent() {
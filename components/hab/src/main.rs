@@ -21,6 +21,8 @@ extern crate lazy_static;
 #[macro_use]
 extern crate log;
 
+use chrono::{TimeZone,
+             Utc};
 use clap::{ArgMatches,
            Shell};
 use env_logger;
@@ -48,6 +50,7 @@ use habitat_common::{self as common,
                                                  InstallMode,
                                                  InstallSource,
                                                  LocalPackageUsage},
+                     lockfile::Lockfile,
                      output,
                      types::ListenCtlAddr,
                      ui::{Status,
@@ -70,6 +73,8 @@ use habitat_core::{crypto::{init,
                                  ShutdownTimeout},
                    package::{target,
                              PackageIdent,
+                             PackageIdentConstraint,
+                             PackageInstall,
                              PackageTarget},
                    service::{HealthCheckInterval,
                              ServiceGroup},
@@ -250,15 +255,24 @@ fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                 ("binds", Some(m)) => sub_pkg_binds(m)?,
                 ("binlink", Some(m)) => sub_pkg_binlink(ui, m)?,
                 ("build", Some(m)) => sub_pkg_build(ui, m)?,
+                ("bundle", Some(m)) => {
+                    match m.subcommand() {
+                        ("create", Some(sc)) => sub_pkg_bundle_create(ui, sc)?,
+                        ("install", Some(sc)) => sub_pkg_bundle_install(ui, sc)?,
+                        _ => unreachable!(),
+                    }
+                }
                 ("channels", Some(m)) => sub_pkg_channels(ui, m)?,
                 ("config", Some(m)) => sub_pkg_config(m)?,
                 ("dependencies", Some(m)) => sub_pkg_dependencies(m)?,
                 ("env", Some(m)) => sub_pkg_env(m)?,
                 ("exec", Some(m)) => sub_pkg_exec(m, &remaining_args)?,
                 ("export", Some(m)) => sub_pkg_export(ui, m)?,
+                ("gc", Some(m)) => sub_pkg_gc(ui, m)?,
                 ("hash", Some(m)) => sub_pkg_hash(m)?,
                 ("install", Some(m)) => sub_pkg_install(ui, m, feature_flags)?,
                 ("list", Some(m)) => sub_pkg_list(m)?,
+                ("lock", Some(m)) => sub_pkg_lock(ui, m)?,
                 ("path", Some(m)) => sub_pkg_path(m)?,
                 ("provides", Some(m)) => sub_pkg_provides(m)?,
                 ("search", Some(m)) => sub_pkg_search(m)?,
@@ -288,6 +302,7 @@ fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                         ("export", Some(sc)) => sub_ring_key_export(sc)?,
                         ("import", Some(sc)) => sub_ring_key_import(ui, sc)?,
                         ("generate", Some(sc)) => sub_ring_key_generate(ui, sc)?,
+                        ("rotate", Some(sc)) => sub_ring_key_rotate(sc)?,
                         _ => unreachable!(),
                     }
                 }
@@ -303,6 +318,7 @@ fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                     }
                 }
                 ("load", Some(m)) => sub_svc_load(m)?,
+                ("logs", Some(m)) => sub_svc_logs(m)?,
                 ("unload", Some(m)) => sub_svc_unload(m, feature_flags)?,
                 ("start", Some(m)) => sub_svc_start(m)?,
                 ("stop", Some(m)) => sub_svc_stop(m, feature_flags)?,
@@ -607,6 +623,42 @@ fn sub_pkg_uninstall(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
                                    &excludes,
                                    &services)
 }
+
+fn sub_pkg_gc(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
+    let execute_strategy = if m.is_present("DRYRUN") {
+        command::pkg::ExecutionStrategy::DryRun
+    } else {
+        command::pkg::ExecutionStrategy::Run
+    };
+    let keep_releases = m.value_of("KEEP_RELEASES")
+                         .unwrap() // Required via clap
+                         .parse()
+                         .map_err(Error::ParseIntError)?;
+    let binlink_dir = Path::new(m.value_of("BINLINK_DIR").unwrap()); // Required via clap
+
+    // Running services are kept even if they were since unloaded, along with the latest release
+    // of any service that is still loaded, which is what the Supervisor would run next.
+    let mut services = supervisor_services()?.into_iter()
+                                             .map(Into::into)
+                                             .collect::<Vec<_>>();
+    services.extend(command::pkg::gc::loaded_services(&sup_proto::sup_root(None))?);
+    // unwrap safe as we've validated the input
+    let mut keep = m.values_of("KEEP")
+                    .unwrap_or_default()
+                    .map(|i| PackageIdent::from_str(i).unwrap())
+                    .collect::<Vec<_>>();
+    keep.extend(command::pkg::binlink::binlinked_packages(binlink_dir, &*FS_ROOT)?);
+    // The running Supervisor and Launcher may be older than the newest installed release.
+    keep.extend(command::pkg::gc::running_packages(Path::new("/proc"), &*FS_ROOT));
+
+    command::pkg::gc::start(ui,
+                            &*FS_ROOT,
+                            &services,
+                            &keep,
+                            keep_releases,
+                            execute_strategy)
+}
+
 fn sub_bldr_channel_create(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
     let url = bldr_url_from_matches(&m)?;
     let origin = origin_param_or_env(&m)?;
@@ -733,7 +785,6 @@ fn sub_plan_render(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
 fn sub_pkg_install(ui: &mut UI, m: &ArgMatches<'_>, feature_flags: FeatureFlag) -> Result<()> {
     let url = bldr_url_from_matches(&m)?;
     let channel = channel_from_matches_or_default(m);
-    let install_sources = install_sources_from_matches(m)?;
     let token = maybe_auth_token(&m);
     let install_mode =
         if feature_flags.contains(FeatureFlag::OFFLINE_INSTALL) && m.is_present("OFFLINE") {
@@ -757,7 +808,23 @@ fn sub_pkg_install(ui: &mut UI, m: &ArgMatches<'_>, feature_flags: FeatureFlag)
 
     init();
 
-    for install_source in install_sources.iter() {
+    if let Some(lockfile) = m.value_of("LOCKFILE") {
+        let lockfile = Lockfile::from_file(lockfile)?;
+        let pkg_install =
+            common::command::package::install::start_locked(ui,
+                                                            &url,
+                                                            &lockfile,
+                                                            PRODUCT,
+                                                            VERSION,
+                                                            &*FS_ROOT,
+                                                            &cache_artifact_path(Some(&*FS_ROOT)),
+                                                            token.as_ref().map(String::as_str),
+                                                            &install_mode,
+                                                            install_hook_mode)?;
+        return binlink_pkg_install(ui, m, &pkg_install);
+    }
+
+    for install_source in install_sources_from_matches(m)?.iter() {
         let pkg_install =
             common::command::package::install::start(ui,
                                                      &url,
@@ -771,19 +838,77 @@ fn sub_pkg_install(ui: &mut UI, m: &ArgMatches<'_>, feature_flags: FeatureFlag)
                                                      &install_mode,
                                                      &local_package_usage,
                                                      install_hook_mode)?;
+        binlink_pkg_install(ui, m, &pkg_install)?;
+    }
+    Ok(())
+}
 
-        if let Some(dest_dir) = binlink_dest_dir_from_matches(m) {
-            let force = m.is_present("FORCE");
-            command::pkg::binlink::binlink_all_in_pkg(ui,
-                                                      pkg_install.ident(),
-                                                      &dest_dir,
-                                                      &FS_ROOT,
-                                                      force)?;
-        }
+fn binlink_pkg_install(ui: &mut UI,
+                       m: &ArgMatches<'_>,
+                       pkg_install: &PackageInstall)
+                       -> Result<()> {
+    if let Some(dest_dir) = binlink_dest_dir_from_matches(m) {
+        let force = m.is_present("FORCE");
+        command::pkg::binlink::binlink_all_in_pkg(ui,
+                                                  pkg_install.ident(),
+                                                  &dest_dir,
+                                                  &FS_ROOT,
+                                                  force)?;
     }
     Ok(())
 }
 
+fn sub_pkg_bundle_create(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
+    let url = bldr_url_from_matches(&m)?;
+    let channel = channel_from_matches_or_default(m);
+    let install_sources = install_sources_from_matches(m)?;
+    let token = maybe_auth_token(&m);
+    let bundle = Path::new(m.value_of("OUTPUT").unwrap()); // Required via clap
+
+    init();
+
+    command::pkg::bundle::create(ui,
+                                 &url,
+                                 &channel,
+                                 &install_sources,
+                                 token.as_ref().map(String::as_str),
+                                 &*FS_ROOT,
+                                 bundle)
+}
+
+fn sub_pkg_bundle_install(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
+    let bundle = Path::new(m.value_of("BUNDLE").unwrap()); // Required via clap
+    let install_hook_mode = if m.is_present("IGNORE_INSTALL_HOOK") {
+        InstallHookMode::Ignore
+    } else {
+        InstallHookMode::default()
+    };
+
+    init();
+
+    command::pkg::bundle::install(ui, bundle, &*FS_ROOT, install_hook_mode)
+}
+
+fn sub_pkg_lock(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
+    let url = bldr_url_from_matches(&m)?;
+    let channel = channel_from_matches_or_default(m);
+    let install_source: InstallSource = m.value_of("PKG_IDENT_OR_ARTIFACT")
+                          .unwrap() // Required via clap
+                          .parse()?;
+    let token = maybe_auth_token(&m);
+    let lockfile = Path::new(m.value_of("LOCKFILE").unwrap()); // Required via clap
+
+    init();
+
+    command::pkg::lock::start(ui,
+                              &url,
+                              &channel,
+                              &install_source,
+                              token.as_ref().map(String::as_str),
+                              &*FS_ROOT,
+                              lockfile)
+}
+
 fn sub_pkg_path(m: &ArgMatches<'_>) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
 
@@ -951,8 +1076,18 @@ fn sub_svc_set(m: &ArgMatches<'_>) -> Result<()> {
         }
         _ => set.cfg = Some(buf.to_vec()),
     }
+    let version = value_t!(m, "VERSION_NUMBER", u64).unwrap();
+    let content = sup_proto::butterfly::signed_content(&service_group,
+                                                       version,
+                                                       None,
+                                                       set.is_encrypted.unwrap_or(false),
+                                                       set.cfg.as_ref().unwrap());
+    if let Some((signer, signature)) = sign_gossip(&mut ui, m, &cache, "TOML", &content)? {
+        set.signer = Some(signer);
+        set.signature = Some(signature);
+    }
     set.service_group = Some(service_group.into());
-    set.version = Some(value_t!(m, "VERSION_NUMBER", u64).unwrap());
+    set.version = Some(version);
     ui.begin(format!("Setting new configuration version {} for {}",
                      set.version
                         .as_ref()
@@ -1049,7 +1184,14 @@ fn sub_svc_load(m: &ArgMatches<'_>) -> Result<()> {
     let secret_key = ctl_secret_key(&cfg)?;
     let mut msg = sup_proto::ctl::SvcLoad::default();
     update_svc_load_from_input(m, &mut msg)?;
-    let ident: PackageIdent = m.value_of("PKG_IDENT").unwrap().parse()?;
+    let pkg_ident = m.value_of("PKG_IDENT").unwrap();
+    let ident: PackageIdent = if pkg_ident.contains('@') {
+        let constraint: PackageIdentConstraint = pkg_ident.parse()?;
+        msg.version_constraint = Some(constraint.version.to_string());
+        constraint.ident
+    } else {
+        pkg_ident.parse()?
+    };
     msg.ident = Some(ident.into());
     SrvClient::connect(&listen_ctl_addr, &secret_key).and_then(|conn| {
                                                          conn.call(msg)
@@ -1120,6 +1262,22 @@ fn sub_svc_start(m: &ArgMatches<'_>) -> Result<()> {
     Ok(())
 }
 
+fn sub_svc_logs(m: &ArgMatches<'_>) -> Result<()> {
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let secret_key = ctl_secret_key(&cfg)?;
+    let msg = sup_proto::ctl::SvcLogs { ident:  Some(ident.into()),
+                                        follow: Some(m.is_present("FOLLOW")), };
+
+    SrvClient::connect(&listen_ctl_addr, &secret_key).and_then(|conn| {
+                                                         conn.call(msg)
+                                                             .for_each(|m| handle_ctl_reply(&m))
+                                                     })
+                                                     .wait()?;
+    Ok(())
+}
+
 fn sub_svc_status(m: &ArgMatches<'_>) -> Result<()> {
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
@@ -1160,6 +1318,25 @@ fn sub_svc_status(m: &ArgMatches<'_>) -> Result<()> {
     Ok(())
 }
 
+/// Sign the content of a service config or file with the key named by `--signing-key`, if there
+/// is one, returning the name with revision of the key used and the signature.
+fn sign_gossip(ui: &mut UI,
+               m: &ArgMatches<'_>,
+               cache: &Path,
+               what: &str,
+               content: &[u8])
+               -> Result<Option<(String, Vec<u8>)>> {
+    match m.value_of("SIGNING_KEY") {
+        Some(key) => {
+            let pair = SigKeyPair::get_latest_pair_for(key, cache, Some(&PairType::Secret))?;
+            ui.status(Status::Signing,
+                      format!("{} with {}", what, pair.name_with_rev()))?;
+            Ok(Some((pair.name_with_rev(), pair.sign(content)?)))
+        }
+        None => Ok(None),
+    }
+}
+
 fn sub_svc_stop(m: &ArgMatches<'_>, feature_flags: FeatureFlag) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
     let cfg = config::load()?;
@@ -1224,6 +1401,15 @@ fn sub_file_put(m: &ArgMatches<'_>) -> Result<()> {
         }
         _ => msg.content = Some(buf.to_vec()),
     }
+    let content = sup_proto::butterfly::signed_content(&service_group,
+                                                       msg.version.unwrap(),
+                                                       msg.filename.as_ref().map(String::as_str),
+                                                       msg.is_encrypted.unwrap_or(false),
+                                                       msg.content.as_ref().unwrap());
+    if let Some((signer, signature)) = sign_gossip(&mut ui, m, &cache, "file", &content)? {
+        msg.signer = Some(signer);
+        msg.signature = Some(signature);
+    }
     SrvClient::connect(&listen_ctl_addr, &secret_key).and_then(|conn| {
                                                          ui.status(Status::Applying,
                                                                    format!("via peer {}",
@@ -1331,6 +1517,33 @@ fn sub_ring_key_import(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
     command::ring::key::import::start(ui, content.trim(), &cache_key_path)
 }
 
+fn sub_ring_key_rotate(m: &ArgMatches<'_>) -> Result<()> {
+    use sup_proto::ctl::sup_ring_key_rotate::Action;
+
+    let name_with_rev = m.value_of("KEY").unwrap(); // Required via clap
+    let action = match m.value_of("ACTION").unwrap() {
+        "add" => Action::Add,
+        "promote" => Action::Promote,
+        "retire" => Action::Retire,
+        _ => unreachable!(),
+    };
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let secret_key = ctl_secret_key(&cfg)?;
+    init();
+    // Only the name of the revision is sent; the Supervisor reads the key from its own cache,
+    // where it has to be imported with `hab ring key import` before it's added.
+    let msg = sup_proto::ctl::SupRingKeyRotate { action: Some(action as i32),
+                                                 key:    Some(name_with_rev.to_string()), };
+
+    SrvClient::connect(&listen_ctl_addr, &secret_key).and_then(|conn| {
+                                                         conn.call(msg)
+                                                             .for_each(|m| handle_ctl_reply(&m))
+                                                     })
+                                                     .wait()?;
+    Ok(())
+}
+
 fn sub_service_key_generate(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
     let org = org_param_or_env(&m)?;
     let service_group = ServiceGroup::from_str(m.value_of("SERVICE_GROUP").unwrap())?;
@@ -1385,6 +1598,7 @@ fn exec_subcommand_if_called(ui: &mut UI) -> Result<()> {
         ("sup", "", "")
         | ("sup", "term", _)
         | ("sup", "bash", _)
+        | ("sup", "dat", _)
         | ("sup", "sh", _)
         | ("sup", "-V", _)
         | ("sup", "--version", _) => command::sup::start(ui, &args_after_first(2)),
@@ -1666,6 +1880,19 @@ fn print_svc_status<T>(out: &mut T,
              svc_elapsed,
              svc_pid,
              status.service_group,)?;
+    // Reported on stderr so the table above keeps its format.
+    if let Some(rolled_back) = status.rolled_back {
+        eprintln!("{} was rolled back from {} after it failed its health check",
+                  status.service_group, rolled_back);
+    }
+    if let Some(pending_update) = status.pending_update {
+        let applies_at = match pending_update.applies_at {
+            Some(t) => Utc.timestamp(t, 0).format("%Y-%m-%d %H:%M UTC").to_string(),
+            None => "never; its update window does not match any time".to_string(),
+        };
+        eprintln!("{} has a pending update to {}, applying at {}",
+                  status.service_group, pending_update.ident, applies_at);
+    }
     Ok(())
 }
 
@@ -1778,6 +2005,12 @@ fn get_password_from_input(m: &ArgMatches) -> Result<Option<String>> {
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 fn get_password_from_input(_m: &ArgMatches<'_>) -> Result<Option<String>> { Ok(None) }
 
+fn get_restart_policy_from_input(m: &ArgMatches<'_>) -> Option<RestartPolicy> {
+    // There won't be errors, because we validate with `valid_restart_policy`
+    m.value_of("RESTART_POLICY")
+     .and_then(|p| RestartPolicy::from_str(p).ok())
+}
+
 fn get_topology_from_input(m: &ArgMatches<'_>) -> Option<Topology> {
     m.value_of("TOPOLOGY")
      .and_then(|f| Topology::from_str(f).ok())
@@ -1861,6 +2094,25 @@ fn update_svc_load_from_input(m: &ArgMatches<'_>, msg: &mut sup_proto::ctl::SvcL
     msg.binding_mode = get_binding_mode_from_input(m).map(|v| v as i32);
     msg.topology = get_topology_from_input(m).map(|v| v as i32);
     msg.update_strategy = get_strategy_from_input(m).map(|v| v as i32);
+    msg.restart_policy = get_restart_policy_from_input(m).map(|v| v as i32);
+    // Numeric values will have already been validated by `cli::valid_numeric`
+    msg.restart_max_attempts = m.value_of("RESTART_MAX_ATTEMPTS")
+                                .and_then(|s| s.parse().ok());
+    msg.restart_backoff_secs = m.value_of("RESTART_BACKOFF").and_then(|s| s.parse().ok());
+    msg.restart_backoff_max_secs = m.value_of("RESTART_BACKOFF_MAX")
+                                    .and_then(|s| s.parse().ok());
+    msg.restart_reset_secs = m.value_of("RESTART_RESET_WINDOW")
+                              .and_then(|s| s.parse().ok());
+    // Validated by `valid_update_batch_size`
+    msg.update_batch_size = m.value_of("UPDATE_BATCH_SIZE").map(ToString::to_string);
+    msg.update_canary_soak_secs = m.value_of("UPDATE_CANARY_SOAK")
+                                   .and_then(|s| s.parse().ok());
+    // Validated by `valid_update_window`
+    msg.update_window = m.value_of("UPDATE_WINDOW").map(ToString::to_string);
+    msg.leader_priority = m.value_of("LEADER_PRIORITY").and_then(|s| s.parse().ok());
+    if m.is_present("STICKY_LEADER") {
+        msg.sticky_leader = Some(true);
+    }
     Ok(())
 }
 
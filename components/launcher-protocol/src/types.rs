@@ -146,6 +146,7 @@ pub struct Spawn {
     pub env:          HashMap<String, String>,
     pub svc_user_id:  Option<u32>,
     pub svc_group_id: Option<u32>,
+    pub logs:         Option<ServiceLogs>,
 }
 
 impl LauncherMessage for Spawn {
@@ -161,7 +162,8 @@ impl LauncherMessage for Spawn {
                    svc_password: proto.svc_password,
                    env:          proto.env,
                    svc_user_id:  proto.svc_user_id,
-                   svc_group_id: proto.svc_group_id, })
+                   svc_group_id: proto.svc_group_id,
+                   logs:         proto.logs.map(ServiceLogs::from_proto).transpose()?, })
     }
 }
 
@@ -174,7 +176,40 @@ impl From<Spawn> for generated::Spawn {
                            svc_password: value.svc_password,
                            env:          value.env,
                            svc_user_id:  value.svc_user_id,
-                           svc_group_id: value.svc_group_id, }
+                           svc_group_id: value.svc_group_id,
+                           logs:         value.logs.map(Into::into), }
+    }
+}
+
+/// Where the Launcher keeps a copy of a spawned service's output, and how it rotates it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServiceLogs {
+    pub stdout_path:  String,
+    pub stderr_path:  String,
+    pub max_bytes:    u64,
+    pub max_age_secs: u64,
+    pub keep:         u32,
+}
+
+impl ServiceLogs {
+    fn from_proto(proto: generated::ServiceLogs) -> Result<Self> {
+        Ok(ServiceLogs { stdout_path:  proto.stdout_path
+                                            .ok_or(Error::ProtocolMismatch("stdout_path"))?,
+                         stderr_path:  proto.stderr_path
+                                            .ok_or(Error::ProtocolMismatch("stderr_path"))?,
+                         max_bytes:    proto.max_bytes.unwrap_or_default(),
+                         max_age_secs: proto.max_age_secs.unwrap_or_default(),
+                         keep:         proto.keep.unwrap_or_default(), })
+    }
+}
+
+impl From<ServiceLogs> for generated::ServiceLogs {
+    fn from(value: ServiceLogs) -> Self {
+        generated::ServiceLogs { stdout_path:  Some(value.stdout_path),
+                                 stderr_path:  Some(value.stderr_path),
+                                 max_bytes:    Some(value.max_bytes),
+                                 max_age_secs: Some(value.max_age_secs),
+                                 keep:         Some(value.keep), }
     }
 }
 
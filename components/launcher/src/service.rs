@@ -17,8 +17,9 @@ use crate::protocol;
 use core::os::process::windows_child::{ChildStderr,
                                        ChildStdout,
                                        ExitStatus};
-use habitat_common::output::{self,
-                             StructuredOutput};
+use habitat_common::{output::{self,
+                              StructuredOutput},
+                     rotating_log::RotatingLog};
 #[cfg(unix)]
 use std::process::{ChildStderr,
                    ChildStdout,
@@ -28,7 +29,8 @@ use std::{fmt,
                BufRead,
                BufReader,
                Read},
-          thread};
+          thread,
+          time::Duration};
 
 pub use crate::sys::service::*;
 
@@ -45,14 +47,20 @@ impl Service {
                -> Self {
         if let Some(stdout) = stdout {
             let id = spawn.id.to_string();
+            let log = spawn.logs
+                           .as_ref()
+                           .map(|logs| rotating_log(logs, &logs.stdout_path));
             thread::Builder::new().name(format!("{}-out", spawn.id))
-                                  .spawn(move || pipe_stdout(stdout, &id))
+                                  .spawn(move || pipe_stdout(stdout, &id, log))
                                   .ok();
         }
         if let Some(stderr) = stderr {
             let id = spawn.id.to_string();
+            let log = spawn.logs
+                           .as_ref()
+                           .map(|logs| rotating_log(logs, &logs.stderr_path));
             thread::Builder::new().name(format!("{}-err", spawn.id))
-                                  .spawn(move || pipe_stderr(stderr, &id))
+                                  .spawn(move || pipe_stderr(stderr, &id, log))
                                   .ok();
         }
         Service { args: spawn,
@@ -82,14 +90,35 @@ impl fmt::Debug for Service {
     }
 }
 
+fn rotating_log(logs: &protocol::ServiceLogs, path: &str) -> RotatingLog {
+    RotatingLog::new(path,
+                     logs.max_bytes,
+                     Duration::from_secs(logs.max_age_secs),
+                     logs.keep)
+}
+
+/// Copy a line of output to the service's log, if it has one. The log is abandoned after the
+/// first failed write rather than reporting the failure for every line.
+fn write_log(log: &mut Option<RotatingLog>, content: &str) {
+    if let Some(l) = log {
+        if let Err(e) = l.write_line(content) {
+            eprintln!("writing output to {} resulted in error: {}",
+                      l.path().display(),
+                      e);
+            *log = None;
+        }
+    }
+}
+
 /// Consume output from a child process until EOF, then finish
-fn pipe_stdout<T>(out: T, id: &str)
+fn pipe_stdout<T>(out: T, id: &str, mut log: Option<RotatingLog>)
     where T: Read
 {
     let mut reader = BufReader::new(out);
     let mut buffer = String::new();
     while reader.read_line(&mut buffer).unwrap() > 0 {
         let content = &buffer.trim_end_matches('\n');
+        write_log(&mut log, content);
         let so = StructuredOutput::succinct(&id, "O", output::get_format(), content);
         if let Err(e) = so.println() {
             println!("printing output: '{}' to stdout resulted in error: {}",
@@ -100,13 +129,14 @@ fn pipe_stdout<T>(out: T, id: &str)
 }
 
 /// Consume standard error from a child process until EOF, then finish
-fn pipe_stderr<T>(err: T, id: &str)
+fn pipe_stderr<T>(err: T, id: &str, mut log: Option<RotatingLog>)
     where T: Read
 {
     let mut reader = BufReader::new(err);
     let mut buffer = String::new();
     while reader.read_line(&mut buffer).unwrap() > 0 {
         let content = &buffer.trim_end_matches('\n');
+        write_log(&mut log, content);
         let so = StructuredOutput::succinct(&id, "E", output::get_format(), content);
         if let Err(e) = so.eprintln() {
             eprintln!("printing output: '{}' to stderr resulted in error: {}",
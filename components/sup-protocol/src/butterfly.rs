@@ -16,3 +16,21 @@
 pub const MAX_FILE_PUT_SIZE_BYTES: usize = 64 * 1024;
 /// Maximum allowed size for a configuration to be applied to a service (in bytes).
 pub const MAX_SVC_CFG_SIZE: usize = 64 * 1024;
+
+/// The content that a `ServiceConfig` or `ServiceFile` rumor's signature is made over: everything
+/// a receiving Supervisor acts on, so that none of it can be swapped out without breaking the
+/// signature. `filename` is `None` for configuration.
+pub fn signed_content(service_group: &str,
+                      incarnation: u64,
+                      filename: Option<&str>,
+                      encrypted: bool,
+                      payload: &[u8])
+                      -> Vec<u8> {
+    let mut content = format!("{}\n{}\n{}\n{}\n",
+                              service_group,
+                              incarnation,
+                              filename.unwrap_or_default(),
+                              encrypted).into_bytes();
+    content.extend_from_slice(payload);
+    content
+}
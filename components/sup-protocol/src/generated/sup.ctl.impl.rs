@@ -12,6 +12,9 @@ impl message::MessageStatic for ServiceBindList {
 impl message::MessageStatic for SupDepart {
     const MESSAGE_ID: &'static str = "SupDepart";
 }
+impl message::MessageStatic for SupRingKeyRotate {
+    const MESSAGE_ID: &'static str = "SupRingKeyRotate";
+}
 impl message::MessageStatic for SvcFilePut {
     const MESSAGE_ID: &'static str = "SvcFilePut";
 }
@@ -39,6 +42,9 @@ impl message::MessageStatic for SvcStop {
 impl message::MessageStatic for SvcStatus {
     const MESSAGE_ID: &'static str = "SvcStatus";
 }
+impl message::MessageStatic for SvcLogs {
+    const MESSAGE_ID: &'static str = "SvcLogs";
+}
 impl message::MessageStatic for ConsoleLine {
     const MESSAGE_ID: &'static str = "ConsoleLine";
 }
@@ -38,6 +38,32 @@ pub struct SupDepart {
     #[prost(string, optional, tag="1")]
     pub member_id: ::std::option::Option<std::string::String>,
 }
+/// Change which revisions of the ring key a Supervisor encrypts and decrypts gossip with.
+#[derive(Clone, PartialEq, ::prost::Message)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SupRingKeyRotate {
+    #[prost(enumeration="sup_ring_key_rotate::Action", optional, tag="1")]
+    pub action: ::std::option::Option<i32>,
+    /// The name-with-revision of the key. A key that is added has to be in the Supervisor's key
+    /// cache already.
+    #[prost(string, optional, tag="2")]
+    pub key: ::std::option::Option<std::string::String>,
+}
+pub mod sup_ring_key_rotate {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Action {
+        /// Decrypt gossip with another revision of the ring key.
+        Add = 0,
+        /// Encrypt gossip with a revision that was already added.
+        Promote = 1,
+        /// Stop decrypting gossip with a revision that isn't the primary one.
+        Retire = 2,
+    }
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -53,6 +79,12 @@ pub struct SvcFilePut {
     pub version: ::std::option::Option<u64>,
     #[prost(bool, optional, tag="5", default="false")]
     pub is_encrypted: ::std::option::Option<bool>,
+    /// Name with revision of the signing key `signature` was made with.
+    #[prost(string, optional, tag="6")]
+    pub signer: ::std::option::Option<std::string::String>,
+    /// Signature over the file, made with a user or origin signing key.
+    #[prost(bytes, optional, tag="7")]
+    pub signature: ::std::option::Option<std::vec::Vec<u8>>,
 }
 /// Request for retrieving the default configuration for a given service.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -96,6 +128,12 @@ pub struct SvcSetCfg {
     /// If the payload in `cfg` is encrypted with the remote Supervisor's Ring Key.
     #[prost(bool, optional, tag="4", default="false")]
     pub is_encrypted: ::std::option::Option<bool>,
+    /// Name with revision of the signing key `signature` was made with.
+    #[prost(string, optional, tag="5")]
+    pub signer: ::std::option::Option<std::string::String>,
+    /// Signature over the configuration, made with a user or origin signing key.
+    #[prost(bytes, optional, tag="6")]
+    pub signature: ::std::option::Option<std::vec::Vec<u8>>,
 }
 /// Request to load a new service.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -148,6 +186,39 @@ pub struct SvcLoad {
     /// Health Check interval for the service
     #[prost(message, optional, tag="15")]
     pub health_check_interval: ::std::option::Option<super::types::HealthCheckInterval>,
+    /// Restart policy for the service's process
+    #[prost(enumeration="super::types::RestartPolicy", optional, tag="16")]
+    pub restart_policy: ::std::option::Option<i32>,
+    /// Number of consecutive restarts attempted before giving up (`OnFailure` policy only)
+    #[prost(uint32, optional, tag="17")]
+    pub restart_max_attempts: ::std::option::Option<u32>,
+    /// Initial delay in seconds before restarting the process; doubles with each consecutive restart
+    #[prost(uint64, optional, tag="18")]
+    pub restart_backoff_secs: ::std::option::Option<u64>,
+    /// Upper bound in seconds on the delay between consecutive restarts
+    #[prost(uint64, optional, tag="19")]
+    pub restart_backoff_max_secs: ::std::option::Option<u64>,
+    /// Seconds the process must stay up before its consecutive restart count is reset
+    #[prost(uint64, optional, tag="20")]
+    pub restart_reset_secs: ::std::option::Option<u64>,
+    /// Members updated at once by the `Batched` and `Canary` update strategies, e.g. "3" or "25%"
+    #[prost(string, optional, tag="21")]
+    pub update_batch_size: ::std::option::Option<String>,
+    /// Seconds the canary must run a new release before the rest of the group updates
+    #[prost(uint64, optional, tag="22")]
+    pub update_canary_soak_secs: ::std::option::Option<u64>,
+    /// Cron-style expression limiting when updates may be applied, e.g. "* 2-4 * * sat,sun"
+    #[prost(string, optional, tag="23")]
+    pub update_window: ::std::option::Option<String>,
+    /// Ranks this member above members with a lower priority when electing a leader
+    #[prost(uint64, optional, tag="24")]
+    pub leader_priority: ::std::option::Option<u64>,
+    /// Keep the current leader through re-elections while its service is healthy
+    #[prost(bool, optional, tag="25")]
+    pub sticky_leader: ::std::option::Option<bool>,
+    /// Range of versions the service's package must stay within, e.g. ">=1.0.2,<1.1" or "~1.0"
+    #[prost(string, optional, tag="26")]
+    pub version_constraint: ::std::option::Option<String>,
 }
 /// Request to unload a loaded service.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -197,6 +268,18 @@ pub struct SvcStatus {
     #[prost(message, optional, tag="1")]
     pub ident: ::std::option::Option<super::types::PackageIdent>,
 }
+/// Request to stream the output a service's process has written to its logs.
+#[derive(Clone, PartialEq, ::prost::Message)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SvcLogs {
+    #[prost(message, optional, tag="1")]
+    pub ident: ::std::option::Option<super::types::PackageIdent>,
+    /// Keep streaming output as the service writes it instead of completing once the logs have been
+    /// read.
+    #[prost(bool, optional, tag="2")]
+    pub follow: ::std::option::Option<bool>,
+}
 /// A reply to various requests which contains a pre-formatted console line.
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,5 @@
+use crate::message;
+
+impl message::MessageStatic for EventEnvelope {
+    const MESSAGE_ID: &'static str = "EventEnvelope";
+}
@@ -0,0 +1,25 @@
+/// Wrapper around every event published to the Supervisor's event stream. The envelope carries
+/// the information common to all events while the event itself is encoded in the payload.
+#[derive(Clone, PartialEq, ::prost::Message)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventEnvelope {
+    /// Version of the envelope format. Incremented whenever the envelope changes incompatibly.
+    #[prost(uint32, optional, tag="1")]
+    pub version: ::std::option::Option<u32>,
+    /// Per-Supervisor sequence number. Starts at 1 and increases by one for every event published.
+    #[prost(uint64, optional, tag="2")]
+    pub sequence: ::std::option::Option<u64>,
+    /// Time the event was generated, formatted as an RFC 3339 timestamp in UTC.
+    #[prost(string, optional, tag="3")]
+    pub timestamp: ::std::option::Option<std::string::String>,
+    /// Unique identifier of the Supervisor that generated the event.
+    #[prost(string, optional, tag="4")]
+    pub supervisor_id: ::std::option::Option<std::string::String>,
+    /// Kind of event carried in the payload, e.g. "service-started".
+    #[prost(string, optional, tag="5")]
+    pub event_type: ::std::option::Option<std::string::String>,
+    /// JSON encoded event payload.
+    #[prost(bytes, optional, tag="6")]
+    pub payload: ::std::option::Option<std::vec::Vec<u8>>,
+}
@@ -39,6 +39,9 @@ pub struct ServiceBind {
     pub name: std::string::String,
     #[prost(message, required, tag="2")]
     pub service_group: ServiceGroup,
+    /// Only bind to members whose Supervisors carry all of these labels.
+    #[prost(map="string, string", tag="4")]
+    pub labels: ::std::collections::HashMap<std::string::String, std::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -86,6 +89,22 @@ pub struct ServiceStatus {
     pub service_group: ServiceGroup,
     #[prost(enumeration="DesiredState", optional, tag="5")]
     pub desired_state: ::std::option::Option<i32>,
+    /// Release this service rolled back from after it failed its health check following an update.
+    #[prost(message, optional, tag="6")]
+    pub rolled_back: ::std::option::Option<PackageIdent>,
+    /// Update found by the update strategy that is waiting for the service's update window to open.
+    #[prost(message, optional, tag="7")]
+    pub pending_update: ::std::option::Option<PendingUpdate>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PendingUpdate {
+    #[prost(message, required, tag="1")]
+    pub ident: PackageIdent,
+    /// When the update window next opens, in seconds since the epoch. Unset if it never opens.
+    #[prost(int64, optional, tag="2")]
+    pub applies_at: ::std::option::Option<i64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 #[derive(Serialize, Deserialize)]
@@ -112,6 +131,8 @@ pub enum InstallSource {
 pub enum ProcessState {
     Down = 0,
     Up = 1,
+    /// The process exited unexpectedly and is waiting to be restarted
+    BackingOff = 2,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -141,6 +162,24 @@ pub enum UpdateStrategy {
     None = 0,
     AtOnce = 1,
     Rolling = 2,
+    /// Update a batch of members at a time, as sized by the service's update batch size
+    Batched = 3,
+    /// Update the update leader first, wait for it to soak, then update the rest in batches
+    Canary = 4,
+}
+/// Governs whether a service's process is restarted after it exits unexpectedly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Always restart the process, backing off between consecutive restarts
+    Always = 0,
+    /// Restart the process, backing off between consecutive restarts, until the maximum number of
+    /// restart attempts has been reached
+    OnFailure = 1,
+    /// Never restart the process
+    Never = 2,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
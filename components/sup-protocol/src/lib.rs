@@ -56,6 +56,7 @@ extern crate tempfile;
 pub mod butterfly;
 pub mod codec;
 pub mod ctl;
+pub mod event;
 pub mod message;
 pub mod net;
 pub mod types;
@@ -83,6 +83,7 @@ impl fmt::Display for ProcessState {
         let state = match *self {
             ProcessState::Down => "down",
             ProcessState::Up => "up",
+            ProcessState::BackingOff => "backing-off",
         };
         write!(f, "{}", state)
     }
@@ -123,10 +124,11 @@ impl FromStr for ProcessState {
         match value.to_lowercase().as_ref() {
             "0" => Ok(ProcessState::Down),
             "1" => Ok(ProcessState::Up),
+            "2" => Ok(ProcessState::BackingOff),
             _ => {
                 Err(net::err(ErrCode::InvalidPayload,
                              format!("Invalid process state \"{:?}\", must \
-                                      be `up` or `down`.",
+                                      be `up`, `down` or `backing-off`.",
                                      value)))
             }
         }
@@ -167,7 +169,15 @@ impl FromStr for ServiceBind {
 impl From<core::service::ServiceBind> for ServiceBind {
     fn from(bind: core::service::ServiceBind) -> Self {
         Self { name:          bind.name().to_string(),
-               service_group: ServiceGroup::from(bind.service_group().clone()), }
+               service_group: ServiceGroup::from(bind.service_group().clone()),
+               labels:        bind.labels().clone().into_iter().collect(), }
+    }
+}
+
+impl From<ServiceBind> for core::service::ServiceBind {
+    fn from(bind: ServiceBind) -> Self {
+        core::service::ServiceBind::new(&bind.name, bind.service_group.into())
+            .with_labels(bind.labels.into_iter().collect())
     }
 }
 
@@ -331,6 +341,8 @@ impl UpdateStrategy {
             UpdateStrategy::None => "none",
             UpdateStrategy::AtOnce => "at-once",
             UpdateStrategy::Rolling => "rolling",
+            UpdateStrategy::Batched => "batched",
+            UpdateStrategy::Canary => "canary",
         }
     }
 }
@@ -343,6 +355,8 @@ impl FromStr for UpdateStrategy {
             "none" => Ok(UpdateStrategy::None),
             "at-once" => Ok(UpdateStrategy::AtOnce),
             "rolling" => Ok(UpdateStrategy::Rolling),
+            "batched" => Ok(UpdateStrategy::Batched),
+            "canary" => Ok(UpdateStrategy::Canary),
             _ => Err(net::err(ErrCode::InvalidPayload, "Invalid update strategy.")),
         }
     }
@@ -352,6 +366,33 @@ impl fmt::Display for UpdateStrategy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.as_str()) }
 }
 
+impl RestartPolicy {
+    fn as_str(&self) -> &str {
+        match *self {
+            RestartPolicy::Always => "always",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Never => "never",
+        }
+    }
+}
+
+impl FromStr for RestartPolicy {
+    type Err = NetErr;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "always" => Ok(RestartPolicy::Always),
+            "on-failure" => Ok(RestartPolicy::OnFailure),
+            "never" => Ok(RestartPolicy::Never),
+            _ => Err(net::err(ErrCode::InvalidPayload, "Invalid restart policy.")),
+        }
+    }
+}
+
+impl fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.as_str()) }
+}
+
 #[cfg(test)]
 mod test {
     use toml;
@@ -467,4 +508,46 @@ mod test {
 
         assert!(toml.starts_with(r#"key = "at-once""#));
     }
+
+    #[test]
+    fn restart_policy_default() {
+        // Services have always been restarted when their process goes away; this test confirms
+        // we keep doing so unless told otherwise
+        assert_eq!(RestartPolicy::default(), RestartPolicy::Always);
+    }
+
+    #[test]
+    fn restart_policy_from_str() {
+        assert_eq!(RestartPolicy::from_str("always").unwrap(),
+                   RestartPolicy::Always);
+        assert_eq!(RestartPolicy::from_str("on-failure").unwrap(),
+                   RestartPolicy::OnFailure);
+        assert_eq!(RestartPolicy::from_str("never").unwrap(),
+                   RestartPolicy::Never);
+    }
+
+    #[test]
+    fn restart_policy_from_str_invalid() {
+        assert!(RestartPolicy::from_str("sometimes").is_err());
+    }
+
+    #[test]
+    fn restart_policy_toml_round_trip() {
+        #[derive(Deserialize, Serialize)]
+        struct Data {
+            key: RestartPolicy,
+        }
+        let data: Data = toml::from_str(r#"key = "on-failure""#).unwrap();
+        assert_eq!(data.key, RestartPolicy::OnFailure);
+
+        let toml = toml::to_string(&data).unwrap();
+        assert!(toml.starts_with(r#"key = "on-failure""#));
+    }
+
+    #[test]
+    fn process_state_from_str() {
+        assert_eq!(ProcessState::from_str("2").unwrap(),
+                   ProcessState::BackingOff);
+        assert_eq!("backing-off", ProcessState::BackingOff.to_string());
+    }
 }
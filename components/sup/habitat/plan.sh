@@ -91,7 +91,7 @@ do_build() {
 
   pushd "$SRC_PATH" > /dev/null || exit
   cargo build ${build_type#--debug} --target=$rustc_target --verbose --no-default-features \
-    --features apidocs
+    --features "apidocs zmq-transport"
   popd > /dev/null || exit
 }
 
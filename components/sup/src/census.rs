@@ -12,8 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Error,
-                   SupError};
+use crate::{error::{Error,
+                    SupError},
+            manager::service::HealthCheck};
 use habitat_butterfly::{member::{Health,
                                  Member,
                                  MemberList},
@@ -27,8 +28,10 @@ use habitat_butterfly::{member::{Health,
                                 RumorStore}};
 use habitat_common::outputln;
 use habitat_core::{self,
+                   crypto::SigKeyPair,
                    package::PackageIdent,
                    service::ServiceGroup};
+use habitat_sup_protocol::butterfly::signed_content;
 use serde::{ser::SerializeStruct,
             Serialize,
             Serializer};
@@ -98,6 +101,12 @@ impl CensusRing {
         {
             self.changed = true;
 
+            // Rumors are garbage collected once their members are gone, so forget about groups
+            // that no longer have any.
+            self.census_groups.retain(|sg, _| {
+                                  service_rumors.len_for_key(sg.as_ref()) > 0
+                                  || service_file_rumors.len_for_key(sg.as_ref()) > 0
+                              });
             self.populate_census(service_rumors, member_list);
             self.update_from_election_store(election_rumors);
             self.update_from_election_update_store(election_update_rumors);
@@ -302,12 +311,16 @@ pub struct ServiceFile {
     pub filename:    String,
     pub incarnation: u64,
     pub body:        Vec<u8>,
+    /// The key the file was signed with, if it carries a signature that verifies.
+    pub signer:      Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ServiceConfig {
     pub incarnation: u64,
     pub value:       toml::value::Table,
+    /// The key the config was signed with, if it carries a signature that verifies.
+    pub signer:      Option<String>,
 }
 
 #[derive(Debug)]
@@ -402,7 +415,49 @@ impl CensusGroup {
         None
     }
 
+    /// Return the alive members that must have finished updating before our batch of a batched
+    /// rollout may start, or None if we or the update leader aren't alive. A rollout starts at
+    /// the update leader, then proceeds through the ordered members list (wrapping around) in
+    /// batches of `batch_size`.
+    pub fn update_batch_predecessors(&self, batch_size: usize) -> Option<Vec<&CensusMember>> {
+        match (self.me(), self.update_leader()) {
+            (Some(me), Some(leader)) => {
+                Self::update_batch_predecessors_impl(self.population.values(),
+                                                     me,
+                                                     leader,
+                                                     batch_size)
+            }
+            _ => None,
+        }
+    }
+
+    fn update_batch_predecessors_impl<'a>(members: impl Iterator<Item = &'a CensusMember>,
+                                          me: &CensusMember,
+                                          leader: &CensusMember,
+                                          batch_size: usize)
+                                          -> Option<Vec<&'a CensusMember>> {
+        let alive_members: Vec<_> = members.filter(|cm| cm.alive()).collect();
+        let position_of = |member: &CensusMember| {
+            alive_members.iter()
+                         .position(|cm| cm.member_id == member.member_id)
+        };
+        let leader_index = position_of(leader)?;
+        let my_index = position_of(me)?;
+        let count = alive_members.len();
+        // Our place in the rollout; the leader goes first, at 0
+        let place = (my_index + count - leader_index) % count;
+        if place == 0 {
+            return Some(vec![]);
+        }
+        let batch_size = batch_size.max(1);
+        let batch_start = (place - 1) / batch_size * batch_size + 1;
+        Some((0..batch_start).map(|i| alive_members[(leader_index + i) % count])
+                             .collect())
+    }
+
     fn update_from_service_rumors(&mut self, rumors: &HashMap<String, ServiceRumor>) {
+        self.population
+            .retain(|member_id, _| rumors.contains_key(member_id));
         for (member_id, service_rumor) in rumors.iter() {
             // Yeah - we are ourself - we're alive.
             let is_self = member_id == &self.local_member_id;
@@ -466,9 +521,19 @@ impl CensusGroup {
                 if self.service_config.is_none()
                    || service_config.incarnation > self.service_config.as_ref().unwrap().incarnation
                 {
+                    let content = signed_content(&service_config.service_group,
+                                                 service_config.incarnation,
+                                                 None,
+                                                 service_config.encrypted,
+                                                 &service_config.config);
+                    let signer = verified_signer(cache_key_path,
+                                                 service_config.signer.as_ref(),
+                                                 service_config.signature.as_ref(),
+                                                 &content);
                     self.service_config = Some(ServiceConfig { incarnation:
                                                                    service_config.incarnation,
-                                                               value:       config, });
+                                                               value: config,
+                                                               signer });
                 }
             }
             Err(err) => warn!("{}", err),
@@ -489,10 +554,19 @@ impl CensusGroup {
             if service_file_rumor.incarnation > file.incarnation {
                 match service_file_rumor.body(cache_key_path) {
                     Ok(body) => {
+                        let content = signed_content(&service_file_rumor.service_group,
+                                                     service_file_rumor.incarnation,
+                                                     Some(&filename),
+                                                     service_file_rumor.encrypted,
+                                                     &service_file_rumor.body);
                         self.changed_service_files.push(filename.clone());
                         file.filename = filename.clone();
                         file.incarnation = service_file_rumor.incarnation;
                         file.body = body;
+                        file.signer = verified_signer(cache_key_path,
+                                                      service_file_rumor.signer.as_ref(),
+                                                      service_file_rumor.signature.as_ref(),
+                                                      &content);
                     }
                     Err(e) => {
                         warn!("Cannot decrypt service file for {} {} {}: {}",
@@ -563,6 +637,15 @@ impl Serialize for CensusGroup {
 pub struct CensusMember {
     pub member_id: MemberId,
     pub pkg: Option<PackageIdent>,
+    /// The release this member rolled back from after an update failed
+    /// its health check.
+    pub rolled_back: Option<PackageIdent>,
+    /// The result of this member's most recent health check, if it
+    /// has gossiped one.
+    pub health_check: Option<HealthCheck>,
+    /// When `health_check` last changed, in seconds since the Unix
+    /// epoch.
+    pub health_check_since: Option<i64>,
     pub application: Option<String>,
     pub environment: Option<String>,
     pub service: String,
@@ -580,6 +663,8 @@ pub struct CensusMember {
     pub update_election_is_no_quorum: bool,
     pub update_election_is_finished: bool,
     pub sys: SysInfo,
+    /// The labels this member's Supervisor was started with (`hab sup run --label`).
+    pub labels: BTreeMap<String, String>,
 
     alive:     bool,
     suspect:   bool,
@@ -589,7 +674,7 @@ pub struct CensusMember {
     // crate. Additionally, this deserialization method is required to correct any ordering issues
     // with the table being serialized - https://docs.rs/toml/0.4.0/toml/ser/fn.tables_last.html
     #[serde(serialize_with = "toml::ser::tables_last")]
-    pub cfg: toml::value::Table,
+    pub cfg:   toml::value::Table,
 }
 
 impl CensusMember {
@@ -608,6 +693,13 @@ impl CensusMember {
             Ok(ident) => self.pkg = Some(ident),
             Err(err) => warn!("Received a bad package ident from gossip data, err={}", err),
         };
+        self.rolled_back = rumor.rolled_back
+                                .as_ref()
+                                .and_then(|r| PackageIdent::from_str(r).ok());
+        self.health_check = rumor.health_check
+                                 .as_ref()
+                                 .and_then(|h| HealthCheck::from_str(h).ok());
+        self.health_check_since = rumor.health_check_since;
         self.sys = rumor.sys.clone();
         self.cfg = toml::from_slice(&rumor.cfg).unwrap_or_default();
     }
@@ -648,6 +740,7 @@ impl CensusMember {
         self.sys.gossip_ip = member.address.to_string();
         self.sys.gossip_port = u32::from(member.gossip_port);
         self.persistent = true;
+        self.labels = member.labels.clone();
     }
 
     fn update_from_health(&mut self, health: Health) {
@@ -684,7 +777,7 @@ impl<'a> Serialize for CensusMemberProxy<'a> {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
         where S: Serializer
     {
-        let mut strukt = serializer.serialize_struct("census_member", 24)?;
+        let mut strukt = serializer.serialize_struct("census_member", 28)?;
         strukt.serialize_field("member_id", &self.0.member_id)?;
         strukt.serialize_field("pkg", &self.0.pkg)?;
 
@@ -694,12 +787,17 @@ impl<'a> Serialize for CensusMemberProxy<'a> {
             strukt.serialize_field("package", &None::<String>)?;
         }
 
+        strukt.serialize_field("rolled_back", &self.0.rolled_back)?;
+        strukt.serialize_field("health_check", &self.0.health_check)?;
+        strukt.serialize_field("health_check_since", &self.0.health_check_since)?;
+
         strukt.serialize_field("application", &self.0.application)?;
         strukt.serialize_field("environment", &self.0.environment)?;
         strukt.serialize_field("service", &self.0.service)?;
         strukt.serialize_field("group", &self.0.group)?;
         strukt.serialize_field("org", &self.0.org)?;
         strukt.serialize_field("persistent", &self.0.persistent)?;
+        strukt.serialize_field("labels", &self.0.labels)?;
         strukt.serialize_field("leader", &self.0.leader)?;
         strukt.serialize_field("follower", &self.0.follower)?;
         strukt.serialize_field("update_leader", &self.0.update_leader)?;
@@ -732,6 +830,28 @@ fn service_group_from_str(sg: &str) -> Result<ServiceGroup, habitat_core::Error>
                               })
 }
 
+/// The key a service config or file was signed with, if `signature` verifies `content` against
+/// the public half of that key in `cache_key_path`.
+pub fn verified_signer(cache_key_path: &Path,
+                       signer: Option<&String>,
+                       signature: Option<&Vec<u8>>,
+                       content: &[u8])
+                       -> Option<String> {
+    let (signer, signature) = match (signer, signature) {
+        (Some(signer), Some(signature)) => (signer, signature),
+        _ => return None,
+    };
+    match SigKeyPair::get_pair_for(signer, cache_key_path).and_then(|pair| {
+                                                              pair.verify(signature, content)
+                                                          }) {
+        Ok(()) => Some(signer.to_string()),
+        Err(err) => {
+            warn!("Ignoring signature by {}: {}", signer, err);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -771,6 +891,57 @@ mod tests {
         assert_eq!(members.next().unwrap().member_id, "member-b");
     }
 
+    #[test]
+    fn garbage_collected_rumors_leave_the_census() {
+        let pg_id = PackageIdent::new("starkandwayne",
+                                      "shield",
+                                      Some("0.10.4"),
+                                      Some("20170419115548"));
+        let sg_one = ServiceGroup::new(None, "shield", "one", None).unwrap();
+        let sg_two = ServiceGroup::new(None, "shield", "two", None).unwrap();
+        let service_store: RumorStore<ServiceRumor> = RumorStore::default();
+        service_store.insert(ServiceRumor::new("member-a".to_string(),
+                                               &pg_id,
+                                               sg_one.clone(),
+                                               SysInfo::default(),
+                                               None));
+        service_store.insert(ServiceRumor::new("member-a".to_string(),
+                                               &pg_id,
+                                               sg_two.clone(),
+                                               SysInfo::default(),
+                                               None));
+        service_store.insert(ServiceRumor::new("member-b".to_string(),
+                                               &pg_id,
+                                               sg_two.clone(),
+                                               SysInfo::default(),
+                                               None));
+        let election_store: RumorStore<ElectionRumor> = RumorStore::default();
+        let election_update_store: RumorStore<ElectionUpdateRumor> = RumorStore::default();
+        let member_list = MemberList::new();
+        let service_config_store: RumorStore<ServiceConfigRumor> = RumorStore::default();
+        let service_file_store: RumorStore<ServiceFileRumor> = RumorStore::default();
+        let mut ring = CensusRing::new("member-b".to_string());
+        let update = |ring: &mut CensusRing| {
+            ring.update_from_rumors(&cache_key_path(Some(&*FS_ROOT)),
+                                    &service_store,
+                                    &election_store,
+                                    &election_update_store,
+                                    &member_list,
+                                    &service_config_store,
+                                    &service_file_store)
+        };
+        update(&mut ring);
+        assert!(ring.census_group_for(&sg_one).is_some());
+
+        service_store.remove_where(|s| s.member_id == "member-a");
+        update(&mut ring);
+
+        assert!(ring.census_group_for(&sg_one).is_none());
+        let mut members = ring.census_group_for(&sg_two).unwrap().members();
+        assert_eq!(members.next().unwrap().member_id, "member-b");
+        assert!(members.next().is_none());
+    }
+
     #[test]
     fn census_ring_proxy_conforms_to_the_schema() {
         let (ring, ..) = test_census_ring();
@@ -853,6 +1024,9 @@ mod tests {
     fn test_census_member(id: &str, health: Health) -> CensusMember {
         CensusMember { member_id: id.into(),
                        pkg: None,
+                       rolled_back: None,
+                       health_check: None,
+                       health_check_since: None,
                        application: None,
                        environment: None,
                        service: "test_service".to_string(),
@@ -870,6 +1044,7 @@ mod tests {
                        update_election_is_no_quorum: false,
                        update_election_is_finished: false,
                        sys: SysInfo::default(),
+                       labels: BTreeMap::new(),
                        alive: health == Health::Alive,
                        suspect: health == Health::Suspect,
                        confirmed: health == Health::Confirmed,
@@ -965,6 +1140,75 @@ mod tests {
                              Some("2_left_of_me"));
     }
 
+    fn assert_eq_batch_member_ids(cms: Option<Vec<&CensusMember>>, ids: Option<Vec<&str>>) {
+        assert_eq!(cms.map(|cms| cms.iter().map(|cm| cm.member_id.as_str()).collect()),
+                   ids);
+    }
+
+    fn batch_members() -> Vec<CensusMember> {
+        vec![test_census_member("a", Health::Alive),
+             test_census_member("b", Health::Alive),
+             test_census_member("c", Health::Confirmed),
+             test_census_member("d", Health::Alive),
+             test_census_member("e", Health::Alive),
+             test_census_member("f", Health::Alive),]
+    }
+
+    #[test]
+    fn update_batch_predecessors_of_leader() {
+        let members = batch_members();
+        assert_eq_batch_member_ids(CensusGroup::update_batch_predecessors_impl(members.iter(),
+                                                                               &members[1],
+                                                                               &members[1],
+                                                                               2),
+                                   Some(vec![]));
+    }
+
+    #[test]
+    fn update_batch_predecessors_in_first_batch() {
+        let members = batch_members();
+        assert_eq_batch_member_ids(CensusGroup::update_batch_predecessors_impl(members.iter(),
+                                                                               &members[3],
+                                                                               &members[1],
+                                                                               2),
+                                   Some(vec!["b"]));
+        assert_eq_batch_member_ids(CensusGroup::update_batch_predecessors_impl(members.iter(),
+                                                                               &members[4],
+                                                                               &members[1],
+                                                                               2),
+                                   Some(vec!["b"]));
+    }
+
+    #[test]
+    fn update_batch_predecessors_with_wraparound() {
+        let members = batch_members();
+        assert_eq_batch_member_ids(CensusGroup::update_batch_predecessors_impl(members.iter(),
+                                                                               &members[0],
+                                                                               &members[1],
+                                                                               2),
+                                   Some(vec!["b", "d", "e"]));
+    }
+
+    #[test]
+    fn update_batch_predecessors_one_at_a_time() {
+        let members = batch_members();
+        assert_eq_batch_member_ids(CensusGroup::update_batch_predecessors_impl(members.iter(),
+                                                                               &members[4],
+                                                                               &members[1],
+                                                                               1),
+                                   Some(vec!["b", "d"]));
+    }
+
+    #[test]
+    fn update_batch_predecessors_without_alive_leader() {
+        let members = batch_members();
+        assert_eq_batch_member_ids(CensusGroup::update_batch_predecessors_impl(members.iter(),
+                                                                               &members[0],
+                                                                               &members[2],
+                                                                               2),
+                                   None);
+    }
+
     #[test]
     fn previous_peer_with_confirmed_and_wraparound() {
         let me = test_census_member("me", Health::Alive);
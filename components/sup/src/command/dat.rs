@@ -0,0 +1,86 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inspect and edit the DAT file a Supervisor persists its rumors to, while it isn't running.
+
+use crate::error::{Error,
+                   Result};
+use habitat_butterfly::rumor::dat_file::{DatFile,
+                                         DatFileEntry};
+use habitat_common::outputln;
+use serde_json;
+use std::path::Path;
+
+/// Our output key
+static LOGKEY: &'static str = "DT";
+
+/// Print the rumors in a DAT file as JSON. When rumor types or service groups are given, only
+/// the rumors of those types and about those service groups are printed.
+pub fn show<P>(path: P, rumor_types: &[&str], service_groups: &[&str]) -> Result<()>
+    where P: AsRef<Path>
+{
+    let mut contents = DatFile::from_path(path).read_contents()?;
+    contents.entries
+            .retain(|entry| selected(entry, rumor_types, service_groups));
+    let json = serde_json::to_string_pretty(&contents).map_err(|e| {
+                                                          sup_error!(Error::DatFileSerialization(e))
+                                                      })?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Write a copy of a DAT file to `output`, without the rumors about the given members and
+/// without the rumors of the given types about the given service groups.
+pub fn prune<P, Q>(path: P,
+                   output: Q,
+                   member_ids: &[&str],
+                   rumor_types: &[&str],
+                   service_groups: &[&str])
+                   -> Result<()>
+    where P: AsRef<Path>,
+          Q: AsRef<Path>
+{
+    let contents = DatFile::from_path(path).read_contents()?;
+    let select_rumors = !rumor_types.is_empty() || !service_groups.is_empty();
+    let (removed, kept): (Vec<DatFileEntry>, Vec<DatFileEntry>) =
+        contents.entries.into_iter().partition(|entry| {
+                                        let about_member =
+                                            entry.rumor
+                                                 .kind
+                                                 .member_id()
+                                                 .map_or(false, |id| member_ids.contains(&id));
+                                        about_member
+                                        || (select_rumors
+                                            && selected(entry, rumor_types, service_groups))
+                                    });
+    DatFile::from_path(output.as_ref()).write_entries(&kept)?;
+    outputln!("Removed {} of {} rumors; wrote {}",
+              removed.len(),
+              removed.len() + kept.len(),
+              output.as_ref().display());
+    Ok(())
+}
+
+/// Whether a rumor is of one of the given types and about one of the given service groups; an
+/// empty list of either matches everything.
+fn selected(entry: &DatFileEntry, rumor_types: &[&str], service_groups: &[&str]) -> bool {
+    let rumor_type = entry.rumor.r#type.to_string();
+    let of_type = rumor_types.is_empty() || rumor_types.contains(&rumor_type.as_str());
+    let about_service_group = service_groups.is_empty()
+                              || entry.rumor
+                                      .kind
+                                      .service_group()
+                                      .map_or(false, |sg| service_groups.contains(&sg));
+    of_type && about_service_group
+}
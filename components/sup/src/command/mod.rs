@@ -14,4 +14,5 @@
 
 //! The CLI commands.
 
+pub mod dat;
 pub mod shell;
@@ -104,6 +104,14 @@ impl CtlRequest {
         self.send_msg(msg, false);
     }
 
+    /// Like `reply_partial`, but returns `false` if the reply couldn't be delivered because the
+    /// client has gone away.
+    pub fn try_reply_partial<T>(&mut self, msg: T) -> bool
+        where T: Into<habitat_sup_protocol::codec::SrvMessage> + fmt::Debug
+    {
+        self.send_msg(msg, false)
+    }
+
     /// Reply to the transaction with the given message and indicate to the receiver that this is
     /// the final message for the transaction.
     pub fn reply_complete<T>(&mut self, msg: T)
@@ -115,17 +123,17 @@ impl CtlRequest {
     /// Returns true if the request is transactional and false if not.
     pub fn transactional(&self) -> bool { self.transaction.is_some() && self.tx.is_some() }
 
-    fn send_msg<T>(&mut self, msg: T, complete: bool)
+    fn send_msg<T>(&mut self, msg: T, complete: bool) -> bool
         where T: Into<habitat_sup_protocol::codec::SrvMessage> + fmt::Debug
     {
         if !self.transactional() {
             warn!("Attempted to reply to a non-transactional message with {:?}",
                   msg);
-            return;
+            return false;
         }
         let mut wire: habitat_sup_protocol::codec::SrvMessage = msg.into();
         wire.reply_for(self.transaction.unwrap(), complete);
-        self.tx.as_ref().unwrap().start_send(wire).ok(); // ignore Err return
+        self.tx.as_ref().unwrap().start_send(wire).is_ok()
     }
 }
 
@@ -358,6 +358,15 @@ impl SrvHandler {
                                        commands::service_status(state, req, m.clone())
                                    }))
             }
+            "SvcLogs" => {
+                let m = msg.parse::<protocol::ctl::SvcLogs>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, _action_sender| {
+                                       commands::service_logs(state, req, m.clone())
+                                   }))
+            }
             "SupDepart" => {
                 let m = msg.parse::<protocol::ctl::SupDepart>()
                            .map_err(HandlerError::from)?;
@@ -367,6 +376,15 @@ impl SrvHandler {
                                        commands::supervisor_depart(state, req, m.clone())
                                    }))
             }
+            "SupRingKeyRotate" => {
+                let m = msg.parse::<protocol::ctl::SupRingKeyRotate>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, _action_sender| {
+                                       commands::supervisor_ring_key_rotate(state, req, m.clone())
+                                   }))
+            }
             _ => {
                 warn!("Unhandled message, {}", msg.message_id());
                 Err(HandlerError::from(io::Error::from(io::ErrorKind::InvalidData)))
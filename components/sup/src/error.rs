@@ -111,18 +111,22 @@ pub enum Error {
     BadDataPath(PathBuf, io::Error),
     BadDesiredState(String),
     BadElectionStatus(String),
+    BadHealthCheck(String),
     BadSpecsPath(PathBuf, io::Error),
     BadStartStyle(String),
     BindTimeout(String),
+    BindCycle(Vec<package::PackageIdent>),
     LockPoisoned,
     TestBootFail,
     ButterflyError(habitat_butterfly::error::Error),
     CtlSecretIo(PathBuf, io::Error),
+    DatFileSerialization(serde_json::Error),
     APIClient(habitat_api_client::Error),
     EnvJoinPathsError(env::JoinPathsError),
     ExecCommandNotFound(String),
     EventError(nitox::NatsError),
     EventStreamError(nitox::streaming::error::NatsStreamingError),
+    EventSinkIO(PathBuf, io::Error),
     FileNotFound(String),
     FileWatcherFileIsRoot,
     GroupNotFound(String),
@@ -130,6 +134,7 @@ pub enum Error {
     HabitatCore(habitat_core::Error),
     InvalidBinds(Vec<String>),
     InvalidCertFile(PathBuf),
+    InvalidEventSink(String),
     InvalidKeyFile(PathBuf),
     InvalidKeyParameter(String),
     InvalidPidFile,
@@ -210,6 +215,9 @@ impl fmt::Display for SupError {
                 format!("Unknown service desired state style '{}'", state)
             }
             Error::BadElectionStatus(ref status) => format!("Unknown election status '{}'", status),
+            Error::BadHealthCheck(ref status) => {
+                format!("Unknown health check status '{}'", status)
+            }
             Error::BadSpecsPath(ref path, ref err) => {
                 format!("Unable to create the specs directory '{}' ({})",
                         path.display(),
@@ -217,6 +225,14 @@ impl fmt::Display for SupError {
             }
             Error::BadStartStyle(ref style) => format!("Unknown service start style '{}'", style),
             Error::BindTimeout(ref err) => format!("Timeout waiting to bind to {}", err),
+            Error::BindCycle(ref idents) => {
+                let mut cycle: Vec<String> = idents.iter().map(ToString::to_string).collect();
+                if let Some(first) = idents.first() {
+                    cycle.push(first.to_string());
+                }
+                format!("Services bind to each other in a cycle, {}",
+                        cycle.join(" -> "))
+            }
             Error::LockPoisoned => "A mutex or read/write lock has failed.".to_string(),
             Error::TestBootFail => "Simulated boot failure".to_string(),
             Error::ButterflyError(ref err) => format!("Butterfly error: {}", err),
@@ -225,11 +241,17 @@ impl fmt::Display for SupError {
                         path.display(),
                         err)
             }
+            Error::DatFileSerialization(ref e) => format!("Can't serialize DAT file: {}", e),
             Error::ExecCommandNotFound(ref c) => {
                 format!("`{}' was not found on the filesystem or in PATH", c)
             }
             Error::EventError(ref err) => err.to_string(),
             Error::EventStreamError(ref err) => err.to_string(),
+            Error::EventSinkIO(ref path, ref err) => {
+                format!("Unable to open event stream sink at {}, {}",
+                        path.display(),
+                        err)
+            }
             Error::Permissions(ref err) => err.to_string(),
             Error::HabitatCommon(ref err) => err.to_string(),
             Error::HabitatCore(ref err) => err.to_string(),
@@ -239,6 +261,7 @@ impl fmt::Display for SupError {
             Error::GroupNotFound(ref e) => format!("No GID for group '{}' could be found", e),
             Error::InvalidBinds(ref e) => format!("Invalid bind(s), {}", e.join(", ")),
             Error::InvalidCertFile(ref path) => format!("Invalid cert file: {}", path.display()),
+            Error::InvalidEventSink(ref s) => format!("Invalid event stream sink: {}", s),
             Error::InvalidKeyFile(ref path) => format!("Invalid key file: {}", path.display()),
             Error::InvalidKeyParameter(ref e) => {
                 format!("Invalid parameter for key generation: {:?}", e)
@@ -350,18 +373,22 @@ impl error::Error for SupError {
             Error::BadDataFile(..) => "Unable to read or write to a data file",
             Error::BadDataPath(..) => "Unable to read or write to data directory",
             Error::BadElectionStatus(_) => "Unknown election status",
+            Error::BadHealthCheck(_) => "Unknown health check status",
             Error::BadDesiredState(_) => "Unknown desired state in service spec",
             Error::BadSpecsPath(..) => "Unable to create the specs directory",
             Error::BadStartStyle(_) => "Unknown start style in service spec",
             Error::BindTimeout(_) => "Timeout waiting to bind to an address",
+            Error::BindCycle(_) => "Services bind to each other in a cycle",
             Error::LockPoisoned => "A mutex or read/write lock has failed",
             Error::TestBootFail => "Simulated boot failure",
             Error::ButterflyError(ref err) => err.description(),
             Error::CtlSecretIo(..) => "IoError while reading ctl secret",
+            Error::DatFileSerialization(_) => "Can't serialize DAT file",
             Error::ExecCommandNotFound(_) => "Exec command was not found on filesystem or in PATH",
             Error::EventError(_) => "event error", // underlying NATS error doesn't implement Error
             Error::EventStreamError(_) => "event streaming error", // underlying NATS error
             // doesn't implement Error
+            Error::EventSinkIO(..) => "Unable to open event stream sink",
             Error::GroupNotFound(_) => "No matching GID for group found",
             Error::HabitatCommon(ref err) => err.description(),
             Error::HabitatCore(ref err) => err.description(),
@@ -372,6 +399,7 @@ impl error::Error for SupError {
                 "Service binds detected that are neither required nor optional package binds"
             }
             Error::InvalidCertFile(_) => "Invalid cert file",
+            Error::InvalidEventSink(_) => "Invalid event stream sink",
             Error::InvalidKeyFile(_) => "Invalid key file",
             Error::InvalidKeyParameter(_) => "Key parameter error",
             Error::InvalidPidFile => "Invalid child process PID file",
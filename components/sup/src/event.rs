@@ -15,38 +15,56 @@
 //! Main interface for a stream of events the Supervisor can send out
 //! in the course of its operations.
 //!
-//! Currently, the Supervisor is able to send events to a [NATS
-//! Streaming][1] server. The `init_stream` function must be called
-//! before sending events to initialize the publishing thread in the
-//! background. Thereafter, you can pass "event" structs to the
-//! `event` function, which will publish the event to the stream.
+//! Every event is wrapped in a versioned `EventEnvelope`, which
+//! carries the `EventCore` information, a timestamp, and a sequence
+//! number alongside the event payload. Envelopes are handed to an
+//! `EventSink`, which can publish them to a [NATS Streaming][1]
+//! server, append them to a local JSON lines file, write them to a
+//! Unix socket, or print them to standard output.
 //!
-//! All events are published under the "habitat" subject.
+//! The `init_stream` function must be called before sending events
+//! to initialize the publishing thread in the background. Thereafter,
+//! you can pass "event" structs to the `publish` function, which will
+//! send the event to the configured sink.
 //!
 //! [1]:https://github.com/nats-io/nats-streaming-server
 
+mod envelope;
+mod nats;
+mod sink;
 mod types;
 
-use crate::error::Result;
+#[cfg(unix)]
+use self::sink::UnixSocketSink;
+pub use self::{envelope::{EventEnvelope,
+                          ENVELOPE_VERSION},
+               nats::EventConnectionInfo,
+               sink::EventSink};
+use self::{nats::NatsSink,
+           sink::{FileSink,
+                  StdoutSink}};
+use crate::error::{Error,
+                   Result,
+                   SupError};
 use futures::{sync::{mpsc as futures_mpsc,
                      mpsc::UnboundedSender},
-              Future,
               Stream};
-use nitox::{commands::ConnectCommand,
-            streaming::{client::NatsStreamingClient,
-                        error::NatsStreamingError},
-            NatsClient,
-            NatsClientOptions};
+use serde::Serialize;
 use state::Container;
-use std::{fmt::Debug,
-          sync::{mpsc as std_mpsc,
+use std::{path::PathBuf,
+          result,
+          str::FromStr,
+          sync::{atomic::{AtomicUsize,
+                          Ordering},
                  Once},
           thread};
-use tokio::{executor,
-            runtime::current_thread::Runtime as ThreadRuntime};
 pub use types::*;
 
+static LOGKEY: &'static str = "EV";
+
 static INIT: Once = Once::new();
+/// The sequence number of the most recently published event.
+static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
 lazy_static! {
     // TODO (CM): When const fn support lands in stable, we can ditch
     // this lazy_static call.
@@ -57,16 +75,21 @@ lazy_static! {
     static ref EVENT_CORE: Container = Container::new();
 }
 
-/// Starts a new thread for sending events to a NATS Streaming
-/// server. Stashes the handle to the stream, as well as the core
-/// event information that will be a part of all events, in a global
-/// static reference for access later.
-pub fn init_stream(conn_info: EventConnectionInfo, event_core: EventCore) {
+/// Starts a new thread for sending events to the configured
+/// sink. Stashes the handle to the stream, as well as the core event
+/// information that will be a part of all events, in a global static
+/// reference for access later.
+pub fn init_stream(config: EventStreamConfig, event_core: EventCore) -> Result<()> {
+    let mut result = Ok(());
     INIT.call_once(|| {
-            let event_stream = init_nats_stream(conn_info).expect("Could not start NATS thread");
-            EVENT_STREAM.set(event_stream);
-            EVENT_CORE.set(event_core);
+            result = config.into_sink()
+                           .and_then(EventStream::start)
+                           .map(|event_stream| {
+                               EVENT_STREAM.set(event_stream);
+                               EVENT_CORE.set(event_core);
+                           });
         });
+    result
 }
 
 /// Publish an event. This is the main interface that client code will
@@ -78,32 +101,31 @@ pub fn init_stream(conn_info: EventConnectionInfo, event_core: EventCore) {
 // subsystem if users don't wish to send events out; just don't call
 // `init_stream` if they don't want it.
 pub fn publish(event: &impl Event) {
-    // TODO: incorporate the current timestamp into the rendered event
-    // (which will require tweaks to the rendering logic, but we know
-    // that'll need to be updated anyway).
-
-    // We render the event to bytes here, rather than over in the
-    // publication thread, because it allows our Event types to deal
-    // with references, which means less allocations and unnecessary
+    // We build the envelope here, rather than over in the publication
+    // thread, because it allows our Event types to deal with
+    // references, which means less allocations and unnecessary
     // copying (otherwise, we'd have to have copies of everything to
     // send it over to the other thread, or much more complicated
-    // thread-safe types!). It also makes the publication thread logic
-    // a bit simpler; it just takes bytes and sends them out.
+    // thread-safe types!). It also means the timestamp and sequence
+    // number reflect the moment the event actually happened.
     if let Some(e) = EVENT_STREAM.try_get::<EventStream>() {
-        e.send(event.render(EVENT_CORE.get::<EventCore>()));
+        let sequence = SEQUENCE.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+        match EventEnvelope::new(event, EVENT_CORE.get::<EventCore>(), sequence) {
+            Ok(envelope) => e.send(envelope),
+            Err(err) => error!("Failed to render {} event: {}", event.event_type(), err),
+        }
     }
 }
 
 /// A collection of data that will be present in all events. Rather
 /// than baking this into the structure of each event, we represent it
-/// once and merge the information into the final rendered form of the
-/// event.
+/// once and merge the information into the envelope of the event.
 ///
 /// This prevents us from having to thread information throughout the
 /// system, just to get it to the places where the events are
 /// generated (e.g., not all code has direct access to the
 /// Supervisor's ID).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct EventCore {
     /// The unique identifier of the Supervisor sending the event.
     pub supervisor_id: String,
@@ -111,11 +133,28 @@ pub struct EventCore {
 
 /// A lightweight handle for the event stream. All events get to the
 /// event stream through this.
-pub(self) struct EventStream(UnboundedSender<Vec<u8>>);
+pub(self) struct EventStream(UnboundedSender<EventEnvelope>);
 
 impl EventStream {
+    /// Starts the background thread that hands each queued event to
+    /// `sink`.
+    fn start(mut sink: Box<dyn EventSink>) -> Result<Self> {
+        // TODO (CM): Investigate back-pressure scenarios
+        let (event_tx, event_rx) = futures_mpsc::unbounded();
+        thread::Builder::new().name("events".to_string())
+                              .spawn(move || {
+                                  // The receiver only errors once every sender is gone.
+                                  for event in event_rx.wait().filter_map(result::Result::ok) {
+                                      if let Err(e) = sink.send(&event) {
+                                          error!("Failed to send event {}: {}", event.sequence, e);
+                                      }
+                                  }
+                              })?;
+        Ok(EventStream(event_tx))
+    }
+
     /// Queues an event to be sent out.
-    fn send(&self, event: Vec<u8>) {
+    fn send(&self, event: EventEnvelope) {
         trace!("About to queue an event: {:?}", event);
         if let Err(e) = self.0.unbounded_send(event) {
             error!("Failed to queue event: {:?}", e);
@@ -123,106 +162,147 @@ impl EventStream {
     }
 }
 
-/// Defines the logic for transforming concrete event into a
-/// byte representation to publish to the event stream.
-// TODO: The ultimate format we need is not well defined yet, so we're
-// using the absolute simplest thing that could possibly "work". Maybe
-// it'll be JSON, maybe it'll be protobuf, maybe it'll be something
-// else. Whatever it ultimately becomes, this is where the
-// transformation logic will live.
-pub trait Event: Debug {
-    fn render(&self, core: &EventCore) -> Vec<u8> {
-        format!("{:?} - {:?}", core, self).into_bytes()
-    }
+/// Defines how a concrete event becomes the payload of an
+/// `EventEnvelope`.
+pub trait Event: Serialize {
+    /// The name the event is published under, e.g. "service-started".
+    fn event_type(&self) -> &'static str;
 }
 
 ////////////////////////////////////////////////////////////////////////
 
-/// All messages are published under this subject.
-const HABITAT_SUBJECT: &str = "habitat";
-
-/// All the information needed to establish a connection to a NATS
-/// Streaming server.
-// TODO: This will change as we firm up what the interaction between
-// Habitat and A2 looks like.
-pub struct EventConnectionInfo {
-    pub name:        String,
-    pub verbose:     bool,
-    pub cluster_uri: String,
-    pub cluster_id:  String,
+/// How the event stream is set up: where events go, and how they are
+/// encoded on the way there.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventStreamConfig {
+    pub sink:   EventSinkConfig,
+    /// Only used by the NATS and Unix socket sinks; the file and
+    /// standard output sinks always write JSON lines.
+    pub format: EventFormat,
+}
+
+impl EventStreamConfig {
+    fn into_sink(self) -> Result<Box<dyn EventSink>> {
+        let sink: Box<dyn EventSink> = match self.sink {
+            EventSinkConfig::Nats(conn_info) => Box::new(NatsSink::new(conn_info, self.format)?),
+            EventSinkConfig::File(path) => Box::new(FileSink::new(path)?),
+            #[cfg(unix)]
+            EventSinkConfig::UnixSocket(path) => Box::new(UnixSocketSink::new(path, self.format)),
+            EventSinkConfig::Stdout => Box::new(StdoutSink),
+        };
+        Ok(sink)
+    }
+}
+
+/// The destination of the event stream.
+///
+/// Parsed from the command line as one of `nats`, `nats:<ADDRESS>`,
+/// `file:<PATH>`, `unix:<PATH>` or `stdout`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventSinkConfig {
+    /// Publish to a NATS Streaming server.
+    Nats(EventConnectionInfo),
+    /// Append JSON lines to a local file.
+    File(PathBuf),
+    /// Write to a Unix socket some other process is listening on.
+    #[cfg(unix)]
+    UnixSocket(PathBuf),
+    /// Print JSON lines to standard output.
+    Stdout,
+}
+
+impl Default for EventSinkConfig {
+    fn default() -> Self { EventSinkConfig::Nats(EventConnectionInfo::default()) }
+}
+
+impl FromStr for EventSinkConfig {
+    type Err = SupError;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let (kind, arg) = match value.find(':') {
+            Some(idx) => (&value[..idx], Some(&value[idx + 1..])),
+            None => (value, None),
+        };
+        match (kind, arg) {
+            ("nats", None) => Ok(EventSinkConfig::default()),
+            ("nats", Some(uri)) if !uri.is_empty() => {
+                Ok(EventSinkConfig::Nats(EventConnectionInfo { cluster_uri: uri.to_string(),
+                                                               ..Default::default() }))
+            }
+            ("file", Some(path)) if !path.is_empty() => Ok(EventSinkConfig::File(path.into())),
+            #[cfg(unix)]
+            ("unix", Some(path)) if !path.is_empty() => {
+                Ok(EventSinkConfig::UnixSocket(path.into()))
+            }
+            ("stdout", None) => Ok(EventSinkConfig::Stdout),
+            _ => Err(sup_error!(Error::InvalidEventSink(value.to_string()))),
+        }
+    }
+}
+
+/// The encoding of each event sent to the sink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventFormat {
+    Json,
+    Protobuf,
+}
+
+impl Default for EventFormat {
+    fn default() -> Self { EventFormat::Json }
 }
 
-/// Defines default connection information for a NATS Streaming server
-/// running on localhost.
-// TODO: As we become clear on the interaction between Habitat and A2,
-// this implementation *may* disappear. It's useful for testing and
-// prototyping, though.
-impl Default for EventConnectionInfo {
-    fn default() -> Self {
-        EventConnectionInfo { name:        String::from("habitat"),
-                              verbose:     true,
-                              cluster_uri: String::from("127.0.0.1:4223"),
-                              cluster_id:  String::from("test-cluster"), }
+impl FromStr for EventFormat {
+    type Err = SupError;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(EventFormat::Json),
+            "protobuf" => Ok(EventFormat::Protobuf),
+            _ => Err(sup_error!(Error::InvalidEventSink(format!("unknown format '{}'", value)))),
+        }
     }
 }
 
-fn init_nats_stream(conn_info: EventConnectionInfo) -> Result<EventStream> {
-    // TODO (CM): Investigate back-pressure scenarios
-    let (event_tx, event_rx) = futures_mpsc::unbounded();
-    let (sync_tx, sync_rx) = std_mpsc::sync_channel(0); // rendezvous channel
-
-    // TODO (CM): We could theoretically create this future and spawn
-    // it in the Supervisor's Tokio runtime, but there's currently a
-    // bug: https://github.com/YellowInnovation/nitox/issues/24
-
-    thread::Builder::new().name("events".to_string())
-                          .spawn(move || {
-                              let EventConnectionInfo { name,
-                                                        verbose,
-                                                        cluster_uri,
-                                                        cluster_id, } = conn_info;
-
-                              let cc = ConnectCommand::builder()
-                // .user(Some("nats".to_string()))
-                // .pass(Some("S3Cr3TP@5w0rD".to_string()))
-                .name(Some(name))
-                .verbose(verbose)
-                .build()
-                .unwrap();
-                              let opts =
-                                  NatsClientOptions::builder().connect_command(cc)
-                                                              .cluster_uri(cluster_uri.as_str())
-                                                              .build()
-                                                              .unwrap();
-
-                              let publisher = NatsClient::from_options(opts)
-                .map_err(Into::<NatsStreamingError>::into)
-                .and_then(|client| {
-                    NatsStreamingClient::from(client)
-                        .cluster_id(cluster_id)
-                        .connect()
-                })
-                .map_err(|streaming_error| error!("{}", streaming_error))
-                .and_then(move |client| {
-                    sync_tx.send(()).expect("Couldn't synchronize!");
-                    event_rx.for_each(move |event: Vec<u8>| {
-                        let publish_event = client
-                            .publish(HABITAT_SUBJECT.into(), event.into())
-                            .map_err(|e| {
-                                error!("Error publishing event: {:?}", e);
-                            });
-                        executor::spawn(publish_event);
-                        Ok(())
-                    })
-                });
-
-                              ThreadRuntime::new().expect("Couldn't create event stream runtime!")
-                                                  .spawn(publisher)
-                                                  .run()
-                                                  .expect("something seriously wrong has occurred");
-                          })
-                          .expect("Couldn't start events thread!");
-
-    sync_rx.recv()?; // TODO (CM): nicer error message
-    Ok(EventStream(event_tx))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_sink_config_from_str() {
+        assert_eq!(EventSinkConfig::from_str("nats").unwrap(),
+                   EventSinkConfig::default());
+        assert_eq!(EventSinkConfig::from_str("stdout").unwrap(),
+                   EventSinkConfig::Stdout);
+        assert_eq!(EventSinkConfig::from_str("file:/hab/sup/default/events.jsonl").unwrap(),
+                   EventSinkConfig::File(PathBuf::from("/hab/sup/default/events.jsonl")));
+
+        match EventSinkConfig::from_str("nats:10.0.0.1:4222").unwrap() {
+            EventSinkConfig::Nats(conn_info) => assert_eq!(conn_info.cluster_uri, "10.0.0.1:4222"),
+            other => panic!("Expected a NATS sink, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn event_sink_config_from_str_unix_socket() {
+        assert_eq!(EventSinkConfig::from_str("unix:/tmp/events.sock").unwrap(),
+                   EventSinkConfig::UnixSocket(PathBuf::from("/tmp/events.sock")));
+    }
+
+    #[test]
+    fn event_sink_config_from_str_invalid() {
+        for value in &["", "kafka", "file", "file:", "stdout:/dev/null", "nats:"] {
+            assert!(EventSinkConfig::from_str(value).is_err(),
+                    "'{}' should not be a valid sink",
+                    value);
+        }
+    }
+
+    #[test]
+    fn event_format_from_str() {
+        assert_eq!(EventFormat::from_str("json").unwrap(), EventFormat::Json);
+        assert_eq!(EventFormat::from_str("protobuf").unwrap(),
+                   EventFormat::Protobuf);
+        assert!(EventFormat::from_str("xml").is_err());
+    }
 }
@@ -0,0 +1,171 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The versioned envelope every event is wrapped in before it is
+//! handed to a sink.
+
+use super::{Event,
+            EventCore,
+            EventFormat};
+use habitat_sup_protocol::event as proto;
+use prost::Message;
+use serde_json::{self,
+                 Value};
+
+/// Version of the envelope format. Consumers can use this to detect
+/// incompatible changes to the envelope itself; the payload of each
+/// event type evolves independently.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventEnvelope {
+    pub version:    u32,
+    pub sequence:   u64,
+    /// When the event was generated, as an RFC 3339 timestamp in UTC.
+    pub timestamp:  String,
+    #[serde(flatten)]
+    pub core:       EventCore,
+    pub event_type: String,
+    pub payload:    Value,
+}
+
+impl EventEnvelope {
+    pub fn new(event: &impl Event, core: &EventCore, sequence: u64) -> serde_json::Result<Self> {
+        Ok(EventEnvelope { version: ENVELOPE_VERSION,
+                           sequence,
+                           timestamp: time::now_utc().rfc3339().to_string(),
+                           core: core.clone(),
+                           event_type: event.event_type().to_string(),
+                           payload: serde_json::to_value(event)? })
+    }
+
+    /// Encodes the envelope as a single, self-contained message.
+    pub fn render(&self, format: EventFormat) -> Vec<u8> {
+        match format {
+            EventFormat::Json => self.to_json(),
+            EventFormat::Protobuf => {
+                let msg = self.to_proto();
+                let mut buf = Vec::with_capacity(msg.encoded_len());
+                msg.encode(&mut buf)
+                   .expect("Vec<u8> grows to fit the message");
+                buf
+            }
+        }
+    }
+
+    /// Encodes the envelope so that a series of them can be written to
+    /// a byte stream and split apart again: JSON envelopes are
+    /// terminated by a newline, protobuf envelopes are prefixed with
+    /// their length.
+    pub fn render_delimited(&self, format: EventFormat) -> Vec<u8> {
+        match format {
+            EventFormat::Json => {
+                let mut buf = self.to_json();
+                buf.push(b'\n');
+                buf
+            }
+            EventFormat::Protobuf => {
+                let msg = self.to_proto();
+                let mut buf = Vec::with_capacity(msg.encoded_len() + 10);
+                msg.encode_length_delimited(&mut buf)
+                   .expect("Vec<u8> grows to fit the message");
+                buf
+            }
+        }
+    }
+
+    fn to_json(&self) -> Vec<u8> {
+        // Every field is either a plain value or already a `Value`, so
+        // there is nothing here that can fail to serialize.
+        serde_json::to_vec(self).expect("EventEnvelope is always serializable")
+    }
+
+    fn to_proto(&self) -> proto::EventEnvelope {
+        let payload = serde_json::to_vec(&self.payload).expect("Value is always serializable");
+        proto::EventEnvelope { version:       Some(self.version),
+                               sequence:      Some(self.sequence),
+                               timestamp:     Some(self.timestamp.clone()),
+                               supervisor_id: Some(self.core.supervisor_id.clone()),
+                               event_type:    Some(self.event_type.clone()),
+                               payload:       Some(payload), }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct TestEvent<'a> {
+        name: &'a str,
+    }
+
+    impl<'a> Event for TestEvent<'a> {
+        fn event_type(&self) -> &'static str { "test-event" }
+    }
+
+    fn envelope() -> EventEnvelope {
+        let core = EventCore { supervisor_id: "abc123".to_string(), };
+        EventEnvelope::new(&TestEvent { name: "redis" }, &core, 7).unwrap()
+    }
+
+    #[test]
+    fn envelope_carries_core_fields_and_payload() {
+        let envelope = envelope();
+        assert_eq!(envelope.version, ENVELOPE_VERSION);
+        assert_eq!(envelope.sequence, 7);
+        assert_eq!(envelope.core.supervisor_id, "abc123");
+        assert_eq!(envelope.event_type, "test-event");
+        assert_eq!(envelope.payload, json!({ "name": "redis" }));
+        assert!(envelope.timestamp.ends_with('Z'));
+    }
+
+    #[test]
+    fn json_rendering_round_trips() {
+        let envelope = envelope();
+        let json: Value = serde_json::from_slice(&envelope.render(EventFormat::Json)).unwrap();
+        assert_eq!(json["supervisor_id"], "abc123");
+        assert_eq!(json["event_type"], "test-event");
+        assert_eq!(json["payload"]["name"], "redis");
+
+        let parsed: EventEnvelope =
+            serde_json::from_slice(&envelope.render(EventFormat::Json)).unwrap();
+        assert_eq!(parsed, envelope);
+    }
+
+    #[test]
+    fn json_delimited_rendering_is_a_single_line() {
+        let rendered = envelope().render_delimited(EventFormat::Json);
+        assert_eq!(rendered.last(), Some(&b'\n'));
+        assert_eq!(rendered.iter().filter(|b| **b == b'\n').count(), 1);
+    }
+
+    #[test]
+    fn protobuf_rendering_round_trips() {
+        let envelope = envelope();
+        let msg = proto::EventEnvelope::decode(envelope.render(EventFormat::Protobuf)).unwrap();
+        assert_eq!(msg.version, Some(ENVELOPE_VERSION));
+        assert_eq!(msg.sequence, Some(7));
+        assert_eq!(msg.supervisor_id.as_ref().map(String::as_str),
+                   Some("abc123"));
+        assert_eq!(msg.event_type.as_ref().map(String::as_str),
+                   Some("test-event"));
+        let payload: Value = serde_json::from_slice(&msg.payload.unwrap()).unwrap();
+        assert_eq!(payload, envelope.payload);
+
+        let delimited = envelope.render_delimited(EventFormat::Protobuf);
+        let msg = proto::EventEnvelope::decode_length_delimited(delimited).unwrap();
+        assert_eq!(msg.sequence, Some(7));
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Destinations the event stream can deliver events to.
+
+use super::{EventEnvelope,
+            EventFormat};
+use crate::error::{Error,
+                   Result};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::{fs::{File,
+               OpenOptions},
+          io::{self,
+               Write},
+          path::PathBuf};
+
+static LOGKEY: &'static str = "EV";
+
+/// Something that events can be delivered to.
+///
+/// Sinks are driven from the event stream's own thread, one event at
+/// a time and in sequence order, so implementations are free to block.
+pub trait EventSink: Send {
+    fn send(&mut self, event: &EventEnvelope) -> Result<()>;
+}
+
+/// Prints each event to standard output as a line of JSON.
+pub struct StdoutSink;
+
+impl EventSink for StdoutSink {
+    fn send(&mut self, event: &EventEnvelope) -> Result<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(&event.render_delimited(EventFormat::Json))?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// Appends each event to a local file as a line of JSON.
+pub struct FileSink {
+    file: File,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Result<Self> {
+        let file = OpenOptions::new().create(true)
+                                     .append(true)
+                                     .open(&path)
+                                     .map_err(|err| sup_error!(Error::EventSinkIO(path, err)))?;
+        Ok(FileSink { file })
+    }
+}
+
+impl EventSink for FileSink {
+    fn send(&mut self, event: &EventEnvelope) -> Result<()> {
+        self.file
+            .write_all(&event.render_delimited(EventFormat::Json))?;
+        Ok(())
+    }
+}
+
+/// Writes each event to a Unix socket that a consumer is listening
+/// on.
+///
+/// The connection is made lazily and re-established after any
+/// failure; events published while nothing is listening on the socket
+/// are dropped.
+#[cfg(unix)]
+pub struct UnixSocketSink {
+    path:   PathBuf,
+    format: EventFormat,
+    stream: Option<UnixStream>,
+}
+
+#[cfg(unix)]
+impl UnixSocketSink {
+    pub fn new(path: PathBuf, format: EventFormat) -> Self {
+        UnixSocketSink { path,
+                         format,
+                         stream: None }
+    }
+
+    fn connect(&mut self) -> Result<&mut UnixStream> {
+        if self.stream.is_none() {
+            let stream =
+                UnixStream::connect(&self.path).map_err(|err| {
+                                                   sup_error!(Error::EventSinkIO(self.path.clone(),
+                                                                                 err))
+                                               })?;
+            self.stream = Some(stream);
+        }
+        Ok(self.stream.as_mut().expect("stream was just connected"))
+    }
+}
+
+#[cfg(unix)]
+impl EventSink for UnixSocketSink {
+    fn send(&mut self, event: &EventEnvelope) -> Result<()> {
+        let bytes = event.render_delimited(self.format);
+        if let Err(err) = self.connect()?.write_all(&bytes) {
+            // Most likely the consumer went away; reconnect next time.
+            self.stream = None;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event,
+                       EventCore};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[derive(Serialize)]
+    struct TestEvent {
+        count: u32,
+    }
+
+    impl Event for TestEvent {
+        fn event_type(&self) -> &'static str { "test-event" }
+    }
+
+    fn envelope(sequence: u64) -> EventEnvelope {
+        let core = EventCore { supervisor_id: "abc123".to_string(), };
+        EventEnvelope::new(&TestEvent { count: sequence as u32, }, &core, sequence).unwrap()
+    }
+
+    #[test]
+    fn file_sink_appends_json_lines() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("events.jsonl");
+
+        let mut sink = FileSink::new(path.clone()).unwrap();
+        sink.send(&envelope(1)).unwrap();
+        sink.send(&envelope(2)).unwrap();
+        // Re-opening must not truncate what is already there
+        let mut sink = FileSink::new(path.clone()).unwrap();
+        sink.send(&envelope(3)).unwrap();
+
+        let events = fs::read_to_string(&path).unwrap()
+                                              .lines()
+                                              .map(|l| serde_json::from_str(l).unwrap())
+                                              .collect::<Vec<EventEnvelope>>();
+        assert_eq!(events.iter().map(|e| e.sequence).collect::<Vec<_>>(),
+                   vec![1, 2, 3]);
+        assert_eq!(events[2].payload, json!({ "count": 3 }));
+        assert_eq!(events[2].core.supervisor_id, "abc123");
+    }
+
+    #[test]
+    fn file_sink_fails_for_missing_directory() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("missing").join("events.jsonl");
+        assert!(FileSink::new(path).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unix_socket_sink_writes_delimited_events() {
+        use serde_json::Value;
+        use std::{io::{BufRead,
+                       BufReader},
+                  os::unix::net::UnixListener};
+
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("events.sock");
+        let mut sink = UnixSocketSink::new(path.clone(), EventFormat::Json);
+        assert!(sink.send(&envelope(1)).is_err(),
+                "Nothing is listening on the socket yet");
+
+        let listener = UnixListener::bind(&path).unwrap();
+        sink.send(&envelope(2)).unwrap();
+        sink.send(&envelope(3)).unwrap();
+        drop(sink);
+
+        let (stream, _) = listener.accept().unwrap();
+        let sequences = BufReader::new(stream).lines()
+                                              .map(|l| {
+                                                  let v: Value =
+                                                      serde_json::from_str(&l.unwrap()).unwrap();
+                                                  v["sequence"].as_u64().unwrap()
+                                              })
+                                              .collect::<Vec<_>>();
+        assert_eq!(sequences, vec![2, 3]);
+    }
+}
@@ -25,28 +25,196 @@
 // convenient and flexible (if perhaps a bit verbose) way to start.
 
 use super::Event;
+use crate::{census::ElectionStatus,
+            manager::service::HealthCheck};
 use habitat_core::{package::PackageIdent,
                    service::ServiceGroup};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ServiceStarted<'a> {
     /// The fully-qualified identifier of the service that has just started
     // TODO (CM): I *really* want a FullyQualifiedIdentifier type now
     pub ident: &'a PackageIdent,
     /// The identifier the service was loaded as. This could be (and
     /// often is) an abbreviated identifier, like "core/redis".
-    pub spec_ident: &'a PackageIdent,
+    pub spec_ident:    &'a PackageIdent,
     /// The service group of the running service
     pub service_group: &'a ServiceGroup,
 }
-impl<'a> Event for ServiceStarted<'a> {}
+impl<'a> Event for ServiceStarted<'a> {
+    fn event_type(&self) -> &'static str { "service-started" }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ServiceStopped<'a> {
     /// The fully-qualified identifier of the service that has just started
-    pub ident: &'a PackageIdent,
+    pub ident:         &'a PackageIdent,
     //    pub spec_ident: &'a PackageIdent,
     /// The service group of the running service
     pub service_group: &'a ServiceGroup,
 }
-impl<'a> Event for ServiceStopped<'a> {}
+impl<'a> Event for ServiceStopped<'a> {
+    fn event_type(&self) -> &'static str { "service-stopped" }
+}
+
+/// The result of a service's health check differs from the previous
+/// one.
+#[derive(Debug, Serialize)]
+pub struct HealthCheckChanged<'a> {
+    pub service_group: &'a ServiceGroup,
+    pub previous:      HealthCheck,
+    pub current:       HealthCheck,
+}
+impl<'a> Event for HealthCheckChanged<'a> {
+    fn event_type(&self) -> &'static str { "health-check-changed" }
+}
+
+/// The election status of a leader-topology service group, as seen by
+/// this Supervisor, has changed.
+#[derive(Debug, Serialize)]
+pub struct ElectionStatusChanged<'a> {
+    pub service_group: &'a ServiceGroup,
+    pub status:        ElectionStatus,
+}
+impl<'a> Event for ElectionStatusChanged<'a> {
+    fn event_type(&self) -> &'static str { "election-status-changed" }
+}
+
+/// A finished election produced a different leader than the last one
+/// this Supervisor knew about.
+#[derive(Debug, Serialize)]
+pub struct LeaderChanged<'a> {
+    pub service_group:      &'a ServiceGroup,
+    /// The member ID of the previous leader, if there was one
+    pub previous_leader_id: Option<&'a str>,
+    pub leader_id:          &'a str,
+}
+impl<'a> Event for LeaderChanged<'a> {
+    fn event_type(&self) -> &'static str { "leader-changed" }
+}
+
+/// The service updater found a newer package for a running service,
+/// which will now be restarted with it.
+#[derive(Debug, Serialize)]
+pub struct ServiceUpdateFound<'a> {
+    pub service_group: &'a ServiceGroup,
+    /// The identifier of the package currently running
+    pub ident:         &'a PackageIdent,
+    /// The identifier of the package that will replace it
+    pub new_ident:     &'a PackageIdent,
+}
+impl<'a> Event for ServiceUpdateFound<'a> {
+    fn event_type(&self) -> &'static str { "service-update-found" }
+}
+
+/// An updated service failed its health check, or never came up,
+/// within the rollback window, and will be restarted with the release
+/// it was updated from.
+#[derive(Debug, Serialize)]
+pub struct ServiceUpdateRolledBack<'a> {
+    pub service_group:  &'a ServiceGroup,
+    /// The identifier of the release that failed
+    pub ident:          &'a PackageIdent,
+    /// The identifier of the release being restored
+    pub rolled_back_to: &'a PackageIdent,
+}
+impl<'a> Event for ServiceUpdateRolledBack<'a> {
+    fn event_type(&self) -> &'static str { "service-update-rolled-back" }
+}
+
+/// A newer gossiped configuration was applied to a service.
+#[derive(Debug, Serialize)]
+pub struct ServiceConfigApplied<'a> {
+    pub service_group: &'a ServiceGroup,
+    pub incarnation:   u64,
+}
+impl<'a> Event for ServiceConfigApplied<'a> {
+    fn event_type(&self) -> &'static str { "service-config-applied" }
+}
+
+/// A gossiped file was written to a service's files directory.
+#[derive(Debug, Serialize)]
+pub struct ServiceFileApplied<'a> {
+    pub service_group: &'a ServiceGroup,
+    pub filename:      &'a str,
+    pub incarnation:   u64,
+}
+impl<'a> Event for ServiceFileApplied<'a> {
+    fn event_type(&self) -> &'static str { "service-file-applied" }
+}
+
+/// One of a service's binds went from satisfied to unsatisfied, or
+/// back again.
+#[derive(Debug, Serialize)]
+pub struct BindStatusChanged<'a> {
+    pub service_group:      &'a ServiceGroup,
+    /// The name of the bind, as declared by the package
+    pub bind:               &'a str,
+    /// The service group the bind is pointed at
+    pub bind_service_group: &'a ServiceGroup,
+    pub satisfied:          bool,
+}
+impl<'a> Event for BindStatusChanged<'a> {
+    fn event_type(&self) -> &'static str { "bind-status-changed" }
+}
+
+/// A lifecycle hook did not complete successfully.
+#[derive(Debug, Serialize)]
+pub struct HookFailed<'a> {
+    pub service_group: &'a ServiceGroup,
+    pub hook:          &'static str,
+    /// The exit code of the hook, for hooks that report one
+    pub exit_code:     Option<i32>,
+}
+impl<'a> Event for HookFailed<'a> {
+    fn event_type(&self) -> &'static str { "hook-failed" }
+}
+
+/// The Supervisor was departed from the Habitat network and is
+/// shutting down.
+#[derive(Debug, Serialize)]
+pub struct SupervisorDeparted {}
+impl Event for SupervisorDeparted {
+    fn event_type(&self) -> &'static str { "supervisor-departed" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use std::str::FromStr;
+
+    #[test]
+    fn health_check_changed_payload() {
+        let sg = ServiceGroup::from_str("redis.default").unwrap();
+        let event = HealthCheckChanged { service_group: &sg,
+                                         previous:      HealthCheck::Ok,
+                                         current:       HealthCheck::Critical, };
+        assert_eq!(serde_json::to_value(&event).unwrap(),
+                   json!({ "service_group": "redis.default",
+                           "previous": "Ok",
+                           "current": "Critical" }));
+    }
+
+    #[test]
+    fn bind_status_changed_payload() {
+        let sg = ServiceGroup::from_str("app.default").unwrap();
+        let bound = ServiceGroup::from_str("redis.default").unwrap();
+        let event = BindStatusChanged { service_group:      &sg,
+                                        bind:               "database",
+                                        bind_service_group: &bound,
+                                        satisfied:          false, };
+        assert_eq!(event.event_type(), "bind-status-changed");
+        assert_eq!(serde_json::to_value(&event).unwrap(),
+                   json!({ "service_group": "app.default",
+                           "bind": "database",
+                           "bind_service_group": "redis.default",
+                           "satisfied": false }));
+    }
+
+    #[test]
+    fn supervisor_departed_payload_is_an_empty_object() {
+        assert_eq!(serde_json::to_value(&SupervisorDeparted {}).unwrap(),
+                   json!({}));
+    }
+}
@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{error::{Result,
+use crate::{ctl_gateway::CtlRequest,
+            error::{Result,
                     SupError},
             manager::{self,
-                      service::{HealthCheck,
-                                HealthCheckHook}}};
+                      action::ActionSender,
+                      commands,
+                      service::{spec::{DesiredState,
+                                       ServiceSpec},
+                                HealthCheck,
+                                HealthCheckHook},
+                      ManagerState}};
 use actix;
 use actix_web::{http::{self,
                        StatusCode},
@@ -29,8 +35,10 @@ use actix_web::{http::{self,
                 FromRequest,
                 HttpRequest,
                 HttpResponse,
+                Json,
                 Path,
                 Request};
+use bytes::Bytes;
 use habitat_common::{cli::{LISTEN_HTTP_ADDRESS_ENVVAR,
                            LISTEN_HTTP_DEFAULT_IP,
                            LISTEN_HTTP_DEFAULT_PORT},
@@ -39,7 +47,13 @@ use habitat_common::{cli::{LISTEN_HTTP_ADDRESS_ENVVAR,
 use habitat_core::{crypto,
                    env as henv,
                    env::Config as EnvConfig,
+                   package::PackageIdent,
                    service::ServiceGroup};
+use habitat_sup_protocol::{self as protocol,
+                           net::{self,
+                                 ErrCode,
+                                 NetErr,
+                                 NetResult}};
 use prometheus::{self,
                  CounterVec,
                  Encoder,
@@ -68,6 +82,7 @@ use std::{cell::Cell,
                  Mutex,
                  RwLock},
           thread};
+use toml;
 
 const APIDOCS: &str = include_str!(concat!(env!("OUT_DIR"), "/api.html"));
 pub const HTTP_THREADS_ENVVAR: &str = "HAB_SUP_HTTP_THREADS";
@@ -139,8 +154,9 @@ impl fmt::Display for ListenAddr {
 
 /// This represents an environment variable that holds an authentication token for the supervisor's
 /// HTTP gateway. If the environment variable is present, then its value is the auth token and all
-/// of the HTTP endpoints will require its presence. If it's not present, then everything continues
-/// to work unauthenticated.
+/// of the HTTP endpoints will require its presence. If it's not present, then the read-only
+/// endpoints continue to work unauthenticated, but the endpoints which load, unload, start, stop
+/// or configure services are refused.
 #[derive(Clone, Debug, Default)]
 pub struct GatewayAuthenticationToken(Option<String>);
 
@@ -173,19 +189,86 @@ impl Into<StatusCode> for HealthCheck {
     }
 }
 
+/// Body of a request to load a service. Everything but the package identifier is optional and
+/// follows the defaults of `hab svc load`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ServiceLoadBody {
+    ident:        String,
+    bldr_url:     Option<String>,
+    channel:      Option<String>,
+    binds:        Option<Vec<String>>,
+    binding_mode: Option<String>,
+    topology:     Option<String>,
+    strategy:     Option<String>,
+    force:        bool,
+}
+
+impl ServiceLoadBody {
+    fn into_svc_load(self, service_group: &ServiceGroup) -> NetResult<protocol::ctl::SvcLoad> {
+        let ident =
+            PackageIdent::from_str(&self.ident).map_err(|e| net::err(ErrCode::InvalidPayload, e))?;
+        if ident.name != service_group.service() {
+            return Err(net::err(ErrCode::InvalidPayload,
+                                format!("{} can't be loaded into {}",
+                                        ident, service_group)));
+        }
+        let binds = match self.binds {
+            Some(binds) => {
+                let binds = binds.iter().map(|b| b.parse()).collect::<NetResult<_>>()?;
+                Some(protocol::ctl::ServiceBindList { binds })
+            }
+            None => None,
+        };
+        let binding_mode = match self.binding_mode {
+            Some(m) => Some(m.parse::<protocol::types::BindingMode>()? as i32),
+            None => None,
+        };
+        let topology = match self.topology {
+            Some(t) => Some(t.parse::<protocol::types::Topology>()? as i32),
+            None => None,
+        };
+        let update_strategy = match self.strategy {
+            Some(s) => Some(s.parse::<protocol::types::UpdateStrategy>()? as i32),
+            None => None,
+        };
+        Ok(protocol::ctl::SvcLoad { ident: Some(ident.into()),
+                                    binds,
+                                    binding_mode,
+                                    bldr_url: self.bldr_url,
+                                    bldr_channel: self.channel,
+                                    force: Some(self.force),
+                                    group: Some(service_group.group().to_string()),
+                                    topology,
+                                    update_strategy,
+                                    ..Default::default() })
+    }
+}
+
+/// Body of a request to start or stop a loaded service.
+#[derive(Debug, Deserialize)]
+struct ServiceStateBody {
+    desired_state: String,
+}
+
 struct AppState {
     gateway_state:        Arc<RwLock<manager::GatewayState>>,
+    manager_state:        Arc<ManagerState>,
+    action_sender:        ActionSender,
     authentication_token: Option<String>,
     timer:                Cell<Option<HistogramTimer>>,
     feature_flags:        FeatureFlag,
 }
 
 impl AppState {
-    fn new(gs: Arc<RwLock<manager::GatewayState>>,
+    fn new(manager_state: Arc<ManagerState>,
+           action_sender: ActionSender,
            authentication_token: GatewayAuthenticationToken,
            feature_flags: FeatureFlag)
            -> Self {
-        AppState { gateway_state: gs,
+        AppState { gateway_state: manager_state.gateway_state.clone(),
+                   manager_state,
+                   action_sender,
                    // We'll unwrap to the inner type, since the
                    // GatewayAuthenticationToken type has done its job by this point.
                    authentication_token: authentication_token.0,
@@ -202,6 +285,14 @@ impl Middleware<AppState> for Authentication {
         let current_token = req.state().authentication_token.as_ref();
         let current_token = match current_token {
             Some(t) => t,
+            // Anything that changes the Supervisor's state is only served to authenticated
+            // clients, so without a token there's nobody we could serve it to.
+            None if !req.method().is_safe() => {
+                debug!("No authentication token present. Refusing {} {}.",
+                       req.method(),
+                       req.path());
+                return Ok(Started::Response(HttpResponse::Forbidden().finish()));
+            }
             None => {
                 debug!("No authentication token present. HTTP gateway starting in \
                         unauthenticated mode.");
@@ -277,7 +368,8 @@ pub struct Server;
 impl Server {
     pub fn run(listen_addr: ListenAddr,
                tls_config: Option<ServerConfig>,
-               gateway_state: Arc<RwLock<manager::GatewayState>>,
+               manager_state: Arc<ManagerState>,
+               action_sender: ActionSender,
                authentication_token: GatewayAuthenticationToken,
                feature_flags: FeatureFlag,
                control: Arc<(Mutex<ServerStartup>, Condvar)>) {
@@ -295,7 +387,8 @@ impl Server {
             };
 
             let mut server = server::new(move || {
-                                 let app_state = AppState::new(gateway_state.clone(),
+                                 let app_state = AppState::new(manager_state.clone(),
+                                                               action_sender.clone(),
                                                                authentication_token.clone(),
                                                                feature_flags);
                                  App::with_state(app_state).middleware(Authentication)
@@ -350,23 +443,39 @@ fn routes(app: App<AppState>) -> App<AppState> {
     app.resource("/", |r| r.get().f(doc))
        .resource("/services", |r| r.get().f(services))
        .resource("/services/{svc}/{group}", |r| {
-           r.get().f(service_without_org)
+           r.get().f(service_without_org);
+           r.post().with(load_without_org);
+           r.put().with(desired_state_without_org);
+           r.delete().f(unload_without_org);
        })
        .resource("/services/{svc}/{group}/config", |r| {
-           r.get().f(config_without_org)
+           r.get().f(config_without_org);
+           r.put().with(config_apply_without_org);
        })
        .resource("/services/{svc}/{group}/health", |r| {
            r.get().f(health_without_org)
        })
+       // Registered ahead of the organization routes so that `files` is never mistaken for an
+       // organization name.
+       .resource("/services/{svc}/{group}/files/{name}", |r| {
+           r.put().with(file_put_without_org)
+       })
        .resource("/services/{svc}/{group}/{org}", |r| {
-           r.get().f(service_with_org)
+           r.get().f(service_with_org);
+           r.post().with(load_with_org);
+           r.put().with(desired_state_with_org);
+           r.delete().f(unload_with_org);
        })
        .resource("/services/{svc}/{group}/{org}/config", |r| {
-           r.get().f(config_with_org)
+           r.get().f(config_with_org);
+           r.put().with(config_apply_with_org);
        })
        .resource("/services/{svc}/{group}/{org}/health", |r| {
            r.get().f(health_with_org)
        })
+       .resource("/services/{svc}/{group}/{org}/files/{name}", |r| {
+           r.put().with(file_put_with_org)
+       })
        .resource("/butterfly", |r| r.get().filter(RedactHTTP).f(butterfly))
        .resource("/census", |r| r.get().filter(RedactHTTP).f(census))
        .resource("/metrics", |r| r.get().f(metrics))
@@ -547,8 +656,287 @@ fn metrics(_req: &HttpRequest<AppState>) -> HttpResponse {
 fn doc(_req: &HttpRequest<AppState>) -> HttpResponse {
     HttpResponse::Ok().content_type("text/html").body(APIDOCS)
 }
+
+fn load_with_org((req, body): (HttpRequest<AppState>, Json<ServiceLoadBody>)) -> HttpResponse {
+    let (svc, group, org) = Path::<(String, String, String)>::extract(&req).unwrap()
+                                                                           .into_inner();
+    load(&req, svc, group, Some(&org), body.into_inner())
+}
+
+fn load_without_org((req, body): (HttpRequest<AppState>, Json<ServiceLoadBody>)) -> HttpResponse {
+    let (svc, group) = Path::<(String, String)>::extract(&req).unwrap()
+                                                              .into_inner();
+    load(&req, svc, group, None, body.into_inner())
+}
+
+fn load(req: &HttpRequest<AppState>,
+        svc: String,
+        group: String,
+        org: Option<&str>,
+        body: ServiceLoadBody)
+        -> HttpResponse {
+    let state = req.state();
+    let service_group = match ServiceGroup::new(None, svc, group, org) {
+        Ok(sg) => sg,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    let organization = state.manager_state.cfg.organization.as_ref();
+    if service_group.org() != organization.map(String::as_str) {
+        return HttpResponse::BadRequest().body(format!("This Supervisor can't load services \
+                                                        into {}",
+                                                       service_group));
+    }
+    let msg = match body.into_svc_load(&service_group) {
+        Ok(msg) => msg,
+        Err(err) => return net_err_response(&err),
+    };
+
+    run_command(|ctl| commands::service_load(&state.manager_state, ctl, &msg))
+}
+
+fn unload_with_org(req: &HttpRequest<AppState>) -> HttpResponse {
+    let (svc, group, org) = Path::<(String, String, String)>::extract(&req).unwrap()
+                                                                           .into_inner();
+    unload(req, svc, group, Some(&org))
+}
+
+fn unload_without_org(req: &HttpRequest<AppState>) -> HttpResponse {
+    let (svc, group) = Path::<(String, String)>::extract(&req).unwrap()
+                                                              .into_inner();
+    unload(req, svc, group, None)
+}
+
+fn unload(req: &HttpRequest<AppState>,
+          svc: String,
+          group: String,
+          org: Option<&str>)
+          -> HttpResponse {
+    let state = req.state();
+    let spec = match loaded_spec(state, svc, group, org) {
+        Ok(spec) => spec,
+        Err(resp) => return resp,
+    };
+    let (signal, timeout_in_seconds) = match shutdown_from_query(req) {
+        Ok(shutdown) => shutdown,
+        Err(resp) => return resp,
+    };
+    let msg = protocol::ctl::SvcUnload { ident: Some(spec.ident.into()),
+                                         signal,
+                                         timeout_in_seconds };
+
+    run_command(|ctl| {
+        commands::service_unload(&state.manager_state, ctl, msg, &state.action_sender)
+    })
+}
+
+fn desired_state_with_org((req, body): (HttpRequest<AppState>, Json<ServiceStateBody>))
+                          -> HttpResponse {
+    let (svc, group, org) = Path::<(String, String, String)>::extract(&req).unwrap()
+                                                                           .into_inner();
+    desired_state(&req, svc, group, Some(&org), &body)
+}
+
+fn desired_state_without_org((req, body): (HttpRequest<AppState>, Json<ServiceStateBody>))
+                             -> HttpResponse {
+    let (svc, group) = Path::<(String, String)>::extract(&req).unwrap()
+                                                              .into_inner();
+    desired_state(&req, svc, group, None, &body)
+}
+
+fn desired_state(req: &HttpRequest<AppState>,
+                 svc: String,
+                 group: String,
+                 org: Option<&str>,
+                 body: &ServiceStateBody)
+                 -> HttpResponse {
+    let state = req.state();
+    let desired_state = match DesiredState::from_str(&body.desired_state) {
+        Ok(desired_state) => desired_state,
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+    let spec = match loaded_spec(state, svc, group, org) {
+        Ok(spec) => spec,
+        Err(resp) => return resp,
+    };
+    let ident = Some(spec.ident.into());
+
+    match desired_state {
+        DesiredState::Up => {
+            let msg = protocol::ctl::SvcStart { ident };
+            run_command(|ctl| commands::service_start(&state.manager_state, ctl, msg))
+        }
+        DesiredState::Down => {
+            let (signal, timeout_in_seconds) = match shutdown_from_query(req) {
+                Ok(shutdown) => shutdown,
+                Err(resp) => return resp,
+            };
+            let msg = protocol::ctl::SvcStop { ident,
+                                               signal,
+                                               timeout_in_seconds };
+            run_command(|ctl| {
+                commands::service_stop(&state.manager_state, ctl, msg, &state.action_sender)
+            })
+        }
+    }
+}
+
+fn config_apply_with_org((req, body): (HttpRequest<AppState>, Bytes)) -> HttpResponse {
+    let (svc, group, org) = Path::<(String, String, String)>::extract(&req).unwrap()
+                                                                           .into_inner();
+    config_apply(&req, svc, group, Some(&org), body)
+}
+
+fn config_apply_without_org((req, body): (HttpRequest<AppState>, Bytes)) -> HttpResponse {
+    let (svc, group) = Path::<(String, String)>::extract(&req).unwrap()
+                                                              .into_inner();
+    config_apply(&req, svc, group, None, body)
+}
+
+fn config_apply(req: &HttpRequest<AppState>,
+                svc: String,
+                group: String,
+                org: Option<&str>,
+                body: Bytes)
+                -> HttpResponse {
+    let service_group = match ServiceGroup::new(None, svc, group, org) {
+        Ok(sg) => sg,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    let version = match version_from_query(req) {
+        Ok(version) => version,
+        Err(resp) => return resp,
+    };
+    // The body is sent as-is, so make sure we don't gossip something that isn't TOML.
+    if let Err(e) = toml::from_slice::<toml::value::Table>(&body) {
+        return HttpResponse::BadRequest().body(format!("Unable to decode configuration as \
+                                                        toml, {}",
+                                                       e));
+    }
+    let msg = protocol::ctl::SvcSetCfg { service_group: Some(service_group.into()),
+                                         cfg:           Some(body.to_vec()),
+                                         version:       Some(version),
+                                         is_encrypted:  Some(false),
+                                         signer:        None,
+                                         signature:     None, };
+
+    run_command(|ctl| commands::service_cfg_set(&req.state().manager_state, ctl, msg))
+}
+
+fn file_put_with_org((req, body): (HttpRequest<AppState>, Bytes)) -> HttpResponse {
+    let (svc, group, org, name) =
+        Path::<(String, String, String, String)>::extract(&req).unwrap()
+                                                               .into_inner();
+    file_put(&req, svc, group, Some(&org), name, body)
+}
+
+fn file_put_without_org((req, body): (HttpRequest<AppState>, Bytes)) -> HttpResponse {
+    let (svc, group, name) = Path::<(String, String, String)>::extract(&req).unwrap()
+                                                                            .into_inner();
+    file_put(&req, svc, group, None, name, body)
+}
+
+fn file_put(req: &HttpRequest<AppState>,
+            svc: String,
+            group: String,
+            org: Option<&str>,
+            filename: String,
+            body: Bytes)
+            -> HttpResponse {
+    let service_group = match ServiceGroup::new(None, svc, group, org) {
+        Ok(sg) => sg,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    let version = match version_from_query(req) {
+        Ok(version) => version,
+        Err(resp) => return resp,
+    };
+    let msg = protocol::ctl::SvcFilePut { service_group: Some(service_group.into()),
+                                          content:       Some(body.to_vec()),
+                                          filename:      Some(filename),
+                                          version:       Some(version),
+                                          is_encrypted:  Some(false),
+                                          signer:        None,
+                                          signature:     None, };
+
+    run_command(|ctl| commands::service_file_put(&req.state().manager_state, ctl, msg))
+}
 // End route handlers
 
+/// Run one of the ctl gateway's commands on behalf of an HTTP request. There's no transaction to
+/// reply to, so anything the command reports along the way only ends up in the Supervisor's
+/// output.
+fn run_command<F>(command: F) -> HttpResponse
+    where F: FnOnce(&mut CtlRequest) -> NetResult<()>
+{
+    match command(&mut CtlRequest::default()) {
+        Ok(()) => HttpResponse::Accepted().finish(),
+        Err(err) => net_err_response(&err),
+    }
+}
+
+fn net_err_response(err: &NetErr) -> HttpResponse {
+    HttpResponse::build(net_err_status(err)).body(err.msg.clone())
+}
+
+fn net_err_status(err: &NetErr) -> StatusCode {
+    match ErrCode::from_i32(err.code) {
+        Some(ErrCode::NotFound) => StatusCode::NOT_FOUND,
+        Some(ErrCode::Conflict) => StatusCode::CONFLICT,
+        Some(ErrCode::Unauthorized) => StatusCode::UNAUTHORIZED,
+        Some(ErrCode::EntityTooLarge) => StatusCode::PAYLOAD_TOO_LARGE,
+        Some(ErrCode::NotSupported)
+        | Some(ErrCode::BadPayload)
+        | Some(ErrCode::InvalidPayload)
+        | Some(ErrCode::UpdateClient) => StatusCode::BAD_REQUEST,
+        Some(ErrCode::Internal) | Some(ErrCode::Io) | None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// The spec of the service loaded into the given service group, or the response to send if
+/// there isn't one.
+fn loaded_spec(state: &AppState,
+               svc: String,
+               group: String,
+               org: Option<&str>)
+               -> result::Result<ServiceSpec, HttpResponse> {
+    let service_group =
+        ServiceGroup::new(None, svc, group, org).map_err(|_| HttpResponse::BadRequest().finish())?;
+    state.manager_state
+         .cfg
+         .spec_for_service_group(&service_group)
+         .ok_or_else(|| HttpResponse::NotFound().finish())
+}
+
+/// Config and file uploads are gossiped with an explicit version, just like with
+/// `hab config apply` and `hab file upload`.
+fn version_from_query(req: &HttpRequest<AppState>) -> result::Result<u64, HttpResponse> {
+    req.query()
+       .get("version")
+       .and_then(|v| v.parse().ok())
+       .ok_or_else(|| {
+           HttpResponse::BadRequest().body("A numeric `version` query parameter is required")
+       })
+}
+
+/// The optional `signal` and `timeout` query parameters of requests which shut a service down.
+fn shutdown_from_query(req: &HttpRequest<AppState>)
+                       -> result::Result<(Option<String>, Option<u32>), HttpResponse> {
+    let query = req.query();
+    let timeout = match query.get("timeout") {
+        Some(t) => {
+            match t.parse() {
+                Ok(t) => Some(t),
+                Err(_) => {
+                    return Err(HttpResponse::BadRequest().body("The `timeout` query parameter \
+                                                                must be a number of seconds"));
+                }
+            }
+        }
+        None => None,
+    };
+    Ok((query.get("signal").cloned(), timeout))
+}
+
 fn service_from_services(service_group: &ServiceGroup, services_json: &str) -> Option<Json> {
     match serde_json::from_str(services_json) {
         Ok(Json::Array(services)) => {
@@ -561,12 +949,32 @@ fn service_from_services(service_group: &ServiceGroup, services_json: &str) -> O
 
 #[cfg(test)]
 mod tests {
-    use crate::test_helpers::*;
+    use super::{net_err_status,
+                routes,
+                AppState,
+                Authentication,
+                GatewayAuthenticationToken,
+                ServiceLoadBody};
+    use crate::{manager::{ManagerConfig,
+                          ManagerState},
+                test_helpers::*};
+    use actix_web::{client::ClientRequestBuilder,
+                    http::{header,
+                           Method,
+                           StatusCode},
+                    test::TestServer,
+                    App,
+                    HttpMessage};
     use habitat_butterfly::{member::Member,
                             server::{Server,
                                      ServerProxy,
                                      Suitability},
                             trace::Trace};
+    use habitat_common::FeatureFlag;
+    use habitat_core::service::ServiceGroup;
+    use habitat_sup_protocol::{self as protocol,
+                               net::{self,
+                                     ErrCode}};
     use serde_json;
     use std::{fs::File,
               io::Read,
@@ -574,7 +982,49 @@ mod tests {
                     Ipv4Addr,
                     SocketAddr},
               path::PathBuf,
-              sync::Mutex};
+              str::{self,
+                    FromStr},
+              sync::{mpsc,
+                     Arc,
+                     Mutex}};
+    use tempfile::TempDir;
+
+    const TOKEN: &str = "sekrit";
+
+    /// An HTTP gateway for a Supervisor with no services loaded, which only serves requests
+    /// carrying `TOKEN`.
+    fn gateway(tmpdir: &TempDir) -> TestServer {
+        let mut cfg = ManagerConfig::default();
+        cfg.custom_state_path = Some(tmpdir.path().to_path_buf());
+        let manager_state = Arc::new(ManagerState { cfg,
+                                                    services: Default::default(),
+                                                    gateway_state: Default::default(),
+                                                    ring_keys: Default::default() });
+        let (action_sender, _) = mpsc::channel();
+        let token = GatewayAuthenticationToken::from_str(TOKEN).unwrap();
+
+        TestServer::with_factory(move || {
+            let app_state = AppState::new(manager_state.clone(),
+                                          action_sender.clone(),
+                                          token.clone(),
+                                          FeatureFlag::empty());
+            App::with_state(app_state).middleware(Authentication)
+                                      .configure(routes)
+        })
+    }
+
+    /// Send a request built by `build` with `TOKEN` attached, returning the response status and
+    /// body.
+    fn send<F>(srv: &mut TestServer, method: Method, path: &str, build: F) -> (StatusCode, String)
+        where F: FnOnce(&mut ClientRequestBuilder) -> actix_web::client::ClientRequest
+    {
+        let mut builder = srv.client(method, path);
+        builder.header(header::AUTHORIZATION, format!("Bearer {}", TOKEN));
+        let request = build(&mut builder);
+        let response = srv.execute(request.send()).unwrap();
+        let body = srv.execute(response.body()).unwrap();
+        (response.status(), str::from_utf8(&body).unwrap().to_string())
+    }
 
     fn validate_sample_file_against_schema(name: &str, schema: &str) {
         let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
@@ -679,6 +1129,147 @@ mod tests {
                                             "http_gateway_services_schema.json");
     }
 
+    #[test]
+    fn net_errors_map_to_http_statuses() {
+        let status = |code| net_err_status(&net::err(code, "nope"));
+        assert_eq!(status(ErrCode::NotFound), StatusCode::NOT_FOUND);
+        assert_eq!(status(ErrCode::Conflict), StatusCode::CONFLICT);
+        assert_eq!(status(ErrCode::EntityTooLarge),
+                   StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(status(ErrCode::InvalidPayload), StatusCode::BAD_REQUEST);
+        assert_eq!(status(ErrCode::Internal), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn service_load_body_becomes_svc_load() {
+        let json = r#"{"ident": "core/redis", "binds": ["db:pg.default"], "topology": "leader"}"#;
+        let body: ServiceLoadBody = serde_json::from_str(json).unwrap();
+        let service_group = ServiceGroup::new(None, "redis", "prod", None).unwrap();
+        let msg = body.into_svc_load(&service_group).unwrap();
+
+        assert_eq!(msg.ident.unwrap().to_string(), "core/redis");
+        assert_eq!(msg.group, Some("prod".to_string()));
+        assert_eq!(msg.binds.unwrap().binds.len(), 1);
+        assert_eq!(msg.topology, Some(protocol::types::Topology::Leader as i32));
+        assert_eq!(msg.update_strategy, None);
+        assert_eq!(msg.force, Some(false));
+    }
+
+    #[test]
+    fn service_load_body_must_match_service_group() {
+        let body: ServiceLoadBody = serde_json::from_str(r#"{"ident": "core/redis"}"#).unwrap();
+        let service_group = ServiceGroup::new(None, "nginx", "default", None).unwrap();
+        let err = body.into_svc_load(&service_group).unwrap_err();
+
+        assert_eq!(net_err_status(&err), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn writes_without_the_bearer_token_are_refused() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut srv = gateway(&tmpdir);
+
+        let request = srv.client(Method::DELETE, "/services/redis/default")
+                         .finish()
+                         .unwrap();
+        let response = srv.execute(request.send()).unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let request = srv.client(Method::PUT, "/services/redis/default")
+                         .header(header::AUTHORIZATION, "Bearer nope")
+                         .json(json!({"desired_state": "down"}))
+                         .unwrap();
+        let response = srv.execute(request.send()).unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&mut srv, Method::DELETE, "/services/redis/default", |b| {
+            b.finish().unwrap()
+        });
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn service_loads_are_dispatched() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut srv = gateway(&tmpdir);
+
+        let (status, body) = send(&mut srv, Method::POST, "/services/redis/prod", |b| {
+            b.json(json!({"ident": "core/nginx"})).unwrap()
+        });
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("core/nginx can't be loaded into redis.prod"),
+                "{}",
+                body);
+
+        let (status, body) = send(&mut srv, Method::POST, "/services/redis/prod/acme", |b| {
+            b.json(json!({"ident": "core/redis"})).unwrap()
+        });
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("can't load services into redis.prod@acme"),
+                "{}",
+                body);
+    }
+
+    #[test]
+    fn service_state_changes_are_dispatched() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut srv = gateway(&tmpdir);
+
+        for path in &["/services/redis/prod", "/services/redis/prod/acme"] {
+            let (status, _) = send(&mut srv, Method::PUT, path, |b| {
+                b.json(json!({"desired_state": "up"})).unwrap()
+            });
+            assert_eq!(status, StatusCode::NOT_FOUND, "PUT {}", path);
+
+            let (status, _) = send(&mut srv, Method::PUT, path, |b| {
+                b.json(json!({"desired_state": "sideways"})).unwrap()
+            });
+            assert_eq!(status, StatusCode::BAD_REQUEST, "PUT {}", path);
+
+            let (status, _) = send(&mut srv, Method::DELETE, path, |b| b.finish().unwrap());
+            assert_eq!(status, StatusCode::NOT_FOUND, "DELETE {}", path);
+        }
+    }
+
+    #[test]
+    fn config_applies_are_dispatched() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut srv = gateway(&tmpdir);
+
+        for path in &["/services/redis/prod/config",
+                      "/services/redis/prod/acme/config"]
+        {
+            let (status, body) = send(&mut srv, Method::PUT, path, |b| {
+                b.body("port = 6379").unwrap()
+            });
+            assert_eq!(status, StatusCode::BAD_REQUEST, "PUT {}", path);
+            assert!(body.contains("`version` query parameter"), "{}", body);
+
+            let path = format!("{}?version=1", path);
+            let (status, body) = send(&mut srv, Method::PUT, &path, |b| b.body("port = ").unwrap());
+            assert_eq!(status, StatusCode::BAD_REQUEST, "PUT {}", path);
+            assert!(body.contains("Unable to decode configuration as toml"),
+                    "{}",
+                    body);
+        }
+    }
+
+    #[test]
+    fn file_puts_are_dispatched() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut srv = gateway(&tmpdir);
+
+        for path in &["/services/redis/prod/files/redis.conf",
+                      "/services/redis/prod/acme/files/redis.conf"]
+        {
+            let (status, body) = send(&mut srv, Method::PUT, path, |b| {
+                b.body("maxmemory 1gb").unwrap()
+            });
+            assert_eq!(status, StatusCode::BAD_REQUEST, "PUT {}", path);
+            assert!(body.contains("`version` query parameter"), "{}", body);
+        }
+    }
+
     #[test]
     fn trivial_services_failure() {
         let failure = validate_string(r#"[{"lulz": true}]"#, "http_gateway_services_schema.json");
@@ -37,6 +37,7 @@ use crate::sup::{cli::cli,
                  error::{Error,
                          Result,
                          SupError},
+                 event::EventStreamConfig,
                  manager::{Manager,
                            ManagerConfig,
                            TLSConfig,
@@ -57,6 +58,8 @@ use habitat_common::{cli::{cache_key_path_from_matches,
 use habitat_core::crypto::dpapi::encrypt;
 use habitat_core::{crypto::{self,
                             SymKey},
+                   service::{parse_label,
+                             UpdateWindow},
                    url::{bldr_url_from_env,
                          default_bldr_url},
                    ChannelIdent};
@@ -65,6 +68,7 @@ use habitat_launcher_client::{LauncherCli,
 use habitat_sup_protocol::{ctl::ServiceBindList,
                            types::{ApplicationEnvironment,
                                    BindingMode,
+                                   RestartPolicy,
                                    ServiceBind,
                                    Topology,
                                    UpdateStrategy}};
@@ -151,6 +155,7 @@ fn start(feature_flags: FeatureFlag) -> Result<()> {
     };
     match app_matches.subcommand() {
         ("bash", Some(_)) => sub_bash(),
+        ("dat", Some(m)) => sub_dat(m),
         ("run", Some(m)) => {
             let launcher = launcher.ok_or(sup_error!(Error::NoLauncher))?;
             sub_run(m, launcher, feature_flags)
@@ -163,6 +168,28 @@ fn start(feature_flags: FeatureFlag) -> Result<()> {
 
 fn sub_bash() -> Result<()> { command::shell::bash() }
 
+fn sub_dat(m: &ArgMatches) -> Result<()> {
+    match m.subcommand() {
+        ("show", Some(m)) => {
+            command::dat::show(m.value_of("DAT_FILE").unwrap(),
+                               &values_of(m, "RUMOR_TYPE"),
+                               &values_of(m, "SERVICE_GROUP"))
+        }
+        ("prune", Some(m)) => {
+            command::dat::prune(m.value_of("DAT_FILE").unwrap(),
+                                m.value_of("OUTPUT").unwrap(),
+                                &values_of(m, "MEMBER_ID"),
+                                &values_of(m, "RUMOR_TYPE"),
+                                &values_of(m, "SERVICE_GROUP"))
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn values_of<'a>(m: &'a ArgMatches, name: &str) -> Vec<&'a str> {
+    m.values_of(name).map(Iterator::collect).unwrap_or_default()
+}
+
 fn sub_run(m: &ArgMatches, launcher: LauncherCli, feature_flags: FeatureFlag) -> Result<()> {
     set_supervisor_logging_options(m);
 
@@ -196,6 +223,10 @@ fn sub_run(m: &ArgMatches, launcher: LauncherCli, feature_flags: FeatureFlag) ->
                 install.ident.into()
             }
             InstallSource::Ident(ident, _) => ident.into(),
+            InstallSource::Constraint(constraint, _) => {
+                msg.version_constraint = Some(constraint.version.to_string());
+                constraint.ident.into()
+            }
         };
         msg.ident = Some(ident);
         Some(msg)
@@ -232,13 +263,30 @@ fn mgrcfg_from_sup_run_matches(m: &ArgMatches,
 
     let cfg = ManagerConfig {
         auto_update: m.is_present("AUTO_UPDATE"),
+        // Validated by `valid_update_window`
+        auto_update_window: m.value_of("AUTO_UPDATE_WINDOW")
+                             .and_then(|w| UpdateWindow::from_str(w).ok()),
+        ordered_startup: m.is_present("ORDERED_STARTUP"),
         custom_state_path: None, // remove entirely?
         cache_key_path,
         update_url: bldr_url(m),
         update_channel: channel(m),
         http_disable: m.is_present("HTTP_DISABLE"),
         organization: m.value_of("ORGANIZATION").map(str::to_string),
+        // Validated by `valid_label`
+        labels: m.values_of("LABEL")
+                 .into_iter()
+                 .flatten()
+                 .filter_map(|label| parse_label(label).ok())
+                 .collect(),
         gossip_permanent: m.is_present("PERMANENT_PEER"),
+        // Validated by `possible_values`, which hab only offers zmq in when it is built in
+        gossip_transport: m.value_of("GOSSIP_TRANSPORT")
+                           .and_then(|t| t.parse().ok())
+                           .unwrap_or_default(),
+        lifeguard: m.is_present("LIFEGUARD"),
+        // Validated by `valid_numeric`
+        cross_zone_fanout: m.value_of("CROSS_ZONE_FANOUT").and_then(|f| f.parse().ok()),
         ring_key: get_ring_key(m, &cache_key_path_from_matches(m))?,
         gossip_peers: get_peers(m)?,
         watch_peer_file: m.value_of("PEER_WATCH_FILE").map(str::to_string),
@@ -294,7 +342,8 @@ fn mgrcfg_from_sup_run_matches(m: &ArgMatches,
                 ca_cert_path,
             }
         }),
-        feature_flags
+        feature_flags,
+        event_stream: get_event_stream_config(m)?,
     };
 
     Ok(cfg)
@@ -303,6 +352,17 @@ fn mgrcfg_from_sup_run_matches(m: &ArgMatches,
 // Various CLI Parsing Functions
 ////////////////////////////////////////////////////////////////////////
 
+fn get_event_stream_config(m: &ArgMatches) -> Result<EventStreamConfig> {
+    let mut config = EventStreamConfig::default();
+    if let Some(sink) = m.value_of("EVENT_STREAM_SINK") {
+        config.sink = sink.parse()?;
+    }
+    if let Some(format) = m.value_of("EVENT_STREAM_FORMAT") {
+        config.format = format.parse()?;
+    }
+    Ok(config)
+}
+
 fn get_peers(matches: &ArgMatches) -> Result<Vec<SocketAddr>> {
     // TODO fn: Clean this up--using a for loop doesn't feel good however an iterator was
     // causing a lot of developer/compiler type confusion
@@ -405,6 +465,12 @@ fn get_strategy_from_input(m: &ArgMatches) -> Option<UpdateStrategy> {
      .and_then(|f| UpdateStrategy::from_str(f).ok())
 }
 
+fn get_restart_policy_from_input(m: &ArgMatches) -> Option<RestartPolicy> {
+    // There won't be errors, because we validate with `valid_restart_policy`
+    m.value_of("RESTART_POLICY")
+     .and_then(|p| RestartPolicy::from_str(p).ok())
+}
+
 fn get_binds_from_input(m: &ArgMatches) -> Result<Option<ServiceBindList>> {
     match m.values_of("BIND") {
         Some(bind_strs) => {
@@ -495,6 +561,25 @@ fn update_svc_load_from_input(m: &ArgMatches,
     msg.binding_mode = get_binding_mode_from_input(m).map(|v| v as i32);
     msg.topology = get_topology_from_input(m).map(|v| v as i32);
     msg.update_strategy = get_strategy_from_input(m).map(|v| v as i32);
+    msg.restart_policy = get_restart_policy_from_input(m).map(|v| v as i32);
+    // Numeric values will have already been validated by `valid_numeric`
+    msg.restart_max_attempts = m.value_of("RESTART_MAX_ATTEMPTS")
+                                .and_then(|s| s.parse().ok());
+    msg.restart_backoff_secs = m.value_of("RESTART_BACKOFF").and_then(|s| s.parse().ok());
+    msg.restart_backoff_max_secs = m.value_of("RESTART_BACKOFF_MAX")
+                                    .and_then(|s| s.parse().ok());
+    msg.restart_reset_secs = m.value_of("RESTART_RESET_WINDOW")
+                              .and_then(|s| s.parse().ok());
+    // Validated by `valid_update_batch_size`
+    msg.update_batch_size = m.value_of("UPDATE_BATCH_SIZE").map(ToString::to_string);
+    msg.update_canary_soak_secs = m.value_of("UPDATE_CANARY_SOAK")
+                                   .and_then(|s| s.parse().ok());
+    // Validated by `valid_update_window`
+    msg.update_window = m.value_of("UPDATE_WINDOW").map(ToString::to_string);
+    msg.leader_priority = m.value_of("LEADER_PRIORITY").and_then(|s| s.parse().ok());
+    if m.is_present("STICKY_LEADER") {
+        msg.sticky_leader = Some(true);
+    }
     Ok(())
 }
 
@@ -502,7 +587,10 @@ fn update_svc_load_from_input(m: &ArgMatches,
 mod test {
     use super::*;
     use crate::sup::{config::GossipListenAddr,
+                     event::{EventFormat,
+                             EventSinkConfig},
                      http_gateway};
+    use habitat_butterfly::server::transport::GossipTransport;
     use habitat_common::{locked_env_var,
                          types::ListenCtlAddr};
 
@@ -541,6 +629,39 @@ mod test {
             assert_eq!(config.auto_update, false);
         }
 
+        #[test]
+        fn auto_update_window_should_be_set() {
+            let config = config_from_cmd_vec(vec!["hab-sup",
+                                                  "run",
+                                                  "--auto-update",
+                                                  "--auto-update-window",
+                                                  "* 2-4 * * sat,sun"]);
+            assert_eq!(config.auto_update_window,
+                       Some(UpdateWindow::from_str("* 2-4 * * sat,sun").unwrap()));
+
+            let config = config_from_cmd_str("hab-sup run --auto-update");
+            assert_eq!(config.auto_update_window, None);
+        }
+
+        #[test]
+        fn ordered_startup_should_be_set() {
+            let config = config_from_cmd_str("hab-sup run --ordered-startup");
+            assert_eq!(config.ordered_startup, true);
+
+            let config = config_from_cmd_str("hab-sup run");
+            assert_eq!(config.ordered_startup, false);
+        }
+
+        #[test]
+        fn labels_should_be_set() {
+            let config = config_from_cmd_str("hab-sup run --label zone=us-east-1a --label rack=12");
+            assert_eq!(config.labels.get("zone"), Some(&"us-east-1a".to_string()));
+            assert_eq!(config.labels.get("rack"), Some(&"12".to_string()));
+
+            let config = config_from_cmd_str("hab-sup run");
+            assert!(config.labels.is_empty());
+        }
+
         #[test]
         fn update_url_should_be_set() {
             let config = config_from_cmd_str("hab-sup run -u http://fake.example.url");
@@ -581,6 +702,52 @@ mod test {
             assert_eq!(config.gossip_listen, expected_addr);
         }
 
+        #[test]
+        fn gossip_transport_should_be_set() {
+            let config = config_from_cmd_str("hab-sup run --gossip-transport tcp");
+            assert_eq!(config.gossip_transport, GossipTransport::Tcp);
+        }
+
+        #[test]
+        #[cfg(feature = "zmq-transport")]
+        fn gossip_transport_is_set_to_default_when_not_specified() {
+            let config = config_from_cmd_str("hab-sup run");
+            assert_eq!(config.gossip_transport, GossipTransport::Zmq);
+        }
+
+        #[test]
+        #[cfg(not(feature = "zmq-transport"))]
+        fn gossip_transport_is_tcp_without_zmq() {
+            let config = config_from_cmd_str("hab-sup run");
+            assert_eq!(config.gossip_transport, GossipTransport::Tcp);
+            let cmd_vec = cmd_vec_from_cmd_str("hab-sup run --gossip-transport zmq");
+            assert!(cli().get_matches_from_safe(cmd_vec).is_err());
+        }
+
+        #[test]
+        fn lifeguard_should_be_set() {
+            let config = config_from_cmd_str("hab-sup run --lifeguard");
+            assert!(config.lifeguard);
+        }
+
+        #[test]
+        fn lifeguard_is_disabled_by_default() {
+            let config = config_from_cmd_str("hab-sup run");
+            assert!(!config.lifeguard);
+        }
+
+        #[test]
+        fn cross_zone_fanout_should_be_set() {
+            let config = config_from_cmd_str("hab-sup run --cross-zone-fanout 2");
+            assert_eq!(config.cross_zone_fanout, Some(2));
+        }
+
+        #[test]
+        fn cross_zone_fanout_is_unset_by_default() {
+            let config = config_from_cmd_str("hab-sup run");
+            assert_eq!(config.cross_zone_fanout, None);
+        }
+
         #[test]
         fn http_listen_should_be_set() {
             let config = config_from_cmd_str("hab-sup run --listen-http 2.2.2.2:2222");
@@ -677,6 +844,19 @@ mod test {
             assert_eq!(config.watch_peer_file, None);
         }
 
+        #[test]
+        fn event_stream_should_be_set() {
+            let config = config_from_cmd_str("hab-sup run --event-stream-sink \
+                                              file:/tmp/events.jsonl --event-stream-format \
+                                              protobuf");
+            assert_eq!(config.event_stream.sink,
+                       EventSinkConfig::File(PathBuf::from("/tmp/events.jsonl")));
+            assert_eq!(config.event_stream.format, EventFormat::Protobuf);
+
+            let config = config_from_cmd_str("hab-sup run");
+            assert_eq!(config.event_stream, EventStreamConfig::default());
+        }
+
         #[test]
         fn ring_key_is_set_properly_by_name() {
             let key_cache = TempDir::new().expect("Could not create tempdir");
@@ -22,16 +22,23 @@ use crate::{ctl_gateway::CtlRequest,
                                        ServiceSpec},
                                 DesiredState,
                                 ProcessState},
-                      ManagerState},
+                      ManagerState,
+                      RING_KEY_FILE},
             util};
 use habitat_butterfly as butterfly;
 use habitat_common::{command::package::install::InstallSource,
                      outputln,
+                     rotating_log,
                      templating::package::Pkg,
                      ui::UIWriter};
-use habitat_core::{package::{Identifiable,
+use habitat_core::{crypto::{keys::parse_name_with_rev,
+                            SymKey},
+                   fs as hab_fs,
+                   package::{Identifiable,
                              PackageIdent,
-                             PackageTarget},
+                             PackageIdentConstraint,
+                             PackageTarget,
+                             VersionConstraint},
                    service::ServiceGroup,
                    ChannelIdent};
 use habitat_sup_protocol::{self as protocol,
@@ -40,7 +47,18 @@ use habitat_sup_protocol::{self as protocol,
                                  NetResult}};
 use serde_json;
 use std::{fmt,
-          result};
+          fs::{self,
+               File},
+          io::{self,
+               BufRead,
+               BufReader,
+               Seek,
+               SeekFrom},
+          path::{Path,
+                 PathBuf},
+          result,
+          thread,
+          time::Duration};
 use time::{self,
            Duration as TimeDuration,
            Timespec};
@@ -48,6 +66,9 @@ use toml;
 
 static LOGKEY: &'static str = "CMD";
 
+/// How often `hab svc logs --follow` checks a service's logs for new output.
+const LOG_FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
 pub fn service_cfg(mgr: &ManagerState,
                    req: &mut CtlRequest,
                    opts: protocol::ctl::SvcGetDefaultCfg)
@@ -144,17 +165,19 @@ pub fn service_cfg_set(mgr: &ManagerState,
     outputln!("Setting new configuration version {} for {}",
               version,
               service_group,);
-    let mut client =
-        match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
-                                             mgr.cfg.ring_key.clone())
-        {
-            Ok(client) => client,
-            Err(err) => {
-                outputln!("Failed to connect to own gossip server, {}", err);
-                return Err(net::err(ErrCode::Internal, err.to_string()));
-            }
-        };
-    client.send_service_config(service_group, version, &cfg, is_encrypted)
+    let mut client = match gossip_client(mgr) {
+        Ok(client) => client,
+        Err(err) => {
+            outputln!("Failed to connect to own gossip server, {}", err);
+            return Err(net::err(ErrCode::Internal, err.to_string()));
+        }
+    };
+    client.send_service_config(service_group,
+                               version,
+                               &cfg,
+                               is_encrypted,
+                               opts.signer,
+                               opts.signature)
           .map_err(|e| net::err(ErrCode::Internal, e.to_string()))
           .map(|_| {
               req.reply_complete(net::ok());
@@ -177,17 +200,20 @@ pub fn service_file_put(mgr: &ManagerState,
               version,
               filename,
               service_group,);
-    let mut client =
-        match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
-                                             mgr.cfg.ring_key.clone())
-        {
-            Ok(client) => client,
-            Err(err) => {
-                outputln!("Failed to connect to own gossip server, {}", err);
-                return Err(net::err(ErrCode::Internal, err.to_string()));
-            }
-        };
-    client.send_service_file(service_group, filename, version, &content, is_encrypted)
+    let mut client = match gossip_client(mgr) {
+        Ok(client) => client,
+        Err(err) => {
+            outputln!("Failed to connect to own gossip server, {}", err);
+            return Err(net::err(ErrCode::Internal, err.to_string()));
+        }
+    };
+    client.send_service_file(service_group,
+                             filename,
+                             version,
+                             &content,
+                             is_encrypted,
+                             opts.signer,
+                             opts.signature)
           .map_err(|e| net::err(ErrCode::Internal, e.to_string()))
           .map(|_| {
               req.reply_complete(net::ok());
@@ -207,11 +233,20 @@ pub fn service_load(mgr: &ManagerState,
                            .map(ChannelIdent::from)
                            .unwrap_or_default();
     let force = opts.force.unwrap_or(false);
-    let source = InstallSource::Ident(ident.clone(), PackageTarget::active_target());
+    let target = PackageTarget::active_target();
+    let source = match opts.version_constraint {
+        Some(ref version) => {
+            let version = version.parse::<VersionConstraint>()
+                                 .map_err(|e| net::err(ErrCode::BadPayload, e.to_string()))?;
+            InstallSource::Constraint(PackageIdentConstraint::new(ident.clone(), version), target)
+        }
+        None => InstallSource::Ident(ident.clone(), target),
+    };
     match mgr.cfg.spec_for_ident(source.as_ref()) {
         None => {
             let mut spec = ServiceSpec::default();
             opts.into_spec(&mut spec);
+            check_bind_cycle(mgr, &spec)?;
 
             // We don't have any record of this thing; let's set it up!
             //
@@ -240,6 +275,7 @@ pub fn service_load(mgr: &ManagerState,
             }
 
             opts.into_spec(&mut spec);
+            check_bind_cycle(mgr, &spec)?;
 
             // Only install if we don't have something
             // locally; otherwise you could potentially
@@ -257,6 +293,18 @@ pub fn service_load(mgr: &ManagerState,
     Ok(())
 }
 
+/// With ordered startup, refuse to load a service whose binds would form a cycle with the
+/// services already loaded, since none of them could ever start.
+fn check_bind_cycle(mgr: &ManagerState, spec: &ServiceSpec) -> NetResult<()> {
+    if !mgr.cfg.ordered_startup {
+        return Ok(());
+    }
+    match mgr.cfg.bind_cycle_with(spec) {
+        Some(cycle) => Err(net::err(ErrCode::Conflict, Error::BindCycle(cycle).to_string())),
+        None => Ok(()),
+    }
+}
+
 pub fn service_unload(mgr: &ManagerState,
                       req: &mut CtlRequest,
                       opts: protocol::ctl::SvcUnload,
@@ -341,16 +389,13 @@ pub fn supervisor_depart(mgr: &ManagerState,
                          opts: protocol::ctl::SupDepart)
                          -> NetResult<()> {
     let member_id = opts.member_id.ok_or_else(err_update_client)?;
-    let mut client =
-        match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
-                                             mgr.cfg.ring_key.clone())
-        {
-            Ok(client) => client,
-            Err(err) => {
-                outputln!("Failed to connect to own gossip server, {}", err);
-                return Err(net::err(ErrCode::Internal, err.to_string()));
-            }
-        };
+    let mut client = match gossip_client(mgr) {
+        Ok(client) => client,
+        Err(err) => {
+            outputln!("Failed to connect to own gossip server, {}", err);
+            return Err(net::err(ErrCode::Internal, err.to_string()));
+        }
+    };
     outputln!("Attempting to depart member: {}", member_id);
     match client.send_departure(&member_id) {
         Ok(()) => {
@@ -361,6 +406,73 @@ pub fn supervisor_depart(mgr: &ManagerState,
     }
 }
 
+pub fn supervisor_ring_key_rotate(mgr: &ManagerState,
+                                  req: &mut CtlRequest,
+                                  opts: protocol::ctl::SupRingKeyRotate)
+                                  -> NetResult<()> {
+    use protocol::ctl::sup_ring_key_rotate::Action;
+
+    let action = opts.action
+                     .and_then(Action::from_i32)
+                     .ok_or_else(err_update_client)?;
+    let key = opts.key.ok_or_else(err_update_client)?;
+    let mut ring_keys = mgr.ring_keys.write().expect("Ring keys lock poisoned");
+    let ring = match ring_keys.primary() {
+        Some(primary) => primary.name.clone(),
+        None => {
+            return Err(net::err(ErrCode::NotSupported,
+                                "Supervisor isn't running with a ring key."));
+        }
+    };
+    match action {
+        Action::Add => {
+            // The secret key never travels over the gateway; it has to be imported into the
+            // Supervisor's key cache on its own host first.
+            ring_key_revision(&ring, &key)?;
+            let ring_key = SymKey::get_pair_for(&key, &mgr.cfg.cache_key_path).map_err(|e| {
+                               net::err(ErrCode::NotFound,
+                                        format!("{}. Import it on the Supervisor's host with \
+                                                 `hab ring key import` first.",
+                                                e))
+                           })?;
+            ring_keys.add(ring_key).map_err(ring_key_err)?;
+            outputln!("Decrypting gossip with ring key {}", key);
+            req.info(format!("Added ring key {}", key))?;
+        }
+        Action::Promote => {
+            let rev = ring_key_revision(&ring, &key)?;
+            ring_keys.promote(&rev).map_err(ring_key_err)?;
+            // Keep encrypting with the promoted revision after a restart, rather than going back
+            // to the one given with `--ring`.
+            let path = mgr.cfg.sup_root().join(RING_KEY_FILE);
+            if let Err(err) = hab_fs::atomic_write(&path, &key) {
+                outputln!("Failed to persist promoted ring key in {}, {}",
+                          path.display(),
+                          err);
+            }
+            outputln!("Encrypting gossip with ring key {}", key);
+            req.info(format!("Promoted ring key {}", key))?;
+        }
+        Action::Retire => {
+            let rev = ring_key_revision(&ring, &key)?;
+            ring_keys.retire(&rev).map_err(ring_key_err)?;
+            // Remove the retired revision from the key cache too, otherwise it would be picked
+            // back up the next time the Supervisor starts.
+            if let Ok(path) = SymKey::get_secret_key_path(&key, &mgr.cfg.cache_key_path) {
+                if let Err(err) = fs::remove_file(&path) {
+                    outputln!("Failed to remove retired ring key {}, {}",
+                              path.display(),
+                              err);
+                }
+            }
+            outputln!("No longer decrypting gossip with ring key {}", key);
+            req.info(format!("Retired ring key {}", key))?;
+        }
+    }
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
 pub fn service_status(mgr: &ManagerState,
                       req: &mut CtlRequest,
                       opts: protocol::ctl::SvcStatus)
@@ -401,16 +513,210 @@ pub fn service_status(mgr: &ManagerState,
     Ok(())
 }
 
+pub fn service_logs(mgr: &ManagerState,
+                    req: &mut CtlRequest,
+                    opts: protocol::ctl::SvcLogs)
+                    -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    let spec =
+        mgr.cfg
+           .spec_for_ident(&ident)
+           .ok_or_else(|| net::err(ErrCode::NotFound, format!("Service not loaded, {}", &ident)))?;
+    let service = &spec.ident.name;
+    let logs = vec![LogTail::new(hab_fs::svc_stdout_log_file(service), None),
+                    LogTail::new(hab_fs::svc_stderr_log_file(service), Some("Red"))];
+    let follow = opts.follow.unwrap_or(false);
+
+    // Reading the logs, and following them in particular, takes as long as it takes; do it off
+    // the main thread.
+    let req = req.clone();
+    thread::Builder::new().name(format!("svc-logs-{}", service))
+                          .spawn(move || stream_logs(req, logs, follow))
+                          .map_err(|e| {
+                              net::err(ErrCode::Internal,
+                                       format!("Unable to stream logs for {}, {}", &ident, e))
+                          })?;
+    Ok(())
+}
+
 ////////////////////////////////////////////////////////////////////////
 // Private helper functions
+
+/// Send everything kept of a service's standard output, then of its standard error, and then,
+/// when following, new output to either as it is written until the client goes away.
+///
+/// A client that disconnects while the service is quiet is only noticed the next time there is
+/// output to send it.
+fn stream_logs(mut req: CtlRequest, mut logs: Vec<LogTail>, follow: bool) {
+    for log in logs.iter_mut() {
+        match log.send_history(&mut req) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(err) => {
+                req.reply_complete(net::err(ErrCode::Internal,
+                                            format!("Unable to read {}, {}",
+                                                    log.path.display(),
+                                                    err)));
+                return;
+            }
+        }
+    }
+    if !follow {
+        req.reply_complete(net::ok());
+        return;
+    }
+    loop {
+        thread::sleep(LOG_FOLLOW_INTERVAL);
+        for log in logs.iter_mut() {
+            match log.send_new_lines(&mut req) {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(err) => debug!("Unable to read {}, {}", log.path.display(), err),
+            }
+        }
+    }
+}
+
+/// A service log being streamed to a client, and how far into the live log it has got.
+struct LogTail {
+    path:     PathBuf,
+    color:    Option<&'static str>,
+    position: u64,
+}
+
+impl LogTail {
+    fn new(path: PathBuf, color: Option<&'static str>) -> Self {
+        LogTail { path,
+                  color,
+                  position: 0 }
+    }
+
+    /// Send the rotated copies of the log, oldest first, and then the live log. Returns `false`
+    /// if the client has gone away.
+    fn send_history(&mut self, req: &mut CtlRequest) -> io::Result<bool> {
+        for path in rotating_log::existing_logs(&self.path) {
+            match self.send_lines(req, &path, 0)? {
+                Some(position) => self.position = position,
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Send the lines added to the live log since it was last read. Returns `false` if the
+    /// client has gone away.
+    fn send_new_lines(&mut self, req: &mut CtlRequest) -> io::Result<bool> {
+        let len = match self.path.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(err),
+        };
+        if len < self.position {
+            // The log was rotated; start again at the top of the new one.
+            self.position = 0;
+        }
+        if len == self.position {
+            return Ok(true);
+        }
+        let path = self.path.clone();
+        match self.send_lines(req, &path, self.position)? {
+            Some(position) => {
+                self.position = position;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Send each complete line of `path` from `from` onwards, returning the position after the
+    /// last one sent, or `None` if the client has gone away.
+    fn send_lines(&self, req: &mut CtlRequest, path: &Path, from: u64) -> io::Result<Option<u64>> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(from))?;
+        let mut reader = BufReader::new(file);
+        let mut position = from;
+        let mut line = String::new();
+        // A line without a newline is still being written; it's picked up on the next read.
+        while reader.read_line(&mut line)? > 0 && line.ends_with('\n') {
+            let mut msg = protocol::ctl::ConsoleLine::default();
+            msg.line = line.clone();
+            msg.color = self.color.map(str::to_string);
+            if !req.try_reply_partial(msg) {
+                return Ok(None);
+            }
+            position += line.len() as u64;
+            line.clear();
+        }
+        Ok(Some(position))
+    }
+}
+
 fn err_update_client() -> net::NetErr { net::err(ErrCode::UpdateClient, "client out of date") }
 
+/// A client for our own gossip server, over the transport it listens with.
+fn gossip_client(mgr: &ManagerState) -> butterfly::error::Result<butterfly::client::Client> {
+    butterfly::client::Client::with_transport(&mgr.cfg.gossip_listen.local_addr().to_string(),
+                                              ring_key(mgr),
+                                              mgr.cfg.gossip_transport)
+}
+
+/// The ring key gossip is currently encrypted with.
+fn ring_key(mgr: &ManagerState) -> Option<SymKey> {
+    mgr.ring_keys
+       .read()
+       .expect("Ring keys lock poisoned")
+       .primary()
+       .cloned()
+}
+
+/// The revision of a name-with-revision of the ring key `ring`.
+fn ring_key_revision(ring: &str, name_with_rev: &str) -> NetResult<String> {
+    let (name, rev) = parse_name_with_rev(name_with_rev).map_err(|e| {
+                                                            net::err(ErrCode::BadPayload,
+                                                                     e.to_string())
+                                                        })?;
+    if name != ring {
+        return Err(net::err(ErrCode::InvalidPayload,
+                            format!("{} is not a revision of the ring key {}",
+                                    name_with_rev, ring)));
+    }
+    Ok(rev)
+}
+
+fn ring_key_err(err: butterfly::error::Error) -> net::NetErr {
+    let code = match err {
+        butterfly::error::Error::UnknownRingKeyRevision(_) => ErrCode::NotFound,
+        butterfly::error::Error::RetirePrimaryRingKey(_) => ErrCode::Conflict,
+        _ => ErrCode::InvalidPayload,
+    };
+    net::err(code, err.to_string())
+}
+
 #[derive(Deserialize)]
 struct ServiceStatus {
-    pkg:           Pkg,
-    process:       ProcessStatus,
-    service_group: ServiceGroup,
-    desired_state: DesiredState,
+    pkg:            Pkg,
+    process:        ProcessStatus,
+    service_group:  ServiceGroup,
+    desired_state:  DesiredState,
+    #[serde(default)]
+    rolled_back:    Option<PackageIdent>,
+    #[serde(default)]
+    pending_update: Option<PendingUpdate>,
+}
+
+#[derive(Deserialize)]
+struct PendingUpdate {
+    ident:      PackageIdent,
+    applies_at: Option<i64>,
+}
+
+impl From<PendingUpdate> for protocol::types::PendingUpdate {
+    fn from(other: PendingUpdate) -> Self {
+        let mut proto = protocol::types::PendingUpdate::default();
+        proto.ident = other.ident.into();
+        proto.applies_at = other.applies_at;
+        proto
+    }
 }
 
 impl fmt::Display for ServiceStatus {
@@ -428,6 +734,8 @@ impl From<ServiceStatus> for protocol::types::ServiceStatus {
         proto.process = Some(other.process.into());
         proto.service_group = other.service_group.into();
         proto.desired_state = Some(other.desired_state.into());
+        proto.rolled_back = other.rolled_back.map(Into::into);
+        proto.pending_update = other.pending_update.map(Into::into);
         proto
     }
 }
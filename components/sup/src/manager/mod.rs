@@ -24,6 +24,7 @@ mod self_updater;
 mod service_updater;
 mod spec_dir;
 mod spec_watcher;
+mod start_order;
 mod sys;
 mod user_config_watcher;
 
@@ -35,6 +36,7 @@ use self::{action::{ShutdownSpec,
            service::{ConfigRendering,
                      DesiredState,
                      HealthCheck,
+                     ProcessState,
                      Service,
                      ServiceProxy,
                      ServiceSpec,
@@ -42,9 +44,11 @@ use self::{action::{ShutdownSpec,
            service_updater::ServiceUpdater,
            spec_dir::SpecDir,
            spec_watcher::SpecWatcher,
+           start_order::StartOrder,
            sys::Sys,
            user_config_watcher::UserConfigWatcher};
-use crate::{census::{CensusRing,
+use crate::{census::{self,
+                     CensusRing,
                      CensusRingProxy},
             config::GossipListenAddr,
             ctl_gateway::{self,
@@ -54,8 +58,8 @@ use crate::{census::{CensusRing,
                     Result,
                     SupError},
             event::{self,
-                    EventConnectionInfo,
-                    EventCore},
+                    EventCore,
+                    EventStreamConfig},
             http_gateway,
             VERSION};
 use cpu_time::ProcessTime;
@@ -63,8 +67,13 @@ use futures::{future,
               prelude::*,
               sync::{mpsc as fut_mpsc,
                      oneshot}};
-use habitat_butterfly::{member::Member,
+use habitat_butterfly::{keyring::Keyring,
+                        member::Member,
+                        rumor::{service_config::ServiceConfig as ServiceConfigRumor,
+                                service_file::ServiceFile as ServiceFileRumor},
                         server::{timing::Timing,
+                                 transport::GossipTransport,
+                                 RumorTrust,
                                  ServerProxy,
                                  Suitability},
                         trace::Trace};
@@ -74,7 +83,8 @@ use habitat_common::{outputln,
 #[cfg(unix)]
 use habitat_core::os::{process::Signal,
                        signals::SignalEvent};
-use habitat_core::{crypto::SymKey,
+use habitat_core::{crypto::{keys::parse_name_with_rev,
+                            SymKey},
                    env::{self,
                          Config},
                    fs::FS_ROOT_PATH,
@@ -84,13 +94,15 @@ use habitat_core::{crypto::SymKey,
                    package::{Identifiable,
                              PackageIdent,
                              PackageInstall},
-                   service::ServiceGroup,
+                   service::{ServiceGroup,
+                             UpdateWindow},
                    util::ToI64,
                    ChannelIdent};
 use habitat_launcher_client::{LauncherCli,
                               LAUNCHER_LOCK_CLEAN_ENV,
                               LAUNCHER_PID_ENV};
-use habitat_sup_protocol;
+use habitat_sup_protocol::{self,
+                           butterfly::signed_content};
 use num_cpus;
 #[cfg(unix)]
 use palaver;
@@ -103,7 +115,8 @@ use rustls::{internal::pemfile,
              RootCertStore,
              ServerConfig};
 use serde_json;
-use std::{collections::{HashMap,
+use std::{collections::{BTreeMap,
+                        HashMap,
                         HashSet},
           ffi::OsStr,
           fs::{self,
@@ -141,6 +154,9 @@ use winapi::{shared::minwindef::PDWORD,
 
 const MEMBER_ID_FILE: &str = "MEMBER_ID";
 pub const PROC_LOCK_FILE: &str = "LOCK";
+/// The name-with-revision of the ring key last promoted at runtime, which gossip is encrypted
+/// with in place of the revision given with `--ring`.
+const RING_KEY_FILE: &str = "RING_KEY";
 
 static LOGKEY: &'static str = "MR";
 
@@ -217,22 +233,29 @@ impl FsCfg {
 
 #[derive(Clone, Debug)]
 pub struct ManagerConfig {
-    pub auto_update:       bool,
-    pub custom_state_path: Option<PathBuf>,
-    pub cache_key_path:    PathBuf,
-    pub update_url:        String,
-    pub update_channel:    ChannelIdent,
-    pub gossip_listen:     GossipListenAddr,
-    pub ctl_listen:        ListenCtlAddr,
-    pub http_listen:       http_gateway::ListenAddr,
-    pub http_disable:      bool,
-    pub gossip_peers:      Vec<SocketAddr>,
-    pub gossip_permanent:  bool,
-    pub ring_key:          Option<SymKey>,
-    pub organization:      Option<String>,
-    pub watch_peer_file:   Option<String>,
-    pub tls_config:        Option<TLSConfig>,
-    pub feature_flags:     FeatureFlag,
+    pub auto_update:        bool,
+    pub auto_update_window: Option<UpdateWindow>,
+    pub ordered_startup:    bool,
+    pub custom_state_path:  Option<PathBuf>,
+    pub cache_key_path:     PathBuf,
+    pub update_url:         String,
+    pub update_channel:     ChannelIdent,
+    pub gossip_listen:      GossipListenAddr,
+    pub ctl_listen:         ListenCtlAddr,
+    pub http_listen:        http_gateway::ListenAddr,
+    pub http_disable:       bool,
+    pub gossip_peers:       Vec<SocketAddr>,
+    pub gossip_permanent:   bool,
+    pub gossip_transport:   GossipTransport,
+    pub lifeguard:          bool,
+    pub cross_zone_fanout:  Option<usize>,
+    pub ring_key:           Option<SymKey>,
+    pub organization:       Option<String>,
+    pub labels:             BTreeMap<String, String>,
+    pub watch_peer_file:    Option<String>,
+    pub tls_config:         Option<TLSConfig>,
+    pub feature_flags:      FeatureFlag,
+    pub event_stream:       EventStreamConfig,
 }
 
 #[derive(Clone, Debug)]
@@ -266,6 +289,34 @@ impl ManagerConfig {
         // should we check for Err ?
         ServiceSpec::from_file(&spec_file).ok()
     }
+
+    /// Given a `ServiceGroup`, return the spec of the service loaded into that group, if any.
+    pub fn spec_for_service_group(&self, service_group: &ServiceGroup) -> Option<ServiceSpec> {
+        let organization = self.organization.as_ref().map(String::as_str);
+        let spec_dir = SpecDir::new(self.sup_root().join("specs")).ok()?;
+        for spec in spec_dir.specs() {
+            let app_env = spec.application_environment.as_ref();
+            match ServiceGroup::new(app_env, &spec.ident.name, &spec.group, organization) {
+                Ok(ref sg) if sg == service_group => return Some(spec),
+                _ => continue,
+            }
+        }
+        None
+    }
+
+    /// The services that `spec` would bind to in a cycle if it were loaded alongside the services
+    /// already loaded on this Supervisor, if any. Cycles among the other loaded services that
+    /// `spec` isn't part of don't count.
+    pub fn bind_cycle_with(&self, spec: &ServiceSpec) -> Option<Vec<PackageIdent>> {
+        let organization = self.organization.as_ref().map(String::as_str);
+        let spec_dir = SpecDir::new(self.sup_root().join("specs")).ok()?;
+        let mut specs: Vec<ServiceSpec> = spec_dir.specs()
+                                                  .into_iter()
+                                                  .filter(|s| s.ident != spec.ident)
+                                                  .collect();
+        specs.push(spec.clone());
+        StartOrder::new(&specs, organization).cycle_through(&spec.ident)
+    }
 }
 
 /// Once a formerly-busy service is no longer doing something
@@ -337,6 +388,9 @@ pub struct ManagerState {
     pub cfg: ManagerConfig,
     pub services: Arc<RwLock<HashMap<PackageIdent, Service>>>,
     pub gateway_state: Arc<RwLock<GatewayState>>,
+    /// The revisions of the ring key shared with the Butterfly server, so they can be rotated
+    /// at runtime.
+    pub ring_keys: Arc<RwLock<Keyring>>,
 }
 
 /// All the data that is ultimately served from the Supervisor's HTTP
@@ -395,6 +449,9 @@ pub struct Manager {
     // the different operations.
     busy_services: Arc<Mutex<HashSet<PackageIdent>>>,
     services_need_reconciliation: ReconciliationFlag,
+    /// The bind cycle we last told the user about when starting services in order, so it isn't
+    /// repeated on every reconciliation.
+    reported_bind_cycle:          Option<Vec<PackageIdent>>,
 
     feature_flags: FeatureFlag,
 }
@@ -440,7 +497,10 @@ impl Manager {
         let cfg_static = cfg.clone();
         let self_updater = if cfg.auto_update {
             if current.fully_qualified() {
-                Some(SelfUpdater::new(current, cfg.update_url, cfg.update_channel))
+                Some(SelfUpdater::new(current,
+                                      cfg.update_url,
+                                      cfg.update_channel,
+                                      cfg.auto_update_window))
             } else {
                 warn!("Supervisor version not fully qualified, unable to start self-updater");
                 None
@@ -452,18 +512,36 @@ impl Manager {
                                cfg.gossip_listen,
                                cfg.ctl_listen,
                                cfg.http_listen);
-        let member = Self::load_member(&mut sys, &fs_cfg)?;
+        let mut member = Self::load_member(&mut sys, &fs_cfg)?;
+        member.labels = cfg.labels.clone();
         let services = Arc::new(RwLock::new(HashMap::new()));
 
-        let server = habitat_butterfly::Server::new(sys.gossip_listen(),
-                                                    sys.gossip_listen(),
-                                                    member,
-                                                    Trace::default(),
-                                                    cfg.ring_key,
-                                                    None,
-                                                    Some(&fs_cfg.data_path),
-                                                    Box::new(SuitabilityLookup(services.clone())))?;
+        let mut server =
+            habitat_butterfly::Server::new(sys.gossip_listen(),
+                                           sys.gossip_listen(),
+                                           member,
+                                           Trace::default(),
+                                           cfg.ring_key,
+                                           None,
+                                           Some(&fs_cfg.data_path),
+                                           Box::new(SuitabilityLookup(services.clone())))?;
+        server.set_gossip_transport(cfg.gossip_transport);
+        server.set_lifeguard_enabled(cfg.lifeguard);
+        server.set_cross_zone_fanout(cfg.cross_zone_fanout);
+        let trust = TrustLookup { services:       services.clone(),
+                                  cache_key_path: cfg_static.cache_key_path.clone(), };
+        server.set_rumor_trust(Box::new(trust));
         outputln!("Supervisor Member-ID {}", sys.member_id);
+        let ring_keys = server.ring_keys();
+        if let Some(ref ring_key) = cfg_static.ring_key {
+            // Every revision of the ring key in the cache is still part of a rotation that
+            // hasn't been retired yet, so keep decrypting gossip with all of them.
+            let mut keyring = ring_keys.write().expect("Ring keys lock poisoned");
+            for key in SymKey::get_pairs_for(&ring_key.name, &cfg_static.cache_key_path)? {
+                keyring.add(key)?;
+            }
+            Self::load_promoted_ring_key(&mut keyring, &fs_cfg);
+        }
         for peer_addr in &cfg.gossip_peers {
             let mut peer = Member::default();
             peer.address = format!("{}", peer_addr.ip());
@@ -489,17 +567,17 @@ impl Manager {
             // initialize the stream; everything else will turn into a
             // no-op automatically.
             let ec = EventCore { supervisor_id: sys.member_id.clone(), };
-            // TODO: Determine what the actual connection parameters
-            // should be, and process them at some point before here.
-            event::init_stream(EventConnectionInfo::default(), ec);
+            event::init_stream(cfg.event_stream, ec)?;
         }
 
         Ok(Manager { state: Arc::new(ManagerState { cfg: cfg_static,
                                                     services,
+                                                    ring_keys,
                                                     gateway_state:
                                                         Arc::new(RwLock::new(GatewayState::default())) }),
                      self_updater,
-                     updater: Arc::new(Mutex::new(ServiceUpdater::new(server.clone()))),
+                     updater: Arc::new(Mutex::new(ServiceUpdater::new(server.clone(),
+                                                                      &fs_cfg.data_path))),
                      census_ring: CensusRing::new(sys.member_id.clone()),
                      butterfly: server,
                      launcher,
@@ -514,6 +592,7 @@ impl Manager {
                      http_disable: cfg.http_disable,
                      busy_services: Arc::new(Mutex::new(HashSet::new())),
                      services_need_reconciliation: ReconciliationFlag::new(false),
+                     reported_bind_cycle: None,
                      feature_flags: cfg.feature_flags })
     }
 
@@ -558,6 +637,35 @@ impl Manager {
         Ok(member)
     }
 
+    /// Encrypt gossip with the revision of the ring key that was promoted before the Supervisor
+    /// last stopped, as long as it's still a revision of the ring key we were started with.
+    fn load_promoted_ring_key(keyring: &mut Keyring, fs_cfg: &FsCfg) {
+        let path = fs_cfg.sup_root.join(RING_KEY_FILE);
+        let name_with_rev = match fs::read_to_string(&path) {
+            Ok(name_with_rev) => name_with_rev,
+            Err(_) => return,
+        };
+        let name_with_rev = name_with_rev.trim();
+        let ring = keyring.primary().map(|primary| primary.name.clone());
+        match parse_name_with_rev(name_with_rev) {
+            Ok((ref name, ref rev)) if Some(name) == ring.as_ref() => {
+                match keyring.promote(rev) {
+                    Ok(()) => outputln!("Encrypting gossip with ring key {}", name_with_rev),
+                    Err(err) => {
+                        outputln!("Not encrypting gossip with ring key {}, {}",
+                                  name_with_rev,
+                                  err)
+                    }
+                }
+            }
+            _ => {
+                debug!("Ignoring promoted ring key {} in {}",
+                       name_with_rev,
+                       path.display())
+            }
+        }
+    }
+
     fn clean_dirty_state(fs_cfg: &FsCfg) -> Result<()> {
         let data_path = &fs_cfg.data_path;
         debug!("Cleaning cached health checks");
@@ -602,14 +710,35 @@ impl Manager {
         // back to us. Since we consume and deconstruct the spec in `Service::new()` which
         // `Service::load()` eventually delegates to we just can't have that. We should clean
         // this up in the future.
-        let service = match Service::load(self.sys.clone(),
-                                          spec.clone(),
-                                          self.fs_cfg.clone(),
-                                          self.organization.as_ref().map(|org| &**org),
-                                          self.state.gateway_state.clone())
-        {
-            Ok(service) => {
+        let (rollback, release) = {
+            let updater = self.updater.lock().expect("Updater lock poisoned");
+            (updater.rollback_for(&spec.ident), updater.release_for(&spec.ident))
+        };
+        let organization = self.organization.as_ref().map(|org| &**org);
+        let loaded = match release {
+            // A previous update of this service failed, or an update is
+            // held until its update window opens, so keep running the
+            // release it was rolled back to or was already running.
+            Some(ref release) => {
+                Service::load_release(self.sys.clone(),
+                                      spec.clone(),
+                                      release,
+                                      self.fs_cfg.clone(),
+                                      organization,
+                                      self.state.gateway_state.clone())
+            }
+            None => {
+                Service::load(self.sys.clone(),
+                              spec.clone(),
+                              self.fs_cfg.clone(),
+                              organization,
+                              self.state.gateway_state.clone())
+            }
+        };
+        let service = match loaded {
+            Ok(mut service) => {
                 outputln!("Starting {} ({})", &spec.ident, service.pkg.ident);
+                service.rolled_back = rollback.map(|r| r.from);
                 service
             }
             Err(err) => {
@@ -700,6 +829,8 @@ impl Manager {
         let (mgr_sender, mgr_receiver) = fut_mpsc::unbounded();
         let (ctl_shutdown_tx, ctl_shutdown_rx) = oneshot::channel();
         let (action_sender, action_receiver) = std_mpsc::channel();
+        // The HTTP gateway's write endpoints drive the same actions as the ctl gateway.
+        let http_action_sender = action_sender.clone();
 
         let ctl_handler = CtlAcceptor::new(self.state.clone(),
                                            mgr_receiver,
@@ -756,7 +887,8 @@ impl Manager {
             outputln!("Starting http-gateway on {}", &http_listen_addr);
             http_gateway::Server::run(http_listen_addr,
                                       tls_server_config,
-                                      self.state.gateway_state.clone(),
+                                      self.state.clone(),
+                                      http_action_sender,
                                       http_gateway::GatewayAuthenticationToken::configured_value(),
                                       self.feature_flags,
                                       pair.clone());
@@ -848,6 +980,7 @@ impl Manager {
                 break ShutdownMode::Normal;
             }
             if self.check_for_departure() {
+                event::publish(&event::SupervisorDeparted {});
                 break ShutdownMode::Departed;
             }
 
@@ -1025,8 +1158,17 @@ impl Manager {
                                    .write()
                                    .expect("Services lock is poisoned!");
 
-                for (_ident, svc) in svcs.drain() {
-                    runtime.spawn(self.stop(svc));
+                if self.state.cfg.ordered_startup {
+                    // Stop each stage completely before starting on the next, so services are
+                    // still around while the services that bind to them shut down.
+                    for stage in self.stop_stages(svcs.drain().map(|(_ident, svc)| svc)) {
+                        let stops: Vec<_> = stage.into_iter().map(|svc| self.stop(svc)).collect();
+                        runtime.block_on(future::join_all(stops)).ok();
+                    }
+                } else {
+                    for (_ident, svc) in svcs.drain() {
+                        runtime.spawn(self.stop(svc));
+                    }
                 }
             }
         }
@@ -1062,15 +1204,34 @@ impl Manager {
                                      .services
                                      .write()
                                      .expect("Services lock is poisoned!");
-        let idents_to_restart: Vec<_> = state_services.iter()
+        let idents_to_restart: Vec<_> = state_services.iter_mut()
                                                       .filter_map(|(current_ident, service)| {
-                                                          if let Some(new_ident) =
+                                                          if let Some(rollback) =
+                    updater.check_for_failed_update(&service)
+                {
+                    let service_group = &service.service_group;
+                    outputln!(preamble service_group,
+                              "{} failed its health check; rolling back to {}",
+                              rollback.from,
+                              rollback.to);
+                    event::publish(&event::ServiceUpdateRolledBack { service_group,
+                                                                     ident: &rollback.from,
+                                                                     rolled_back_to:
+                                                                         &rollback.to });
+                    Some(current_ident.clone())
+                } else if let Some(new_ident) =
                     updater.check_for_updated_package(&service, &self.census_ring)
                 {
                     outputln!("Updating from {} to {}", current_ident, new_ident);
+                    let service_group = &service.service_group;
+                    event::publish(&event::ServiceUpdateFound { service_group,
+                                                                ident: current_ident,
+                                                                new_ident: &new_ident });
                     Some(current_ident.clone())
                 } else {
                     trace!("No update found for {}", current_ident);
+                    service.pending_update =
+                        updater.pending_update(&service.spec_ident).cloned();
                     None
                 }
                                                       })
@@ -1254,6 +1415,28 @@ impl Manager {
                                   self.services_need_reconciliation.clone())
     }
 
+    /// Group `services` into stages to stop them in, where each service stops in an earlier stage
+    /// than the services on this Supervisor that it binds to.
+    fn stop_stages<I>(&self, services: I) -> Vec<Vec<Service>>
+        where I: IntoIterator<Item = Service>
+    {
+        let mut services: HashMap<PackageIdent, Service> =
+            services.into_iter()
+                    .map(|svc| (svc.spec_ident.clone(), svc))
+                    .collect();
+        let specs: Vec<ServiceSpec> = services.values().map(Service::to_spec).collect();
+        let organization = self.organization.as_ref().map(String::as_str);
+        let idents: Vec<PackageIdent> = services.keys().cloned().collect();
+        StartOrder::new(&specs, organization).stop_stages(idents)
+                                             .into_iter()
+                                             .map(|stage| {
+                                                 stage.iter()
+                                                      .filter_map(|ident| services.remove(ident))
+                                                      .collect()
+                                             })
+                                             .collect()
+    }
+
     fn stop(&self, service: Service) -> impl Future<Item = (), Error = ()> {
         Self::service_stop_future(service,
                                   // TODO (CM): when services can
@@ -1431,12 +1614,77 @@ impl Manager {
         let busy_services = self.busy_services
                                 .lock()
                                 .expect("busy_services lock is poisoned");
-        let on_disk_specs = self.spec_dir
-                                .specs()
-                                .into_iter()
-                                .filter(|s| !busy_services.contains(&s.ident));
+        let all_specs = self.spec_dir.specs();
+        let on_disk_specs = all_specs.iter()
+                                     .filter(|s| !busy_services.contains(&s.ident))
+                                     .cloned();
+
+        let ops = Self::specs_to_operations(currently_running_specs, on_disk_specs);
+        if !self.state.cfg.ordered_startup {
+            return ops;
+        }
+
+        // Hold back any service whose producers on this Supervisor aren't up and healthy yet,
+        // and never start services that bind to each other in a cycle.
+        let organization = self.organization.as_ref().map(String::as_str);
+        let start_order = StartOrder::new(&all_specs, organization);
+        let cycle = start_order.cycle();
+        if let Some(ref idents) = cycle {
+            if self.reported_bind_cycle.as_ref() != Some(idents) {
+                outputln!("Not starting {}: {}",
+                          idents.iter()
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                          Error::BindCycle(idents.clone()));
+            }
+        }
+        self.reported_bind_cycle = cycle.clone();
+
+        let (ops, deferred) =
+            Self::hold_back_unready_starts(ops, &start_order, cycle.as_ref(), |producer| {
+                services.get(producer).map_or(false, |svc| {
+                                          svc.process_state() == ProcessState::Up
+                                          && svc.health_check() == HealthCheck::Ok
+                                      })
+            });
+        if deferred {
+            // Look again once the services being waited on have had a chance to come up.
+            self.services_need_reconciliation.set();
+        }
+        ops
+    }
 
-        Self::specs_to_operations(currently_running_specs, on_disk_specs)
+    /// Drop the starts of the services in `cycle`, and of the services whose producers on this
+    /// Supervisor aren't `up` yet. Returns the operations left, and whether any start is waiting
+    /// on a producer.
+    fn hold_back_unready_starts<F>(ops: Vec<ServiceOperation>,
+                                   start_order: &StartOrder,
+                                   cycle: Option<&Vec<PackageIdent>>,
+                                   up: F)
+                                   -> (Vec<ServiceOperation>, bool)
+        where F: Fn(&PackageIdent) -> bool
+    {
+        let mut deferred = false;
+        let ops = ops.into_iter()
+                     .filter(|op| {
+                         let spec = match op {
+                             ServiceOperation::Start(spec) => spec,
+                             _ => return true,
+                         };
+                         if cycle.map_or(false, |idents| idents.contains(&spec.ident)) {
+                             return false;
+                         }
+                         let ready = start_order.producers(&spec.ident).iter().all(|p| up(p));
+                         if !ready {
+                             debug!("Reconciliation: '{}' waiting on the services it binds to",
+                                    spec.ident);
+                             deferred = true;
+                         }
+                         ready
+                     })
+                     .collect();
+        (ops, deferred)
     }
 
     /// Pure utility function to generate a list of operations to
@@ -1647,6 +1895,80 @@ impl Suitability for SuitabilityLookup {
             .and_then(Service::suitability)
             .unwrap_or(u64::min_value())
     }
+
+    fn leader_priority(&self, service_group: &str) -> u64 {
+        self.0
+            .read()
+            .expect("Services lock is poisoned!")
+            .values()
+            .find(|s| *s.service_group == service_group)
+            .map(Service::leader_priority)
+            .unwrap_or(0)
+    }
+
+    fn sticky_leader(&self, service_group: &str) -> bool {
+        self.0
+            .read()
+            .expect("Services lock is poisoned!")
+            .values()
+            .find(|s| *s.service_group == service_group)
+            .map(Service::sticky_leader)
+            .unwrap_or(false)
+    }
+}
+
+/// Config and file rumors for a service group are only accepted if they're signed by a key that
+/// every loaded service in the group trusts; see `Service::trusts`.
+#[derive(Debug)]
+struct TrustLookup {
+    services:       Arc<RwLock<HashMap<PackageIdent, Service>>>,
+    cache_key_path: PathBuf,
+}
+
+impl TrustLookup {
+    fn trusts(&self,
+              service_group: &ServiceGroup,
+              signer: Option<&String>,
+              signature: Option<&Vec<u8>>,
+              content: &[u8])
+              -> bool {
+        let services = self.services.read().expect("Services lock is poisoned!");
+        let group = services.values()
+                            .filter(|s| s.service_group == *service_group)
+                            .collect::<Vec<_>>();
+        // Save verifying the signature when no service has a say
+        if group.iter().all(|s| s.trusts(None)) {
+            return true;
+        }
+        let signer = census::verified_signer(&self.cache_key_path, signer, signature, content);
+        group.iter().all(|s| s.trusts(signer.as_ref()))
+    }
+}
+
+impl RumorTrust for TrustLookup {
+    fn trusts_service_config(&self, service_config: &ServiceConfigRumor) -> bool {
+        let content = signed_content(&service_config.service_group,
+                                     service_config.incarnation,
+                                     None,
+                                     service_config.encrypted,
+                                     &service_config.config);
+        self.trusts(&service_config.service_group,
+                    service_config.signer.as_ref(),
+                    service_config.signature.as_ref(),
+                    &content)
+    }
+
+    fn trusts_service_file(&self, service_file: &ServiceFileRumor) -> bool {
+        let content = signed_content(&service_file.service_group,
+                                     service_file.incarnation,
+                                     Some(&service_file.filename),
+                                     service_file.encrypted,
+                                     &service_file.body);
+        self.trusts(&service_file.service_group,
+                    service_file.signer.as_ref(),
+                    service_file.signature.as_ref(),
+                    &content)
+    }
 }
 
 fn obtain_process_lock(fs_cfg: &FsCfg) -> Result<()> {
@@ -1807,22 +2129,29 @@ mod test {
     // code, so only implement it under test configuration.
     impl Default for ManagerConfig {
         fn default() -> Self {
-            ManagerConfig { auto_update:       false,
-                            custom_state_path: None,
-                            cache_key_path:    cache_key_path(Some(&*FS_ROOT)),
-                            update_url:        "".to_string(),
-                            update_channel:    ChannelIdent::default(),
-                            gossip_listen:     GossipListenAddr::default(),
-                            ctl_listen:        ListenCtlAddr::default(),
-                            http_listen:       http_gateway::ListenAddr::default(),
-                            http_disable:      false,
-                            gossip_peers:      vec![],
-                            gossip_permanent:  false,
-                            ring_key:          None,
-                            organization:      None,
-                            watch_peer_file:   None,
-                            tls_config:        None,
-                            feature_flags:     FeatureFlag::empty(), }
+            ManagerConfig { auto_update:        false,
+                            auto_update_window: None,
+                            ordered_startup:    false,
+                            custom_state_path:  None,
+                            cache_key_path:     cache_key_path(Some(&*FS_ROOT)),
+                            update_url:         "".to_string(),
+                            update_channel:     ChannelIdent::default(),
+                            gossip_listen:      GossipListenAddr::default(),
+                            ctl_listen:         ListenCtlAddr::default(),
+                            http_listen:        http_gateway::ListenAddr::default(),
+                            http_disable:       false,
+                            gossip_peers:       vec![],
+                            gossip_permanent:   false,
+                            gossip_transport:   GossipTransport::default(),
+                            lifeguard:          false,
+                            cross_zone_fanout:  None,
+                            ring_key:           None,
+                            organization:       None,
+                            labels:             BTreeMap::new(),
+                            watch_peer_file:    None,
+                            tls_config:         None,
+                            feature_flags:      FeatureFlag::empty(),
+                            event_stream:       EventStreamConfig::default(), }
         }
     }
 
@@ -1885,6 +2214,125 @@ mod test {
 
     }
 
+    mod ring_keys {
+        use super::*;
+        use tempfile::TempDir;
+
+        fn keyring(revs: &[&str]) -> Keyring {
+            let mut keys = revs.iter().map(|rev| {
+                                          let mut key =
+                                              SymKey::generate_pair_for_ring("beyonce").unwrap();
+                                          key.rev = rev.to_string();
+                                          key
+                                      });
+            let mut keyring = Keyring::new(keys.next());
+            for key in keys {
+                keyring.add(key).unwrap();
+            }
+            keyring
+        }
+
+        #[test]
+        fn promoted_revisions_outlive_restarts() {
+            let tmpdir = TempDir::new().unwrap();
+            let mut keyring = keyring(&["20190101000000", "20190201000000"]);
+            fs::write(tmpdir.path().join(RING_KEY_FILE),
+                      "beyonce-20190201000000\n").unwrap();
+
+            Manager::load_promoted_ring_key(&mut keyring, &FsCfg::new(tmpdir.path()));
+            assert_eq!(keyring.primary().unwrap().rev, "20190201000000");
+        }
+
+        #[test]
+        fn promoted_revisions_of_other_rings_are_ignored() {
+            let tmpdir = TempDir::new().unwrap();
+            let mut keyring = keyring(&["20190101000000", "20190201000000"]);
+            fs::write(tmpdir.path().join(RING_KEY_FILE), "jayz-20190201000000").unwrap();
+
+            Manager::load_promoted_ring_key(&mut keyring, &FsCfg::new(tmpdir.path()));
+            assert_eq!(keyring.primary().unwrap().rev, "20190101000000");
+        }
+    }
+
+    mod ordered_startup {
+        use super::super::*;
+        use habitat_core::service::ServiceBind;
+        use tempfile::TempDir;
+
+        fn spec(ident: &str, binds: &[&str]) -> ServiceSpec {
+            let mut spec = ServiceSpec::default_for(PackageIdent::from_str(ident).unwrap());
+            spec.binds = binds.iter()
+                              .map(|bind| ServiceBind::from_str(bind).unwrap())
+                              .collect();
+            spec
+        }
+
+        fn ident(ident: &str) -> PackageIdent { PackageIdent::from_str(ident).unwrap() }
+
+        /// A config for a Supervisor whose state lives in `tmpdir`, with `specs` loaded.
+        fn cfg_with_specs(tmpdir: &TempDir, specs: &[ServiceSpec]) -> ManagerConfig {
+            let mut cfg = ManagerConfig::default();
+            cfg.custom_state_path = Some(tmpdir.path().to_path_buf());
+            let specs_path = cfg.sup_root().join("specs");
+            for spec in specs {
+                spec.to_file(specs_path.join(spec.file_name())).unwrap();
+            }
+            cfg
+        }
+
+        #[test]
+        fn starts_wait_for_the_services_they_bind_to() {
+            let app = spec("core/app", &["db:postgres.default"]);
+            let postgres = spec("core/postgres", &[]);
+            let specs = vec![app.clone(), postgres.clone()];
+            let start_order = StartOrder::new(&specs, None);
+            let ops = vec![ServiceOperation::Start(app.clone()),
+                           ServiceOperation::Start(postgres.clone())];
+
+            let (held, deferred) =
+                Manager::hold_back_unready_starts(ops.clone(), &start_order, None, |_| false);
+            assert_eq!(held, vec![ServiceOperation::Start(postgres.clone())]);
+            assert!(deferred);
+
+            let (held, deferred) =
+                Manager::hold_back_unready_starts(ops.clone(), &start_order, None, |producer| {
+                    *producer == ident("core/postgres")
+                });
+            assert_eq!(held, ops);
+            assert!(!deferred);
+        }
+
+        #[test]
+        fn services_in_a_bind_cycle_are_not_started() {
+            let postgres = spec("core/postgres", &["backup:backup.default"]);
+            let backup = spec("core/backup", &["db:postgres.default"]);
+            let other = spec("core/other", &[]);
+            let specs = vec![postgres.clone(), backup.clone(), other.clone()];
+            let start_order = StartOrder::new(&specs, None);
+            let cycle = start_order.cycle();
+            let ops = vec![ServiceOperation::Start(postgres),
+                           ServiceOperation::Start(backup),
+                           ServiceOperation::Start(other.clone())];
+
+            let (held, _) =
+                Manager::hold_back_unready_starts(ops, &start_order, cycle.as_ref(), |_| true);
+            assert_eq!(held, vec![ServiceOperation::Start(other)]);
+        }
+
+        #[test]
+        fn only_bind_cycles_through_the_loaded_service_are_rejected() {
+            let tmpdir = TempDir::new().unwrap();
+            let cfg = cfg_with_specs(&tmpdir,
+                                     &[spec("core/postgres", &["backup:backup.default"]),
+                                       spec("core/backup", &["db:postgres.default"])]);
+
+            assert_eq!(cfg.bind_cycle_with(&spec("core/app", &["db:postgres.default"])),
+                       None);
+            assert_eq!(cfg.bind_cycle_with(&spec("core/backup", &["db:postgres.default"])),
+                       Some(vec![ident("core/backup"), ident("core/postgres")]));
+        }
+    }
+
     mod specs_to_operations {
         //! Testing out the reconciliation of on-disk spec files with
         //! what is currently running.
@@ -25,13 +25,16 @@ use std::{sync::mpsc::{sync_channel,
 use crate::{env,
             util};
 use habitat_common::{command::package::install::InstallSource,
+                     outputln,
                      ui::UI};
 use habitat_core::{package::{PackageIdent,
                              PackageInstall},
+                   service::UpdateWindow,
                    ChannelIdent};
 use time::{Duration as TimeDuration,
            SteadyTime};
 
+static LOGKEY: &str = "SLU";
 pub const SUP_PKG_IDENT: &str = "core/hab-sup";
 const DEFAULT_FREQUENCY: i64 = 60_000;
 const FREQUENCY_ENVVAR: &str = "HAB_SUP_UPDATE_MS";
@@ -41,61 +44,103 @@ pub struct SelfUpdater {
     current:        PackageIdent,
     update_url:     String,
     update_channel: ChannelIdent,
+    update_window:  Option<UpdateWindow>,
+    /// A newer Supervisor that has been installed but is waiting for the update window to open.
+    pending:        Option<PackageInstall>,
 }
 
 // TODO (CM): Want to use the Periodic trait here, but can't due to
 // how things are currently structured (The service updater had a worker)
 
 impl SelfUpdater {
-    pub fn new(current: PackageIdent, update_url: String, update_channel: ChannelIdent) -> Self {
-        let rx = Self::init(current.clone(), update_url.clone(), update_channel.clone());
+    pub fn new(current: PackageIdent,
+               update_url: String,
+               update_channel: ChannelIdent,
+               update_window: Option<UpdateWindow>)
+               -> Self {
+        let rx = Self::init(current.clone(),
+                            update_url.clone(),
+                            update_channel.clone(),
+                            update_window.clone());
         SelfUpdater { rx,
                       current,
                       update_url,
-                      update_channel }
+                      update_channel,
+                      update_window,
+                      pending: None }
     }
 
     /// Spawn a new Supervisor updater thread.
     fn init(current: PackageIdent,
             update_url: String,
-            update_channel: ChannelIdent)
+            update_channel: ChannelIdent,
+            update_window: Option<UpdateWindow>)
             -> Receiver<PackageInstall> {
         let (tx, rx) = sync_channel(0);
         thread::Builder::new().name("self-updater".to_string())
-                              .spawn(move || Self::run(&tx, &current, &update_url, &update_channel))
+                              .spawn(move || {
+                                  Self::run(&tx,
+                                            &current,
+                                            &update_url,
+                                            &update_channel,
+                                            update_window.as_ref())
+                              })
                               .expect("Unable to start self-updater thread");
         rx
     }
 
+    /// Poll for a newer Supervisor, installing it when found. Outside the update window, a newer
+    /// Supervisor is only downloaded, so that restarting the Supervisor before the window opens
+    /// doesn't start it.
     fn run(sender: &SyncSender<PackageInstall>,
            current: &PackageIdent,
            builder_url: &str,
-           channel: &ChannelIdent) {
+           channel: &ChannelIdent,
+           update_window: Option<&UpdateWindow>) {
         debug!("Self updater current package, {}", current);
         // SUP_PKG_IDENT will always parse as a valid PackageIdent,
         // and thus a valid InstallSource
         let install_source: InstallSource = SUP_PKG_IDENT.parse().unwrap();
+        let mut held = None;
         loop {
             let next_check = SteadyTime::now() + TimeDuration::milliseconds(update_frequency());
 
-            match util::pkg::install(// We don't want anything in here to print
-                                     &mut UI::with_sinks(),
-                                     builder_url,
-                                     &install_source,
-                                     channel)
-            {
-                Ok(package) => {
-                    if current < package.ident() {
-                        debug!("Self updater installing newer Supervisor, {}",
-                               package.ident());
-                        sender.send(package).expect("Main thread has gone away!");
-                        break;
-                    } else {
-                        debug!("Supervisor package found is not newer than ours");
+            if let Some(window) = update_window.filter(|window| !window.is_open()) {
+                match util::pkg::fetch(&mut UI::with_sinks(), builder_url, &install_source, channel)
+                {
+                    Ok(ident) => {
+                        if current < &ident && held.as_ref() != Some(&ident) {
+                            outputln!("Supervisor update to {} deferred until the update window \
+                                       '{}' opens",
+                                      ident,
+                                      window);
+                            held = Some(ident);
+                        }
+                    }
+                    Err(err) => {
+                        warn!("Self updater failed to download latest, {}", err);
                     }
                 }
-                Err(err) => {
-                    warn!("Self updater failed to get latest, {}", err);
+            } else {
+                match util::pkg::install(// We don't want anything in here to print
+                                         &mut UI::with_sinks(),
+                                         builder_url,
+                                         &install_source,
+                                         channel)
+                {
+                    Ok(package) => {
+                        if current < package.ident() {
+                            debug!("Self updater installing newer Supervisor, {}",
+                                   package.ident());
+                            sender.send(package).expect("Main thread has gone away!");
+                            break;
+                        } else {
+                            debug!("Supervisor package found is not newer than ours");
+                        }
+                    }
+                    Err(err) => {
+                        warn!("Self updater failed to get latest, {}", err);
+                    }
                 }
             }
 
@@ -106,19 +151,43 @@ impl SelfUpdater {
         }
     }
 
+    /// Returns the newer Supervisor package once it has been installed and the update window, if
+    /// any, is open.
     pub fn updated(&mut self) -> Option<PackageInstall> {
+        if self.pending.is_some() {
+            return self.take_pending();
+        }
         match self.rx.try_recv() {
-            Ok(package) => Some(package),
+            Ok(package) => {
+                if let Some(ref window) = self.update_window {
+                    if !window.is_open() {
+                        outputln!("Supervisor update to {} deferred until the update window '{}' \
+                                   opens",
+                                  package.ident(),
+                                  window);
+                    }
+                }
+                self.pending = Some(package);
+                self.take_pending()
+            }
             Err(TryRecvError::Empty) => None,
             Err(TryRecvError::Disconnected) => {
                 debug!("Self updater has died, restarting...");
                 self.rx = Self::init(self.current.clone(),
                                      self.update_url.clone(),
-                                     self.update_channel.clone());
+                                     self.update_channel.clone(),
+                                     self.update_window.clone());
                 None
             }
         }
     }
+
+    fn take_pending(&mut self) -> Option<PackageInstall> {
+        match self.update_window {
+            Some(ref window) if !window.is_open() => None,
+            _ => self.pending.take(),
+        }
+    }
 }
 
 fn update_frequency() -> i64 {
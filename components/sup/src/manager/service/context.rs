@@ -52,7 +52,8 @@ use crate::{census::{CensusGroup,
                      CensusRing,
                      ElectionStatus,
                      MemberId},
-            manager::Sys};
+            manager::{service::HealthCheck,
+                      Sys}};
 use habitat_butterfly::rumor::service::SysInfo;
 use habitat_common::templating::{config::Cfg,
                                  package::{Env,
@@ -64,7 +65,8 @@ use serde::{ser::SerializeMap,
             Serialize,
             Serializer};
 use std::{borrow::Cow,
-          collections::HashMap,
+          collections::{BTreeMap,
+                        HashMap},
           net::IpAddr,
           path::PathBuf,
           result};
@@ -370,7 +372,7 @@ impl<'a> Binds<'a> {
         let mut map = HashMap::default();
         for bind in bindings {
             if let Some(group) = census.census_group_for(&bind.service_group()) {
-                map.insert(bind.name().to_string(), BindGroup::new(group));
+                map.insert(bind.name().to_string(), BindGroup::new(group, bind));
             }
         }
         Binds(map)
@@ -385,11 +387,18 @@ struct BindGroup<'a> {
 }
 
 impl<'a> BindGroup<'a> {
-    fn new(group: &'a CensusGroup) -> Self {
-        BindGroup { first:   select_first(group),
-                    leader:  group.leader().map(|m| SvcMember::from_census_member(m)),
+    /// Only members whose Supervisors carry all of the bind's labels
+    /// are exposed, so a group whose leader doesn't carry them has no
+    /// `leader` in the bind.
+    fn new(group: &'a CensusGroup, bind: &ServiceBind) -> Self {
+        let selected = |m: &&CensusMember| bind.selects(&m.labels);
+        let leader = group.leader().filter(selected);
+        BindGroup { first:   leader.or_else(|| group.members().find(selected))
+                                   .map(SvcMember::from_census_member),
+                    leader:  leader.map(SvcMember::from_census_member),
                     members: group.active_members()
-                                  .map(|m| SvcMember::from_census_member(m))
+                                  .filter(selected)
+                                  .map(SvcMember::from_census_member)
                                   .collect(), }
     }
 }
@@ -410,6 +419,7 @@ struct SvcMember<'a> {
     group: Cow<'a, String>,
     org: Cow<'a, Option<String>>,
     persistent: Cow<'a, bool>,
+    labels: Cow<'a, BTreeMap<String, String>>,
     leader: Cow<'a, bool>,
     follower: Cow<'a, bool>,
     update_leader: Cow<'a, bool>,
@@ -425,6 +435,8 @@ struct SvcMember<'a> {
     suspect: Cow<'a, bool>,
     confirmed: Cow<'a, bool>,
     departed: Cow<'a, bool>,
+    health_check: Cow<'a, Option<HealthCheck>>,
+    health_check_since: Cow<'a, Option<i64>>,
     cfg: Cow<'a, toml::value::Table>,
 }
 
@@ -438,6 +450,7 @@ impl<'a> SvcMember<'a> {
                     group: Cow::Borrowed(&c.group),
                     org: Cow::Borrowed(&c.org),
                     persistent: Cow::Borrowed(&c.persistent),
+                    labels: Cow::Borrowed(&c.labels),
                     leader: Cow::Borrowed(&c.leader),
                     follower: Cow::Borrowed(&c.follower),
                     update_leader: Cow::Borrowed(&c.update_leader),
@@ -459,6 +472,9 @@ impl<'a> SvcMember<'a> {
                     confirmed: Cow::Owned(c.confirmed()),
                     departed:  Cow::Owned(c.departed()),
 
+                    health_check:       Cow::Borrowed(&c.health_check),
+                    health_check_since: Cow::Borrowed(&c.health_check_since),
+
                     cfg: Cow::Borrowed(&c.cfg), }
     }
 }
@@ -496,6 +512,7 @@ impl<'a> Serialize for SvcMember<'a> {
         // it's a boolean ("permanent", because this is actually the
         // permanent peer status of this member)
         map.serialize_entry("persistent", &self.persistent)?;
+        map.serialize_entry("labels", &self.labels)?;
         // TODO (CM): add an "is_leader" field to make it clear it's a boolean
         map.serialize_entry("leader", &self.leader)?;
         // TODO (CM): is_follower
@@ -524,6 +541,9 @@ impl<'a> Serialize for SvcMember<'a> {
         map.serialize_entry("confirmed", &self.confirmed)?;
         map.serialize_entry("departed", &self.departed)?;
 
+        map.serialize_entry("health_check", &self.health_check)?;
+        map.serialize_entry("health_check_since", &self.health_check_since)?;
+
         map.serialize_entry("cfg", &self.cfg)?;
 
         map.end()
@@ -645,6 +665,7 @@ two = 2
                     group: Cow::Owned("default".into()),
                     org: Cow::Owned(None),
                     persistent: Cow::Owned(true),
+                    labels: Cow::Owned(BTreeMap::new()),
                     leader: Cow::Owned(false),
                     follower: Cow::Owned(false),
                     update_leader: Cow::Owned(false),
@@ -660,6 +681,8 @@ two = 2
                     suspect: Cow::Owned(false),
                     confirmed: Cow::Owned(false),
                     departed: Cow::Owned(false),
+                    health_check: Cow::Owned(Some(HealthCheck::Ok)),
+                    health_check_since: Cow::Owned(Some(1_546_300_800)),
                     cfg: Cow::Owned(toml::value::Table::new()), }
     }
 
@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::fmt;
+use crate::error::{Error,
+                   SupError};
+use std::{fmt,
+          str::FromStr};
+
+static LOGKEY: &'static str = "HC";
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 pub enum HealthCheck {
@@ -49,3 +54,17 @@ impl fmt::Display for HealthCheck {
         write!(f, "{}", msg)
     }
 }
+
+impl FromStr for HealthCheck {
+    type Err = SupError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_ref() {
+            "ok" => Ok(HealthCheck::Ok),
+            "warning" => Ok(HealthCheck::Warning),
+            "critical" => Ok(HealthCheck::Critical),
+            "unknown" => Ok(HealthCheck::Unknown),
+            _ => Err(sup_error!(Error::BadHealthCheck(value.to_string()))),
+        }
+    }
+}
@@ -22,7 +22,8 @@
 //! `std::process::Command`, we could consider it. In the meantime,
 //! this seems to do the trick.
 
-use super::{hook_timer,
+use super::{hook_exit_value,
+            hook_timer,
             spawned_future::SpawnedFuture,
             Pkg};
 use crate::error::SupError;
@@ -31,13 +32,15 @@ use futures::{sync::oneshot,
 use habitat_common::templating::hooks::Hook;
 use habitat_core::service::ServiceGroup;
 use std::{sync::Arc,
-          thread};
+          thread,
+          time::Duration};
 
 pub struct HookRunner<H: Hook + Sync> {
     hook:          Arc<H>,
     service_group: ServiceGroup,
     pkg:           Pkg,
     passwd:        Option<String>,
+    timeout:       Option<Duration>,
 }
 
 impl<H> HookRunner<H> where H: Hook + Sync
@@ -45,12 +48,14 @@ impl<H> HookRunner<H> where H: Hook + Sync
     pub fn new(hook: Arc<H>,
                service_group: ServiceGroup,
                pkg: Pkg,
-               passwd: Option<String>)
+               passwd: Option<String>,
+               timeout: Option<Duration>)
                -> HookRunner<H> {
         HookRunner { hook,
                      service_group,
                      pkg,
-                     passwd }
+                     passwd,
+                     timeout }
     }
 }
 impl<H: Hook + Sync + 'static> IntoFuture for HookRunner<H> {
@@ -64,16 +69,15 @@ impl<H: Hook + Sync + 'static> IntoFuture for HookRunner<H> {
         // TODO (CM): Consider using a short abbreviation for the hook
         // name in the thread name (e.g. "HC" for "health_check", "I"
         // for "init", etc.
-
-        // TODO (CM): May want to consider adding a configurable
-        // timeout to how long this hook is allowed to run.
         let handle_result =
             thread::Builder::new().name(format!("{}-{}", H::file_name(), self.service_group))
                                   .spawn(move || {
                                       let _timer = hook_timer(H::file_name());
-                                      let exit_value = self.hook.run(&self.service_group,
-                                                                     &self.pkg,
-                                                                     self.passwd.as_ref());
+                                      let outcome = self.hook.run(&self.service_group,
+                                                                  &self.pkg,
+                                                                  self.passwd.as_ref(),
+                                                                  self.timeout);
+                                      let exit_value = hook_exit_value(H::file_name(), outcome);
                                       tx.send(exit_value)
                                         .expect("Couldn't send oneshot signal from HookRunner: \
                                                  receiver went away");
@@ -17,6 +17,7 @@ use habitat_common::{outputln,
                      templating::{hooks::{self,
                                           ExitCode,
                                           Hook,
+                                          HookOutcome,
                                           HookOutput,
                                           RenderPair},
                                   package::Pkg,
@@ -30,7 +31,8 @@ use std::{self,
           io::prelude::*,
           path::{Path,
                  PathBuf},
-          sync::Arc};
+          sync::Arc,
+          time::Duration};
 
 static LOGKEY: &'static str = "HK";
 
@@ -77,6 +79,8 @@ impl Hook for HealthCheckHook {
 
     fn file_name() -> &'static str { "health-check" }
 
+    fn timed_out_value() -> Self::ExitValue { health::HealthCheck::Critical }
+
     fn new(package_name: &str, pair: RenderPair) -> Self {
         HealthCheckHook { render_pair:     pair,
                           stdout_log_path: hooks::stdout_log_path::<Self>(package_name),
@@ -173,7 +177,12 @@ impl Hook for RunHook {
                   stderr_log_path: hooks::stderr_log_path::<Self>(package_name), }
     }
 
-    fn run<T>(&self, _: &str, _: &Pkg, _: Option<T>) -> Self::ExitValue
+    fn run<T>(&self,
+              _: &str,
+              _: &Pkg,
+              _: Option<T>,
+              _: Option<Duration>)
+              -> HookOutcome<Self::ExitValue>
         where T: ToString
     {
         panic!("The run hook is a an exception to the lifetime of a service. It should only be \
@@ -37,15 +37,21 @@ use self::{context::RenderContext,
 pub use self::{health::HealthCheck,
                hooks::HealthCheckHook,
                spec::{DesiredState,
+                      HookTimeouts,
                       IntoServiceSpec,
-                      ServiceSpec}};
+                      LogRotation,
+                      RestartConfig,
+                      ServiceSpec,
+                      UpdateRollout}};
 use crate::{census::{CensusGroup,
                      CensusRing,
                      ElectionStatus,
+                     MemberId,
                      ServiceFile},
             error::{Error,
                     Result,
                     SupError},
+            event,
             manager::{action::ShutdownSpec,
                       FsCfg,
                       GatewayState,
@@ -61,26 +67,33 @@ pub use habitat_common::templating::{config::{Cfg,
                                                PkgProxy}};
 use habitat_common::{outputln,
                      templating::{config::CfgRenderer,
-                                  hooks::Hook}};
-use habitat_core::{crypto::hash,
+                                  hooks::{Hook,
+                                          HookOutcome}}};
+use habitat_core::{crypto::{hash,
+                            keys::parse_name_with_rev},
                    fs::{atomic_write,
                         svc_hooks_path,
                         SvcDir,
                         FS_ROOT_PATH},
                    package::{metadata::Bind,
+                             Identifiable,
                              PackageIdent,
-                             PackageInstall},
+                             PackageInstall,
+                             VersionConstraint},
                    service::{HealthCheckInterval,
                              ServiceBind,
-                             ServiceGroup},
+                             ServiceGroup,
+                             UpdateWindow},
                    ChannelIdent};
 use habitat_launcher_client::LauncherCli;
 use habitat_sup_protocol::types::BindingMode;
 pub use habitat_sup_protocol::types::{ProcessState,
+                                      RestartPolicy,
                                       Topology,
                                       UpdateStrategy};
 use prometheus::{HistogramTimer,
-                 HistogramVec};
+                 HistogramVec,
+                 IntCounterVec};
 use serde::{ser::SerializeStruct,
             Serialize,
             Serializer};
@@ -106,6 +119,15 @@ lazy_static! {
         register_histogram_vec!("hab_sup_hook_duration_seconds",
                                 "The time it takes for a hook to run",
                                 &["hook"]).unwrap();
+    static ref HOOK_TIMEOUTS: IntCounterVec =
+        register_int_counter_vec!("hab_sup_hook_timeouts_total",
+                                  "The number of hooks killed for exceeding their timeout",
+                                  &["hook"]).unwrap();
+    static ref UNTRUSTED_UPDATES: IntCounterVec =
+        register_int_counter_vec!("hab_sup_untrusted_updates_total",
+                                  "The number of service config and file updates dropped for not \
+                                   being signed by a trusted key",
+                                  &["service_group", "kind"]).unwrap();
 }
 
 /// When evaluating whether a particular service group can satisfy a
@@ -138,18 +160,36 @@ pub struct Service {
     pub desired_state:       DesiredState,
     pub spec_file:           PathBuf,
     pub spec_ident:          PackageIdent,
+    /// The range of versions of the spec's package this service may run.
+    pub version_constraint:  Option<VersionConstraint>,
     pub topology:            Topology,
     pub update_strategy:     UpdateStrategy,
+    pub update_rollout:      UpdateRollout,
+    pub update_window:       Option<UpdateWindow>,
     pub cfg:                 Cfg,
     pub pkg:                 Pkg,
     pub sys:                 Arc<Sys>,
     pub initialized:         bool,
     pub user_config_updated: bool,
+    /// The release this service was rolled back from after it failed
+    /// its health check following an update.
+    pub rolled_back:         Option<PackageIdent>,
+    /// A release found by the update strategy that is waiting for
+    /// the update window to open.
+    pub pending_update:      Option<PackageIdent>,
 
     #[serde(skip_serializing)]
     config_renderer: CfgRenderer,
     health_check: HealthCheck,
+    /// When `health_check` last changed, in seconds since the Unix epoch.
+    health_check_since: Option<i64>,
     last_election_status: ElectionStatus,
+    /// The leader of the last finished election this service saw.
+    last_leader_id: Option<MemberId>,
+    /// Ranks this member in leader elections; see `leader_priority`.
+    leader_priority: u64,
+    /// Whether this member stays leader through re-elections; see `sticky_leader`.
+    sticky_leader: bool,
     needs_reload: bool,
     needs_reconfiguration: bool,
     /// The mapping of bind name to a service group, specified by the
@@ -186,6 +226,15 @@ pub struct Service {
     supervisor: Supervisor,
     svc_encrypted_password: Option<String>,
     health_check_interval: HealthCheckInterval,
+    restart_config: RestartConfig,
+    log_rotation: LogRotation,
+    hook_timeouts: HookTimeouts,
+    /// The signing keys whose config and file updates are applied; see `trusts`.
+    trusted_signers: Vec<String>,
+    /// The latest config incarnation dropped for not being signed by a trusted key, so that it
+    /// is only dropped once.
+    #[serde(skip_serializing)]
+    untrusted_config_incarnation: u64,
 
     #[serde(skip_serializing)]
     /// Whether a service's default configuration changed on a package
@@ -220,16 +269,22 @@ impl Service {
                      channel: spec.channel,
                      desired_state: spec.desired_state,
                      health_check: HealthCheck::default(),
+                     health_check_since: None,
                      hooks: HookTable::load(&pkg.name,
                                             &hooks_root,
                                             svc_hooks_path(&service_group.service())),
                      initialized: false,
                      last_election_status: ElectionStatus::None,
+                     last_leader_id: None,
                      needs_reload: false,
                      needs_reconfiguration: false,
                      user_config_updated: false,
+                     rolled_back: None,
+                     pending_update: None,
                      manager_fs_cfg,
-                     supervisor: Supervisor::new(&service_group),
+                     supervisor: Supervisor::new(&service_group,
+                                                 spec.restart,
+                                                 spec.log_rotation),
                      pkg,
                      service_group,
                      binds: spec.binds,
@@ -237,13 +292,23 @@ impl Service {
                      unsatisfied_binds: HashSet::new(),
                      binding_mode: spec.binding_mode,
                      spec_ident: spec.ident,
+                     version_constraint: spec.version_constraint,
                      spec_file,
                      topology: spec.topology,
+                     leader_priority: spec.leader_priority,
+                     sticky_leader: spec.sticky_leader,
                      update_strategy: spec.update_strategy,
+                     update_rollout: spec.update_rollout,
+                     update_window: spec.update_window,
                      config_from: spec.config_from,
                      scheduled_health_check: Some(Instant::now()),
                      svc_encrypted_password: spec.svc_encrypted_password,
                      health_check_interval: spec.health_check_interval,
+                     restart_config: spec.restart,
+                     log_rotation: spec.log_rotation,
+                     hook_timeouts: spec.hook_timeouts,
+                     trusted_signers: spec.trusted_signers,
+                     untrusted_config_incarnation: 0,
                      defaults_updated: false,
                      gateway_state })
     }
@@ -270,7 +335,30 @@ impl Service {
                 -> Result<Service> {
         // The package for a spec should already be installed.
         let fs_root_path = Path::new(&*FS_ROOT_PATH);
-        let package = PackageInstall::load(&spec.ident, Some(fs_root_path))?;
+        let package = match spec.ident_constraint() {
+            Some(constraint) => PackageInstall::load_matching(&constraint, Some(fs_root_path))?,
+            None => PackageInstall::load(&spec.ident, Some(fs_root_path))?,
+        };
+        Ok(Self::new(sys,
+                     &package,
+                     spec,
+                     manager_fs_cfg,
+                     organization,
+                     gateway_state)?)
+    }
+
+    /// Like `load`, but runs the given installed release of the spec's
+    /// package instead of the latest one. Used when rolling back an
+    /// update.
+    pub fn load_release(sys: Arc<Sys>,
+                        spec: ServiceSpec,
+                        release: &PackageIdent,
+                        manager_fs_cfg: Arc<FsCfg>,
+                        organization: Option<&str>,
+                        gateway_state: Arc<RwLock<GatewayState>>)
+                        -> Result<Service> {
+        let fs_root_path = Path::new(&*FS_ROOT_PATH);
+        let package = PackageInstall::load(release, Some(fs_root_path))?;
         Ok(Self::new(sys,
                      &package,
                      spec,
@@ -345,9 +433,10 @@ impl Service {
             }
         } else {
             let hook = self.hooks.reload.as_ref().unwrap();
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.svc_encrypted_password.as_ref());
+            let exit_code = self.run_hook(hook);
+            if exit_code.0 != 0 {
+                self.hook_failed(hooks::ReloadHook::file_name(), Some(exit_code.0));
+            }
         }
     }
 
@@ -355,8 +444,11 @@ impl Service {
 
     /// Performs updates and executes hooks.
     ///
-    /// Returns `true` if the service was updated.
+    /// Returns `true` if the service was updated or the result of its health check changed,
+    /// either of which its service rumor needs to be gossiped again for.
     pub fn tick(&mut self, census_ring: &CensusRing, launcher: &LauncherCli) -> bool {
+        let health_check = self.health_check;
+
         // We may need to block the service from starting until all
         // its binds are satisfied
         if !self.initialized {
@@ -392,6 +484,12 @@ impl Service {
                 let census_group =
                     census_ring.census_group_for(&self.service_group)
                                .expect("Service Group's census entry missing from list!");
+                if self.last_election_status != census_group.election_status {
+                    let status = census_group.election_status;
+                    event::publish(&event::ElectionStatusChanged { service_group:
+                                                                       &self.service_group,
+                                                                   status });
+                }
                 match census_group.election_status {
                     ElectionStatus::None => {
                         if self.last_election_status != census_group.election_status {
@@ -426,6 +524,16 @@ impl Service {
                                       leader_id.to_string());
                             self.last_election_status = census_group.election_status;
                         }
+                        if self.last_leader_id.as_ref() != Some(leader_id) {
+                            event::publish(&event::LeaderChanged { service_group:
+                                                                       &self.service_group,
+                                                                   previous_leader_id:
+                                                                       self.last_leader_id
+                                                                           .as_ref()
+                                                                           .map(String::as_str),
+                                                                   leader_id });
+                            self.last_leader_id = Some(leader_id.clone());
+                        }
                         self.execute_hooks(launcher)
                     }
                 }
@@ -435,11 +543,21 @@ impl Service {
             self.schedule_health_check_at_next_tick();
         }
 
-        svc_updated
+        svc_updated || self.health_check != health_check
+    }
+
+    /// Whether this service may run the given release of its package: any release, unless its
+    /// spec constrains the version.
+    pub fn allows_release(&self, release: &PackageIdent) -> bool {
+        match self.version_constraint {
+            Some(ref constraint) => release.version().map_or(false, |v| constraint.matches(v)),
+            None => true,
+        }
     }
 
     pub fn to_spec(&self) -> ServiceSpec {
         let mut spec = ServiceSpec::default_for(self.spec_ident.clone());
+        spec.version_constraint = self.version_constraint.clone();
         spec.group = self.service_group.group().to_string();
         if let Some(appenv) = self.service_group.application_environment() {
             spec.application_environment = Some(appenv)
@@ -447,7 +565,11 @@ impl Service {
         spec.bldr_url = self.bldr_url.clone();
         spec.channel = self.channel.clone();
         spec.topology = self.topology;
+        spec.leader_priority = self.leader_priority;
+        spec.sticky_leader = self.sticky_leader;
         spec.update_strategy = self.update_strategy;
+        spec.update_rollout = self.update_rollout;
+        spec.update_window = self.update_window.clone();
         spec.binds = self.binds.clone();
         spec.binding_mode = self.binding_mode;
         spec.config_from = self.config_from.clone();
@@ -455,6 +577,10 @@ impl Service {
             spec.svc_encrypted_password = Some(password.clone())
         }
         spec.health_check_interval = self.health_check_interval;
+        spec.restart = self.restart_config;
+        spec.log_rotation = self.log_rotation;
+        spec.hook_timeouts = self.hook_timeouts;
+        spec.trusted_signers = self.trusted_signers.clone();
         spec
     }
 
@@ -514,7 +640,14 @@ impl Service {
 
             if bind_is_unsatisfied {
                 // TODO (CM): use Entry API to clone only when necessary
-                self.unsatisfied_binds.insert((bind).clone())
+                if self.unsatisfied_binds.insert((bind).clone()) {
+                    event::publish(&event::BindStatusChanged { service_group:
+                                                                   &self.service_group,
+                                                               bind:               bind.name(),
+                                                               bind_service_group:
+                                                                   bind.service_group(),
+                                                               satisfied:          false, });
+                }
             } else if self.unsatisfied_binds.remove(bind) {
                 // We'll log if the bind was previously
                 // unsatisfied, but now it is satisfied.
@@ -522,10 +655,11 @@ impl Service {
                               "The group '{}' satisfies the `{}` bind",
                               bind.service_group(),
                               bind.name());
-                true
-            } else {
-                false
-            };
+                event::publish(&event::BindStatusChanged { service_group:      &self.service_group,
+                                                           bind:               bind.name(),
+                                                           bind_service_group: bind.service_group(),
+                                                           satisfied:          true, });
+            }
         }
     }
 
@@ -538,7 +672,9 @@ impl Service {
         match census_ring.census_group_for(service_bind.service_group()) {
             None => BindStatus::NotPresent,
             Some(group) => {
-                if group.active_members().count() == 0 {
+                if !group.active_members()
+                         .any(|member| service_bind.selects(&member.labels))
+                {
                     BindStatus::Empty
                 } else {
                     match self.unsatisfied_bind_exports(group, service_bind.name()) {
@@ -598,7 +734,13 @@ impl Service {
     /// Updates the process state of the service's supervisor
     fn check_process(&mut self) -> bool { self.supervisor.check_process() }
 
-    fn process_down(&self) -> bool { self.supervisor.state == ProcessState::Down }
+    fn process_down(&self) -> bool { self.supervisor.state != ProcessState::Up }
+
+    /// The state of the service's process, as of the last check.
+    pub fn process_state(&self) -> ProcessState { self.supervisor.state }
+
+    /// The result of the service's most recent health check.
+    pub fn health_check(&self) -> HealthCheck { self.health_check }
 
     /// Updates the service configuration with data from a census group if the census group has
     /// newer data than the current configuration.
@@ -607,11 +749,25 @@ impl Service {
     fn update_gossip(&mut self, census_group: &CensusGroup) -> bool {
         match census_group.service_config {
             Some(ref config) => {
-                if config.incarnation <= self.cfg.gossip_incarnation {
+                if config.incarnation <= self.cfg.gossip_incarnation
+                   || config.incarnation <= self.untrusted_config_incarnation
+                {
+                    return false;
+                }
+                if !self.trusts(config.signer.as_ref()) {
+                    outputln!(preamble self.service_group,
+                              "Dropping configuration version {}, it is not signed by a \
+                               trusted key",
+                              config.incarnation);
+                    self.untrusted_config_incarnation = config.incarnation;
+                    UNTRUSTED_UPDATES.with_label_values(&[self.service_group.as_ref(), "config"])
+                                     .inc();
                     return false;
                 }
                 self.cfg
                     .set_gossip(config.incarnation, config.value.clone());
+                event::publish(&event::ServiceConfigApplied { service_group: &self.service_group,
+                                                              incarnation:   config.incarnation, });
                 true
             }
             None => false,
@@ -681,6 +837,9 @@ impl Service {
                                           self.sys.as_sys_info().clone(),
                                           exported);
         rumor.incarnation = incarnation;
+        rumor.rolled_back = self.rolled_back.as_ref().map(ToString::to_string);
+        rumor.health_check = Some(self.health_check.to_string());
+        rumor.health_check_since = self.health_check_since;
         rumor
     }
 
@@ -696,9 +855,10 @@ impl Service {
         outputln!(preamble self.service_group, "Initializing");
         self.initialized = true;
         if let Some(ref hook) = self.hooks.init {
-            self.initialized = hook.run(&self.service_group,
-                                        &self.pkg,
-                                        self.svc_encrypted_password.as_ref())
+            self.initialized = self.run_hook(hook);
+            if !self.initialized {
+                self.hook_failed(hooks::InitHook::file_name(), None);
+            }
         }
     }
 
@@ -708,9 +868,10 @@ impl Service {
 
         self.needs_reconfiguration = false;
         if let Some(ref hook) = self.hooks.reconfigure {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.svc_encrypted_password.as_ref());
+            let exit_code = self.run_hook(hook);
+            if exit_code.0 != 0 {
+                self.hook_failed(hooks::ReconfigureHook::file_name(), Some(exit_code.0));
+            }
         }
     }
 
@@ -718,24 +879,52 @@ impl Service {
         let _timer = hook_timer("post-run");
 
         if let Some(ref hook) = self.hooks.post_run {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.svc_encrypted_password.as_ref());
+            let exit_code = self.run_hook(hook);
+            if exit_code.0 != 0 {
+                self.hook_failed(hooks::PostRunHook::file_name(), Some(exit_code.0));
+            }
         }
     }
 
+    /// Run one of the service's hooks, killing it if it outlives its timeout.
+    fn run_hook<H: Hook>(&self, hook: &H) -> H::ExitValue {
+        let outcome = hook.run(&self.service_group,
+                               &self.pkg,
+                               self.svc_encrypted_password.as_ref(),
+                               self.hook_timeouts.for_hook(H::file_name()));
+        hook_exit_value(H::file_name(), outcome)
+    }
+
+    fn hook_failed(&self, hook: &'static str, exit_code: Option<i32>) {
+        event::publish(&event::HookFailed { service_group: &self.service_group,
+                                            hook,
+                                            exit_code });
+    }
+
     // This hook method looks different from all the others because
     // it's the only one that runs async right now.
     fn post_stop(&self) -> Option<hook_runner::HookRunner<hooks::PostStopHook>> {
+        let timeout = self.hook_timeouts.for_hook(hooks::PostStopHook::file_name());
         self.hooks.post_stop.as_ref().map(|hook| {
                                          hook_runner::HookRunner::new(Arc::clone(&hook),
                                                                       self.service_group.clone(),
                                                                       self.pkg.clone(),
                                                                       self.svc_encrypted_password
-                                                                          .clone())
+                                                                          .clone(),
+                                                                      timeout)
                                      })
     }
 
+    /// The priority this member stands for leader with, ranked above its suitability.
+    pub fn leader_priority(&self) -> u64 { self.leader_priority }
+
+    /// Whether this member, if it led the last election, should keep leading through the next
+    /// one. A leader whose service isn't healthy gives way, so that a failing leader doesn't
+    /// hold on to the role.
+    pub fn sticky_leader(&self) -> bool {
+        self.sticky_leader && self.health_check == HealthCheck::Ok
+    }
+
     pub fn suitability(&self) -> Option<u64> {
         let _timer = hook_timer("suitability");
 
@@ -743,11 +932,10 @@ impl Service {
             return None;
         }
 
-        self.hooks.suitability.as_ref().and_then(|hook| {
-                                           hook.run(&self.service_group,
-                                                    &self.pkg,
-                                                    self.svc_encrypted_password.as_ref())
-                                       })
+        self.hooks
+            .suitability
+            .as_ref()
+            .and_then(|hook| self.run_hook(hook))
     }
 
     fn cache_health_check(&self, check_result: HealthCheck) {
@@ -862,7 +1050,10 @@ impl Service {
 
             // NOTE: if you need reconfiguration and you DON'T have a
             // reload script, you're going to restart anyway.
-            if self.needs_reload || self.process_down() || self.needs_reconfiguration {
+            //
+            // A process that has gone away is only restarted once its
+            // restart policy allows it.
+            if self.needs_reload || self.supervisor.restart_due() || self.needs_reconfiguration {
                 self.reload(launcher);
                 if self.needs_reconfiguration {
                     // NOTE this only runs the hook if it's defined
@@ -878,9 +1069,11 @@ impl Service {
 
         if self.initialized {
             if let Some(ref hook) = self.hooks.file_updated {
-                return hook.run(&self.service_group,
-                                &self.pkg,
-                                self.svc_encrypted_password.as_ref());
+                let success = self.run_hook(hook);
+                if !success {
+                    self.hook_failed(hooks::FileUpdatedHook::file_name(), None);
+                }
+                return success;
             }
         }
 
@@ -899,15 +1092,46 @@ impl Service {
                                 group");
         let mut updated = false;
         for service_file in census_group.changed_service_files() {
+            if !self.trusts(service_file.signer.as_ref()) {
+                outputln!(preamble self.service_group,
+                          "Dropping version {} of service file {}, it is not signed by a \
+                           trusted key",
+                          service_file.incarnation,
+                          service_file.filename);
+                UNTRUSTED_UPDATES.with_label_values(&[self.service_group.as_ref(), "file"])
+                                 .inc();
+                continue;
+            }
             if self.cache_service_file(&service_file) {
                 outputln!(preamble self.service_group, "Service file updated, {}",
                     service_file.filename);
+                let event = event::ServiceFileApplied { service_group: &self.service_group,
+                                                        filename:      &service_file.filename,
+                                                        incarnation:   service_file.incarnation, };
+                event::publish(&event);
                 updated = true;
             }
         }
         updated
     }
 
+    /// Whether a config or file update signed by `signer` may be applied. Anything may be when
+    /// the service has no trusted signers; otherwise the update must be signed by one of them,
+    /// either by name with revision or by any revision of a named key.
+    pub fn trusts(&self, signer: Option<&String>) -> bool {
+        if self.trusted_signers.is_empty() {
+            return true;
+        }
+        let signer = match signer {
+            Some(signer) => signer,
+            None => return false,
+        };
+        let name = parse_name_with_rev(signer).map(|(name, _)| name).ok();
+        self.trusted_signers
+            .iter()
+            .any(|trusted| trusted == signer || Some(trusted) == name.as_ref())
+    }
+
     /// Helper for constructing a new render context for the service.
     fn render_context<'a>(&'a self, census: &'a CensusRing) -> RenderContext<'a> {
         // Unsatisfied binds are filtered out; you only get bind
@@ -927,9 +1151,7 @@ impl Service {
         let _timer = hook_timer("health-check");
         debug!("Running Health Check hook for ({})", self.spec_ident);
         let check_result = if let Some(ref hook) = self.hooks.health_check {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.svc_encrypted_password.as_ref())
+            self.run_hook(hook)
         } else {
             match self.supervisor.status() {
                 (true, _) => HealthCheck::Ok,
@@ -950,6 +1172,12 @@ impl Service {
                    self.spec_ident, check_result);
             self.schedule_special_health_check();
         }
+        if self.health_check != check_result {
+            self.health_check_since = Some(time::get_time().sec);
+            event::publish(&event::HealthCheckChanged { service_group: &self.service_group,
+                                                        previous:      self.health_check,
+                                                        current:       check_result, });
+        }
         self.health_check = check_result;
         self.cache_health_check(check_result);
     }
@@ -1068,6 +1296,14 @@ fn hook_timer(name: &str) -> HistogramTimer {
     HOOK_DURATION.with_label_values(&[name]).start_timer()
 }
 
+// Unwraps the exit value of a hook, counting the hook as timed out if it was killed.
+fn hook_exit_value<V>(name: &str, outcome: HookOutcome<V>) -> V {
+    if outcome.timed_out() {
+        HOOK_TIMEOUTS.with_label_values(&[name]).inc();
+    }
+    outcome.into_value()
+}
+
 /// This enum represents whether or not we want to render config information when we serialize this
 /// service via the ServiceProxy struct below. Choosing ConfigRendering::Full will render the
 /// config, and choosing ConfigRendering::Redacted will not render it. This matches up to the
@@ -1083,6 +1319,25 @@ pub enum ConfigRendering {
     Redacted,
 }
 
+/// The serialized form of `Service::pending_update`.
+#[derive(Serialize)]
+struct PendingUpdateProxy<'a> {
+    ident:      &'a PackageIdent,
+    /// When the update window next opens, in seconds since the epoch.
+    applies_at: Option<i64>,
+}
+
+impl<'a> PendingUpdateProxy<'a> {
+    fn new(ident: &'a PackageIdent, update_window: Option<&UpdateWindow>) -> Self {
+        let now = time::get_time();
+        let applies_at = match update_window {
+            Some(window) => window.next_open(now).map(|t| t.sec),
+            None => Some(now.sec),
+        };
+        PendingUpdateProxy { ident, applies_at }
+    }
+}
+
 /// This is a proxy struct to represent what information we're writing to the dat file, and
 /// therefore what information gets sent out via the HTTP API. Right now, we're just wrapping the
 /// actual Service struct, but this will give us something we can refactor against without
@@ -1104,9 +1359,9 @@ impl<'a> Serialize for ServiceProxy<'a> {
         where S: Serializer
     {
         let num_fields: usize = if self.config_rendering == ConfigRendering::Full {
-            27
+            37
         } else {
-            26
+            36
         };
 
         let s = &self.service;
@@ -1124,26 +1379,41 @@ impl<'a> Serialize for ServiceProxy<'a> {
         strukt.serialize_field("config_from", &s.config_from)?;
         strukt.serialize_field("desired_state", &s.desired_state)?;
         strukt.serialize_field("health_check", &s.health_check)?;
+        strukt.serialize_field("hook_timeouts", &s.hook_timeouts)?;
         strukt.serialize_field("hooks", &s.hooks)?;
         strukt.serialize_field("initialized", &s.initialized)?;
         strukt.serialize_field("last_election_status", &s.last_election_status)?;
+        strukt.serialize_field("leader_priority", &s.leader_priority)?;
+        strukt.serialize_field("log_rotation", &s.log_rotation)?;
         strukt.serialize_field("manager_fs_cfg", &s.manager_fs_cfg)?;
         strukt.serialize_field("needs_reconfiguration", &s.needs_reconfiguration)?;
         strukt.serialize_field("needs_reload", &s.needs_reload)?;
 
+        let pending_update =
+            s.pending_update
+             .as_ref()
+             .map(|ident| PendingUpdateProxy::new(ident, s.update_window.as_ref()));
+        strukt.serialize_field("pending_update", &pending_update)?;
+
         let pkg_proxy = PkgProxy::new(&s.pkg);
         strukt.serialize_field("pkg", &pkg_proxy)?;
 
         strukt.serialize_field("process", &s.supervisor)?;
+        strukt.serialize_field("restart_config", &s.restart_config)?;
+        strukt.serialize_field("rolled_back", &s.rolled_back)?;
         strukt.serialize_field("service_group", &s.service_group)?;
         strukt.serialize_field("spec_file", &s.spec_file)?;
         strukt.serialize_field("spec_ident", &s.spec_ident)?;
         strukt.serialize_field("spec_identifier", &s.spec_ident.to_string())?;
+        strukt.serialize_field("sticky_leader", &s.sticky_leader)?;
         strukt.serialize_field("svc_encrypted_password", &s.svc_encrypted_password)?;
         strukt.serialize_field("health_check_interval", &s.health_check_interval)?;
         strukt.serialize_field("sys", &s.sys)?;
         strukt.serialize_field("topology", &s.topology)?;
+        strukt.serialize_field("trusted_signers", &s.trusted_signers)?;
         strukt.serialize_field("update_strategy", &s.update_strategy)?;
+        strukt.serialize_field("update_rollout", &s.update_rollout)?;
+        strukt.serialize_field("update_window", &s.update_window)?;
         strukt.serialize_field("user_config_updated", &s.user_config_updated)?;
         strukt.end()
     }
@@ -1203,6 +1473,23 @@ mod tests {
                 "Expected health check due at creation");
     }
 
+    #[test]
+    fn trusted_signers_match_a_key_name_or_revision() {
+        let mut service = initialize_test_service();
+        let signer = String::from("ops-20190301100000");
+        assert!(service.trusts(None),
+                "Expected anything to be trusted without a policy");
+
+        service.trusted_signers = vec![String::from("ops")];
+        assert!(service.trusts(Some(&signer)));
+        assert!(!service.trusts(None));
+        assert!(!service.trusts(Some(&String::from("dev-20190301100000"))));
+
+        service.trusted_signers = vec![String::from("ops-20190101100000")];
+        assert!(!service.trusts(Some(&signer)));
+        assert!(service.trusts(Some(&String::from("ops-20190101100000"))));
+    }
+
     #[test]
     fn service_proxy_conforms_to_the_schema() {
         let service = initialize_test_service();
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use super::{BindingMode,
+            RestartPolicy,
             Topology,
             UpdateStrategy};
 use crate::error::{Error,
@@ -20,10 +21,14 @@ use crate::error::{Error,
                    SupError};
 use habitat_core::{fs::atomic_write,
                    package::{PackageIdent,
-                             PackageInstall},
+                             PackageIdentConstraint,
+                             PackageInstall,
+                             VersionConstraint},
                    service::{ApplicationEnvironment,
                              HealthCheckInterval,
-                             ServiceBind},
+                             ServiceBind,
+                             UpdateBatchSize,
+                             UpdateWindow},
                    url::DEFAULT_BLDR_URL,
                    util::{deserialize_using_from_str,
                           serialize_using_to_string},
@@ -41,7 +46,8 @@ use std::{collections::HashSet,
           path::{Path,
                  PathBuf},
           result,
-          str::FromStr};
+          str::FromStr,
+          time::Duration};
 use toml;
 
 static LOGKEY: &str = "SS";
@@ -109,6 +115,10 @@ pub trait IntoServiceSpec {
 impl IntoServiceSpec for habitat_sup_protocol::ctl::SvcLoad {
     fn into_spec(&self, spec: &mut ServiceSpec) {
         spec.ident = self.ident.clone().unwrap().into();
+        // Validated by the Supervisor when the service is loaded
+        spec.version_constraint = self.version_constraint
+                                      .as_ref()
+                                      .and_then(|c| VersionConstraint::from_str(c).ok());
         spec.group = self.group
                          .clone()
                          .unwrap_or_else(|| DEFAULT_GROUP.to_string());
@@ -124,18 +134,17 @@ impl IntoServiceSpec for habitat_sup_protocol::ctl::SvcLoad {
         if let Some(topology) = self.topology {
             spec.topology = Topology::from_i32(topology).unwrap_or_default();
         }
+        if let Some(leader_priority) = self.leader_priority {
+            spec.leader_priority = leader_priority;
+        }
+        if let Some(sticky_leader) = self.sticky_leader {
+            spec.sticky_leader = sticky_leader;
+        }
         if let Some(update_strategy) = self.update_strategy {
             spec.update_strategy = UpdateStrategy::from_i32(update_strategy).unwrap_or_default();
         }
         if let Some(ref list) = self.binds {
-            spec.binds =
-                list.binds
-                    .iter()
-                    .map(|pb: &habitat_sup_protocol::types::ServiceBind| {
-                        habitat_core::service::ServiceBind::new(&pb.name,
-                                                                pb.service_group.clone().into())
-                    })
-                    .collect();
+            spec.binds = list.binds.iter().cloned().map(Into::into).collect();
         }
         if let Some(binding_mode) = self.binding_mode {
             spec.binding_mode = BindingMode::from_i32(binding_mode).unwrap_or_default();
@@ -149,6 +158,176 @@ impl IntoServiceSpec for habitat_sup_protocol::ctl::SvcLoad {
         if let Some(ref interval) = self.health_check_interval {
             spec.health_check_interval = interval.seconds.into()
         }
+        if let Some(restart_policy) = self.restart_policy {
+            spec.restart.policy = RestartPolicy::from_i32(restart_policy).unwrap_or_default();
+        }
+        if let Some(max_attempts) = self.restart_max_attempts {
+            spec.restart.max_attempts = max_attempts;
+        }
+        if let Some(backoff_secs) = self.restart_backoff_secs {
+            spec.restart.backoff_secs = backoff_secs;
+        }
+        if let Some(backoff_max_secs) = self.restart_backoff_max_secs {
+            spec.restart.backoff_max_secs = backoff_max_secs;
+        }
+        if let Some(reset_secs) = self.restart_reset_secs {
+            spec.restart.reset_secs = reset_secs;
+        }
+        if let Some(ref batch_size) = self.update_batch_size {
+            // Validated by the client; an invalid value keeps the default
+            if let Ok(batch_size) = UpdateBatchSize::from_str(batch_size) {
+                spec.update_rollout.batch_size = batch_size;
+            }
+        }
+        if let Some(canary_soak_secs) = self.update_canary_soak_secs {
+            spec.update_rollout.canary_soak_secs = canary_soak_secs;
+        }
+        if let Some(ref update_window) = self.update_window {
+            // Validated by the client
+            spec.update_window = UpdateWindow::from_str(update_window).ok();
+        }
+    }
+}
+
+/// Controls if, and how quickly, a service's process is restarted after it exits without the
+/// Supervisor asking it to.
+///
+/// The Launcher reaps service processes, so the Supervisor never sees an exit code; any exit it
+/// didn't initiate is treated as a failure.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RestartConfig {
+    pub policy:           RestartPolicy,
+    /// Consecutive restarts attempted before giving up. Only consulted for the `on-failure`
+    /// policy; `always` keeps trying.
+    pub max_attempts:     u32,
+    /// Delay before the first restart; doubled for each consecutive restart after that.
+    pub backoff_secs:     u64,
+    /// Upper bound on the delay between consecutive restarts.
+    pub backoff_max_secs: u64,
+    /// How long the process must stay up before the consecutive restart count is reset.
+    pub reset_secs:       u64,
+}
+
+impl RestartConfig {
+    /// Returns `true` if another restart should be attempted after `restarts` consecutive ones.
+    pub fn allows_restart(&self, restarts: u32) -> bool {
+        match self.policy {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => restarts < self.max_attempts,
+            RestartPolicy::Never => false,
+        }
+    }
+
+    /// The delay to wait before restarting a process that has already been restarted
+    /// `restarts` consecutive times.
+    pub fn backoff(&self, restarts: u32) -> Duration {
+        let factor = 2u64.checked_pow(restarts).unwrap_or_else(u64::max_value);
+        let secs = self.backoff_secs.saturating_mul(factor);
+        Duration::from_secs(secs.min(self.backoff_max_secs))
+    }
+
+    pub fn reset_window(&self) -> Duration { Duration::from_secs(self.reset_secs) }
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        RestartConfig { policy:           RestartPolicy::default(),
+                        max_attempts:     10,
+                        backoff_secs:     1,
+                        backoff_max_secs: 300,
+                        reset_secs:       300, }
+    }
+}
+
+/// Controls how the `batched` and `canary` update strategies roll a new release out across a
+/// service group.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct UpdateRollout {
+    /// How many members update at once, either a count (`3`) or a percentage of the group
+    /// (`25%`).
+    #[serde(deserialize_with = "deserialize_using_from_str",
+            serialize_with = "serialize_using_to_string")]
+    pub batch_size:       UpdateBatchSize,
+    /// How long the canary must run a new release before the rest of the group follows it.
+    pub canary_soak_secs: u64,
+}
+
+impl Default for UpdateRollout {
+    fn default() -> Self {
+        UpdateRollout { batch_size:       UpdateBatchSize::default(),
+                        canary_soak_secs: 300, }
+    }
+}
+
+/// Controls when the logs the Launcher keeps of a service's output are rotated.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct LogRotation {
+    /// Rotate a log once it reaches this size; `0` disables size-based rotation.
+    pub max_bytes:    u64,
+    /// Rotate a log once it has been written to for this long; `0` disables age-based rotation.
+    pub max_age_secs: u64,
+    /// How many rotated copies of each log to keep.
+    pub keep:         u32,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        LogRotation { max_bytes:    10 * 1024 * 1024,
+                      max_age_secs: 0,
+                      keep:         5, }
+    }
+}
+
+/// How long, in seconds, each lifecycle hook may run before it is killed; `0` lets a hook run for
+/// as long as it likes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HookTimeouts {
+    pub file_updated: u64,
+    pub health_check: u64,
+    pub init:         u64,
+    pub post_run:     u64,
+    pub post_stop:    u64,
+    pub reconfigure:  u64,
+    pub reload:       u64,
+    pub suitability:  u64,
+}
+
+impl HookTimeouts {
+    /// The timeout for the hook with the given file name, if it has one.
+    pub fn for_hook(&self, file_name: &str) -> Option<Duration> {
+        let secs = match file_name {
+            "file-updated" => self.file_updated,
+            "health-check" => self.health_check,
+            "init" => self.init,
+            "post-run" => self.post_run,
+            "post-stop" => self.post_stop,
+            "reconfigure" => self.reconfigure,
+            "reload" => self.reload,
+            "suitability" => self.suitability,
+            _ => 0,
+        };
+        if secs == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(secs))
+        }
+    }
+}
+
+impl Default for HookTimeouts {
+    fn default() -> Self {
+        HookTimeouts { file_updated: 60,
+                       health_check: 30,
+                       init:         600,
+                       post_run:     300,
+                       post_stop:    60,
+                       reconfigure:  60,
+                       reload:       60,
+                       suitability:  30, }
     }
 }
 
@@ -158,22 +337,43 @@ pub struct ServiceSpec {
     #[serde(deserialize_with = "deserialize_using_from_str",
             serialize_with = "serialize_using_to_string")]
     pub ident: PackageIdent,
-    pub group: String,
+    /// The range of versions of the package the service may run, both when it is loaded and
+    /// when it is updated; the latest of any version if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_constraint:      Option<VersionConstraint>,
+    pub group:                   String,
     #[serde(deserialize_with = "deserialize_application_environment",
             skip_serializing_if = "Option::is_none")]
     pub application_environment: Option<ApplicationEnvironment>,
     pub bldr_url: String,
     pub channel: ChannelIdent,
     pub topology: Topology,
+    /// Ranks this member above those with a lower priority when electing a leader, before
+    /// suitability is considered.
+    pub leader_priority: u64,
+    /// Keep the current leader through a re-election, as long as it takes part and its service
+    /// is healthy, rather than letting leadership move to another member.
+    pub sticky_leader: bool,
     pub update_strategy: UpdateStrategy,
+    /// When updates found by the update strategy may be applied; any time if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_window: Option<UpdateWindow>,
     pub binds: Vec<ServiceBind>,
     pub binding_mode: BindingMode,
     pub config_from: Option<PathBuf>,
     #[serde(deserialize_with = "deserialize_using_from_str",
             serialize_with = "serialize_using_to_string")]
     pub desired_state: DesiredState,
+    /// The signing keys, named with or without a revision, whose service config and file
+    /// updates are applied. Updates from anyone are applied if this is empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trusted_signers: Vec<String>,
     pub health_check_interval: HealthCheckInterval,
     pub svc_encrypted_password: Option<String>,
+    pub restart: RestartConfig,
+    pub update_rollout: UpdateRollout,
+    pub log_rotation: LogRotation,
+    pub hook_timeouts: HookTimeouts,
 }
 
 impl ServiceSpec {
@@ -227,6 +427,13 @@ impl ServiceSpec {
 
     pub fn file_name(&self) -> String { format!("{}.{}", &self.ident.name, SPEC_FILE_EXT) }
 
+    /// The spec's package and version constraint, if it has one.
+    pub fn ident_constraint(&self) -> Option<PackageIdentConstraint> {
+        self.version_constraint
+            .clone()
+            .map(|version| PackageIdentConstraint::new(self.ident.clone(), version))
+    }
+
     /// Validates that all required package binds are present in service binds and all remaining
     /// service binds are optional package binds.
     ///
@@ -275,18 +482,27 @@ impl ServiceSpec {
 impl Default for ServiceSpec {
     fn default() -> Self {
         ServiceSpec { ident:                   PackageIdent::default(),
+                      version_constraint:      None,
                       group:                   DEFAULT_GROUP.to_string(),
                       application_environment: None,
                       bldr_url:                DEFAULT_BLDR_URL.to_string(),
                       channel:                 ChannelIdent::stable(),
                       topology:                Topology::default(),
+                      leader_priority:         0,
+                      sticky_leader:           false,
                       update_strategy:         UpdateStrategy::default(),
+                      update_window:           None,
                       binds:                   Vec::default(),
                       binding_mode:            BindingMode::Strict,
                       config_from:             None,
                       desired_state:           DesiredState::default(),
+                      trusted_signers:         Vec::new(),
                       health_check_interval:   HealthCheckInterval::default(),
-                      svc_encrypted_password:  None, }
+                      svc_encrypted_password:  None,
+                      restart:                 RestartConfig::default(),
+                      update_rollout:          UpdateRollout::default(),
+                      log_rotation:            LogRotation::default(),
+                      hook_timeouts:           HookTimeouts::default(), }
     }
 }
 
@@ -432,16 +648,22 @@ mod test {
 
     #[test]
     fn service_spec_to_toml_string() {
+        let update_window = UpdateWindow::from_str("* 2-4 * * sat,sun").unwrap();
         let spec =
             ServiceSpec { ident:
                               PackageIdent::from_str("origin/name/1.2.3/20170223130020").unwrap(),
+                          version_constraint:
+                              Some(VersionConstraint::from_str("~1.2").unwrap()),
                           group:                   String::from("jobs"),
                           application_environment:
                               Some(ApplicationEnvironment::from_str("theinternet.preprod").unwrap()),
                           bldr_url:                String::from("http://example.com/depot"),
                           channel:                 ChannelIdent::unstable(),
                           topology:                Topology::Leader,
+                          leader_priority:         10,
+                          sticky_leader:           true,
                           update_strategy:         UpdateStrategy::AtOnce,
+                          update_window:           Some(update_window),
                           binds:                   vec![
                 ServiceBind::from_str("cache:redis.cache@acmecorp").unwrap(),
                 ServiceBind::from_str("db:postgres.app@acmecorp").unwrap(),
@@ -450,24 +672,50 @@ mod test {
                           health_check_interval:   HealthCheckInterval::from_str("123").unwrap(),
                           config_from:             Some(PathBuf::from("/only/for/development")),
                           desired_state:           DesiredState::Down,
-                          svc_encrypted_password:  None, };
+                          trusted_signers:         vec![String::from("ops")],
+                          svc_encrypted_password:  None,
+                          restart:                 RestartConfig { policy:
+                                                                       RestartPolicy::OnFailure,
+                                                                   max_attempts: 3,
+                                                                   ..Default::default() },
+                          update_rollout:          UpdateRollout { batch_size:
+                                                                       UpdateBatchSize::Percent(25),
+                                                                   ..Default::default() },
+                          log_rotation:            LogRotation { max_age_secs: 86_400,
+                                                                 ..Default::default() },
+                          hook_timeouts:           HookTimeouts { health_check: 5,
+                                                                  ..Default::default() }, };
         let toml = spec.to_toml_string().unwrap();
 
         assert!(toml.contains(r#"ident = "origin/name/1.2.3/20170223130020""#,));
+        assert!(toml.contains(r#"version_constraint = "~1.2""#));
         assert!(toml.contains(r#"group = "jobs""#));
         assert!(toml.contains(r#"application_environment = "theinternet.preprod""#,));
         assert!(toml.contains(r#"bldr_url = "http://example.com/depot""#));
         assert!(toml.contains(r#"channel = "unstable""#));
         assert!(toml.contains(r#"topology = "leader""#));
+        assert!(toml.contains(r#"leader_priority = 10"#));
+        assert!(toml.contains(r#"sticky_leader = true"#));
         assert!(toml.contains(r#"update_strategy = "at-once""#));
         assert!(toml.contains(r#""cache:redis.cache@acmecorp""#));
         assert!(toml.contains(r#""db:postgres.app@acmecorp""#));
         assert!(toml.contains(r#"desired_state = "down""#));
         assert!(toml.contains(r#"config_from = "/only/for/development""#));
         assert!(toml.contains(r#"binding_mode = "relaxed""#));
+        assert!(toml.contains(r#"trusted_signers = ["ops"]"#));
         assert!(toml.contains(r#"[health_check_interval]"#));
         assert!(toml.contains(r#"secs = 123"#));
         assert!(toml.contains(r#"nanos = 0"#));
+        assert!(toml.contains(r#"[restart]"#));
+        assert!(toml.contains(r#"policy = "on-failure""#));
+        assert!(toml.contains(r#"max_attempts = 3"#));
+        assert!(toml.contains(r#"update_window = "* 2-4 * * sat,sun""#));
+        assert!(toml.contains(r#"[update_rollout]"#));
+        assert!(toml.contains(r#"batch_size = "25%""#));
+        assert!(toml.contains(r#"[log_rotation]"#));
+        assert!(toml.contains(r#"max_age_secs = 86400"#));
+        assert!(toml.contains(r#"[hook_timeouts]"#));
+        assert!(toml.contains(r#"health_check = 5"#));
     }
 
     #[test]
@@ -551,6 +799,174 @@ mod test {
         assert_eq!(spec.health_check_interval, HealthCheckInterval::default());
     }
 
+    #[test]
+    fn service_spec_from_str_restart() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+
+            [restart]
+            policy = "on-failure"
+            max_attempts = 4
+            backoff_secs = 2
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.restart.policy, RestartPolicy::OnFailure);
+        assert_eq!(spec.restart.max_attempts, 4);
+        assert_eq!(spec.restart.backoff_secs, 2);
+        assert_eq!(spec.restart.backoff_max_secs,
+                   RestartConfig::default().backoff_max_secs,
+                   "Unspecified restart settings should fall back to their defaults");
+    }
+
+    #[test]
+    fn service_spec_from_str_missing_restart() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.restart, RestartConfig::default());
+    }
+
+    #[test]
+    fn service_spec_from_str_update_rollout() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+            update_strategy = "canary"
+
+            [update_rollout]
+            batch_size = "3"
+            canary_soak_secs = 60
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.update_strategy, UpdateStrategy::Canary);
+        assert_eq!(spec.update_rollout.batch_size, UpdateBatchSize::Members(3));
+        assert_eq!(spec.update_rollout.canary_soak_secs, 60);
+    }
+
+    #[test]
+    fn service_spec_from_str_log_rotation() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+
+            [log_rotation]
+            max_bytes = 1048576
+            keep = 2
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.log_rotation.max_bytes, 1_048_576);
+        assert_eq!(spec.log_rotation.keep, 2);
+        assert_eq!(spec.log_rotation.max_age_secs,
+                   LogRotation::default().max_age_secs,
+                   "Unspecified log rotation settings should fall back to their defaults");
+    }
+
+    #[test]
+    fn service_spec_from_str_hook_timeouts() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+
+            [hook_timeouts]
+            init = 0
+            health_check = 10
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.hook_timeouts.for_hook("init"), None);
+        assert_eq!(spec.hook_timeouts.for_hook("health-check"),
+                   Some(Duration::from_secs(10)));
+        assert_eq!(spec.hook_timeouts.for_hook("post-stop"),
+                   Some(Duration::from_secs(HookTimeouts::default().post_stop)));
+    }
+
+    #[test]
+    fn service_spec_from_str_trusted_signers() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+            trusted_signers = ["ops", "core-20160810182414"]
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.trusted_signers,
+                   vec!["ops".to_string(), "core-20160810182414".to_string()]);
+        assert!(ServiceSpec::default().trusted_signers.is_empty());
+    }
+
+    #[test]
+    fn service_spec_from_str_leader_election() {
+        let toml = r#"
+            ident = "origin/name/1.2.3/20170223130020"
+            topology = "leader"
+            leader_priority = 100
+            sticky_leader = true
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.leader_priority, 100);
+        assert!(spec.sticky_leader);
+        assert_eq!(ServiceSpec::default().leader_priority, 0);
+        assert!(!ServiceSpec::default().sticky_leader);
+    }
+
+    #[test]
+    fn service_spec_from_str_version_constraint() {
+        let toml = r#"
+            ident = "core/openssl"
+            version_constraint = ">=1.0.2,<1.1"
+            "#;
+        let spec = ServiceSpec::from_str(toml).unwrap();
+
+        assert_eq!(spec.ident_constraint(),
+                   Some(PackageIdentConstraint::from_str("core/openssl@>=1.0.2,<1.1").unwrap()));
+        assert_eq!(ServiceSpec::default().ident_constraint(), None);
+
+        let toml = r#"
+            ident = "core/openssl"
+            version_constraint = "latest"
+            "#;
+        match ServiceSpec::from_str(toml) {
+            Err(e) => {
+                match e.err {
+                    ServiceSpecParse(_) => (), // expected outcome
+                    e => panic!("Unexpected error returned: {:?}", e),
+                }
+            }
+            Ok(_) => panic!("Spec TOML should fail to parse"),
+        }
+    }
+
+    #[test]
+    fn restart_config_backoff_doubles_up_to_the_cap() {
+        let config = RestartConfig { backoff_secs: 2,
+                                     backoff_max_secs: 30,
+                                     ..Default::default() };
+
+        assert_eq!(config.backoff(0), Duration::from_secs(2));
+        assert_eq!(config.backoff(1), Duration::from_secs(4));
+        assert_eq!(config.backoff(3), Duration::from_secs(16));
+        assert_eq!(config.backoff(4), Duration::from_secs(30));
+        assert_eq!(config.backoff(100), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn restart_config_allows_restart() {
+        let mut config = RestartConfig { max_attempts: 2,
+                                         ..Default::default() };
+
+        config.policy = RestartPolicy::Always;
+        assert!(config.allows_restart(1000));
+
+        config.policy = RestartPolicy::OnFailure;
+        assert!(config.allows_restart(1));
+        assert!(!config.allows_restart(2));
+
+        config.policy = RestartPolicy::Never;
+        assert!(!config.allows_restart(0));
+    }
+
     #[test]
     fn service_spec_from_file_missing() {
         let tmpdir = TempDir::new().unwrap();
@@ -608,13 +1024,17 @@ mod test {
         let spec =
             ServiceSpec { ident:
                               PackageIdent::from_str("origin/name/1.2.3/20170223130020").unwrap(),
+                          version_constraint:      None,
                           group:                   String::from("jobs"),
                           application_environment:
                               Some(ApplicationEnvironment::from_str("theinternet.preprod").unwrap()),
                           bldr_url:                String::from("http://example.com/depot"),
                           channel:                 ChannelIdent::unstable(),
                           topology:                Topology::Leader,
+                          leader_priority:         0,
+                          sticky_leader:           false,
                           update_strategy:         UpdateStrategy::AtOnce,
+                          update_window:           None,
                           binds:                   vec![
                 ServiceBind::from_str("cache:redis.cache@acmecorp").unwrap(),
                 ServiceBind::from_str("db:postgres.app@acmecorp").unwrap(),
@@ -623,7 +1043,15 @@ mod test {
                           health_check_interval:   HealthCheckInterval::from_str("23").unwrap(),
                           config_from:             Some(PathBuf::from("/only/for/development")),
                           desired_state:           DesiredState::Down,
-                          svc_encrypted_password:  None, };
+                          trusted_signers:         Vec::new(),
+                          svc_encrypted_password:  None,
+                          restart:                 RestartConfig { policy:
+                                                                       RestartPolicy::OnFailure,
+                                                                   max_attempts: 3,
+                                                                   ..Default::default() },
+                          update_rollout:          UpdateRollout::default(),
+                          log_rotation:            LogRotation::default(),
+                          hook_timeouts:           HookTimeouts::default(), };
         spec.to_file(&path).unwrap();
         let toml = string_from_file(path);
 
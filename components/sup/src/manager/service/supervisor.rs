@@ -17,7 +17,9 @@
 /// The Supervisor is responsible for running any services we are asked to start. It handles
 /// spawning the new process, watching for failure, and ensuring the service is either up or
 /// down. If the process dies, the Supervisor will restart it.
-use super::{terminator,
+use super::{spec::{LogRotation,
+                   RestartConfig},
+            terminator,
             ProcessState};
 use crate::{error::{Error,
                     Result,
@@ -34,7 +36,10 @@ use habitat_core::{fs,
                    os::process::{self,
                                  Pid},
                    service::ServiceGroup};
-use habitat_launcher_client::LauncherCli;
+use habitat_launcher_client::{LauncherCli,
+                              ServiceLogs};
+use prometheus::{IntCounterVec,
+                 IntGaugeVec};
 use serde::{ser::SerializeStruct,
             Serialize,
             Serializer};
@@ -44,11 +49,26 @@ use std::{fs::File,
                Write},
           path::{Path,
                  PathBuf},
-          result};
+          result,
+          time::{Duration,
+                 Instant}};
 use time::Timespec;
 
 static LOGKEY: &'static str = "SV";
 
+lazy_static! {
+    static ref SERVICE_RESTARTS: IntCounterVec =
+        register_int_counter_vec!("hab_sup_service_restarts_total",
+                                  "Number of times a service's process was restarted after \
+                                   exiting unexpectedly",
+                                  &["service"]).unwrap();
+    static ref SERVICE_BACKING_OFF: IntGaugeVec =
+        register_int_gauge_vec!("hab_sup_service_backing_off",
+                                "Whether a service's process is waiting to be restarted (1) or \
+                                 not (0)",
+                                &["service"]).unwrap();
+}
+
 #[derive(Debug)]
 pub struct Supervisor {
     pub preamble:      String,
@@ -56,18 +76,36 @@ pub struct Supervisor {
     pub state_entered: Timespec,
     pid:               Option<Pid>,
     pid_file:          PathBuf,
+    restart_config:    RestartConfig,
+    log_rotation:      LogRotation,
+    /// Number of times the process has been restarted since it last stayed up for the length of
+    /// the restart reset window.
+    restarts:          u32,
+    /// When a process in the `BackingOff` state is due to be restarted.
+    restart_at:        Option<Instant>,
 }
 
 impl Supervisor {
-    pub fn new(service_group: &ServiceGroup) -> Supervisor {
-        Supervisor { preamble:      service_group.to_string(),
-                     state:         ProcessState::Down,
-                     state_entered: time::get_time(),
-                     pid:           None,
-                     pid_file:      fs::svc_pid_file(service_group.service()), }
+    pub fn new(service_group: &ServiceGroup,
+               restart: RestartConfig,
+               logs: LogRotation)
+               -> Supervisor {
+        Supervisor { preamble:       service_group.to_string(),
+                     state:          ProcessState::Down,
+                     state_entered:  time::get_time(),
+                     pid:            None,
+                     pid_file:       fs::svc_pid_file(service_group.service()),
+                     restart_config: restart,
+                     log_rotation:   logs,
+                     restarts:       0,
+                     restart_at:     None, }
     }
 
-    /// Check if the child process is running
+    /// Check if the child process is running.
+    ///
+    /// If a process we previously started has gone away, the service's restart policy decides
+    /// whether it moves to `BackingOff` (to be restarted once `restart_due` says so) or stays
+    /// `Down`.
     pub fn check_process(&mut self) -> bool {
         let pid = match self.pid {
             Some(pid) => Some(pid),
@@ -83,16 +121,70 @@ impl Supervisor {
             if process::is_alive(pid) {
                 self.change_state(ProcessState::Up);
                 self.pid = Some(pid);
+                self.maybe_reset_restarts();
                 return true;
             }
         }
         debug!("Could not find a live process with pid {:?}", self.pid);
-        self.change_state(ProcessState::Down);
+        if self.state == ProcessState::Up {
+            outputln!(preamble self.preamble, "Process exited unexpectedly");
+            self.schedule_restart();
+        }
         self.cleanup_pidfile();
         self.pid = None;
         false
     }
 
+    /// Returns `true` if the process is not running and the restart policy says it should be
+    /// (re)started now.
+    pub fn restart_due(&self) -> bool {
+        match self.state {
+            ProcessState::Up => false,
+            ProcessState::Down => self.restart_config.allows_restart(self.restarts),
+            ProcessState::BackingOff => {
+                self.restart_at
+                    .map_or(true, |restart_at| restart_at <= Instant::now())
+            }
+        }
+    }
+
+    /// Put the process into the `BackingOff` state, or give up on it altogether if the restart
+    /// policy doesn't allow another attempt.
+    fn schedule_restart(&mut self) {
+        if self.restart_config.allows_restart(self.restarts) {
+            let backoff = self.restart_config.backoff(self.restarts);
+            outputln!(preamble self.preamble,
+                      "Restarting in {}s ({} restart policy, {} consecutive restarts)",
+                      backoff.as_secs(),
+                      self.restart_config.policy,
+                      self.restarts);
+            self.restart_at = Some(Instant::now() + backoff);
+            self.change_state(ProcessState::BackingOff);
+        } else {
+            outputln!(preamble self.preamble,
+                      "Not restarting ({} restart policy, {} consecutive restarts)",
+                      self.restart_config.policy,
+                      self.restarts);
+            self.restart_at = None;
+            self.change_state(ProcessState::Down);
+        }
+    }
+
+    /// Forget about previous restarts once the process has stayed up for the reset window.
+    fn maybe_reset_restarts(&mut self) {
+        if self.restarts == 0 {
+            return;
+        }
+        let up_for =
+            (time::get_time() - self.state_entered).to_std()
+                                                   .unwrap_or_else(|_| Duration::new(0, 0));
+        if up_for >= self.restart_config.reset_window() {
+            debug!("{} stayed up for {:?}; resetting restart count",
+                   self.preamble, up_for);
+            self.restarts = 0;
+        }
+    }
+
     // NOTE: the &self argument is only used to get access to
     // self.preamble, and even then only for Linux :/
     #[cfg(unix)]
@@ -142,6 +234,18 @@ impl Supervisor {
                       ..Default::default() })
     }
 
+    /// Where the Launcher should keep a copy of the service's output.
+    fn service_logs(&self, group: &ServiceGroup) -> ServiceLogs {
+        let service = group.service();
+        ServiceLogs { stdout_path:  fs::svc_stdout_log_file(service).to_string_lossy()
+                                                                    .into_owned(),
+                      stderr_path:  fs::svc_stderr_log_file(service).to_string_lossy()
+                                                                    .into_owned(),
+                      max_bytes:    self.log_rotation.max_bytes,
+                      max_age_secs: self.log_rotation.max_age_secs,
+                      keep:         self.log_rotation.keep, }
+    }
+
     pub fn start(&mut self,
                  pkg: &Pkg,
                  group: &ServiceGroup,
@@ -173,11 +277,20 @@ impl Supervisor {
         // Launcher versions on Linux (and current Windows versions)
         // will use these, while newer versions will prefer the UID
         // and GID, ignoring the names.
-        let pid = launcher.spawn(&group,
-                                 &pkg.svc_run,
-                                 user_info,
-                                 svc_password, // Windows optional
-                                 (*pkg.env).clone())?;
+        self.record_start();
+        let pid = match launcher.spawn(&group,
+                                       &pkg.svc_run,
+                                       user_info,
+                                       svc_password, // Windows optional
+                                       (*pkg.env).clone(),
+                                       Some(self.service_logs(group)))
+        {
+            Ok(pid) => pid,
+            Err(err) => {
+                self.schedule_restart();
+                return Err(sup_error!(Error::Launcher(err)));
+            }
+        };
         self.pid = Some(pid);
         self.create_pidfile()?;
         self.change_state(ProcessState::Up);
@@ -191,7 +304,7 @@ impl Supervisor {
                              time::get_time() - self.state_entered);
         let healthy = match self.state {
             ProcessState::Up => true,
-            ProcessState::Down => false,
+            ProcessState::Down | ProcessState::BackingOff => false,
         };
         (healthy, status)
     }
@@ -235,7 +348,8 @@ impl Supervisor {
                     }
                     Err(err) => {
                         self.cleanup_pidfile();
-                        self.change_state(ProcessState::Down);
+                        self.pid = None;
+                        self.schedule_restart();
                         Err(sup_error!(Error::Launcher(err)))
                     }
                 }
@@ -274,12 +388,27 @@ impl Supervisor {
         }
     }
 
+    /// Count a start of the process against the restart policy if it happens because of a
+    /// previous unexpected exit. Any other start (the initial one, or one caused by a
+    /// configuration change) gives the process a clean slate.
+    fn record_start(&mut self) {
+        if self.state == ProcessState::BackingOff {
+            self.restarts += 1;
+            SERVICE_RESTARTS.with_label_values(&[&self.preamble]).inc();
+        } else {
+            self.restarts = 0;
+        }
+        self.restart_at = None;
+    }
+
     fn change_state(&mut self, state: ProcessState) {
         if self.state == state {
             return;
         }
         self.state = state;
         self.state_entered = time::get_time();
+        SERVICE_BACKING_OFF.with_label_values(&[&self.preamble])
+                           .set((state == ProcessState::BackingOff) as i64);
     }
 }
 
@@ -291,6 +420,7 @@ impl Serialize for Supervisor {
         strukt.serialize_field("pid", &self.pid)?;
         strukt.serialize_field("state", &self.state)?;
         strukt.serialize_field("state_entered", &self.state_entered.sec)?;
+        strukt.serialize_field("restarts", &self.restarts)?;
         strukt.end()
     }
 }
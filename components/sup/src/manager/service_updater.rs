@@ -12,26 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{census::CensusRing,
+use crate::{census::{CensusGroup,
+                     CensusMember,
+                     CensusRing},
+            error::Result,
             manager::{periodic::Periodic,
-                      service::{Service,
+                      service::{HealthCheck,
+                                ProcessState,
+                                Service,
                                 Topology,
                                 UpdateStrategy}},
             util};
 use habitat_butterfly;
-use habitat_common::{outputln,
+use habitat_common::{command::package::install::InstallSource,
+                     outputln,
                      ui::UI};
 use habitat_core::{env as henv,
                    env::Config as EnvConfig,
+                   fs::atomic_write,
                    package::{PackageIdent,
+                             PackageIdentConstraint,
                              PackageInstall,
-                             PackageTarget},
-                   service::ServiceGroup,
+                             PackageTarget,
+                             VersionConstraint},
+                   service::{ServiceGroup,
+                             UpdateWindow},
+                   util::{deserialize_using_from_str,
+                          serialize_using_to_string},
                    ChannelIdent};
 use std::{cmp::{Ordering,
                 PartialOrd},
-          collections::HashMap,
+          collections::{HashMap,
+                        HashSet},
+          fs,
           num::ParseIntError,
+          path::{Path,
+                 PathBuf},
           result,
           str::FromStr,
           sync::mpsc::{channel,
@@ -42,6 +58,7 @@ use std::{cmp::{Ordering,
           time};
 use time_crate::{Duration,
                  SteadyTime};
+use toml;
 
 static LOGKEY: &'static str = "SU";
 // TODO (CM): Yes, the variable value should be "period" and not
@@ -54,7 +71,7 @@ lazy_static! {
 type UpdaterStateList = HashMap<ServiceGroup, UpdaterState>;
 
 enum UpdaterState {
-    AtOnce(Receiver<PackageInstall>, Sender<()>),
+    AtOnce(Receiver<Found>, Sender<()>),
     Rolling(RollingState),
 }
 
@@ -66,8 +83,11 @@ enum RollingState {
 }
 
 enum LeaderState {
-    Polling(Receiver<PackageInstall>, Sender<()>),
+    Polling(Receiver<Found>, Sender<()>),
     Waiting,
+    /// A follower rolled back the release we're rolling out; no
+    /// further updates are attempted until this service restarts.
+    Halted,
 }
 
 /// Current package update state of a follower in a leader-follower
@@ -75,23 +95,145 @@ enum LeaderState {
 enum FollowerState {
     /// Waiting to be told to update
     Waiting,
+    /// Waiting for the canary (the update leader) to run the given
+    /// release for the soak time, which we started timing at the given
+    /// time
+    Soaking(Option<PackageIdent>, SteadyTime),
     /// Currently updating
-    Updating(Receiver<PackageInstall>, Sender<()>),
+    Updating(Receiver<Found>, Sender<()>),
+}
+
+/// A newer release found by an update worker.
+enum Found {
+    /// The release has been downloaded, but won't be installed until
+    /// the service's update window opens.
+    Held(PackageIdent),
+    /// The release has been installed.
+    Installed(PackageInstall),
+}
+
+/// An update that has been downloaded but is held until the
+/// service's update window opens. Until then the service keeps
+/// running `from`, even across restarts.
+struct HeldUpdate {
+    from: PackageIdent,
+    to:   PackageIdent,
+}
+
+/// A release that was just updated to and must prove itself healthy
+/// before the rollback window closes.
+struct UpdateTrial {
+    from:    PackageIdent,
+    to:      PackageIdent,
+    /// When the updated release was first seen running; `None` until
+    /// the restarted service reports the new release.
+    started: Option<SteadyTime>,
+}
+
+/// A failed update; the service should be run at `to` instead of the
+/// latest installed release of its spec ident.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Rollback {
+    /// The release that failed its health check.
+    #[serde(deserialize_with = "deserialize_using_from_str",
+            serialize_with = "serialize_using_to_string")]
+    pub from: PackageIdent,
+    /// The release that was running before the update.
+    #[serde(deserialize_with = "deserialize_using_from_str",
+            serialize_with = "serialize_using_to_string")]
+    pub to:   PackageIdent,
+}
+
+/// The rollbacks of a service, as kept in the Supervisor's data
+/// directory so that they outlive the Supervisor.
+#[derive(Debug, Deserialize, Serialize)]
+struct RollbackFile {
+    #[serde(deserialize_with = "deserialize_using_from_str",
+            serialize_with = "serialize_using_to_string")]
+    ident:    PackageIdent,
+    /// The releases of the service that failed after an update.
+    pinned:   Vec<String>,
+    rollback: Option<Rollback>,
 }
 
 /// The ServiceUpdater is in charge of updating a Service when a more recent version of a package
 /// has been published to a depot or installed to the local package cache.
 /// To use an update strategy, the supervisor must be configured to watch a depot for new versions.
 pub struct ServiceUpdater {
-    states: UpdaterStateList,
+    states:         UpdaterStateList,
+    trials:         HashMap<ServiceGroup, UpdateTrial>,
+    /// Releases that failed after an update and must not be updated to again.
+    pinned:         HashSet<PackageIdent>,
+    /// Rollbacks to apply the next time a service is loaded, keyed by spec ident.
+    rollbacks:      HashMap<PackageIdent, Rollback>,
+    /// Updates that have been found but are waiting for the service's
+    /// update window to open, keyed by spec ident.
+    pending:        HashMap<PackageIdent, HeldUpdate>,
+    /// Where the pinned releases and rollbacks of each service are
+    /// kept, so they aren't forgotten when the Supervisor restarts.
+    rollbacks_path: PathBuf,
 
     butterfly: habitat_butterfly::Server,
 }
 
 impl ServiceUpdater {
-    pub fn new(butterfly: habitat_butterfly::Server) -> Self {
-        ServiceUpdater { states: UpdaterStateList::default(),
-                         butterfly }
+    /// Create a `ServiceUpdater`, picking up the pinned releases and
+    /// rollbacks kept in the given Supervisor data directory.
+    pub fn new(butterfly: habitat_butterfly::Server, data_path: &Path) -> Self {
+        let mut updater = ServiceUpdater { states: UpdaterStateList::default(),
+                                           trials: HashMap::default(),
+                                           pinned: HashSet::default(),
+                                           rollbacks: HashMap::default(),
+                                           pending: HashMap::default(),
+                                           rollbacks_path: data_path.join("rollbacks"),
+                                           butterfly };
+        updater.load_rollbacks();
+        updater
+    }
+
+    fn load_rollbacks(&mut self) {
+        let entries = match fs::read_dir(&self.rollbacks_path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+            let file: RollbackFile = match fs::read_to_string(&path).map(|s| toml::from_str(&s)) {
+                Ok(Ok(file)) => file,
+                Ok(Err(e)) => {
+                    outputln!("Unable to parse rollbacks in {}, {}", path.display(), e);
+                    continue;
+                }
+                Err(e) => {
+                    outputln!("Unable to read rollbacks from {}, {}", path.display(), e);
+                    continue;
+                }
+            };
+            self.pinned
+                .extend(file.pinned.iter().filter_map(|ident| ident.parse().ok()));
+            if let Some(rollback) = file.rollback {
+                self.rollbacks.insert(file.ident, rollback);
+            }
+        }
+    }
+
+    /// Keep the pinned releases and the rollback of the service with
+    /// the given spec ident in the Supervisor's data directory.
+    fn save_rollbacks(&self, spec_ident: &PackageIdent) {
+        let file = RollbackFile { ident:    spec_ident.clone(),
+                                  pinned:   self.pinned
+                                                .iter()
+                                                .filter(|ident| {
+                                                    ident.origin == spec_ident.origin
+                                                    && ident.name == spec_ident.name
+                                                })
+                                                .map(ToString::to_string)
+                                                .collect(),
+                                  rollback: self.rollbacks.get(spec_ident).cloned(), };
+        let path = self.rollbacks_path
+                       .join(format!("{}.toml", spec_ident.name));
+        if let Err(e) = write_rollbacks(&path, &file) {
+            outputln!("Unable to save rollbacks to {}, {}", path.display(), e);
+        }
     }
 
     /// Register a new `Service` for updates. Returns `true` if the
@@ -101,16 +243,19 @@ impl ServiceUpdater {
         match service.update_strategy {
             UpdateStrategy::None => false,
             UpdateStrategy::AtOnce => {
+                let pinned = &self.pinned;
                 self.states
                     .entry(service.service_group.clone())
                     .or_insert_with(|| {
                         let (kill_tx, kill_rx) = channel();
-                        let rx = Worker::new(service).start(&service.service_group, None, kill_rx);
+                        let rx = Worker::new(service, pinned).start(&service.service_group,
+                                                                    None,
+                                                                    kill_rx);
                         UpdaterState::AtOnce(rx, kill_tx)
                     });
                 true
             }
-            UpdateStrategy::Rolling => {
+            UpdateStrategy::Rolling | UpdateStrategy::Batched | UpdateStrategy::Canary => {
                 self.states
                     .entry(service.service_group.clone())
                     .or_insert(UpdaterState::Rolling(RollingState::AwaitingElection));
@@ -137,7 +282,7 @@ impl ServiceUpdater {
                                             dead.");
                                 }
                             }
-                            LeaderState::Waiting => {}
+                            LeaderState::Waiting | LeaderState::Halted => {}
                         }
                     }
                     RollingState::Follower(fs) => {
@@ -148,7 +293,7 @@ impl ServiceUpdater {
                                             dead.");
                                 }
                             }
-                            FollowerState::Waiting => {}
+                            FollowerState::Waiting | FollowerState::Soaking(..) => {}
                         }
                     }
                     _ => {}
@@ -161,14 +306,100 @@ impl ServiceUpdater {
         }
     }
 
+    /// The rollback to apply when loading a service with the given
+    /// spec ident, if one of its updates has failed.
+    pub fn rollback_for(&self, spec_ident: &PackageIdent) -> Option<Rollback> {
+        self.rollbacks.get(spec_ident).cloned()
+    }
+
+    /// The release to load a service with the given spec ident at,
+    /// instead of the latest installed release of its package: the
+    /// release a failed update was rolled back to, or the release it
+    /// was running when an update was held until its update window
+    /// opens.
+    pub fn release_for(&self, spec_ident: &PackageIdent) -> Option<PackageIdent> {
+        self.rollbacks
+            .get(spec_ident)
+            .map(|rollback| rollback.to.clone())
+            .or_else(|| self.pending.get(spec_ident).map(|held| held.from.clone()))
+    }
+
+    /// Check whether the service is running a freshly-updated release
+    /// that has failed its trial: its health check went critical, or
+    /// it didn't reach `Up` before the rollback window closed. When it
+    /// has, the release is pinned so it isn't updated to again and the
+    /// rollback is recorded for the service's spec ident and returned,
+    /// signalling that the service should be restarted.
+    pub fn check_for_failed_update(&mut self, service: &Service) -> Option<Rollback> {
+        let window: Duration = UpdateRollbackWindow::configured_value().into();
+        let failed = match self.trials.get_mut(&service.service_group) {
+            Some(trial) if trial.to == service.pkg.ident => {
+                let started = *trial.started.get_or_insert_with(SteadyTime::now);
+                if service.health_check() == HealthCheck::Critical {
+                    true
+                } else if SteadyTime::now() - started >= window {
+                    service.process_state() != ProcessState::Up
+                } else {
+                    return None;
+                }
+            }
+            Some(_) => {
+                // The update hasn't been restarted into yet.
+                return None;
+            }
+            None => return None,
+        };
+        let trial = self.trials
+                        .remove(&service.service_group)
+                        .expect("Update trial disappeared");
+        if !failed {
+            return None;
+        }
+        let rollback = Rollback { from: trial.to,
+                                  to:   trial.from, };
+        self.pinned.insert(rollback.from.clone());
+        self.rollbacks
+            .insert(service.spec_ident.clone(), rollback.clone());
+        self.save_rollbacks(&service.spec_ident);
+        Some(rollback)
+    }
+
+    /// Start the rollback window for an update that was just
+    /// installed, if rollbacks are enabled.
+    fn start_trial(&mut self, service: &Service, to: &PackageIdent) {
+        if self.rollbacks.remove(&service.spec_ident).is_some() {
+            self.save_rollbacks(&service.spec_ident);
+        }
+        if UpdateRollbackWindow::configured_value().0 > Duration::zero() {
+            self.trials.insert(service.service_group.clone(),
+                               UpdateTrial { from:    service.pkg.ident.clone(),
+                                             to:      to.clone(),
+                                             started: None, });
+        }
+    }
+
+    /// The update that has been found for the service with the given
+    /// spec ident but is waiting for the service's update window to
+    /// open.
+    pub fn pending_update(&self, spec_ident: &PackageIdent) -> Option<&PackageIdent> {
+        self.pending.get(spec_ident).map(|held| &held.to)
+    }
+
+    /// Hold an update of the service with the given spec ident from
+    /// the release it is running to a newer one until its update
+    /// window opens. Returns `false` if that update was already held.
+    fn hold(&mut self, spec_ident: &PackageIdent, from: &PackageIdent, to: PackageIdent) -> bool {
+        let previous = self.pending.insert(spec_ident.clone(),
+                                           HeldUpdate { from: from.clone(),
+                                                        to:   to.clone(), });
+        previous.map_or(true, |held| held.to != to)
+    }
+
     /// See if the given service has an update. Returns the identifier
     /// of the newly-updated service if a new version was installed,
-    /// thus signalling that the service should be restarted.
-    // If we ever need to modify this function, it would be an excellent opportunity to
-    // simplify the redundant aspects and remove this allow(clippy::cyclomatic_complexity),
-    // but changing it in the absence of other necessity seems like too much risk for the
-    // expected reward.
-    #[allow(clippy::cyclomatic_complexity)]
+    /// thus signalling that the service should be restarted. Updates
+    /// found outside the service's update window are only downloaded,
+    /// and held until it opens.
     pub fn check_for_updated_package(&mut self,
                                      service: &Service,
                                      // TODO (CM): Strictly speaking, we don't need to pass
@@ -176,22 +407,52 @@ impl ServiceUpdater {
                                      // service.
                                      census_ring: &CensusRing)
                                      -> Option<PackageIdent> {
+        match self.poll_for_updated_package(service, census_ring)? {
+            Found::Held(ident) => {
+                if self.hold(&service.spec_ident, &service.pkg.ident, ident.clone()) {
+                    if let Some(ref window) = service.update_window {
+                        outputln!(preamble service.service_group,
+                                  "Update to {} deferred until the update window '{}' opens",
+                                  ident,
+                                  window);
+                    }
+                }
+                None
+            }
+            Found::Installed(package) => {
+                self.pending.remove(&service.spec_ident);
+                self.start_trial(service, &package.ident);
+                Some(package.ident)
+            }
+        }
+    }
+
+    // If we ever need to modify this function, it would be an excellent opportunity to
+    // simplify the redundant aspects and remove this allow(clippy::cyclomatic_complexity),
+    // but changing it in the absence of other necessity seems like too much risk for the
+    // expected reward.
+    #[allow(clippy::cyclomatic_complexity)]
+    fn poll_for_updated_package(&mut self,
+                                service: &Service,
+                                census_ring: &CensusRing)
+                                -> Option<Found> {
         debug!("Checking for updated package!");
+        let pinned = &self.pinned;
 
         // TODO (CM): can we do without this?
-        let mut ident = None;
+        let mut found = None;
 
         match self.states.get_mut(&service.service_group) {
             Some(&mut UpdaterState::AtOnce(ref mut rx, ref mut kill_tx)) => {
                 match rx.try_recv() {
-                    Ok(package) => {
-                        return Some(package.ident.clone());
+                    Ok(update) => {
+                        return Some(update);
                     }
                     Err(TryRecvError::Empty) => return None,
                     Err(TryRecvError::Disconnected) => {
                         debug!("Service Updater worker has died; restarting...");
                         let (ktx, krx) = channel();
-                        *rx = Worker::new(service).start(&service.service_group, None, krx);
+                        *rx = Worker::new(service, pinned).start(&service.service_group, None, krx);
                         *kill_tx = ktx;
                     }
                 }
@@ -246,15 +507,17 @@ impl ServiceUpdater {
                 match *state {
                     LeaderState::Polling(ref mut rx, ref mut kill_tx) => {
                         match rx.try_recv() {
-                            Ok(package) => {
+                            Ok(update) => {
                                 debug!("Rolling Update, polling found a new package");
-                                ident = Some(package.ident.clone());
+                                found = Some(update);
                             }
                             Err(TryRecvError::Empty) => return None,
                             Err(TryRecvError::Disconnected) => {
                                 debug!("Service Updater worker has died; restarting...");
                                 let (ktx, krx) = channel();
-                                *rx = Worker::new(service).start(&service.service_group, None, krx);
+                                *rx = Worker::new(service, pinned).start(&service.service_group,
+                                                                         None,
+                                                                         krx);
                                 *kill_tx = ktx;
                             }
                         }
@@ -262,6 +525,20 @@ impl ServiceUpdater {
                     LeaderState::Waiting => {
                         match census_ring.census_group_for(&service.service_group) {
                             Some(census_group) => {
+                                let me = census_group.me().unwrap();
+                                if let Some(member) =
+                                    census_group.members().find(|cm| {
+                                                              cm.rolled_back.is_some()
+                                                              && cm.rolled_back == me.pkg
+                                                          })
+                                {
+                                    outputln!(preamble service.service_group,
+                                              "Halting rolling update; {} rolled back from {}",
+                                              member.member_id,
+                                              service.pkg.ident);
+                                    *state = LeaderState::Halted;
+                                    return None;
+                                }
                                 if census_group.members()
                                                .any(|cm| cm.pkg != census_group.me().unwrap().pkg)
                                 {
@@ -269,9 +546,9 @@ impl ServiceUpdater {
                                     return None;
                                 }
                                 let (kill_tx, kill_rx) = channel();
-                                let rx = Worker::new(service).start(&service.service_group,
-                                                                    None,
-                                                                    kill_rx);
+                                let rx = Worker::new(service, pinned).start(&service.service_group,
+                                                                            None,
+                                                                            kill_rx);
                                 *state = LeaderState::Polling(rx, kill_tx);
                             }
                             None => {
@@ -280,8 +557,9 @@ impl ServiceUpdater {
                             }
                         }
                     }
+                    LeaderState::Halted => return None,
                 }
-                if ident.is_some() {
+                if let Some(Found::Installed(_)) = found {
                     *state = LeaderState::Waiting;
                 }
             }
@@ -299,15 +577,94 @@ impl ServiceUpdater {
                                             debug!("We're not in an update");
                                             return None;
                                         }
-                                        if leader.pkg != peer.pkg {
+                                        if leader.pkg.as_ref().map_or(false, |p| pinned.contains(p))
+                                        {
+                                            debug!("We're in an update to a release we rolled \
+                                                    back from");
+                                            return None;
+                                        }
+                                        if leader.pkg
+                                                 .as_ref()
+                                                 .map_or(false, |p| !service.allows_release(p))
+                                        {
+                                            debug!("We're in an update to a release outside our \
+                                                    version constraint");
+                                            return None;
+                                        }
+                                        if service.update_strategy == UpdateStrategy::Canary {
+                                            debug!("We're in an update; waiting for the canary \
+                                                    to soak");
+                                            *state = FollowerState::Soaking(leader.pkg.clone(),
+                                                                            SteadyTime::now());
+                                            return None;
+                                        }
+                                        if !is_our_turn(service, census_group, leader, peer) {
                                             debug!("We're in an update but it's not our turn");
                                             return None;
                                         }
                                         debug!("We're in an update and it's our turn");
                                         let (kill_tx, kill_rx) = channel();
-                                        let rx = Worker::new(service).start(&service.service_group,
-                                                                            leader.pkg.clone(),
-                                                                            kill_rx);
+                                        let worker = Worker::new(service, pinned);
+                                        let rx = worker.start(&service.service_group,
+                                                              leader.pkg.clone(),
+                                                              kill_rx);
+                                        *state = FollowerState::Updating(rx, kill_tx);
+                                    }
+                                    _ => return None,
+                                }
+                            }
+                            None => {
+                                panic!("Expected census list to have service group '{}'!",
+                                       &*service.service_group)
+                            }
+                        }
+                    }
+                    FollowerState::Soaking(ref release, since) => {
+                        match census_ring.census_group_for(&service.service_group) {
+                            Some(census_group) => {
+                                match (census_group.update_leader(),
+                                       census_group.previous_peer(),
+                                       census_group.me())
+                                {
+                                    (Some(leader), Some(peer), Some(me)) => {
+                                        let soak_secs = service.update_rollout.canary_soak_secs;
+                                        let soak = Duration::seconds(soak_secs as i64);
+                                        match soak_progress(release.as_ref(),
+                                                            since,
+                                                            soak,
+                                                            leader,
+                                                            me)
+                                        {
+                                            Soak::Abort => {
+                                                debug!("The canary is no longer running the \
+                                                        release we were soaking");
+                                                *state = FollowerState::Waiting;
+                                                return None;
+                                            }
+                                            Soak::Restart => {
+                                                debug!("The canary isn't healthy; restarting the \
+                                                        soak");
+                                                *state = FollowerState::Soaking(release.clone(),
+                                                                                SteadyTime::now());
+                                                return None;
+                                            }
+                                            Soak::Continue => {
+                                                debug!("We're in an update but the canary is \
+                                                        still soaking");
+                                                return None;
+                                            }
+                                            Soak::Done => {}
+                                        }
+                                        if !is_our_turn(service, census_group, leader, peer) {
+                                            debug!("We're in an update but it's not our turn");
+                                            return None;
+                                        }
+                                        debug!("We're in an update and it's our turn");
+                                        let (kill_tx, kill_rx) = channel();
+                                        let worker = Worker::new(service, pinned);
+                                        let rx = worker.start(&service.service_group,
+                                                              leader.pkg.clone(),
+                                                              kill_rx);
                                         *state = FollowerState::Updating(rx, kill_tx);
                                     }
                                     _ => return None,
@@ -323,8 +680,8 @@ impl ServiceUpdater {
                         match census_ring.census_group_for(&service.service_group) {
                             Some(census_group) => {
                                 match rx.try_recv() {
-                                    Ok(package) => {
-                                        ident = Some(package.ident.clone());
+                                    Ok(update) => {
+                                        found = Some(update);
                                     }
                                     Err(TryRecvError::Empty) => return None,
                                     Err(TryRecvError::Disconnected) => {
@@ -332,9 +689,8 @@ impl ServiceUpdater {
                                         let package =
                                             census_group.update_leader().unwrap().pkg.clone();
                                         let (ktx, krx) = channel();
-                                        *rx = Worker::new(service).start(&service.service_group,
-                                                                         package,
-                                                                         krx);
+                                        let worker = Worker::new(service, pinned);
+                                        *rx = worker.start(&service.service_group, package, krx);
                                         *kill_tx = ktx;
                                     }
                                 }
@@ -346,13 +702,75 @@ impl ServiceUpdater {
                         }
                     }
                 }
-                if ident.is_some() {
+                if let Some(Found::Installed(_)) = found {
                     *state = FollowerState::Waiting;
                 }
             }
             None => {}
         }
-        ident
+        found
+    }
+}
+
+/// How a follower's soak of the canary's release is going.
+#[derive(Debug, PartialEq)]
+enum Soak {
+    /// The canary is no longer running the release being soaked.
+    Abort,
+    /// The canary isn't healthy, so the soak has to start over.
+    Restart,
+    /// The canary hasn't run the release for the soak time yet.
+    Continue,
+    /// The canary has run the release healthily for the soak time.
+    Done,
+}
+
+/// Check on the soak of `release`, which started at `since`, given
+/// the update leader that is the canary running it.
+fn soak_progress(release: Option<&PackageIdent>,
+                 since: SteadyTime,
+                 soak: Duration,
+                 leader: &CensusMember,
+                 me: &CensusMember)
+                 -> Soak {
+    if leader.pkg.as_ref() != release || leader.pkg == me.pkg {
+        Soak::Abort
+    } else if leader.health_check != Some(HealthCheck::Ok) {
+        Soak::Restart
+    } else if !leader.alive() || SteadyTime::now() - since < soak {
+        Soak::Continue
+    } else {
+        Soak::Done
+    }
+}
+
+fn write_rollbacks(path: &Path, file: &RollbackFile) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    atomic_write(path, toml::to_string(file)?)?;
+    Ok(())
+}
+
+/// Whether it's a follower's turn to update to the update leader's
+/// release. Rolling updates go one member at a time, each waiting for
+/// the member before it; batched and canary updates go a batch at a
+/// time, each waiting for every member of the batches before it.
+fn is_our_turn(service: &Service,
+               census_group: &CensusGroup,
+               leader: &CensusMember,
+               peer: &CensusMember)
+               -> bool {
+    match service.update_strategy {
+        UpdateStrategy::Batched | UpdateStrategy::Canary => {
+            let group_size = census_group.members().filter(|cm| cm.alive()).count();
+            let batch_size = service.update_rollout.batch_size.members(group_size);
+            census_group.update_batch_predecessors(batch_size)
+                        .map_or(false, |members| {
+                            members.iter().all(|cm| cm.pkg == leader.pkg)
+                        })
+        }
+        _ => leader.pkg == peer.pkg,
     }
 }
 
@@ -397,11 +815,47 @@ impl EnvConfig for ServiceUpdatePeriod {
     const ENVVAR: &'static str = "HAB_UPDATE_STRATEGY_FREQUENCY_MS";
 }
 
+/// How long, in seconds, an updated service has to reach `Up` without
+/// failing its health check before it's rolled back to the release it
+/// was updated from. The default of zero disables rollbacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UpdateRollbackWindow(Duration);
+
+impl Default for UpdateRollbackWindow {
+    fn default() -> Self { UpdateRollbackWindow(Duration::zero()) }
+}
+
+impl FromStr for UpdateRollbackWindow {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let raw = s.parse::<u32>()?;
+        Ok(UpdateRollbackWindow(Duration::seconds(i64::from(raw))))
+    }
+}
+
+impl Into<Duration> for UpdateRollbackWindow {
+    fn into(self) -> Duration { self.0 }
+}
+
+impl EnvConfig for UpdateRollbackWindow {
+    const ENVVAR: &'static str = "HAB_UPDATE_ROLLBACK_WINDOW_SECS";
+}
+
 struct Worker {
-    current:     PackageIdent,
-    spec_ident:  PackageIdent,
-    builder_url: String,
-    channel:     ChannelIdent,
+    current:            PackageIdent,
+    spec_ident:         PackageIdent,
+    /// Only releases within this range of versions are updated to.
+    version_constraint: Option<VersionConstraint>,
+    builder_url:        String,
+    channel:            ChannelIdent,
+    /// Releases that must be skipped because we rolled back from them.
+    pinned:             HashSet<PackageIdent>,
+    /// Updates are only installed while this window is open; outside
+    /// of it, they are only downloaded.
+    update_window:      Option<UpdateWindow>,
+    /// The release last reported as held until the window opens.
+    held:               Option<PackageIdent>,
 }
 
 impl Periodic for Worker {
@@ -418,11 +872,49 @@ impl Periodic for Worker {
 }
 
 impl Worker {
-    fn new(service: &Service) -> Self {
-        Worker { current:     service.pkg.ident.clone(),
-                 spec_ident:  service.spec_ident.clone(),
-                 builder_url: service.bldr_url.clone(),
-                 channel:     service.channel.clone(), }
+    fn new(service: &Service, pinned: &HashSet<PackageIdent>) -> Self {
+        Worker { current:            service.pkg.ident.clone(),
+                 spec_ident:         service.spec_ident.clone(),
+                 version_constraint: service.version_constraint.clone(),
+                 builder_url:        service.bldr_url.clone(),
+                 channel:            service.channel.clone(),
+                 pinned:             pinned.clone(),
+                 update_window:      service.update_window.clone(),
+                 held:               None, }
+    }
+
+    /// Whether updates may be installed right now.
+    fn may_install(&self) -> bool {
+        self.update_window
+            .as_ref()
+            .map_or(true, UpdateWindow::is_open)
+    }
+
+    /// Download the release `install_source` resolves to without
+    /// installing it.
+    fn fetch(&self, install_source: &InstallSource) -> Option<PackageIdent> {
+        match util::pkg::fetch(// We don't want anything in here to print
+                               &mut UI::with_sinks(),
+                               &self.builder_url,
+                               install_source,
+                               &self.channel)
+        {
+            Ok(ident) => Some(ident),
+            Err(e) => {
+                warn!("Failed to download updated package: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Report a downloaded release as held until the update window
+    /// opens, unless it already has been.
+    fn hold(&mut self, sender: &Sender<Found>, ident: PackageIdent) {
+        if self.held.as_ref() != Some(&ident) {
+            self.held = Some(ident.clone());
+            sender.send(Found::Held(ident))
+                  .expect("Main thread has gone away!");
+        }
     }
 
     /// Start a new update worker.
@@ -434,7 +926,7 @@ impl Worker {
              sg: &ServiceGroup,
              ident: Option<PackageIdent>,
              kill_rx: Receiver<()>)
-             -> Receiver<PackageInstall> {
+             -> Receiver<Found> {
         let (tx, rx) = channel();
         thread::Builder::new().name(format!("SU-{}", sg))
                               .spawn(move || {
@@ -469,15 +961,13 @@ impl Worker {
     // opportunity to collapse those.
     /// Polls until a newer version of the specified package is
     /// available. When such a package is found, it is installed, and
-    /// the function exits.
-    fn run_once(&mut self,
-                sender: &Sender<PackageInstall>,
-                ident: PackageIdent,
-                kill_rx: &Receiver<()>) {
+    /// the function exits. Outside the update window, it is only
+    /// downloaded until the window opens.
+    fn run_once(&mut self, sender: &Sender<Found>, ident: PackageIdent, kill_rx: &Receiver<()>) {
         // Fairly certain that this only gets called in a rolling update
         // scenario, where `ident` is always a fully-qualified identifier
         outputln!("Updating from {} to {}", self.current, ident);
-        let install_source = (ident, PackageTarget::active_target()).into();
+        let install_source = (ident.clone(), PackageTarget::active_target()).into();
         let mut next_time = SteadyTime::now();
 
         loop {
@@ -494,18 +984,25 @@ impl Worker {
             }
 
             if SteadyTime::now() >= next_time {
-                match util::pkg::install(// We don't want anything in here to print
-                                         &mut UI::with_sinks(),
-                                         &self.builder_url,
-                                         &install_source,
-                                         &self.channel)
-                {
-                    Ok(package) => {
-                        self.current = package.ident().clone();
-                        sender.send(package).expect("Main thread has gone away!");
-                        break;
+                if !self.may_install() {
+                    if self.held.as_ref() != Some(&ident) && self.fetch(&install_source).is_some() {
+                        self.hold(sender, ident.clone());
+                    }
+                } else {
+                    match util::pkg::install(// We don't want anything in here to print
+                                             &mut UI::with_sinks(),
+                                             &self.builder_url,
+                                             &install_source,
+                                             &self.channel)
+                    {
+                        Ok(package) => {
+                            self.current = package.ident().clone();
+                            sender.send(Found::Installed(package))
+                                  .expect("Main thread has gone away!");
+                            break;
+                        }
+                        Err(e) => warn!("Failed to install updated package: {:?}", e),
                     }
-                    Err(e) => warn!("Failed to install updated package: {:?}", e),
                 }
 
                 next_time = self.next_period_start();
@@ -516,9 +1013,19 @@ impl Worker {
     }
 
     /// Continually poll for a new version of a package, installing it
-    /// when found.
-    fn run_poll(&mut self, sender: &Sender<PackageInstall>, kill_rx: &Receiver<()>) {
-        let install_source = (self.spec_ident.clone(), PackageTarget::active_target()).into();
+    /// when found. With a version constraint, the latest release within
+    /// it is polled for instead of the latest of all. Outside the update
+    /// window, new versions are only downloaded until the window opens.
+    fn run_poll(&mut self, sender: &Sender<Found>, kill_rx: &Receiver<()>) {
+        let target = PackageTarget::active_target();
+        let install_source = match self.version_constraint {
+            Some(ref version) => {
+                let constraint =
+                    PackageIdentConstraint::new(self.spec_ident.clone(), version.clone());
+                (constraint, target).into()
+            }
+            None => (self.spec_ident.clone(), target).into(),
+        };
         let mut next_time = SteadyTime::now();
 
         loop {
@@ -535,27 +1042,38 @@ impl Worker {
             }
 
             if SteadyTime::now() >= next_time {
-                match util::pkg::install(// We don't want anything in here to print
-                                         &mut UI::with_sinks(),
-                                         &self.builder_url,
-                                         &install_source,
-                                         &self.channel)
-                {
-                    Ok(maybe_newer_package) => {
-                        if self.current < *maybe_newer_package.ident() {
-                            outputln!("Updating from {} to {}",
-                                      self.current,
-                                      maybe_newer_package.ident());
-                            self.current = maybe_newer_package.ident().clone();
-                            sender.send(maybe_newer_package)
-                                  .expect("Main thread has gone away!");
-                            break;
-                        } else {
-                            debug!("Package found {} is not newer than ours",
-                                   maybe_newer_package.ident());
+                if !self.may_install() {
+                    if let Some(ident) = self.fetch(&install_source) {
+                        if !self.pinned.contains(&ident) && self.current < ident {
+                            self.hold(sender, ident);
+                        }
+                    }
+                } else {
+                    match util::pkg::install(// We don't want anything in here to print
+                                             &mut UI::with_sinks(),
+                                             &self.builder_url,
+                                             &install_source,
+                                             &self.channel)
+                    {
+                        Ok(maybe_newer_package) => {
+                            if self.pinned.contains(maybe_newer_package.ident()) {
+                                debug!("Package found {} was rolled back from; skipping it",
+                                       maybe_newer_package.ident());
+                            } else if self.current < *maybe_newer_package.ident() {
+                                outputln!("Updating from {} to {}",
+                                          self.current,
+                                          maybe_newer_package.ident());
+                                self.current = maybe_newer_package.ident().clone();
+                                sender.send(Found::Installed(maybe_newer_package))
+                                      .expect("Main thread has gone away!");
+                                break;
+                            } else {
+                                debug!("Package found {} is not newer than ours",
+                                       maybe_newer_package.ident());
+                            }
                         }
+                        Err(e) => warn!("Updater failed to get latest package: {:?}", e),
                     }
-                    Err(e) => warn!("Updater failed to get latest package: {:?}", e),
                 }
 
                 next_time = self.next_period_start();
@@ -569,7 +1087,11 @@ impl Worker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use habitat_butterfly::{member::Member,
+                            server::Suitability,
+                            trace::Trace};
     use habitat_common::locked_env_var;
+    use tempfile::TempDir;
 
     #[test]
     fn default_update_period_is_equal_to_minimum_allowed_value() {
@@ -580,11 +1102,174 @@ mod tests {
     locked_env_var!(HAB_UPDATE_STRATEGY_FREQUENCY_BYPASS_CHECK, lock_bypass_var);
 
     fn worker() -> Worker {
-        Worker { current:     "core/testing/1.0.0/20181109125930".parse()
-                                                                 .expect("Can't parse ident!"),
-                 spec_ident:  "core/testing".parse().expect("Can't parse ident!"),
-                 builder_url: String::from("https://bldr.habitat.sh"),
-                 channel:     ChannelIdent::stable(), }
+        Worker { current:
+                     "core/testing/1.0.0/20181109125930".parse()
+                                                        .expect("Can't parse ident!"),
+                 spec_ident:         "core/testing".parse().expect("Can't parse ident!"),
+                 version_constraint: None,
+                 builder_url:        String::from("https://bldr.habitat.sh"),
+                 channel:            ChannelIdent::stable(),
+                 pinned:             HashSet::new(),
+                 update_window:      None,
+                 held:               None, }
+    }
+
+    locked_env_var!(HAB_UPDATE_ROLLBACK_WINDOW_SECS, lock_rollback_window_var);
+
+    #[derive(Debug)]
+    struct ZeroSuitability;
+    impl Suitability for ZeroSuitability {
+        fn get(&self, _service_group: &str) -> u64 { 0 }
+    }
+
+    fn updater(data_path: &Path) -> ServiceUpdater {
+        let listen = "127.0.0.1:0".parse().unwrap();
+        let butterfly = habitat_butterfly::Server::new(listen,
+                                                       listen,
+                                                       Member::default(),
+                                                       Trace::default(),
+                                                       None,
+                                                       None,
+                                                       None,
+                                                       Box::new(ZeroSuitability)).unwrap();
+        ServiceUpdater::new(butterfly, data_path)
+    }
+
+    #[test]
+    fn services_keep_their_release_while_an_update_is_held() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut updater = updater(tmpdir.path());
+        let spec_ident: PackageIdent = "core/redis".parse().unwrap();
+        let running: PackageIdent = "core/redis/4.0.14/20190319155852".parse().unwrap();
+        let update: PackageIdent = "core/redis/5.0.5/20190605175405".parse().unwrap();
+        assert_eq!(updater.release_for(&spec_ident), None);
+
+        assert!(updater.hold(&spec_ident, &running, update.clone()));
+        assert!(!updater.hold(&spec_ident, &running, update.clone()),
+                "The same update shouldn't be held twice");
+        assert_eq!(updater.pending_update(&spec_ident), Some(&update));
+        // Until the window opens, restarting the service loads it at the release it was already
+        // running, even if the update gets installed by other means.
+        assert_eq!(updater.release_for(&spec_ident), Some(running.clone()));
+
+        // A rollback takes precedence over a held update.
+        let rolled_back_to: PackageIdent = "core/redis/4.0.10/20180801003001".parse().unwrap();
+        updater.rollbacks.insert(spec_ident.clone(),
+                                 Rollback { from: running,
+                                            to:   rolled_back_to.clone(), });
+        assert_eq!(updater.release_for(&spec_ident), Some(rolled_back_to));
+    }
+
+    fn canary(pkg: &str, health_check: Option<HealthCheck>) -> CensusMember {
+        let mut member = CensusMember::default();
+        member.pkg = Some(pkg.parse().unwrap());
+        member.health_check = health_check;
+        member
+    }
+
+    #[test]
+    fn soaks_are_aborted_when_the_canary_changes_release() {
+        let soaking: PackageIdent = "core/redis/5.0.5/20190605175405".parse().unwrap();
+        let me = canary("core/redis/4.0.14/20190319155852", Some(HealthCheck::Ok));
+        let soak = Duration::seconds(600);
+        let since = SteadyTime::now();
+
+        let leader = canary("core/redis/5.0.5/20190605175405", Some(HealthCheck::Ok));
+        assert_eq!(soak_progress(Some(&soaking), since, soak, &leader, &me),
+                   Soak::Continue);
+
+        let leader = canary("core/redis/5.0.6/20190716123045", Some(HealthCheck::Ok));
+        assert_eq!(soak_progress(Some(&soaking), since, soak, &leader, &me),
+                   Soak::Abort);
+
+        let leader = canary("core/redis/4.0.14/20190319155852", Some(HealthCheck::Ok));
+        assert_eq!(soak_progress(Some(&soaking), since, soak, &leader, &me),
+                   Soak::Abort,
+                   "A canary that went back to our release has nothing left to soak");
+    }
+
+    #[test]
+    fn soaks_restart_while_the_canary_is_unhealthy() {
+        let soaking: PackageIdent = "core/redis/5.0.5/20190605175405".parse().unwrap();
+        let me = canary("core/redis/4.0.14/20190319155852", Some(HealthCheck::Ok));
+        let soak = Duration::seconds(600);
+        let since = SteadyTime::now() - Duration::seconds(900);
+
+        for health_check in &[Some(HealthCheck::Warning),
+                              Some(HealthCheck::Critical),
+                              Some(HealthCheck::Unknown),
+                              None]
+        {
+            let leader = canary("core/redis/5.0.5/20190605175405", *health_check);
+            assert_eq!(soak_progress(Some(&soaking), since, soak, &leader, &me),
+                       Soak::Restart,
+                       "Health check {:?}",
+                       health_check);
+        }
+    }
+
+    #[test]
+    fn rollbacks_outlive_the_updater() {
+        let tmpdir = TempDir::new().unwrap();
+        let spec_ident: PackageIdent = "core/redis".parse().unwrap();
+        let failed: PackageIdent = "core/redis/5.0.5/20190605175405".parse().unwrap();
+        let earlier_failure: PackageIdent = "core/redis/5.0.4/20190508190912".parse().unwrap();
+        let running: PackageIdent = "core/redis/4.0.14/20190319155852".parse().unwrap();
+        let unrelated: PackageIdent = "core/nginx/1.15.6/20181212185120".parse().unwrap();
+        {
+            let mut updater = updater(tmpdir.path());
+            updater.pinned.insert(earlier_failure.clone());
+            updater.pinned.insert(failed.clone());
+            updater.pinned.insert(unrelated.clone());
+            updater.rollbacks.insert(spec_ident.clone(),
+                                     Rollback { from: failed.clone(),
+                                                to:   running.clone(), });
+            updater.save_rollbacks(&spec_ident);
+        }
+
+        let updater = updater(tmpdir.path());
+        let rollback = updater.rollback_for(&spec_ident)
+                              .expect("The rollback should have been loaded");
+        assert_eq!(rollback.from, failed);
+        assert_eq!(rollback.to, running);
+        assert_eq!(updater.release_for(&spec_ident), Some(running));
+        assert!(updater.pinned.contains(&failed));
+        assert!(updater.pinned.contains(&earlier_failure));
+        assert!(!updater.pinned.contains(&unrelated),
+                "Only the service's own releases should be kept with its rollbacks");
+    }
+
+    #[test]
+    fn unreadable_rollbacks_are_skipped() {
+        let tmpdir = TempDir::new().unwrap();
+        let rollbacks_path = tmpdir.path().join("rollbacks");
+        fs::create_dir_all(&rollbacks_path).unwrap();
+        fs::write(rollbacks_path.join("redis.toml"), "ident = ").unwrap();
+
+        let updater = updater(tmpdir.path());
+        assert!(updater.rollbacks.is_empty());
+        assert!(updater.pinned.is_empty());
+    }
+
+    #[test]
+    fn rollback_window_is_disabled_by_default() {
+        let window = lock_rollback_window_var();
+        window.unset();
+        assert_eq!(UpdateRollbackWindow::configured_value().0, Duration::zero());
+    }
+
+    #[test]
+    fn rollback_window_can_be_overridden_by_env_var() {
+        let window = lock_rollback_window_var();
+        window.set("90");
+        assert_eq!(UpdateRollbackWindow::configured_value().0,
+                   Duration::seconds(90));
+    }
+
+    #[test]
+    fn rollback_window_must_be_positive() {
+        assert!(UpdateRollbackWindow::from_str("-30").is_err());
+        assert!(UpdateRollbackWindow::from_str("30").is_ok());
     }
 
     #[test]
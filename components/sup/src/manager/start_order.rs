@@ -0,0 +1,254 @@
+// Copyright (c) 2019 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Works out the order in which services loaded on this Supervisor that bind to one another are
+//! started and stopped: a service starts after the services it binds to, and stops before them.
+
+use super::service::ServiceSpec;
+use habitat_core::{package::PackageIdent,
+                   service::ServiceGroup};
+use std::collections::{HashMap,
+                       HashSet};
+
+/// Which of a set of locally loaded services bind to which.
+#[derive(Debug, Default)]
+pub struct StartOrder {
+    /// Each service, mapped to the services in the set that it binds to.
+    producers: HashMap<PackageIdent, Vec<PackageIdent>>,
+}
+
+impl StartOrder {
+    pub fn new<'a, I>(specs: I, organization: Option<&str>) -> Self
+        where I: IntoIterator<Item = &'a ServiceSpec>
+    {
+        let specs: Vec<&ServiceSpec> = specs.into_iter().collect();
+        let mut groups: HashMap<ServiceGroup, &PackageIdent> = HashMap::new();
+        for spec in &specs {
+            let app_env = spec.application_environment.as_ref();
+            if let Ok(group) =
+                ServiceGroup::new(app_env, &spec.ident.name, &spec.group, organization)
+            {
+                groups.insert(group, &spec.ident);
+            }
+        }
+        let producers = specs.iter()
+                             .map(|spec| {
+                                 // A service that binds to its own group doesn't have to wait
+                                 // for itself.
+                                 let mut producers: Vec<PackageIdent> =
+                                     spec.binds
+                                         .iter()
+                                         .filter_map(|bind| groups.get(bind.service_group()))
+                                         .filter(|ident| ***ident != spec.ident)
+                                         .map(|ident| (*ident).clone())
+                                         .collect();
+                                 producers.sort_by_key(ToString::to_string);
+                                 producers.dedup();
+                                 (spec.ident.clone(), producers)
+                             })
+                             .collect();
+        StartOrder { producers }
+    }
+
+    /// The services in the set that `ident` binds to.
+    pub fn producers(&self, ident: &PackageIdent) -> &[PackageIdent] {
+        self.producers.get(ident).map_or(&[], Vec::as_slice)
+    }
+
+    /// A cycle of services that bind to one another, if there is one. Each service in the cycle
+    /// binds to the next, and the last binds to the first.
+    pub fn cycle(&self) -> Option<Vec<PackageIdent>> {
+        let mut idents: Vec<&PackageIdent> = self.producers.keys().collect();
+        idents.sort_by_key(|ident| ident.to_string());
+        let mut finished = HashSet::new();
+        idents.into_iter()
+              .filter_map(|ident| self.find_cycle(ident, &mut Vec::new(), &mut finished))
+              .next()
+    }
+
+    /// A cycle of services that bind to one another and that `ident` is part of, if there is
+    /// one. The cycle starts with `ident`; each service in it binds to the next, and the last
+    /// binds to `ident`.
+    pub fn cycle_through(&self, ident: &PackageIdent) -> Option<Vec<PackageIdent>> {
+        let mut cycle = self.path_between(ident, ident, &mut HashSet::new())?;
+        cycle.insert(0, ident.clone());
+        Some(cycle)
+    }
+
+    /// The services a chain of binds from `from` to `to` passes through, not counting either,
+    /// where `visited` holds the services the search has already been through.
+    fn path_between<'a>(&'a self,
+                        from: &'a PackageIdent,
+                        to: &PackageIdent,
+                        visited: &mut HashSet<&'a PackageIdent>)
+                        -> Option<Vec<PackageIdent>> {
+        for producer in self.producers(from) {
+            if producer == to {
+                return Some(Vec::new());
+            }
+            if visited.insert(producer) {
+                if let Some(mut path) = self.path_between(producer, to, visited) {
+                    path.insert(0, producer.clone());
+                    return Some(path);
+                }
+            }
+        }
+        None
+    }
+
+    /// Depth-first search for a cycle through the services reachable from `ident`, where `path`
+    /// is how the search got to `ident` and `finished` holds the services already known not to
+    /// lead to a cycle.
+    fn find_cycle<'a>(&'a self,
+                      ident: &'a PackageIdent,
+                      path: &mut Vec<&'a PackageIdent>,
+                      finished: &mut HashSet<&'a PackageIdent>)
+                      -> Option<Vec<PackageIdent>> {
+        if finished.contains(ident) {
+            return None;
+        }
+        if let Some(start) = path.iter().position(|visited| *visited == ident) {
+            return Some(path[start..].iter()
+                                     .map(|visited| (*visited).clone())
+                                     .collect());
+        }
+        path.push(ident);
+        for producer in self.producers(ident) {
+            if let Some(cycle) = self.find_cycle(producer, path, finished) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        finished.insert(ident);
+        None
+    }
+
+    /// Split `idents` into stages to stop them in, so that each service stops in an earlier
+    /// stage than the services it binds to.
+    pub fn stop_stages<I>(&self, idents: I) -> Vec<Vec<PackageIdent>>
+        where I: IntoIterator<Item = PackageIdent>
+    {
+        let mut remaining: HashSet<PackageIdent> = idents.into_iter().collect();
+        let mut stages = Vec::new();
+        while !remaining.is_empty() {
+            let bound_to: HashSet<&PackageIdent> = remaining.iter()
+                                                            .flat_map(|ident| self.producers(ident))
+                                                            .collect();
+            let mut stage: Vec<PackageIdent> = remaining.iter()
+                                                        .filter(|ident| !bound_to.contains(ident))
+                                                        .cloned()
+                                                        .collect();
+            if stage.is_empty() {
+                // Everything left is, or is bound to by, a cycle; there's no right order to stop
+                // it in.
+                stage = remaining.iter().cloned().collect();
+            }
+            for ident in &stage {
+                remaining.remove(ident);
+            }
+            stage.sort_by_key(ToString::to_string);
+            stages.push(stage);
+        }
+        stages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use habitat_core::service::ServiceBind;
+    use std::str::FromStr;
+
+    fn spec(ident: &str, binds: &[&str]) -> ServiceSpec {
+        let mut spec = ServiceSpec::default_for(PackageIdent::from_str(ident).unwrap());
+        spec.binds = binds.iter()
+                          .map(|bind| ServiceBind::from_str(bind).unwrap())
+                          .collect();
+        spec
+    }
+
+    fn ident(ident: &str) -> PackageIdent { PackageIdent::from_str(ident).unwrap() }
+
+    #[test]
+    fn producers_are_the_loaded_services_a_service_binds_to() {
+        let specs = vec![spec("core/app",
+                              &["db:postgres.default", "cache:redis.elsewhere"]),
+                         spec("core/postgres", &[]),
+                         spec("core/redis", &[])];
+        let order = StartOrder::new(&specs, None);
+
+        assert_eq!(order.producers(&ident("core/app")),
+                   &[ident("core/postgres")]);
+        assert!(order.producers(&ident("core/postgres")).is_empty());
+        assert!(order.producers(&ident("core/unknown")).is_empty());
+    }
+
+    #[test]
+    fn binding_to_your_own_group_is_not_a_dependency() {
+        let specs = vec![spec("core/consul", &["peer:consul.default"])];
+        let order = StartOrder::new(&specs, None);
+
+        assert!(order.producers(&ident("core/consul")).is_empty());
+        assert_eq!(order.cycle(), None);
+    }
+
+    #[test]
+    fn bind_cycles_are_found() {
+        let specs = vec![spec("core/app", &["db:postgres.default"]),
+                         spec("core/postgres", &["backup:backup.default"]),
+                         spec("core/backup", &["db:postgres.default"])];
+        let order = StartOrder::new(&specs, None);
+
+        assert_eq!(order.cycle(),
+                   Some(vec![ident("core/postgres"), ident("core/backup")]));
+    }
+
+    #[test]
+    fn bind_cycles_through_a_service_are_found() {
+        let specs = vec![spec("core/app", &["db:postgres.default"]),
+                         spec("core/postgres", &["backup:backup.default"]),
+                         spec("core/backup", &["db:postgres.default"])];
+        let order = StartOrder::new(&specs, None);
+
+        assert_eq!(order.cycle_through(&ident("core/backup")),
+                   Some(vec![ident("core/backup"), ident("core/postgres")]));
+        assert_eq!(order.cycle_through(&ident("core/app")), None);
+    }
+
+    #[test]
+    fn acyclic_binds_have_no_cycle() {
+        let specs = vec![spec("core/app", &["db:postgres.default", "cache:redis.default"]),
+                         spec("core/redis", &["db:postgres.default"]),
+                         spec("core/postgres", &[])];
+        let order = StartOrder::new(&specs, None);
+
+        assert_eq!(order.cycle(), None);
+    }
+
+    #[test]
+    fn consumers_stop_before_their_producers() {
+        let specs = vec![spec("core/app", &["db:postgres.default", "cache:redis.default"]),
+                         spec("core/redis", &["db:postgres.default"]),
+                         spec("core/postgres", &[]),
+                         spec("core/other", &[])];
+        let order = StartOrder::new(&specs, None);
+
+        let stages = order.stop_stages(specs.iter().map(|spec| spec.ident.clone()));
+
+        assert_eq!(stages,
+                   vec![vec![ident("core/app"), ident("core/other")],
+                        vec![ident("core/redis")],
+                        vec![ident("core/postgres")]]);
+    }
+}
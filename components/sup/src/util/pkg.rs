@@ -80,6 +80,31 @@ pub fn install<T>(ui: &mut T,
                                              InstallHookMode::Ignore).map_err(SupError::from)
 }
 
+/// Helper function for use in the Supervisor to download a package and its dependencies into the
+/// artifact cache without installing them. Returns the fully-qualified identifier of the release
+/// that was downloaded.
+pub fn fetch<T>(ui: &mut T,
+                url: &str,
+                install_source: &InstallSource,
+                channel: &ChannelIdent)
+                -> Result<PackageIdent>
+    where T: UIWriter
+{
+    let fs_root_path = Path::new(&*FS_ROOT_PATH);
+    let auth_token = henv::var(AUTH_TOKEN_ENVVAR).ok();
+
+    habitat_common::command::package::install::fetch(ui,
+                                                     url,
+                                                     channel,
+                                                     install_source,
+                                                     PRODUCT,
+                                                     VERSION,
+                                                     fs_root_path,
+                                                     &fs::cache_artifact_path(None::<String>),
+                                                     auth_token.as_ref().map(String::as_str))
+        .map_err(SupError::from)
+}
+
 /// Given an InstallSource, install a new package only if an existing
 /// one that can satisfy the package identifier is not already
 /// present.
@@ -106,10 +131,15 @@ pub fn satisfy_or_install<T>(ui: &mut T,
      })
 }
 
-/// Returns an installed package for the given ident, if one is present.
-pub fn installed<T>(ident: T) -> Option<PackageInstall>
-    where T: AsRef<PackageIdent>
-{
+/// Returns an installed package for the given install source, if one is
+/// present. For a version constraint, this is the latest installed
+/// release within it.
+pub fn installed(install_source: &InstallSource) -> Option<PackageInstall> {
     let fs_root_path = Path::new(&*FS_ROOT_PATH);
-    PackageInstall::load(ident.as_ref(), Some(fs_root_path)).ok()
+    match *install_source {
+        InstallSource::Constraint(ref constraint, _) => {
+            PackageInstall::load_matching(constraint, Some(fs_root_path)).ok()
+        }
+        _ => PackageInstall::load(install_source.as_ref(), Some(fs_root_path)).ok(),
+    }
 }
@@ -72,7 +72,9 @@ impl Client {
                                                    None).unwrap(),
                                  incarnation,
                                  config,
-                                 false)
+                                 false,
+                                 None,
+                                 None)
             .expect("Cannot send the service configuration");
     }
 